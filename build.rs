@@ -0,0 +1,41 @@
+//! Build script that embeds build metadata in the binary.
+//!
+//! Sets `SM_MENU_COMMIT` to the short git commit the binary is built from,
+//! when the source is a git checkout with `git` available, and
+//! `SM_MENU_BUILT` to the build time in seconds since the Unix epoch. The
+//! build time honors `SOURCE_DATE_EPOCH`, for reproducible builds.
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .filter(|commit| !commit.is_empty());
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=SM_MENU_COMMIT={commit}");
+    }
+
+    let built = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<u64>().ok())
+        .or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since| since.as_secs())
+        });
+    if let Some(built) = built {
+        println!("cargo:rustc-env=SM_MENU_BUILT={built}");
+    }
+}
@@ -8,7 +8,7 @@
 //! cargo run --example custom_command
 //! ```
 
-use sm_menu::{CliContext, CliError, CliResult, Command, CommandResult};
+use sm_menu::{CliError, CliResult, Command, CommandResult};
 
 /// A custom hello command that greets the user
 #[derive(Debug)]
@@ -21,15 +21,15 @@ impl HelloCommand {
 }
 
 impl Command for HelloCommand {
-    fn name(&self) -> &str {
+    fn name(&self) -> &'static str {
         "hello"
     }
 
-    fn description(&self) -> &str {
+    fn description(&self) -> &'static str {
         "Greet the user with a friendly message"
     }
 
-    fn aliases(&self) -> Vec<&str> {
+    fn aliases(&self) -> Vec<&'static str> {
         vec!["hi", "greet"]
     }
 
@@ -84,15 +84,15 @@ impl CalculatorCommand {
 }
 
 impl Command for CalculatorCommand {
-    fn name(&self) -> &str {
+    fn name(&self) -> &'static str {
         "calc"
     }
 
-    fn description(&self) -> &str {
+    fn description(&self) -> &'static str {
         "Simple calculator operations"
     }
 
-    fn aliases(&self) -> Vec<&str> {
+    fn aliases(&self) -> Vec<&'static str> {
         vec!["calculator"]
     }
 
@@ -134,15 +134,15 @@ impl AddCommand {
 }
 
 impl Command for AddCommand {
-    fn name(&self) -> &str {
+    fn name(&self) -> &'static str {
         "add"
     }
 
-    fn description(&self) -> &str {
+    fn description(&self) -> &'static str {
         "Add two numbers"
     }
 
-    fn aliases(&self) -> Vec<&str> {
+    fn aliases(&self) -> Vec<&'static str> {
         vec!["+"]
     }
 
@@ -154,15 +154,18 @@ impl Command for AddCommand {
             )));
         }
 
-        let a: f64 = args[0]
-            .parse()
-            .map_err(|_| CliError::invalid_input(&format!("'{}' is not a valid number", args[0])))?;
-        let b: f64 = args[1]
-            .parse()
-            .map_err(|_| CliError::invalid_input(&format!("'{}' is not a valid number", args[1])))?;
+        let a: f64 = args[0].parse().map_err(|_| {
+            CliError::invalid_input(&format!("'{}' is not a valid number", args[0]))
+        })?;
+        let b: f64 = args[1].parse().map_err(|_| {
+            CliError::invalid_input(&format!("'{}' is not a valid number", args[1]))
+        })?;
 
         let result = a + b;
-        Ok(CommandResult::Success(format!("{} + {} = {}", a, b, result)))
+        Ok(CommandResult::Success(format!(
+            "{} + {} = {}",
+            a, b, result
+        )))
     }
 
     fn has_subcommands(&self) -> bool {
@@ -190,15 +193,15 @@ impl SubtractCommand {
 }
 
 impl Command for SubtractCommand {
-    fn name(&self) -> &str {
+    fn name(&self) -> &'static str {
         "subtract"
     }
 
-    fn description(&self) -> &str {
+    fn description(&self) -> &'static str {
         "Subtract two numbers"
     }
 
-    fn aliases(&self) -> Vec<&str> {
+    fn aliases(&self) -> Vec<&'static str> {
         vec!["-", "sub"]
     }
 
@@ -210,15 +213,18 @@ impl Command for SubtractCommand {
             )));
         }
 
-        let a: f64 = args[0]
-            .parse()
-            .map_err(|_| CliError::invalid_input(&format!("'{}' is not a valid number", args[0])))?;
-        let b: f64 = args[1]
-            .parse()
-            .map_err(|_| CliError::invalid_input(&format!("'{}' is not a valid number", args[1])))?;
+        let a: f64 = args[0].parse().map_err(|_| {
+            CliError::invalid_input(&format!("'{}' is not a valid number", args[0]))
+        })?;
+        let b: f64 = args[1].parse().map_err(|_| {
+            CliError::invalid_input(&format!("'{}' is not a valid number", args[1]))
+        })?;
 
         let result = a - b;
-        Ok(CommandResult::Success(format!("{} - {} = {}", a, b, result)))
+        Ok(CommandResult::Success(format!(
+            "{} - {} = {}",
+            a, b, result
+        )))
     }
 
     fn has_subcommands(&self) -> bool {
@@ -0,0 +1,1001 @@
+//! Interactive session loop for the CLI application.
+//!
+//! This module owns the command stack and dispatches each input line to the
+//! command it names. The binary is a thin wrapper around [`App::run`]; tests
+//! drive an [`App`] directly through [`App::handle_input`]. Tools can use
+//! [`App::run_json_requests`] instead, which reads one JSON request per line
+//! and answers each with one JSON response.
+
+use crate::commands::{RootCommand, available_commands};
+use crate::core::error::ErrorSeverity;
+use crate::core::pipeline::expand_history;
+use crate::core::{
+    CliContext, CliError, CliResult, Command, CommandResult, Dispatch, JsonValue, Redirect,
+    Resolution, join_tokens, pipeline, resolve_case, validate_command_tree,
+};
+use crate::ui::{CapturedOutput, LineReader, Profile, Transcript, run_picker, strip_ansi};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Maximum navigation depth to prevent stack overflow
+const MAX_NAVIGATION_DEPTH: usize = 10;
+
+/// Most runs of cacheable commands kept for replay
+const RESULT_CACHE_SIZE: usize = 64;
+
+/// Commands after which the shortcut hint stops appearing, unless it is
+/// turned on with the `shortcut_hints` preference
+pub const SHORTCUT_HINT_COMMANDS: usize = 5;
+
+/// Input line that opens the command picker: Ctrl-P, then Enter
+pub const PICKER_HOTKEY: &str = "\x10";
+
+/// Banner shown when an interactive session starts
+pub const WELCOME_MESSAGE: &str = "\n\tWelcome to sm-menu!\n";
+
+/// Message shown when a session ends
+pub const GOODBYE_MESSAGE: &str = "\nThank you for using sm-menu!";
+
+/// Hook run on each input line before it is dispatched
+///
+/// Returning `Ok(Some(line))` replaces the input with `line` for the rest
+/// of the chain and for dispatch, `Ok(None)` leaves it unchanged, and an
+/// error stops the line from being dispatched at all.
+pub type Middleware = Box<dyn Fn(&str, &mut CliContext) -> CliResult<Option<String>>>;
+
+/// Middleware registered on a session, in the order it runs
+#[derive(Default)]
+struct MiddlewareChain(Vec<Middleware>);
+
+impl fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MiddlewareChain({} hooks)", self.0.len())
+    }
+}
+
+/// Writer for redirected output, dropping the color codes meant for a terminal
+struct PlainFileWriter(File);
+
+impl Write for PlainFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(strip_ansi(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Command path and arguments identifying a cacheable command run
+type CacheKey = (Vec<String>, Vec<String>);
+
+/// Output and result of a cacheable command run, replayed when it repeats
+#[derive(Debug, Clone)]
+struct CachedRun {
+    /// Everything the command wrote to the display
+    output: String,
+    /// What the command returned
+    result: CommandResult,
+}
+
+/// Runs of cacheable commands, oldest first, bounded by [`RESULT_CACHE_SIZE`]
+#[derive(Debug, Default)]
+struct ResultCache(VecDeque<(CacheKey, CachedRun)>);
+
+impl ResultCache {
+    /// Find the run recorded for a command path and arguments
+    fn get(&self, key: &CacheKey) -> Option<&CachedRun> {
+        self.0
+            .iter()
+            .find(|(cached, _)| cached == key)
+            .map(|(_, run)| run)
+    }
+
+    /// Record a run, forgetting the oldest one when the cache is full
+    fn insert(&mut self, key: CacheKey, run: CachedRun) {
+        if self.0.len() >= RESULT_CACHE_SIZE {
+            self.0.pop_front();
+        }
+        self.0.push_back((key, run));
+    }
+}
+
+/// Counters for the input lines a session has dispatched
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionStats {
+    /// Input lines dispatched to a command
+    pub commands_run: usize,
+    /// Lines whose command succeeded
+    pub succeeded: usize,
+    /// Lines that failed to resolve or whose command failed
+    pub failed: usize,
+    /// Time the session has been running
+    pub elapsed: Duration,
+}
+
+impl SessionStats {
+    /// Format the stats as a one-line summary
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::app::SessionStats;
+    /// use std::time::Duration;
+    ///
+    /// let stats = SessionStats {
+    ///     commands_run: 3,
+    ///     succeeded: 2,
+    ///     failed: 1,
+    ///     elapsed: Duration::from_millis(1500),
+    /// };
+    /// assert_eq!(stats.summary(), "3 commands run: 2 succeeded, 1 failed in 1.50s");
+    /// ```
+    pub fn summary(&self) -> String {
+        format!(
+            "{} commands run: {} succeeded, {} failed in {:.2}s",
+            self.commands_run,
+            self.succeeded,
+            self.failed,
+            self.elapsed.as_secs_f64()
+        )
+    }
+
+    /// Format the stats as a JSON object
+    pub fn summary_json(&self) -> String {
+        format!(
+            "{{\"commands_run\":{},\"succeeded\":{},\"failed\":{},\"elapsed_ms\":{}}}",
+            self.commands_run,
+            self.succeeded,
+            self.failed,
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+/// Interactive CLI session
+///
+/// Holds the session context together with the stack of menus the user
+/// has navigated into. The last entry of the stack is the current menu.
+#[derive(Debug)]
+pub struct App {
+    /// Session state shared with commands
+    context: CliContext,
+    /// Whether a batch of lines is currently being dispatched
+    in_batch: bool,
+    /// When the session was created
+    started: Instant,
+    /// Input lines dispatched so far
+    commands_run: usize,
+    /// Input lines that failed so far
+    failed: usize,
+    /// CSV timing log of the session's commands, if one was requested
+    profile: Option<Profile>,
+    /// Whether command lines read from a script are echoed to stderr
+    echo: bool,
+    /// Whether a script stops at its first failed line
+    strict: bool,
+    /// Hooks run on each input line before dispatch
+    middleware: MiddlewareChain,
+    /// Earlier runs of commands that are cacheable
+    result_cache: ResultCache,
+    /// Line being typed at an interactive prompt, kept across Ctrl-C
+    line_reader: LineReader,
+}
+
+impl App {
+    /// Create a session rooted at the main menu
+    pub fn new(context: CliContext) -> Self {
+        Self::with_root(context, Box::new(RootCommand))
+    }
+
+    /// Create a session rooted at a custom menu
+    ///
+    /// In debug builds this panics if a command in the tree has a name or
+    /// alias that cannot be typed as one token.
+    pub fn with_root(mut context: CliContext, root: Box<dyn Command>) -> Self {
+        if cfg!(debug_assertions)
+            && let Err(e) = validate_command_tree(root.as_ref())
+        {
+            panic!("{e}");
+        }
+        context.navigator_mut().set_root(root);
+        Self {
+            context,
+            in_batch: false,
+            started: Instant::now(),
+            commands_run: 0,
+            failed: 0,
+            profile: None,
+            echo: false,
+            strict: false,
+            middleware: MiddlewareChain::default(),
+            result_cache: ResultCache::default(),
+            line_reader: LineReader::default(),
+        }
+    }
+
+    /// Get the session context
+    pub fn context(&self) -> &CliContext {
+        &self.context
+    }
+
+    /// Get mutable access to the session context
+    pub fn context_mut(&mut self) -> &mut CliContext {
+        &mut self.context
+    }
+
+    /// Get the stack of entered menus, root first
+    pub fn command_stack(&self) -> &[Box<dyn Command>] {
+        self.context.navigator().menus()
+    }
+
+    /// Mirror the session into a transcript
+    ///
+    /// Everything the display shows, with the lines typed at its prompts,
+    /// is recorded as plain text, see
+    /// [`DisplayManager::set_transcript`](crate::ui::DisplayManager::set_transcript).
+    pub fn set_transcript(&mut self, transcript: Transcript) {
+        self.context.display().set_transcript(&transcript);
+        let echo = transcript.tee(Box::new(io::sink()));
+        self.context.io_mut().set_echo(Some(echo));
+    }
+
+    /// Record the timing of every command the session runs
+    ///
+    /// Each command gets one CSV row with its name as typed, its
+    /// arguments, its duration, and whether it succeeded.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = Some(profile);
+    }
+
+    /// Echo each command line read from a script to stderr, like `set -x`
+    ///
+    /// Lines are printed after the prompt they were read at, without color,
+    /// before they run. Typed input is not echoed, since the terminal
+    /// already shows it.
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// Stop a script at its first failed line, like `set -e`
+    ///
+    /// Without it a script reports each failure and goes on with the next
+    /// line. With it, any failure ends the session with exit code 1,
+    /// including those of [`ErrorSeverity::Warning`] that are otherwise
+    /// only warnings, such as an invalid command or a wrong number of
+    /// arguments. Typed input is unaffected, since the user sees each error
+    /// and decides what to do next.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Add a hook run on each input line before it is dispatched
+    ///
+    /// Hooks run in the order they were added, each seeing the line as
+    /// rewritten by the ones before it. They also run for every line of a
+    /// batch, so a blocked command cannot be reached through `repeat`.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::{App, CliContext, CliError};
+    ///
+    /// let mut app = App::new(CliContext::new());
+    /// app.add_middleware(|input, _context| {
+    ///     if input.starts_with("save") {
+    ///         return Err(CliError::permission_denied("save is disabled"));
+    ///     }
+    ///     Ok(None)
+    /// });
+    /// assert!(app.handle_input("save").is_err());
+    /// ```
+    pub fn add_middleware(
+        &mut self,
+        middleware: impl Fn(&str, &mut CliContext) -> CliResult<Option<String>> + 'static,
+    ) {
+        self.middleware.0.push(Box::new(middleware));
+    }
+
+    /// Get the counters for the input lines read by [`App::run`]
+    pub fn stats(&self) -> SessionStats {
+        SessionStats {
+            commands_run: self.commands_run,
+            succeeded: self.commands_run - self.failed,
+            failed: self.failed,
+            elapsed: self.started.elapsed(),
+        }
+    }
+
+    /// Show the welcome banner through the session's display
+    pub fn welcome(&mut self) {
+        if self.context.preferences().plain {
+            return;
+        }
+        self.context.display().display_text(WELCOME_MESSAGE);
+    }
+
+    /// Show the goodbye message through the session's display
+    pub fn goodbye(&mut self) {
+        if self.context.preferences().plain {
+            return;
+        }
+        self.context.display().display_text(GOODBYE_MESSAGE);
+    }
+
+    /// Run the interactive loop until the user quits or input ends
+    pub fn run(&mut self) -> CliResult<()> {
+        while self.context.running {
+            // Display prompt and read input
+            match self.prompt_and_read_input() {
+                Ok(Some(input)) => {
+                    let input = input.trim();
+                    if input.is_empty() {
+                        // Show available commands when user presses enter with no input
+                        self.show_available_commands();
+                        continue;
+                    }
+                    // Ctrl-P opens the picker; the chosen command runs as typed.
+                    // With an idle timeout stdin belongs to the reading thread,
+                    // so the picker cannot read keys from it.
+                    let io = self.context.io();
+                    let picker_available = io.input_is_terminal() && io.idle_timeout().is_none();
+                    let picked = if input == PICKER_HOTKEY && picker_available {
+                        match self.pick_command() {
+                            Some(name) => Some(name),
+                            None => continue,
+                        }
+                    } else {
+                        None
+                    };
+                    let input = picked.as_deref().unwrap_or(input);
+
+                    // History references are expanded before the line is
+                    // recorded, so `!!` never refers to itself
+                    self.commands_run += 1;
+                    let result = expand_history(input, self.context.history()).and_then(|input| {
+                        self.context.add_to_history(input.clone());
+                        self.handle_input(&input)
+                    });
+                    if let Err(e) = result {
+                        self.failed += 1;
+                        self.display_error(&e);
+                        if self.strict && !self.context.io().input_is_terminal() {
+                            self.stop_strict(&e);
+                        }
+                    }
+                }
+                Ok(None) => {
+                    // End of input (Ctrl-D or the end of a piped script)
+                    self.context.quit();
+                }
+                Err(CliError::InputInterrupted) => {
+                    // Not an error: the line being typed is simply dropped
+                    self.context
+                        .display()
+                        .display_text("\nInput interrupted. Type 'quit' to exit.");
+                    continue;
+                }
+                Err(e @ CliError::IdleTimeout(_)) => {
+                    // An unattended session ends as if the user had quit
+                    self.context
+                        .display()
+                        .display_text(&format!("\n{e}. Exiting."));
+                    self.context.quit();
+                }
+                Err(e) => {
+                    eprintln!("Error reading input: {e}");
+                    // Don't break on IO errors, try to continue
+                    continue;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// End a strict script run at a failed line
+    ///
+    /// The note goes to stderr with the error it follows.
+    fn stop_strict(&mut self, error: &CliError) {
+        match error.severity() {
+            ErrorSeverity::Warning => {
+                eprintln!("Stopping: --strict treats this warning as an error")
+            }
+            ErrorSeverity::Error | ErrorSeverity::Critical => {
+                eprintln!("Stopping at the first failure (--strict)")
+            }
+        }
+        self.context.set_exit_code(1);
+        self.context.quit();
+    }
+
+    /// Answer JSON requests, one per input line, until input ends
+    ///
+    /// Each line is a request such as `{"command":"save","args":["x.txt"]}`
+    /// and is answered with one compact JSON response line. Nothing else is
+    /// written: no prompt, no banner, and command output goes into the
+    /// response. A line that is not a valid request gets an error response,
+    /// and the next line is read as usual.
+    pub fn run_json_requests(&mut self) -> CliResult<()> {
+        while self.context.running {
+            let response = match self.context.io_mut().read_line() {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => self.handle_json_request(&line),
+                Ok(None) => break,
+                Err(CliError::IdleTimeout(_)) => break,
+                Err(e) => Self::json_response(Err(&e), Vec::new(), &[]),
+            };
+            self.context.display().display_text(&response.to_string());
+        }
+        Ok(())
+    }
+
+    /// Run one JSON request and build its response
+    ///
+    /// A request is an object with a `command` string and an optional `args`
+    /// array of strings. The response is `{"ok":true,...}` with the output
+    /// lines and the menu path after the command, or `{"ok":false,...}`
+    /// with an `error` message as well.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::{App, CliContext};
+    ///
+    /// let mut app = App::new(CliContext::new());
+    /// let response = app.handle_json_request(r#"{"command":"file"}"#);
+    /// assert_eq!(response.to_string(), r#"{"ok":true,"output":[],"path":["file"]}"#);
+    /// ```
+    pub fn handle_json_request(&mut self, line: &str) -> JsonValue {
+        let (command_name, args) = match Self::parse_json_request(line) {
+            Ok(request) => request,
+            Err(e) => return Self::json_response(Err(&e), Vec::new(), self.context.current_path()),
+        };
+
+        let output = CapturedOutput::default();
+        let previous = self
+            .context
+            .display()
+            .replace_writer(Box::new(output.clone()));
+        self.commands_run += 1;
+        let result = self.handle_command(&command_name, &args);
+        self.context.display().replace_writer(previous);
+        if result.is_err() {
+            self.failed += 1;
+        }
+
+        Self::json_response(
+            result.as_ref().map(|_| ()),
+            strip_ansi(&output.text())
+                .lines()
+                .map(JsonValue::string)
+                .collect(),
+            self.context.current_path(),
+        )
+    }
+
+    /// Read the command name and arguments out of a JSON request line
+    fn parse_json_request(line: &str) -> CliResult<(String, Vec<String>)> {
+        let request = JsonValue::parse(line.trim()).map_err(|e| match e {
+            CliError::InvalidFileFormat(detail) => {
+                CliError::invalid_input(&format!("request is not valid JSON ({detail})"))
+            }
+            other => other,
+        })?;
+
+        let command = request
+            .get("command")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| CliError::invalid_input("request needs a \"command\" string"))?;
+        let args = match request.get("args") {
+            None => Vec::new(),
+            Some(JsonValue::Array(items)) => items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect::<Option<_>>()
+                .ok_or_else(|| CliError::invalid_input("\"args\" must be an array of strings"))?,
+            Some(_) => {
+                return Err(CliError::invalid_input(
+                    "\"args\" must be an array of strings",
+                ));
+            }
+        };
+        Ok((command.to_string(), args))
+    }
+
+    /// Build the response to a JSON request
+    fn json_response(
+        result: Result<(), &CliError>,
+        output: Vec<JsonValue>,
+        path: &[String],
+    ) -> JsonValue {
+        let mut members = vec![("ok".to_string(), JsonValue::Bool(result.is_ok()))];
+        if let Err(e) = result {
+            members.push(("error".to_string(), JsonValue::string(&e.to_string())));
+        }
+        members.push(("output".to_string(), JsonValue::Array(output)));
+        members.push((
+            "path".to_string(),
+            JsonValue::Array(
+                path.iter()
+                    .map(|segment| JsonValue::string(segment))
+                    .collect(),
+            ),
+        ));
+        JsonValue::Object(members)
+    }
+
+    /// Display the prompt and read a line of input
+    ///
+    /// Returns `Ok(None)` once the session input is exhausted.
+    fn prompt_and_read_input(&mut self) -> CliResult<Option<String>> {
+        self.show_shortcut_hint();
+        let prompt = format!("{}? ", self.context.get_prompt());
+
+        // Read input through the session's input handle, editing the line
+        // key by key when it comes from the terminal
+        let input = if self.context.io().line_editing() {
+            let preserve = self.context.preferences().preserve_interrupted_line;
+            self.line_reader.set_preserve_partial(preserve);
+            let (display, io) = self.context.display_with_io();
+            display.read_edited_line(&prompt, &mut self.line_reader, io)?
+        } else {
+            self.context.display().write_text(&prompt);
+            self.context.io_mut().read_line()?
+        };
+        if self.echo
+            && !self.context.io().input_is_terminal()
+            && let Some(input) = &input
+        {
+            eprintln!("{}{}", strip_ansi(&prompt), input.trim_end());
+        }
+        Ok(input)
+    }
+
+    /// Show the current menu's shortcut letters before an interactive prompt
+    ///
+    /// The hint is only shown for the first few commands of a session, so
+    /// it does not clutter the screen once the user knows the menus.
+    fn show_shortcut_hint(&mut self) {
+        let wanted =
+            self.context.preferences().shortcut_hints || self.commands_run < SHORTCUT_HINT_COMMANDS;
+        if !wanted || self.context.preferences().plain || !self.context.io().input_is_terminal() {
+            return;
+        }
+
+        let (display, navigator) = self.context.display_with_navigator();
+        if let Some(hint) = display.shortcut_hint(&navigator.current().subcommands()) {
+            display.display_text(&hint);
+        }
+    }
+
+    /// Let the user choose one of the current menu's commands from a list
+    ///
+    /// Returns the chosen command's name, or `None` if the picker was
+    /// cancelled or could not be shown.
+    fn pick_command(&mut self) -> Option<String> {
+        let commands: Vec<_> = self
+            .context
+            .navigator()
+            .current()
+            .subcommands()
+            .into_iter()
+            .filter(|cmd| !cmd.hidden())
+            .collect();
+        let items: Vec<String> = commands
+            .iter()
+            .map(|cmd| format!("{} - {}", cmd.name(), cmd.description()))
+            .collect();
+
+        match run_picker(&items) {
+            Ok(choice) => choice.map(|index| commands[index].name().to_string()),
+            Err(e) => {
+                self.display_error(&CliError::terminal_error(&format!(
+                    "Failed to open the command picker: {e}"
+                )));
+                None
+            }
+        }
+    }
+
+    /// Show available commands when user presses enter with no input
+    fn show_available_commands(&mut self) {
+        let (display, navigator) = self.context.display_with_navigator();
+        display.display_text("");
+        display.display_available_commands(navigator.menus());
+        display.display_text("");
+    }
+
+    /// Display error with appropriate formatting
+    fn display_error(&mut self, error: &CliError) {
+        let (display, navigator) = self.context.display_with_navigator();
+        display.display_error(error, navigator.menus());
+    }
+
+    /// Dispatch one line of input to the current menu
+    ///
+    /// A line starting with a user-defined alias is expanded first, see
+    /// [`AliasTable::expand`](crate::core::AliasTable::expand). A trailing
+    /// `> file` or `>> file` sends the messages the command writes through
+    /// the display manager to that file instead, replacing or appending to
+    /// it. The file must be a valid write target inside the root.
+    ///
+    /// # Arguments
+    /// * `input` - The command line, without its terminator
+    ///
+    /// # Returns
+    /// * `Ok(())` - The command ran and its result was applied
+    /// * `Err(CliError)` - If the command was not found or failed, or the
+    ///   redirection target could not be opened
+    pub fn handle_input(&mut self, input: &str) -> CliResult<()> {
+        let input = &self.run_middleware(input)?;
+        let (input, parts, redirect) =
+            pipeline::split_line(input, &self.context, &self.available_commands())?;
+        match parts.split_first() {
+            Some((command_name, args)) => self.dispatch(&input, command_name, args, redirect),
+            None => Err(CliError::EmptyInput),
+        }
+    }
+
+    /// Run a command given by name and arguments, without tokenizing a line
+    ///
+    /// The command is resolved in the current menu, as if typed, but its
+    /// arguments are used as given, so they need no quoting. Middleware,
+    /// aliases, and output redirection only apply to typed lines.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::{App, CliContext};
+    ///
+    /// let mut app = App::new(CliContext::new());
+    /// app.handle_command("file", &[]).unwrap();
+    /// assert_eq!(app.context().current_path(), ["file"]);
+    /// ```
+    pub fn handle_command(&mut self, command_name: &str, args: &[String]) -> CliResult<()> {
+        let mut parts = vec![command_name.to_string()];
+        parts.extend_from_slice(args);
+        self.dispatch(&join_tokens(&parts), command_name, args, None)
+    }
+
+    /// Run one command given by its menu path, as `sm-menu file vers` does
+    ///
+    /// Starting from the current menu, a word is entered as a menu when the
+    /// word after it names one of its subcommands; otherwise it is run with
+    /// the words after it as its arguments, so `file load notes.txt` loads
+    /// the file. A path that ends at a menu just enters it.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::{App, CliContext};
+    ///
+    /// let mut app = App::new(CliContext::new());
+    /// app.run_once(&["file".to_string(), "ls".to_string()]).unwrap();
+    /// assert_eq!(app.context().current_path(), ["file"]);
+    /// ```
+    pub fn run_once(&mut self, words: &[String]) -> CliResult<()> {
+        let case_sensitive = self.context.preferences().case_sensitive_commands;
+        let mut words = words;
+        while let [name, rest @ ..] = words {
+            let enters_menu = match (
+                resolve_case(
+                    name,
+                    self.context.navigator().current().subcommands(),
+                    case_sensitive,
+                ),
+                rest.first(),
+            ) {
+                (Resolution::Found { command, .. }, Some(next)) => matches!(
+                    resolve_case(next, command.subcommands(), case_sensitive),
+                    Resolution::Found { .. }
+                ),
+                _ => false,
+            };
+            if !enters_menu {
+                return self.handle_command(name, rest);
+            }
+            self.handle_command(name, &[])?;
+            words = rest;
+        }
+        Err(CliError::EmptyInput)
+    }
+
+    /// Send the session's display output to a file instead of the terminal
+    ///
+    /// The file must be a valid write target inside the root. It is
+    /// replaced, and color codes are dropped, as with `> file`. Errors are
+    /// not written to it: the caller reports them on stderr.
+    pub fn set_output_file(&mut self, target: &str) -> CliResult<()> {
+        let file = self.open_redirect(&Redirect {
+            target: target.to_string(),
+            append: false,
+        })?;
+        self.context.display().replace_writer(file);
+        Ok(())
+    }
+
+    /// Resolve a command in the current menu, confirm it if needed, and run it
+    ///
+    /// `input` is the line as typed, used when asking for confirmation.
+    fn dispatch(
+        &mut self,
+        input: &str,
+        command_name: &str,
+        args: &[String],
+        redirect: Option<Redirect>,
+    ) -> CliResult<()> {
+        let started = Instant::now();
+        let result = self.resolve_and_run(input, command_name, args, redirect);
+        if let Some(profile) = &mut self.profile {
+            profile.record(command_name, args, started.elapsed(), result.is_ok());
+        }
+        if let Err(e) = &result {
+            log_record!(Error, "Command '{input}' failed: {e}");
+        }
+        result
+    }
+
+    /// Resolve, confirm, and run a command for [`Self::dispatch`]
+    fn resolve_and_run(
+        &mut self,
+        input: &str,
+        command_name: &str,
+        args: &[String],
+        redirect: Option<Redirect>,
+    ) -> CliResult<()> {
+        let Dispatch {
+            command: mut cmd,
+            path,
+            redirect,
+            ..
+        } = pipeline::plan(
+            input.to_string(),
+            command_name,
+            args,
+            redirect,
+            &self.context,
+            self.available_commands(),
+        )?;
+        log_record!(Debug, "Resolved '{command_name}' to '{}'", cmd.name());
+
+        // Interactive-only commands cannot run from a script
+        if cmd.requires_tty() && !self.context.io().input_is_terminal() {
+            return Err(CliError::terminal_error(&format!(
+                "'{}' requires an interactive terminal",
+                cmd.name()
+            )));
+        }
+
+        // Reject malformed arguments before asking for confirmation
+        cmd.validate(args)?;
+
+        // Destructive commands are previewed and confirmed here rather than
+        // by each command
+        if cmd.is_destructive(args, &self.context) {
+            if let Some(preview) = cmd.preview(args, &self.context) {
+                self.context.display().display_text(&preview);
+            }
+            if self.context.preferences().confirm_destructive
+                && !self.confirm(&format!("{input}: this cannot be undone. Continue?"))?
+            {
+                self.context.display().display_text("Cancelled.");
+                return Ok(());
+            }
+        }
+
+        let previous = match &redirect {
+            Some(redirect) => {
+                let file = self.open_redirect(redirect)?;
+                Some(self.context.display().replace_writer(file))
+            }
+            None => None,
+        };
+
+        let result = if cmd.is_cacheable(args) {
+            self.execute_cached(cmd.as_mut(), args, path)
+        } else {
+            cmd.execute_with_context(args, &path, &mut self.context)
+        }
+        .and_then(|result| self.apply_result(cmd, result));
+
+        if let Some(previous) = previous {
+            self.context.display().replace_writer(previous);
+        }
+        result
+    }
+
+    /// Get the commands a typed name is resolved against in the current menu
+    fn available_commands(&self) -> Vec<Box<dyn Command>> {
+        available_commands(self.context.navigator().current())
+    }
+
+    /// Run a cacheable command, or replay its earlier run with the same arguments
+    ///
+    /// The first run's display output is captured and written through, so
+    /// a replay shows exactly the same output. Failed runs are not cached.
+    fn execute_cached(
+        &mut self,
+        cmd: &mut dyn Command,
+        args: &[String],
+        path: Vec<String>,
+    ) -> CliResult<CommandResult> {
+        let key = (path, args.to_vec());
+        if let Some(run) = self.result_cache.get(&key) {
+            let run = run.clone();
+            self.context.display().write_text(&run.output);
+            return Ok(run.result);
+        }
+
+        let capture = CapturedOutput::default();
+        let previous = self
+            .context
+            .display()
+            .replace_writer(Box::new(capture.clone()));
+        let result = cmd.execute_with_context(args, &key.0, &mut self.context);
+        self.context.display().replace_writer(previous);
+        let output = capture.text();
+        self.context.display().write_text(&output);
+
+        let result = result?;
+        self.result_cache.insert(
+            key,
+            CachedRun {
+                output,
+                result: result.clone(),
+            },
+        );
+        Ok(result)
+    }
+
+    /// Open the file an input line's output is redirected to
+    fn open_redirect(&self, redirect: &Redirect) -> CliResult<Box<dyn Write>> {
+        let path = self
+            .context
+            .security()
+            .validate_write_path(&redirect.target)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(redirect.append)
+            .truncate(!redirect.append)
+            .open(&path)?;
+        Ok(Box::new(PlainFileWriter(file)))
+    }
+
+    /// Pass an input line through the middleware chain
+    fn run_middleware(&mut self, input: &str) -> CliResult<String> {
+        let mut input = input.to_string();
+        for middleware in &self.middleware.0 {
+            if let Some(rewritten) = middleware(&input, &mut self.context)? {
+                input = rewritten;
+            }
+        }
+        Ok(input)
+    }
+
+    /// Ask a yes/no question on the session input
+    ///
+    /// With `--yes` the answer is yes and nothing is read. Otherwise
+    /// anything other than `y` or `yes`, including the end of input, is
+    /// taken as no. When the input is not a terminal there is no one to
+    /// answer, so the question is refused with an error rather than read
+    /// from the script, and a destructive operation never runs or silently
+    /// cancels by accident.
+    fn confirm(&mut self, question: &str) -> CliResult<bool> {
+        if self.context.preferences().assume_yes {
+            self.context
+                .display()
+                .display_text(&format!("{question} [y/N] y (--yes)"));
+            return Ok(true);
+        }
+
+        if !self.context.io().input_is_terminal() {
+            return Err(CliError::terminal_error(&format!(
+                "{question} Refused: input is not a terminal; pass --yes to confirm"
+            )));
+        }
+
+        self.context
+            .display()
+            .write_text(&format!("{question} [y/N] "));
+        let answer = self.context.io_mut().read_line()?.unwrap_or_default();
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// Apply the result of a command to the session
+    fn apply_result(&mut self, cmd: Box<dyn Command>, result: CommandResult) -> CliResult<()> {
+        match result {
+            CommandResult::Success(msg) => {
+                if !msg.is_empty() {
+                    self.context.display().display_success(&msg);
+                }
+            }
+            CommandResult::Continue => {
+                // If command has subcommands, enter that submenu
+                if cmd.has_subcommands() {
+                    // Check for maximum navigation depth
+                    if self.context.navigator().menus().len() >= MAX_NAVIGATION_DEPTH {
+                        return Err(CliError::execution_error(
+                            "Maximum navigation depth reached. Use 'exit' to go back.",
+                        ));
+                    }
+                    if let Some(max_depth) = self.context.preferences().max_depth
+                        && self.context.depth() >= max_depth
+                    {
+                        return Err(CliError::execution_error(&format!(
+                            "Cannot enter {}: the menu depth is limited to {max_depth}. Use 'exit' to go back.",
+                            cmd.name()
+                        )));
+                    }
+                    let name = cmd.name();
+                    self.context.navigator_mut().push(cmd);
+                    if self.context.preferences().verbose {
+                        self.context
+                            .display()
+                            .display_info(&format!("Entered {name} menu"));
+                    }
+                }
+            }
+            CommandResult::GoUp => {
+                // Return to parent menu
+                if self.context.navigator_mut().pop().is_none() {
+                    // Already at root level
+                    self.context
+                        .display()
+                        .display_text("Already at root level.");
+                } else if self.context.preferences().verbose {
+                    let message = if self.context.navigator().is_root() {
+                        "Returned to root".to_string()
+                    } else {
+                        format!(
+                            "Returned to {} menu",
+                            self.context.navigator().current().name()
+                        )
+                    };
+                    self.context.display().display_info(&message);
+                }
+            }
+            CommandResult::Quit => {
+                self.context.quit();
+            }
+            CommandResult::Batch { lines, keep_going } => {
+                self.run_batch(&lines, keep_going)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dispatch a batch of lines through the normal input path
+    ///
+    /// Stops at the first error unless `keep_going` is set, in which case
+    /// each error is displayed and the remaining lines still run. Batches
+    /// cannot be nested.
+    fn run_batch(&mut self, lines: &[String], keep_going: bool) -> CliResult<()> {
+        if self.in_batch {
+            return Err(CliError::execution_error(
+                "A batch cannot be started from inside another batch",
+            ));
+        }
+
+        self.in_batch = true;
+        let result = self.run_batch_lines(lines, keep_going);
+        self.in_batch = false;
+        result
+    }
+
+    fn run_batch_lines(&mut self, lines: &[String], keep_going: bool) -> CliResult<()> {
+        for line in lines {
+            if !self.context.running {
+                break;
+            }
+            match self.handle_input(line) {
+                Ok(()) => {}
+                Err(e) if keep_going => self.display_error(&e),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,93 +1,201 @@
 //! Axis command implementation for configuring axis properties.
 //!
 //! This command provides axis configuration functionality that can be used
-//! in different contexts (edit, view) to configure axis properties for
-//! the respective environment.
+//! in different menus (edit, view) to configure axis properties for the
+//! respective environment. The menu is read from the command path, and
+//! each menu stores its own axes in the session context.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::core::axes::Axis;
+use crate::{
+    ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory, CommandResult,
+};
 
 /// Axis command for configuring axis properties
 ///
 /// This command allows users to configure axis properties within different
-/// contexts such as editing or viewing modes. It supports optional axis name
+/// menus such as editing or viewing modes. It supports optional axis name
 /// parameters with validation for proper naming conventions.
 #[derive(Debug)]
 pub struct AxisCommand {
-    context: String,
+    /// Help category, following the menu the command is offered in
+    category: CommandCategory,
+}
+
+impl Default for AxisCommand {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AxisCommand {
-    /// Creates a new AxisCommand with the specified context
+    /// Creates a new AxisCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::axis::AxisCommand;
+    /// let axis_cmd = AxisCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_category(CommandCategory::General)
+    }
+
+    /// Creates a new AxisCommand listed under the given help category
+    ///
+    /// The edit and view menus use this so the command is grouped with
+    /// the rest of their commands.
     ///
     /// # Arguments
-    /// * `context` - The context string (e.g., "edit", "view")
+    /// * `category` - The category of the menu offering the command
     ///
     /// # Examples
     /// ```
     /// use sm_menu::commands::axis::AxisCommand;
-    /// let axis_cmd = AxisCommand::new("edit");
+    /// use sm_menu::{Command, CommandCategory};
+    ///
+    /// let axis_cmd = AxisCommand::with_category(CommandCategory::View);
+    /// assert_eq!(axis_cmd.category(), CommandCategory::View);
     /// ```
-    pub fn new(context: &str) -> Self {
-        AxisCommand {
-            context: context.to_string(),
+    pub fn with_category(category: CommandCategory) -> Self {
+        AxisCommand { category }
+    }
+
+    /// Split the arguments into the axis name and any `set` pairs
+    fn parse_args<'a>(&self, args: &'a [String]) -> CliResult<(&'a str, Option<&'a [String]>)> {
+        // Axis command takes an optional axis name, optionally followed by
+        // `set` and property/value pairs
+        let set_args = match args {
+            [_, action, rest @ ..] if action == "set" => Some(rest),
+            _ => None,
+        };
+        if set_args.is_none() {
+            self.validate_exact_or_range(args, 0, 1)?;
         }
+
+        let axis_name = args.first().map_or("default", String::as_str);
+
+        // Validate axis name
+        if axis_name.trim().is_empty() {
+            return Err(CliError::invalid_input("Axis name cannot be empty"));
+        }
+
+        // Validate axis name format
+        if !axis_name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(CliError::invalid_input(
+                "Axis name can only contain alphanumeric characters, underscores, and hyphens",
+            ));
+        }
+
+        Ok((axis_name, set_args))
+    }
+
+    /// Apply `<property> <value>` pairs on top of an axis
+    ///
+    /// Only `min` and `max` are known properties, and values must be
+    /// finite numbers. The bounds are not validated here, so the caller
+    /// can check the combined result before storing it.
+    fn apply_properties(&self, mut axis: Axis, pairs: &[String]) -> CliResult<Axis> {
+        if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+            return Err(CliError::invalid_input(
+                "Expected 'set <property> <value> [<property> <value>...]'",
+            ));
+        }
+
+        for pair in pairs.chunks(2) {
+            let (property, value) = (pair[0].as_str(), pair[1].as_str());
+            let value: f64 = self.parse_number(value, property)?;
+            if !value.is_finite() {
+                return Err(CliError::invalid_input(&format!(
+                    "{property} must be a finite number, got '{}'",
+                    pair[1]
+                )));
+            }
+
+            match property {
+                "min" => axis.min = Some(value),
+                "max" => axis.max = Some(value),
+                _ => {
+                    return Err(CliError::invalid_input(&format!(
+                        "Unknown axis property '{property}' (expected min or max)"
+                    )));
+                }
+            }
+        }
+
+        Ok(axis)
     }
 }
 
+impl ArgumentValidator for AxisCommand {}
+
 impl Command for AxisCommand {
     fn name(&self) -> &'static str {
         "axis"
     }
 
     fn description(&self) -> &'static str {
-        match self.context.as_str() {
-            "edit" => "Configure axis properties for the editing environment",
-            "view" => "Configure axis properties for the viewing environment",
-            _ => "Configure axis properties",
-        }
+        "Configure axis properties"
     }
 
     fn aliases(&self) -> Vec<&'static str> {
         vec!["a"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
-        // Axis command can take 0 or 1 arguments (optional axis name)
-        if args.len() > 1 {
-            return Err(CliError::TooManyArguments {
-                expected: 1,
-                found: args.len(),
-            });
+    fn usage(&self) -> String {
+        "axis [name] [set <property> <value>...]".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        let (_, set_args) = self.parse_args(args)?;
+        if let Some(pairs) = set_args {
+            // Bounds are checked against the stored axis when the command runs
+            self.apply_properties(Axis::default(), pairs)?;
         }
+        Ok(())
+    }
 
-        let axis_name = if args.is_empty() {
-            "default".to_string()
-        } else {
-            args[0].clone()
-        };
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
 
-        // Validate axis name
-        if axis_name.trim().is_empty() {
-            return Err(CliError::invalid_input("Axis name cannot be empty"));
-        }
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        let (axis_name, set_args) = self.parse_args(args)?;
 
-        // Validate axis name format
-        if !axis_name
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-        {
-            return Err(CliError::invalid_input(
-                "Axis name can only contain alphanumeric characters, underscores, and hyphens",
-            ));
-        }
+        // The enclosing menu is the path segment before this command
+        let menu = match path {
+            [.., menu, _] => menu.as_str(),
+            _ => "",
+        };
 
-        match self.context.as_str() {
-            "edit" => println!("Configuring axis properties for editing: {axis_name}"),
-            "view" => println!("Configuring axis properties for viewing: {axis_name}"),
-            _ => println!("Configuring axis properties: {axis_name}"),
+        if let Some(pairs) = set_args {
+            let current = context
+                .axes(menu)
+                .and_then(|axes| axes.get(axis_name))
+                .copied()
+                .unwrap_or_default();
+            let axis = self.apply_properties(current, pairs)?;
+            context.axes_mut(menu).set(axis_name, axis)?;
+            context
+                .display()
+                .display_success(&format!("Axis '{axis_name}': {axis}"));
+            return Ok(CommandResult::success_silent());
         }
 
+        let message = match menu {
+            "edit" => format!("Configuring axis properties for editing: {axis_name}"),
+            "view" => format!("Configuring axis properties for viewing: {axis_name}"),
+            _ => format!("Configuring axis properties: {axis_name}"),
+        };
+        context.display().display_info(&message);
+
         Ok(CommandResult::Continue)
     }
 
@@ -97,4 +205,8 @@ impl Command for AxisCommand {
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        self.category
+    }
 }
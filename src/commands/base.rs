@@ -3,7 +3,7 @@
 //! This module provides foundational command implementations that can be
 //! reused by various menus throughout the application.
 
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
 
 /// Base info command that displays information about the current menu
 ///
@@ -33,7 +33,11 @@ impl Command for InfoCommand {
         vec!["i"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+    fn hidden(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
         // Validate arguments - info command takes no arguments
         if !args.is_empty() {
             return Err(CliError::TooManyArguments {
@@ -42,10 +46,26 @@ impl Command for InfoCommand {
             });
         }
 
-        println!("{} menu information:", self.command);
-        println!("Available commands in this menu:");
-        println!("  Type any command name to execute it");
-        println!("  Use 'exit' (or 'e') to return to parent menu");
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let display = context.display();
+        display.display_text(&format!("{} menu information:", self.command));
+        display.display_text("Available commands in this menu:");
+        display.display_text("  Type any command name to execute it");
+        display.display_text("  Use 'exit' (or 'e') to return to parent menu");
         Ok(CommandResult::Success(String::new()))
     }
 }
@@ -83,7 +103,7 @@ impl Command for ExitCommand {
         vec!["e"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+    fn validate(&self, args: &[String]) -> CliResult<()> {
         // Validate arguments - exit command takes no arguments
         if !args.is_empty() {
             return Err(CliError::TooManyArguments {
@@ -92,6 +112,16 @@ impl Command for ExitCommand {
             });
         }
 
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
         Ok(CommandResult::GoUp)
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
 }
@@ -0,0 +1,149 @@
+//! Config command implementation for inspecting the session preferences.
+//!
+//! `config show` prints the preferences in effect as a configuration file,
+//! in the same commented TOML that `--init` writes, so it can be pasted
+//! into one. `config path` prints where the session reads its
+//! configuration from, and `config edit` opens that file in `$EDITOR` when
+//! the session is interactive; `reload` then applies the edits.
+//!
+//! The module is named `config_cmd` because the crate root re-exports both
+//! `commands` and `core`, and `core::config` already takes the name.
+
+use crate::{ArgumentValidator, CliContext, CliError, CliResult, Command, CommandResult};
+use std::path::PathBuf;
+use std::process;
+
+/// Actions the config command accepts, in the order usage lists them
+const ACTIONS: [&str; 3] = ["show", "path", "edit"];
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Config command that shows the preferences and the config file
+#[derive(Debug)]
+pub struct ConfigCommand;
+
+impl Default for ConfigCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigCommand {
+    /// Creates a new ConfigCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::config_cmd::ConfigCommand;
+    /// let config_cmd = ConfigCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        ConfigCommand
+    }
+
+    /// Get the configuration file of the session
+    fn config_path(context: &CliContext) -> CliResult<PathBuf> {
+        context.config_path().map(PathBuf::from).ok_or_else(|| {
+            CliError::execution_error("No configuration file (configuration is off)")
+        })
+    }
+
+    /// Open the configuration file in the user's editor and wait for it
+    ///
+    /// The editor command may carry arguments, as in `EDITOR="code -w"`.
+    fn edit(context: &mut CliContext) -> CliResult<CommandResult> {
+        if !context.io().input_is_terminal() {
+            return Err(CliError::terminal_error(
+                "'config edit' requires an interactive terminal",
+            ));
+        }
+        let path = Self::config_path(context)?;
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or(FALLBACK_EDITOR);
+
+        let status = process::Command::new(program)
+            .args(words)
+            .arg(&path)
+            .status()
+            .map_err(|e| {
+                CliError::execution_error(&format!("Could not start editor '{program}': {e}"))
+            })?;
+        if !status.success() {
+            return Err(CliError::execution_error(&format!(
+                "Editor '{program}' exited with {status}"
+            )));
+        }
+
+        Ok(CommandResult::success_fmt(format_args!(
+            "Edited {}; use 'reload' to apply the changes",
+            path.display()
+        )))
+    }
+}
+
+impl ArgumentValidator for ConfigCommand {}
+
+impl Command for ConfigCommand {
+    fn name(&self) -> &'static str {
+        "config"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the preferences in effect or the configuration file"
+    }
+
+    fn usage(&self) -> String {
+        "config <show|path|edit>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Config command takes exactly one action
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+        self.validate_arg_count(args, 1)?;
+        if !ACTIONS.contains(&args[0].as_str()) {
+            return Err(CliError::invalid_input(&format!(
+                "Unknown config action: {} (expected {})",
+                args[0],
+                ACTIONS.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        match args[0].as_str() {
+            "show" => {
+                let text = context.preferences().to_toml();
+                context.display().display_text(text.trim_end());
+            }
+            "path" => {
+                let path = Self::config_path(context)?;
+                context.display().display_text(&path.display().to_string());
+            }
+            _ => return Self::edit(context),
+        }
+        Ok(CommandResult::success_silent())
+    }
+}
@@ -0,0 +1,142 @@
+//! Diff command implementation for comparing two files.
+//!
+//! This command prints the changes between two text files as a unified
+//! diff, using the same line diff and theme colors as `save --diff`. Both
+//! paths are validated against the working root and size-checked before
+//! they are read, and binary files are refused, since a line diff of them
+//! means nothing.
+
+use crate::core::line_diff::unified_diff;
+use crate::core::security::{FileFormat, detect_format, sanitize_for_display};
+use crate::{
+    ArgKind, ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory,
+    CommandResult,
+};
+use std::fs;
+
+/// Diff command for showing the changes between two files
+#[derive(Debug)]
+pub struct DiffCommand;
+
+impl Default for DiffCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffCommand {
+    /// Creates a new DiffCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::diff::DiffCommand;
+    /// let diff_cmd = DiffCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        DiffCommand
+    }
+
+    /// Read a file to compare, as sanitized lines
+    ///
+    /// The lines are sanitized before they are colored, so file contents
+    /// cannot send escape codes to the terminal.
+    fn read_lines(filename: &str, context: &CliContext) -> CliResult<Vec<String>> {
+        let security = context.security();
+        let path = security.validate_path(filename)?;
+        if !path.is_file() {
+            return Err(CliError::invalid_input(&format!(
+                "Not a regular file: {}",
+                sanitize_for_display(filename)
+            )));
+        }
+
+        security.validate_size(fs::metadata(&path)?.len())?;
+        let bytes = fs::read(&path)?;
+        if detect_format(&bytes) == FileFormat::Binary {
+            return Err(CliError::InvalidFileFormat(format!(
+                "binary file: {}",
+                sanitize_for_display(filename)
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(sanitize_for_display)
+            .collect())
+    }
+}
+
+impl ArgumentValidator for DiffCommand {}
+
+impl Command for DiffCommand {
+    fn name(&self) -> &'static str {
+        "diff"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the changes between two text files as a unified diff"
+    }
+
+    fn usage(&self) -> String {
+        "diff <fileA> <fileB>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Diff command takes exactly two filenames
+        if args.len() < 2 {
+            return Err(CliError::TooFewArguments {
+                expected: 2,
+                found: args.len(),
+            });
+        }
+        self.validate_arg_count(args, 2)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let old = Self::read_lines(&args[0], context)?;
+        let new = Self::read_lines(&args[1], context)?;
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+
+        let (old_name, new_name) = (
+            sanitize_for_display(&args[0]),
+            sanitize_for_display(&args[1]),
+        );
+        let diff = unified_diff(&old_name, &new_name, &old, &new, &context.theme());
+        if diff.is_empty() {
+            return Ok(CommandResult::success_fmt(format_args!(
+                "No differences between {old_name} and {new_name}"
+            )));
+        }
+
+        let display = context.display();
+        for line in &diff {
+            display.display_text(line);
+        }
+        Ok(CommandResult::success_silent())
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, index: usize) -> ArgKind {
+        // Both arguments are filenames
+        if index < 2 {
+            ArgKind::File
+        } else {
+            ArgKind::None
+        }
+    }
+}
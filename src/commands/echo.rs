@@ -0,0 +1,87 @@
+//! Echo command implementation for annotating scripts.
+//!
+//! This command prints its arguments joined by spaces, sanitized like any
+//! other output. It is handy for labelling the steps of a script's output
+//! and for checking how a line is split into arguments.
+
+use crate::core::security::sanitize_for_display;
+use crate::{CliContext, CliResult, Command, CommandCategory, CommandResult};
+
+/// Echo command that prints its arguments
+///
+/// The session loop makes this command available in every menu. It is
+/// hidden from listings, since it is meant for scripts.
+#[derive(Debug)]
+pub struct EchoCommand;
+
+impl Default for EchoCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EchoCommand {
+    /// Creates a new EchoCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::echo::EchoCommand;
+    /// let echo_cmd = EchoCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        EchoCommand
+    }
+}
+
+impl Command for EchoCommand {
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Print the arguments"
+    }
+
+    fn usage(&self) -> String {
+        "echo [-n] [text...]".to_string()
+    }
+
+    fn hidden(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, _args: &[String]) -> CliResult<()> {
+        // Echo command accepts any arguments, including none
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        // A leading -n leaves off the newline, as in the shell
+        let (newline, words) = match args {
+            [flag, rest @ ..] if flag == "-n" => (false, rest),
+            _ => (true, args),
+        };
+
+        let text = sanitize_for_display(&words.join(" "));
+        let display = context.display();
+        if newline {
+            display.display_text(&text);
+        } else {
+            display.write_text(&text);
+        }
+        Ok(CommandResult::success_silent())
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
+}
@@ -1,19 +1,26 @@
 //! Edit command implementation for editing operations.
 //!
 //! This command provides editing functionality with subcommands for
-//! configuring axis properties and displaying edit state. It serves
+//! editing buffer lines, configuring axis properties, and displaying edit
+//! state. It serves
 //! as the main entry point for all editing-related operations.
 
 use super::axis::AxisCommand;
 use super::base::{ExitCommand, InfoCommand};
+use super::grep::GrepCommand;
+use super::lines::{AppendCommand, DeleteCommand, InsertCommand};
+use super::load_stdin::LoadStdinCommand;
 use super::show::ShowCommand;
-use crate::{CliError, CliResult, Command, CommandResult};
+use super::undo::{RedoCommand, UndoCommand};
+use crate::{CliError, CliResult, Command, CommandCategory, CommandResult};
 
-/// Edit command handling "Axis", "Show", "Info", and "Exit"
+/// Edit command handling "Append", "Insert", "Delete", "Axis", "Show", "Undo",
+/// "Redo", "Info", and "Exit"
 ///
 /// This command provides a submenu for editing operations including
-/// axis configuration, state display, and help functionality. It creates
-/// context-aware subcommands that understand they are operating in edit mode.
+/// line editing, axis configuration, state display, undoing and redoing
+/// buffer edits, and help functionality. Its subcommands read their command path to
+/// know that they are operating in edit mode.
 #[derive(Debug)]
 pub struct EditCommand;
 
@@ -42,14 +49,14 @@ impl Command for EditCommand {
     }
 
     fn description(&self) -> &'static str {
-        "Edit operations: Axis, Show, Info, Exit"
+        "Edit operations: Append, Insert, Delete, Axis, Show, Undo, Redo, Info, Exit"
     }
 
     fn aliases(&self) -> Vec<&'static str> {
         vec!["e"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+    fn validate(&self, args: &[String]) -> CliResult<()> {
         // Validate arguments - edit command takes no arguments when used as menu
         if !args.is_empty() {
             return Err(CliError::TooManyArguments {
@@ -58,15 +65,32 @@ impl Command for EditCommand {
             });
         }
 
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
         Ok(CommandResult::Continue)
     }
 
     fn subcommands(&self) -> Vec<Box<dyn Command>> {
         vec![
-            Box::new(AxisCommand::new("edit")),
-            Box::new(ShowCommand::new("edit")),
+            Box::new(AppendCommand::new()),
+            Box::new(InsertCommand::new()),
+            Box::new(DeleteCommand::new()),
+            Box::new(AxisCommand::with_category(CommandCategory::Edit)),
+            Box::new(ShowCommand::with_category(CommandCategory::Edit)),
+            Box::new(GrepCommand::with_category(CommandCategory::Edit)),
+            Box::new(LoadStdinCommand::new()),
+            Box::new(UndoCommand::new()),
+            Box::new(RedoCommand::new()),
             Box::new(InfoCommand::new(self.name())),
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
 }
@@ -0,0 +1,110 @@
+//! Export command implementation for dumping session state.
+//!
+//! This command writes a snapshot of the session (the edit buffer, the
+//! axes of each menu, and the preferences) as JSON, TOML, or YAML, either
+//! to a file or to the terminal.
+
+use crate::core::security::sanitize_for_display;
+use crate::core::snapshot::{SessionSnapshot, find_format};
+use crate::{ArgumentValidator, CliContext, CliError, CliResult, Command, CommandResult};
+use std::fs;
+
+/// Export command that writes the session state in a chosen format
+///
+/// With only a format the snapshot is shown; with a filename it is written
+/// there. Replacing an existing file counts as destructive.
+#[derive(Debug)]
+pub struct ExportCommand;
+
+impl Default for ExportCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportCommand {
+    /// Creates a new ExportCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::export::ExportCommand;
+    /// let export_cmd = ExportCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        ExportCommand
+    }
+}
+
+impl ArgumentValidator for ExportCommand {}
+
+impl Command for ExportCommand {
+    fn name(&self) -> &'static str {
+        "export"
+    }
+
+    fn description(&self) -> &'static str {
+        "Write the session state as JSON, TOML, or YAML"
+    }
+
+    fn usage(&self) -> String {
+        "export <json|toml|yaml> [filename]".to_string()
+    }
+
+    fn is_destructive(&self, args: &[String], context: &CliContext) -> bool {
+        args.get(1).is_some_and(|filename| {
+            context
+                .security()
+                .validate_path(filename)
+                .is_ok_and(|path| path.is_file())
+        })
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Export command takes a format and an optional filename
+        self.validate_exact_or_range(args, 1, 2)?;
+        find_format(&args[0])?;
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let format = find_format(&args[0])?;
+        let text = format.serialize(&SessionSnapshot::capture(context));
+
+        let Some(filename) = args.get(1) else {
+            context.display().display_text(text.trim_end());
+            return Ok(CommandResult::success_silent());
+        };
+
+        let security = context.security();
+        let path = security.validate_write_path(filename)?;
+        if path.is_dir() {
+            return Err(CliError::invalid_input(&format!(
+                "Cannot export over a directory: {}",
+                sanitize_for_display(filename)
+            )));
+        }
+        security.validate_extension(&path)?;
+        security.validate_size(text.len() as u64)?;
+        fs::write(&path, &text)?;
+
+        let message = format!(
+            "Exported session state as {} to {}",
+            format.name(),
+            sanitize_for_display(filename)
+        );
+        context.display().display_success(&message);
+
+        Ok(CommandResult::success_silent())
+    }
+}
@@ -2,23 +2,30 @@
 //!
 //! This command provides file operation functionality including loading,
 //! saving, and version information. It serves as a submenu for all
-//! file-related operations and demonstrates recursive command structure.
+//! file-related operations and can demonstrate recursive command structure
+//! when the `enable_recursive_menus` preference is on.
 
 use super::base::{ExitCommand, InfoCommand};
+use super::diff::DiffCommand;
 use super::load::LoadCommand;
+use super::ls::LsCommand;
+use super::open::OpenCommand;
 use super::save::SaveCommand;
+use super::save_as::SaveAsCommand;
+use super::stat::StatCommand;
+use super::touch::TouchCommand;
 use super::vers::VersCommand;
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
 
-/// File command handling "Load", "Save", "Version", "Info", and "Exit"
+/// File command handling "Ls", "Load", "Save", "Version", "Info", and "Exit"
 ///
-/// This command provides a submenu for file operations including loading files,
-/// saving files, displaying version information, and accessing help. It supports
-/// recursive functionality by allowing nested file menus.
+/// This command provides a submenu for file operations including listing and loading files,
+/// saving files, displaying version information, and accessing help. With
+/// recursive menus enabled it also offers a nested file menu.
 #[derive(Debug)]
 pub struct FileCommand {
-    #[allow(dead_code)]
-    parent_context: Option<String>,
+    /// Whether the menu offers a nested file menu
+    recursive: bool,
 }
 
 impl Default for FileCommand {
@@ -28,7 +35,7 @@ impl Default for FileCommand {
 }
 
 impl FileCommand {
-    /// Creates a new FileCommand with no parent context
+    /// Creates a new FileCommand without a nested file menu
     ///
     /// # Examples
     /// ```
@@ -36,25 +43,27 @@ impl FileCommand {
     /// let file_cmd = FileCommand::new();
     /// ```
     pub fn new() -> Self {
-        FileCommand {
-            parent_context: None,
-        }
+        Self::with_recursion(false)
     }
 
-    /// Creates a new FileCommand with a parent context for recursive functionality
+    /// Creates a new FileCommand, optionally offering a nested file menu
+    ///
+    /// When entered through the session loop the menu follows the
+    /// `enable_recursive_menus` preference instead.
     ///
     /// # Arguments
-    /// * `parent` - The parent context string
+    /// * `recursive` - Whether the menu offers a nested file menu
     ///
     /// # Examples
     /// ```
     /// use sm_menu::commands::file::FileCommand;
-    /// let recursive_file_cmd = FileCommand::new_with_parent("file");
+    /// use sm_menu::Command;
+    ///
+    /// let recursive_file_cmd = FileCommand::with_recursion(true);
+    /// assert!(recursive_file_cmd.subcommands().iter().any(|cmd| cmd.name() == "file"));
     /// ```
-    pub fn new_with_parent(parent: &str) -> Self {
-        FileCommand {
-            parent_context: Some(parent.to_string()),
-        }
+    pub fn with_recursion(recursive: bool) -> Self {
+        FileCommand { recursive }
     }
 }
 
@@ -64,14 +73,14 @@ impl Command for FileCommand {
     }
 
     fn description(&self) -> &'static str {
-        "File operations: Load, Save, Version, Info, Exit"
+        "File operations: Ls, Load, Save, Version, Info, Exit"
     }
 
     fn aliases(&self) -> Vec<&'static str> {
         vec!["f"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+    fn validate(&self, args: &[String]) -> CliResult<()> {
         // Validate arguments - file command takes no arguments when used as menu
         if !args.is_empty() {
             return Err(CliError::TooManyArguments {
@@ -80,18 +89,48 @@ impl Command for FileCommand {
             });
         }
 
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
         Ok(CommandResult::Continue)
     }
 
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        // The entered menu is this instance, so its subcommands follow the preference
+        self.recursive = context.preferences().enable_recursive_menus;
+        self.execute(args)
+    }
+
     fn subcommands(&self) -> Vec<Box<dyn Command>> {
-        vec![
+        let mut subcommands: Vec<Box<dyn Command>> = vec![
+            Box::new(LsCommand::new()),
             Box::new(LoadCommand::new()),
             Box::new(SaveCommand::new()),
+            Box::new(SaveAsCommand::new()),
+            Box::new(TouchCommand::new()),
+            Box::new(StatCommand::new()),
+            Box::new(DiffCommand::new()),
+            Box::new(OpenCommand::new()),
             Box::new(VersCommand::new()),
+        ];
+        if self.recursive {
             // Recursive file command - creates a nested file menu
-            Box::new(FileCommand::new_with_parent("file")),
-            Box::new(InfoCommand::new(self.name())),
-            Box::new(ExitCommand::new()),
-        ]
+            subcommands.push(Box::new(FileCommand::with_recursion(true)));
+        }
+        subcommands.push(Box::new(InfoCommand::new(self.name())));
+        subcommands.push(Box::new(ExitCommand::new()));
+        subcommands
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
     }
 }
@@ -0,0 +1,100 @@
+//! Goto command implementation for jumping through a large buffer.
+//!
+//! This command scrolls the buffer viewer so a given line is at the top
+//! and prints one screenful of lines from there, numbered and sanitized
+//! like `show`. The viewer position is kept in the session context and
+//! returns to line 1 when a new buffer is loaded.
+
+use super::lines::parse_line_number;
+use super::show::number_lines;
+use crate::ui::TerminalUtils;
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Goto command that scrolls the buffer viewer to a line
+#[derive(Debug)]
+pub struct GotoCommand;
+
+impl Default for GotoCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GotoCommand {
+    /// Creates a new GotoCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::goto::GotoCommand;
+    /// let goto_cmd = GotoCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        GotoCommand
+    }
+
+    /// Get the number of buffer lines shown per screen
+    ///
+    /// Two rows are left for the header and the next prompt.
+    pub fn viewport_height() -> usize {
+        TerminalUtils::get_height().saturating_sub(2).max(1)
+    }
+}
+
+impl Command for GotoCommand {
+    fn name(&self) -> &'static str {
+        "goto"
+    }
+
+    fn description(&self) -> &'static str {
+        "Scroll the buffer viewer to a line"
+    }
+
+    fn usage(&self) -> String {
+        "goto <n>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Goto command takes exactly one line number
+        match args.len() {
+            0 => Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            }),
+            1 => Ok(()),
+            found => Err(CliError::TooManyArguments { expected: 1, found }),
+        }
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let len = context.buffer().len();
+        if len == 0 {
+            return Err(CliError::invalid_input("The buffer is empty"));
+        }
+        let top = parse_line_number(&args[0], len)?;
+        context.set_viewport_top(top);
+
+        let bottom = (top + Self::viewport_height() - 1).min(len);
+        let lines = number_lines(&context.buffer().lines()[top - 1..bottom], top);
+        let display = context.display();
+        display.display_text(&format!("Lines {top}-{bottom} of {len}"));
+        for line in &lines {
+            display.display_text(line);
+        }
+        Ok(CommandResult::success_silent())
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::View
+    }
+}
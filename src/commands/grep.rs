@@ -0,0 +1,185 @@
+//! Grep command implementation for searching the edit buffer.
+//!
+//! This command lists the buffer lines that contain a pattern, with their
+//! 1-based line numbers, and reports how many matched. The match is a
+//! plain substring match, optionally ignoring case. Matched lines are
+//! sanitized, so loaded contents cannot send escape codes to the terminal.
+
+use crate::core::security::sanitize_for_display;
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Flag that makes the match ignore case
+const IGNORE_CASE: &str = "-i";
+
+/// Flag that reports only the number of matching lines
+const COUNT: &str = "-c";
+
+/// Flag that ends the options, so a pattern can start with `-`
+const END_OF_OPTIONS: &str = "--";
+
+/// Options and pattern of a grep command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GrepArgs {
+    /// Whether case is ignored
+    ignore_case: bool,
+    /// Whether only the count is shown
+    count_only: bool,
+    /// Text to search for
+    pattern: String,
+}
+
+/// Grep command for finding buffer lines that contain a pattern
+#[derive(Debug)]
+pub struct GrepCommand {
+    /// Help category, following the menu the command is offered in
+    category: CommandCategory,
+}
+
+impl Default for GrepCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GrepCommand {
+    /// Creates a new GrepCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::grep::GrepCommand;
+    /// let grep_cmd = GrepCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_category(CommandCategory::General)
+    }
+
+    /// Creates a new GrepCommand listed under the given help category
+    ///
+    /// # Arguments
+    /// * `category` - The category of the menu offering the command
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::grep::GrepCommand;
+    /// use sm_menu::{Command, CommandCategory};
+    ///
+    /// let grep_cmd = GrepCommand::with_category(CommandCategory::Edit);
+    /// assert_eq!(grep_cmd.category(), CommandCategory::Edit);
+    /// ```
+    pub fn with_category(category: CommandCategory) -> Self {
+        GrepCommand { category }
+    }
+
+    /// Split the arguments into options and the pattern
+    ///
+    /// Options come first. The remaining arguments are joined with spaces,
+    /// so a pattern with spaces need not be quoted.
+    fn parse_args(args: &[String]) -> CliResult<GrepArgs> {
+        let mut ignore_case = false;
+        let mut count_only = false;
+        let mut rest = args;
+
+        while let [arg, tail @ ..] = rest {
+            match arg.as_str() {
+                IGNORE_CASE => ignore_case = true,
+                COUNT => count_only = true,
+                END_OF_OPTIONS => {
+                    rest = tail;
+                    break;
+                }
+                option if option.starts_with('-') && option.len() > 1 => {
+                    return Err(CliError::invalid_input(&format!(
+                        "Unknown option: {option} (use -- before a pattern that starts with -)"
+                    )));
+                }
+                _ => break,
+            }
+            rest = tail;
+        }
+
+        if rest.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+
+        Ok(GrepArgs {
+            ignore_case,
+            count_only,
+            pattern: rest.join(" "),
+        })
+    }
+}
+
+impl Command for GrepCommand {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the buffer lines that contain a pattern"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["search"]
+    }
+
+    fn usage(&self) -> String {
+        format!("grep [{IGNORE_CASE}] [{COUNT}] [{END_OF_OPTIONS}] <pattern>")
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Grep command needs a pattern after any options
+        Self::parse_args(args).map(|_| ())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        let args = Self::parse_args(args)?;
+
+        let pattern = if args.ignore_case {
+            args.pattern.to_lowercase()
+        } else {
+            args.pattern.clone()
+        };
+        let matches: Vec<String> = context
+            .buffer()
+            .lines()
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                if args.ignore_case {
+                    line.to_lowercase().contains(&pattern)
+                } else {
+                    line.contains(&pattern)
+                }
+            })
+            .map(|(index, line)| format!("{:>4}  {}", index + 1, sanitize_for_display(line)))
+            .collect();
+
+        if !args.count_only {
+            let display = context.display();
+            for line in &matches {
+                display.display_text(line);
+            }
+        }
+
+        Ok(match matches.len() {
+            1 => CommandResult::success("1 matching line"),
+            count => CommandResult::success_fmt(format_args!("{count} matching lines")),
+        })
+    }
+
+    fn category(&self) -> CommandCategory {
+        self.category
+    }
+}
@@ -3,38 +3,15 @@
 //! This command provides comprehensive help functionality including
 //! general help for all commands and specific help for individual
 //! commands. It formats output with visual enhancements like bold
-//! text for improved readability.
+//! text for improved readability. Specific help can be asked for any
+//! command in the tree, found with the same traversal other features use.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
-
-/// Format a command name with bold alias character
-///
-/// This function formats command names with ANSI escape codes to make
-/// the alias character bold for better visual distinction.
-///
-/// # Arguments
-/// * `name` - The command name to format
-/// * `alias` - Optional alias character to make bold
-///
-/// # Returns
-/// A formatted string with the alias character in bold
-fn format_command_with_alias(name: &str, alias: Option<&str>) -> String {
-    if let Some(alias_char) = alias {
-        if let Some(first_char) = name.chars().next() {
-            if alias_char.starts_with(first_char) {
-                // Bold the first character if it matches the alias
-                format!("\x1b[1m{}\x1b[0m{}", first_char, &name[1..])
-            } else {
-                name.to_string()
-            }
-        } else {
-            name.to_string()
-        }
-    } else {
-        name.to_string()
-    }
-}
+use super::{RootCommand, global_commands};
+use crate::core::resolve::suggest;
+use crate::{
+    ArgumentValidator, CliContext, CliError, CliResult, Command, CommandResult, CommandTreeIter,
+};
 
 /// Help command displaying available commands and their descriptions
 ///
@@ -62,8 +39,37 @@ impl HelpCommand {
     pub fn new() -> Self {
         HelpCommand
     }
+
+    /// Get every command in the tree, shallowest first, then the commands
+    /// available in every menu
+    fn all_commands() -> Vec<Box<dyn Command>> {
+        let mut tree: Vec<_> = CommandTreeIter::with_hidden(&RootCommand).collect();
+        tree.sort_by_key(|(path, _)| path.len());
+        tree.into_iter()
+            .map(|(_, cmd)| cmd)
+            .chain(global_commands(&RootCommand))
+            .collect()
+    }
+
+    /// Find a command anywhere in the tree by name, or failing that by alias
+    ///
+    /// A name offered in several menus, such as `axis`, finds the one
+    /// closest to the main menu, so `v` is `view` rather than `file > vers`.
+    fn find_command(name: &str) -> Option<Box<dyn Command>> {
+        let name = name.to_lowercase();
+        let commands = Self::all_commands();
+        let by_name = commands.iter().position(|cmd| cmd.name() == name);
+        let index = by_name.or_else(|| {
+            commands
+                .iter()
+                .position(|cmd| cmd.aliases().contains(&name.as_str()))
+        })?;
+        commands.into_iter().nth(index)
+    }
 }
 
+impl ArgumentValidator for HelpCommand {}
+
 impl Command for HelpCommand {
     fn name(&self) -> &'static str {
         "help"
@@ -77,98 +83,51 @@ impl Command for HelpCommand {
         vec!["h"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+    fn validate(&self, args: &[String]) -> CliResult<()> {
         // Help command can take 0 or 1 arguments (optional command name for specific help)
-        if args.len() > 1 {
-            return Err(CliError::TooManyArguments {
-                expected: 1,
-                found: args.len(),
-            });
-        }
+        self.validate_exact_or_range(args, 0, 1)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
 
         if args.is_empty() {
-            // Show general help
-            println!("simple-menu Help");
-            println!("===========");
-            println!("Available commands:");
-            println!(
-                "  {} - File operations: Load, Save, Version, Info, Exit",
-                format_command_with_alias("file", Some("f"))
-            );
-            println!(
-                "  {} - Edit operations: Axis, Show, Info, Exit",
-                format_command_with_alias("edit", Some("e"))
-            );
-            println!(
-                "  {} - View operations: Axis, Show, Info, Exit",
-                format_command_with_alias("view", Some("v"))
-            );
-            println!(
-                "  {} - Display help information for available commands",
-                format_command_with_alias("help", Some("h"))
-            );
-            println!(
-                "  {} - Exit the program",
-                format_command_with_alias("quit", Some("q"))
-            );
-            println!();
-            println!("Type a command name to enter its submenu or see its options.");
-            println!("Use 'help <command>' for specific command help.");
+            // Show general help, with the main menu's commands grouped by category
+            let display = context.display();
+            display.display_text("simple-menu Help");
+            display.display_text("===========");
+            display.display_commands_by_category(&RootCommand.subcommands());
+            display.display_text("In any menu:");
+            display.display_text("  which - Show which command a token resolves to");
+            display.display_text("  menu - Pick a command of this menu by number");
+            display.display_text("  tree - Draw the commands below this menu");
+            display.display_text("  watch - Re-run a command every interval until Ctrl-C");
+            display.display_text("");
+            display.display_text("Type a command name to enter its submenu or see its options.");
+            display.display_text("Any unambiguous prefix of a command name works too.");
+            display.display_text("Use 'help <command>' for specific command help.");
         } else {
-            // Show specific command help
+            // Show the structured help of the named command
             let command_name = &args[0];
-            match command_name.as_str() {
-                "file" | "f" => {
-                    println!("File Command Help");
-                    println!("=================");
-                    println!("The file command provides file operation functionality.");
-                    println!("Subcommands:");
-                    println!("  load <filename> - Load a file");
-                    println!("  save [filename] - Save a file (default: untitled.txt)");
-                    println!("  vers - Show version information");
-                    println!("  info - Show file menu information");
-                    println!("  exit - Return to main menu");
-                }
-                "edit" | "e" => {
-                    println!("Edit Command Help");
-                    println!("=================");
-                    println!("The edit command provides editing functionality.");
-                    println!("Subcommands:");
-                    println!("  axis [name] - Configure axis properties");
-                    println!("  show - Display current edit state");
-                    println!("  info - Show edit menu information");
-                    println!("  exit - Return to main menu");
-                }
-                "view" | "v" => {
-                    println!("View Command Help");
-                    println!("=================");
-                    println!("The view command provides viewing functionality.");
-                    println!("Subcommands:");
-                    println!("  axis [name] - Configure axis properties for viewing");
-                    println!("  show - Display current view state");
-                    println!("  info - Show view menu information");
-                    println!("  exit - Return to main menu");
-                }
-                "help" | "h" => {
-                    println!("Help Command Help");
-                    println!("=================");
-                    println!("The help command displays information about available commands.");
-                    println!("Usage:");
-                    println!("  help        - Show general help");
-                    println!("  help <cmd>  - Show specific command help");
-                }
-                "quit" | "q" => {
-                    println!("Quit Command Help");
-                    println!("=================");
-                    println!("The quit command exits the program.");
-                    println!("Usage: quit (no arguments)");
-                }
-                _ => {
-                    return Err(CliError::invalid_input(&format!(
-                        "No help available for command: {command_name}"
-                    )));
-                }
-            }
+            let command = Self::find_command(command_name).ok_or_else(|| {
+                let hint = match suggest(command_name, &Self::all_commands()) {
+                    Some(name) => format!("Did you mean '{name}'?"),
+                    None => "Use 'help' to list the commands.".to_string(),
+                };
+                CliError::invalid_input(&format!(
+                    "No help available for command: {command_name}. {hint}"
+                ))
+            })?;
+            context.display().display_help(command.as_ref());
         }
 
         Ok(CommandResult::Continue)
@@ -0,0 +1,86 @@
+//! History command implementation for reviewing entered commands.
+//!
+//! This command lists the commands entered in the session, oldest first,
+//! including any loaded from the persisted history. Entries that carry a
+//! timestamp show it; legacy entries without one show only the command.
+
+use crate::core::history_file::format_timestamp;
+use crate::{CliContext, CliError, CliResult, Command, CommandResult};
+
+/// History command that lists previously entered commands
+#[derive(Debug)]
+pub struct HistoryCommand;
+
+impl Default for HistoryCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HistoryCommand {
+    /// Creates a new HistoryCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::history::HistoryCommand;
+    /// let history_cmd = HistoryCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        HistoryCommand
+    }
+}
+
+impl Command for HistoryCommand {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn description(&self) -> &'static str {
+        "List previously entered commands"
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - history command takes no arguments
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        if context.history().is_empty() {
+            return Ok(CommandResult::success("No history yet"));
+        }
+
+        let lines: Vec<String> = context
+            .history()
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| match entry.timestamp {
+                Some(timestamp) => format!(
+                    "{:>4}  {}  {}",
+                    index + 1,
+                    format_timestamp(timestamp),
+                    entry.command
+                ),
+                None => format!("{:>4}  {}", index + 1, entry.command),
+            })
+            .collect();
+        Ok(CommandResult::success(lines.join("\n")))
+    }
+}
@@ -0,0 +1,262 @@
+//! Line editing command implementations for the edit buffer.
+//!
+//! These commands add, insert, and remove single lines of the shared edit
+//! buffer. Every change goes through the session context, so it marks the
+//! buffer dirty and can be undone. Line numbers are 1-based.
+
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Parse a 1-based line number that must lie within `1..=max`
+pub(crate) fn parse_line_number(arg: &str, max: usize) -> CliResult<usize> {
+    let number = arg
+        .parse::<usize>()
+        .map_err(|_| CliError::invalid_input(&format!("Invalid line number: '{arg}'")))?;
+
+    if number == 0 || number > max {
+        return Err(CliError::invalid_input(&format!(
+            "Line {number} out of range (1-{max})"
+        )));
+    }
+    Ok(number)
+}
+
+/// Append command that adds a line to the end of the buffer
+#[derive(Debug)]
+pub struct AppendCommand;
+
+impl Default for AppendCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppendCommand {
+    /// Creates a new AppendCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::lines::AppendCommand;
+    /// let append_cmd = AppendCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        AppendCommand
+    }
+}
+
+impl Command for AppendCommand {
+    fn name(&self) -> &'static str {
+        "append"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add a line to the end of the buffer"
+    }
+
+    fn usage(&self) -> String {
+        "append <text>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Append command needs the text of the new line
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let text = args.join(" ");
+        let line_number = context.edit_buffer(|buffer| {
+            buffer.lines_mut().push(text);
+            Ok(buffer.len())
+        })?;
+
+        Ok(CommandResult::success_fmt(format_args!(
+            "Appended line {line_number}"
+        )))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
+}
+
+/// Insert command that adds a line before a given line number
+#[derive(Debug)]
+pub struct InsertCommand;
+
+impl Default for InsertCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InsertCommand {
+    /// Creates a new InsertCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::lines::InsertCommand;
+    /// let insert_cmd = InsertCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        InsertCommand
+    }
+}
+
+impl Command for InsertCommand {
+    fn name(&self) -> &'static str {
+        "insert"
+    }
+
+    fn description(&self) -> &'static str {
+        "Insert a line at the given line number"
+    }
+
+    fn usage(&self) -> String {
+        "insert <n> <text>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Insert command needs a line number and the text of the new line
+        if args.len() < 2 {
+            return Err(CliError::TooFewArguments {
+                expected: 2,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        // Inserting one past the last line is the same as appending
+        let line_number = parse_line_number(&args[0], context.buffer().len() + 1)?;
+        let text = args[1..].join(" ");
+        context.edit_buffer(|buffer| {
+            buffer.lines_mut().insert(line_number - 1, text);
+            Ok(())
+        })?;
+
+        Ok(CommandResult::success_fmt(format_args!(
+            "Inserted line {line_number}"
+        )))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
+}
+
+/// Delete command that removes a line from the buffer
+#[derive(Debug)]
+pub struct DeleteCommand;
+
+impl Default for DeleteCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeleteCommand {
+    /// Creates a new DeleteCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::lines::DeleteCommand;
+    /// let delete_cmd = DeleteCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        DeleteCommand
+    }
+}
+
+impl Command for DeleteCommand {
+    fn name(&self) -> &'static str {
+        "delete"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete the line with the given line number"
+    }
+
+    fn usage(&self) -> String {
+        "delete <n>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Delete command expects exactly one argument (line number)
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+
+        if args.len() > 1 {
+            return Err(CliError::TooManyArguments {
+                expected: 1,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        if context.buffer().is_empty() {
+            return Err(CliError::invalid_input("The buffer is empty"));
+        }
+
+        let line_number = parse_line_number(&args[0], context.buffer().len())?;
+        context.edit_buffer(|buffer| {
+            buffer.lines_mut().remove(line_number - 1);
+            Ok(())
+        })?;
+
+        Ok(CommandResult::success_fmt(format_args!(
+            "Deleted line {line_number}"
+        )))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
+}
@@ -5,17 +5,28 @@
 //! operations and prevents directory traversal attacks.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::core::security::{FileFormat, detect_format, sanitize_for_display};
+use crate::core::tokenizer::pasted_path;
+use crate::{
+    ArgKind, CliContext, CliError, CliResult, Command, CommandCategory, CommandResult, EditBuffer,
+};
+use std::fs;
+use std::path::PathBuf;
 
 /// Load command for loading files from the filesystem
 ///
 /// This command handles file loading operations with security validation
 /// to prevent directory traversal attacks and ensure safe file access.
 /// It requires exactly one argument (the filename) and provides meaningful
-/// error messages for various failure conditions.
+/// error messages for various failure conditions. A plain number refers to
+/// an entry of the most recent `ls` listing instead. Binary files are
+/// refused unless `--force` is given.
 #[derive(Debug)]
 pub struct LoadCommand;
 
+/// Option that loads a binary file anyway
+const FORCE: &str = "--force";
+
 impl Default for LoadCommand {
     fn default() -> Self {
         Self::new()
@@ -33,6 +44,14 @@ impl LoadCommand {
     pub fn new() -> Self {
         LoadCommand
     }
+
+    /// Split off a leading `--force` flag
+    fn parse_args(args: &[String]) -> (bool, &[String]) {
+        match args.split_first() {
+            Some((first, rest)) if first == FORCE => (true, rest),
+            _ => (false, args),
+        }
+    }
 }
 
 impl Command for LoadCommand {
@@ -48,8 +67,14 @@ impl Command for LoadCommand {
         vec!["l"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
-        // Load command expects exactly one argument (filename)
+    fn usage(&self) -> String {
+        "load [--force] <filename | N>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Load command expects exactly one argument (filename), after an
+        // optional --force
+        let (_, args) = Self::parse_args(args);
         if args.is_empty() {
             return Err(CliError::TooFewArguments {
                 expected: 1,
@@ -64,6 +89,22 @@ impl Command for LoadCommand {
             });
         }
 
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let (force, args) = Self::parse_args(args);
         let filename = &args[0];
 
         // Validate filename
@@ -71,21 +112,35 @@ impl Command for LoadCommand {
             return Err(CliError::invalid_input("Filename cannot be empty"));
         }
 
-        // Simulate file loading with error handling
-        if filename.contains("..") {
-            return Err(CliError::invalid_input(
-                "Invalid filename: path traversal not allowed",
-            ));
-        }
-
-        println!("Loading file: {filename}");
+        let (display_name, path) = resolve_target(filename, context)?;
 
-        // Simulate file not found error for demonstration
-        if filename.ends_with(".missing") {
-            return Err(CliError::file_not_found(filename));
+        if !path.is_file() {
+            return Err(CliError::invalid_input(&format!(
+                "Not a regular file: {display_name}"
+            )));
         }
 
-        Ok(CommandResult::Continue)
+        let security = context.security();
+        security.validate_size(fs::metadata(&path)?.len())?;
+        let bytes = context.read_file(&path)?;
+        if detect_format(&bytes) == FileFormat::Binary && !force {
+            return Err(CliError::InvalidFileFormat(
+                "binary file; use --force".to_string(),
+            ));
+        }
+        // Forced binary contents keep what decodes; display sanitizes the rest
+        let text = String::from_utf8_lossy(&bytes);
+
+        let mut buffer = EditBuffer::from_text(&text);
+        buffer.set_filename(Some(display_name.clone()));
+        let line_count = buffer.len();
+        context.replace_buffer(buffer);
+
+        context.display().display_success(&format!(
+            "Loaded {line_count} lines from {}",
+            sanitize_for_display(&display_name)
+        ));
+        Ok(CommandResult::success_silent())
     }
 
     fn subcommands(&self) -> Vec<Box<dyn Command>> {
@@ -94,4 +149,60 @@ impl Command for LoadCommand {
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, _index: usize) -> ArgKind {
+        // Every argument is a filename (flags are typed, not completed)
+        ArgKind::File
+    }
+}
+
+/// Resolve a load argument to a display name and validated path
+///
+/// When a listing from `ls` is available, a numeric argument selects the
+/// entry with that listing number. The entry is re-validated, so a listing
+/// that has gone stale (file removed or replaced) is reported rather than
+/// silently loading something else. Otherwise a missing name is retried
+/// with the quoting undone, for paths pasted from a terminal.
+fn resolve_target(arg: &str, context: &CliContext) -> CliResult<(String, PathBuf)> {
+    let security = context.security();
+
+    if let (Ok(number), Some(listing)) = (arg.parse::<usize>(), context.last_listing()) {
+        let entry = listing.entry(number).ok_or_else(|| {
+            CliError::invalid_input(&format!(
+                "No entry {number} in the last listing (1-{})",
+                listing.entries().len()
+            ))
+        })?;
+
+        if entry.ends_with('/') {
+            return Err(CliError::invalid_input(&format!(
+                "Entry {number} is a directory: {entry}"
+            )));
+        }
+
+        let candidate = listing.dir().join(entry);
+        let path = security
+            .validate_path(&candidate.to_string_lossy())
+            .map_err(|e| match e {
+                CliError::FileNotFound(_) => CliError::file_not_found(&format!(
+                    "{entry} (listing is out of date; run 'ls' again)"
+                )),
+                other => other,
+            })?;
+        return Ok((entry.to_string(), path));
+    }
+
+    // A path pasted with its terminal quoting is only unquoted when nothing
+    // exists under the name as typed, so quotes that belong to a name work
+    match (security.validate_path(arg), pasted_path(arg)) {
+        (Err(CliError::FileNotFound(_)), Some(pasted)) => {
+            let path = security.validate_path(&pasted)?;
+            Ok((pasted, path))
+        }
+        (result, _) => Ok((arg.to_string(), result?)),
+    }
 }
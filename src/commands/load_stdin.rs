@@ -0,0 +1,89 @@
+//! Load-stdin command implementation for filling the edit buffer from a pipe.
+//!
+//! This command reads all remaining session input into the edit buffer,
+//! enabling pipelines such as `cat data | sm-menu`. It refuses to run when
+//! the input is an interactive terminal, where reading to end-of-input
+//! would block until the user sends EOF.
+
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Load-stdin command that reads piped input into the edit buffer
+#[derive(Debug)]
+pub struct LoadStdinCommand;
+
+impl Default for LoadStdinCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadStdinCommand {
+    /// Creates a new LoadStdinCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::load_stdin::LoadStdinCommand;
+    /// let load_stdin_cmd = LoadStdinCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        LoadStdinCommand
+    }
+}
+
+impl Command for LoadStdinCommand {
+    fn name(&self) -> &'static str {
+        "load-stdin"
+    }
+
+    fn description(&self) -> &'static str {
+        "Read piped standard input into the edit buffer"
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - load-stdin command takes no arguments
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        // Reading a terminal to end-of-input would hang waiting for Ctrl-D
+        if context.io().input_is_terminal() {
+            return Err(CliError::execution_error(
+                "load-stdin requires piped input; stdin is a terminal",
+            ));
+        }
+
+        let limit = context.security().max_size;
+        let text = context.io_mut().read_to_string(limit)?;
+        let line_count = context.edit_buffer(|buffer| {
+            buffer.set_text(&text);
+            buffer.set_filename(None);
+            Ok(buffer.len())
+        })?;
+
+        Ok(CommandResult::success_fmt(format_args!(
+            "Loaded {line_count} lines from stdin"
+        )))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
+}
@@ -0,0 +1,119 @@
+//! Ls command implementation for listing the working directory.
+//!
+//! This command lists the entries of the working root as a numbered,
+//! paginated listing. The listing is remembered in the session context so
+//! that `load N` can load the Nth entry.
+
+use crate::core::security::sanitize_for_display;
+use crate::{
+    CliContext, CliError, CliResult, Command, CommandCategory, CommandResult, DirectoryListing,
+};
+
+/// Ls command for listing the working directory one page at a time
+///
+/// Page size follows `CliPreferences::max_list_items`. Each entry is shown
+/// with its listing number, which stays stable across pages.
+#[derive(Debug)]
+pub struct LsCommand;
+
+impl Default for LsCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LsCommand {
+    /// Creates a new LsCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::ls::LsCommand;
+    /// let ls_cmd = LsCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        LsCommand
+    }
+}
+
+impl Command for LsCommand {
+    fn name(&self) -> &'static str {
+        "ls"
+    }
+
+    fn description(&self) -> &'static str {
+        "List files in the working directory"
+    }
+
+    fn usage(&self) -> String {
+        "ls [page]".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Ls command can take 0 or 1 arguments (optional page number)
+        if args.len() > 1 {
+            return Err(CliError::TooManyArguments {
+                expected: 1,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let page_number = match args.first() {
+            Some(arg) => arg
+                .parse::<usize>()
+                .map_err(|_| CliError::invalid_input(&format!("Invalid page number: '{arg}'")))?,
+            None => 1,
+        };
+
+        let listing = DirectoryListing::read(context.security().root())?;
+        let per_page = context.preferences().max_list_items;
+        let page = listing.page(page_number, per_page)?;
+
+        let mut lines = Vec::new();
+        if page.entries.is_empty() {
+            lines.push("(empty directory)".to_string());
+        }
+        for (offset, entry) in page.entries.iter().enumerate() {
+            lines.push(format!(
+                "  {:>4}. {}",
+                page.first_index + offset,
+                sanitize_for_display(entry)
+            ));
+        }
+        if page.total_pages > 1 {
+            let mut footer = format!("Page {} of {}", page.number, page.total_pages);
+            if page.number < page.total_pages {
+                footer.push_str(&format!(
+                    " (use 'ls {}' for the next page)",
+                    page.number + 1
+                ));
+            }
+            lines.push(footer);
+        }
+        let display = context.display();
+        for line in &lines {
+            display.display_text(line);
+        }
+
+        context.set_last_listing(listing);
+        Ok(CommandResult::success_silent())
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+}
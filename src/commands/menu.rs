@@ -0,0 +1,138 @@
+//! Menu command implementation for numbered command selection.
+//!
+//! This command lists the current menu's visible commands numbered 1..N,
+//! then reads a number from the session input and runs that command, so
+//! the CLI can be driven like a classic numbered menu.
+
+use crate::{CliContext, CliError, CliResult, Command, CommandResult};
+
+/// Menu command that runs a command chosen by number
+///
+/// The session loop makes this command available in every menu and
+/// constructs it with the commands of the menu it is run from. The chosen
+/// command is dispatched through the normal input path, so it is resolved,
+/// confirmed, and run exactly as if its name had been typed.
+#[derive(Debug)]
+pub struct MenuCommand {
+    siblings: Vec<Box<dyn Command>>,
+}
+
+impl Default for MenuCommand {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl MenuCommand {
+    /// Creates a new MenuCommand for a menu
+    ///
+    /// # Arguments
+    /// * `siblings` - The commands of the menu `menu` is run from
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::RootCommand;
+    /// use sm_menu::commands::menu::MenuCommand;
+    /// use sm_menu::Command;
+    ///
+    /// let menu_cmd = MenuCommand::new(RootCommand.subcommands());
+    /// ```
+    pub fn new(siblings: Vec<Box<dyn Command>>) -> Self {
+        MenuCommand { siblings }
+    }
+
+    /// Get the commands offered for selection, in menu order
+    fn choices(&self) -> Vec<&dyn Command> {
+        self.siblings
+            .iter()
+            .filter(|cmd| !cmd.hidden())
+            .map(|cmd| cmd.as_ref())
+            .collect()
+    }
+
+    /// Turn a selection into the name of the chosen command
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - The name of the command numbered `selection`
+    /// * `Err(CliError)` - `InvalidInput` if the selection is not a number
+    ///   from 1 to the number of choices
+    fn select(choices: &[&dyn Command], selection: &str) -> CliResult<&'static str> {
+        let selection = selection.trim();
+        selection
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| choices.get(index))
+            .map(|cmd| cmd.name())
+            .ok_or_else(|| {
+                CliError::invalid_input(&format!(
+                    "Selection must be a number from 1 to {}, got '{selection}'",
+                    choices.len()
+                ))
+            })
+    }
+}
+
+impl Command for MenuCommand {
+    fn name(&self) -> &'static str {
+        "menu"
+    }
+
+    fn description(&self) -> &'static str {
+        "List this menu's commands by number and run one"
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - menu command takes no arguments
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let choices = self.choices();
+        if choices.is_empty() {
+            return Err(CliError::invalid_input(
+                "This menu has no commands to select",
+            ));
+        }
+
+        let display = context.display();
+        for (cmd, number) in choices.iter().zip(1..) {
+            display.display_text(&format!(
+                "{number:>3}. {} - {}",
+                cmd.name(),
+                cmd.description()
+            ));
+        }
+
+        display.write_text(&format!("Select 1-{}: ", choices.len()));
+
+        let selection = context
+            .io_mut()
+            .read_line()?
+            .ok_or_else(|| CliError::invalid_input("No selection made"))?;
+        let chosen = Self::select(&choices, &selection)?;
+
+        Ok(CommandResult::Batch {
+            lines: vec![chosen.to_string()],
+            keep_going: false,
+        })
+    }
+}
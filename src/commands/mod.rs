@@ -1,14 +1,38 @@
 pub mod axis;
 pub mod base;
+pub mod config_cmd;
+pub mod diff;
+pub mod echo;
 pub mod edit;
+pub mod export;
 pub mod file;
+pub mod goto;
+pub mod grep;
 pub mod help;
+pub mod history;
+pub mod lines;
 pub mod load;
+pub mod load_stdin;
+pub mod ls;
+pub mod menu;
+pub mod open;
 pub mod quit;
+pub mod reload;
+pub mod repeat;
+pub mod reset;
 pub mod save;
+pub mod save_as;
 pub mod show;
+pub mod sleep;
+pub mod stat;
+pub mod system;
+pub mod touch;
+pub mod tree;
+pub mod undo;
 pub mod vers;
 pub mod view;
+pub mod watch;
+pub mod which;
 
 use self::base::InfoCommand;
 use crate::{CliResult, Command, CommandResult};
@@ -37,7 +61,39 @@ impl Command for RootCommand {
             Box::new(view::ViewCommand::new()),
             Box::new(help::HelpCommand::new()),
             Box::new(quit::QuitCommand::new()),
+            Box::new(repeat::RepeatCommand::new()),
+            Box::new(history::HistoryCommand::new()),
+            Box::new(reload::ReloadCommand::new()),
+            Box::new(config_cmd::ConfigCommand::new()),
+            Box::new(export::ExportCommand::new()),
+            Box::new(system::SystemCommand::new()),
             Box::new(InfoCommand::new(self.name())),
         ]
     }
 }
+
+/// Commands available in every menu in addition to the menu's own
+pub(crate) fn global_commands(menu: &dyn Command) -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(which::WhichCommand::new(menu.subcommands())),
+        Box::new(menu::MenuCommand::new(menu.subcommands())),
+        Box::new(tree::TreeCommand::new()),
+        Box::new(sleep::SleepCommand::new()),
+        Box::new(echo::EchoCommand::new()),
+        Box::new(watch::WatchCommand::new()),
+    ]
+}
+
+/// Get the commands a typed name is resolved against in a menu
+///
+/// Commands available at every menu are resolved alongside the menu's
+/// own, which take precedence over a global command of the same name.
+pub(crate) fn available_commands(menu: &dyn Command) -> Vec<Box<dyn Command>> {
+    let mut siblings = menu.subcommands();
+    let globals: Vec<_> = global_commands(menu)
+        .into_iter()
+        .filter(|global| !siblings.iter().any(|cmd| cmd.name() == global.name()))
+        .collect();
+    siblings.extend(globals);
+    siblings
+}
@@ -0,0 +1,206 @@
+//! Open command implementation for launching the system default application.
+//!
+//! This command hands a file inside the working root to the operating
+//! system's default handler: `open` on macOS, the `FileProtocolHandler` of
+//! `url.dll` on Windows, and `xdg-open` elsewhere. None of them go through
+//! a shell, so a file name is never read as shell syntax. The application
+//! is started without waiting for it, and a background thread reaps the
+//! launcher once it exits. Launching an application is only meant for
+//! someone at the terminal, so a script has to opt in with `--yes`.
+
+use crate::core::security::sanitize_for_display;
+use crate::{
+    ArgKind, ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory,
+    CommandResult,
+};
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::{self, Stdio};
+use std::thread;
+
+/// Program and arguments that open a file with the default application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Launcher {
+    /// Program to run
+    pub program: OsString,
+    /// Arguments to run it with, ending with the file
+    pub args: Vec<OsString>,
+}
+
+impl Launcher {
+    /// Build the launcher for the operating system the binary runs on
+    pub fn for_current_os(path: &Path) -> Self {
+        Self::for_os(std::env::consts::OS, path)
+    }
+
+    /// Build the launcher for an operating system, named as in
+    /// [`std::env::consts::OS`]
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::open::Launcher;
+    /// use std::path::Path;
+    ///
+    /// let launcher = Launcher::for_os("linux", Path::new("/srv/notes.txt"));
+    /// assert_eq!(launcher.program, "xdg-open");
+    /// assert_eq!(launcher.args, ["/srv/notes.txt"]);
+    /// ```
+    pub fn for_os(os: &str, path: &Path) -> Self {
+        let path = path.as_os_str().to_os_string();
+        match os {
+            "macos" => Launcher {
+                program: "open".into(),
+                args: vec![path],
+            },
+            // `cmd /C start` would read `&`, `|` and `^` in the file name
+            // as shell syntax; rundll32 hands the path straight to the shell
+            // API that opens files
+            "windows" => Launcher {
+                program: "rundll32".into(),
+                args: vec!["url.dll,FileProtocolHandler".into(), path],
+            },
+            _ => Launcher {
+                program: "xdg-open".into(),
+                args: vec![path],
+            },
+        }
+    }
+}
+
+/// Starts a launcher, for substituting in tests
+pub type Spawner = Box<dyn Fn(&Launcher) -> io::Result<()>>;
+
+/// Start a launcher as a separate process, without waiting for it
+///
+/// The child is waited for on a detached thread, so it does not linger as
+/// a zombie process once it exits.
+fn spawn_process(launcher: &Launcher) -> io::Result<()> {
+    let mut child = process::Command::new(&launcher.program)
+        .args(&launcher.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Open command that launches the default application for a file
+pub struct OpenCommand {
+    /// Starts the launcher for the file
+    spawner: Spawner,
+}
+
+impl fmt::Debug for OpenCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OpenCommand")
+    }
+}
+
+impl Default for OpenCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenCommand {
+    /// Creates a new OpenCommand that starts real processes
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::open::OpenCommand;
+    /// let open_cmd = OpenCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_spawner(Box::new(spawn_process))
+    }
+
+    /// Creates an OpenCommand that starts launchers with `spawner`
+    ///
+    /// Tests use this to see the launcher without starting an application.
+    pub fn with_spawner(spawner: Spawner) -> Self {
+        OpenCommand { spawner }
+    }
+}
+
+impl ArgumentValidator for OpenCommand {}
+
+impl Command for OpenCommand {
+    fn name(&self) -> &'static str {
+        "open"
+    }
+
+    fn description(&self) -> &'static str {
+        "Open a file with the system's default application"
+    }
+
+    fn usage(&self) -> String {
+        "open <filename>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Open command takes exactly one filename
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+        self.validate_arg_count(args, 1)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        // Nobody is at the terminal to see the application start
+        if !context.io().input_is_terminal() && !context.preferences().assume_yes {
+            return Err(CliError::terminal_error(
+                "'open' launches an application; pass --yes to allow it in a script",
+            ));
+        }
+
+        let path = context.security().validate_path(&args[0])?;
+        if !path.exists() {
+            return Err(CliError::file_not_found(&args[0]));
+        }
+
+        let launcher = Launcher::for_current_os(&path);
+        (self.spawner)(&launcher).map_err(|e| {
+            CliError::execution_error_with_source(
+                &format!("Could not start '{}'", launcher.program.to_string_lossy()),
+                e,
+            )
+        })?;
+
+        Ok(CommandResult::success_fmt(format_args!(
+            "Opened {}",
+            sanitize_for_display(&args[0])
+        )))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, index: usize) -> ArgKind {
+        // The only argument is a filename
+        if index == 0 {
+            ArgKind::File
+        } else {
+            ArgKind::None
+        }
+    }
+}
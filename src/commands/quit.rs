@@ -2,10 +2,13 @@
 //!
 //! This command provides functionality to gracefully exit the program.
 //! It ensures proper cleanup and displays a goodbye message before
-//! terminating the application.
+//! terminating the application. An optional exit code is passed on to
+//! the shell, so scripts can signal a result.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::{
+    CliContext, CliError, CliResult, Command, CommandCategory, CommandResult, parse_count,
+};
 
 /// Quit command that exits the program
 ///
@@ -32,6 +35,21 @@ impl QuitCommand {
     pub fn new() -> Self {
         QuitCommand
     }
+
+    /// Parse the optional exit code argument, defaulting to 0
+    fn parse_args(args: &[String]) -> CliResult<u8> {
+        match args {
+            [] => Ok(0),
+            // Exit codes are the counts 0 to 255
+            [code] => parse_count(code, u8::MAX.into())
+                .map(|code| code as u8)
+                .map_err(|_| CliError::invalid_input("Exit code must be a number from 0 to 255")),
+            _ => Err(CliError::TooManyArguments {
+                expected: 1,
+                found: args.len(),
+            }),
+        }
+    }
 }
 
 impl Command for QuitCommand {
@@ -47,16 +65,32 @@ impl Command for QuitCommand {
         vec!["q"]
     }
 
+    fn usage(&self) -> String {
+        "quit [exit-code]".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - quit command takes an optional exit code
+        Self::parse_args(args).map(|_| ())
+    }
+
     fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
-        // Validate arguments - quit command takes no arguments
-        if !args.is_empty() {
-            return Err(CliError::TooManyArguments {
-                expected: 0,
-                found: args.len(),
-            });
-        }
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        let code = Self::parse_args(args)?;
+        context.set_exit_code(code);
 
-        println!("Goodbye!");
+        // Plain output has no banners to say goodbye with
+        if !context.preferences().plain {
+            context.display().display_text("Goodbye!");
+        }
         Ok(CommandResult::Quit)
     }
 
@@ -66,4 +100,8 @@ impl Command for QuitCommand {
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
 }
@@ -0,0 +1,95 @@
+//! Reload command implementation for re-reading the configuration file.
+//!
+//! This command reads the configuration file the session was started with
+//! again and applies it to the running session, so edits to the file take
+//! effect without a restart. A file that fails to load leaves the current
+//! preferences untouched.
+
+use crate::{CliContext, CliError, CliPreferences, CliResult, Command, CommandResult};
+
+/// Reload command that re-reads preferences from the configuration file
+#[derive(Debug)]
+pub struct ReloadCommand;
+
+impl Default for ReloadCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReloadCommand {
+    /// Creates a new ReloadCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::reload::ReloadCommand;
+    /// let reload_cmd = ReloadCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        ReloadCommand
+    }
+}
+
+impl Command for ReloadCommand {
+    fn name(&self) -> &'static str {
+        "reload"
+    }
+
+    fn description(&self) -> &'static str {
+        "Re-read the configuration file"
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - reload command takes no arguments
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let path = context
+            .config_path()
+            .ok_or_else(|| {
+                CliError::execution_error("No configuration file to reload (configuration is off)")
+            })?
+            .to_path_buf();
+
+        // Parse the whole file before touching the live preferences
+        let mut preferences = CliPreferences::from_path(&path)?;
+        // --yes, --trace-errors, and --plain come from the command line, not the file
+        preferences.assume_yes = context.preferences().assume_yes;
+        preferences.trace_errors = context.preferences().trace_errors;
+        preferences.plain = context.preferences().plain;
+
+        let changes = preferences.changes_from(context.preferences());
+        *context.preferences_mut() = preferences;
+
+        let display = context.display();
+        if changes.is_empty() {
+            display.display_success(&format!("Reloaded {}: no changes", path.display()));
+        } else {
+            display.display_success(&format!("Reloaded {}", path.display()));
+            for change in &changes {
+                display.display_text(&format!("  {change}"));
+            }
+        }
+
+        Ok(CommandResult::success_silent())
+    }
+}
@@ -0,0 +1,120 @@
+//! Repeat command implementation for running a command several times.
+//!
+//! This meta-command takes a count and a command line and hands the line
+//! back to the session loop as a batch, so each repetition goes through the
+//! same dispatch path as typed input. It is mainly useful for testing and
+//! demos, e.g. `repeat 5 save`.
+
+use crate::core::tokenizer::join_tokens;
+use crate::{CliError, CliResult, Command, CommandResult, parse_count};
+
+/// Upper bound on the repeat count, to keep a typo from running away
+const MAX_REPEAT_COUNT: usize = 1000;
+
+/// Option that keeps repeating after a failed run
+const KEEP_GOING: &str = "--keep-going";
+
+/// Repeat command that runs a command line a fixed number of times
+///
+/// Repetition stops at the first error unless `--keep-going` is given
+/// before the command.
+#[derive(Debug)]
+pub struct RepeatCommand;
+
+impl Default for RepeatCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepeatCommand {
+    /// Creates a new RepeatCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::repeat::RepeatCommand;
+    /// let repeat_cmd = RepeatCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        RepeatCommand
+    }
+
+    /// Parse a repeat count, which must be a positive integer
+    fn parse_count(arg: &str) -> CliResult<usize> {
+        match parse_count(arg, MAX_REPEAT_COUNT)? {
+            0 => Err(CliError::invalid_input("Repeat count must be at least 1")),
+            count => Ok(count),
+        }
+    }
+
+    /// Split the arguments into the count, `--keep-going`, and the command line
+    fn parse_args<'a>(&self, args: &'a [String]) -> CliResult<(usize, bool, &'a [String])> {
+        // Options may appear before the command; everything after belongs to it
+        let mut keep_going = false;
+        let rest = Self::take_options(args, &mut keep_going);
+        let (count, rest) = match rest.split_first() {
+            Some((count, rest)) => (Self::parse_count(count)?, rest),
+            None => {
+                return Err(CliError::TooFewArguments {
+                    expected: 2,
+                    found: args.len(),
+                });
+            }
+        };
+        let command = Self::take_options(rest, &mut keep_going);
+
+        let Some(command_name) = command.first() else {
+            return Err(CliError::TooFewArguments {
+                expected: 2,
+                found: args.len(),
+            });
+        };
+
+        // Guard against recursion - repeat cannot repeat itself
+        if self.matches(command_name) {
+            return Err(CliError::invalid_input("repeat cannot repeat itself"));
+        }
+
+        Ok((count, keep_going, command))
+    }
+
+    /// Strip leading options, recording whether `--keep-going` was seen
+    fn take_options<'a>(mut args: &'a [String], keep_going: &mut bool) -> &'a [String] {
+        while let Some((first, rest)) = args.split_first() {
+            if first != KEEP_GOING {
+                break;
+            }
+            *keep_going = true;
+            args = rest;
+        }
+        args
+    }
+}
+
+impl Command for RepeatCommand {
+    fn name(&self) -> &'static str {
+        "repeat"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run a command several times"
+    }
+
+    fn usage(&self) -> String {
+        format!("repeat <count> [{KEEP_GOING}] <command> [args...]")
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        self.parse_args(args).map(|_| ())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        let (count, keep_going, command) = self.parse_args(args)?;
+
+        let line = join_tokens(command);
+        Ok(CommandResult::Batch {
+            lines: vec![line; count],
+            keep_going,
+        })
+    }
+}
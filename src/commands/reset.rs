@@ -0,0 +1,93 @@
+//! Reset command implementation for starting over without restarting.
+//!
+//! This command returns the session to a clean state: the buffer and its
+//! undo history are emptied, every axis is removed, and navigation goes
+//! back to the root menu. With `--history` the command history is cleared
+//! as well. Preferences, the working root, and the session's input and
+//! output are kept.
+
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Flag that also clears the command history
+const HISTORY_FLAG: &str = "--history";
+
+/// Reset command that clears the session state
+#[derive(Debug)]
+pub struct ResetCommand;
+
+impl Default for ResetCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResetCommand {
+    /// Creates a new ResetCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::reset::ResetCommand;
+    /// let reset_cmd = ResetCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        ResetCommand
+    }
+}
+
+impl Command for ResetCommand {
+    fn name(&self) -> &'static str {
+        "reset"
+    }
+
+    fn description(&self) -> &'static str {
+        "Clear the buffer, axes, and navigation (and history with --history)"
+    }
+
+    fn usage(&self) -> String {
+        format!("reset [{HISTORY_FLAG}]")
+    }
+
+    fn is_destructive(&self, _args: &[String], _context: &CliContext) -> bool {
+        true
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - reset command takes an optional --history flag
+        match args {
+            [] => Ok(()),
+            [flag] if flag == HISTORY_FLAG => Ok(()),
+            [other] => Err(CliError::invalid_input(&format!(
+                "Unknown option '{other}'. Usage: {}",
+                self.usage()
+            ))),
+            _ => Err(CliError::TooManyArguments {
+                expected: 1,
+                found: args.len(),
+            }),
+        }
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        context.reset();
+        if args.is_empty() {
+            return Ok(CommandResult::success("Session reset"));
+        }
+        context.clear_history();
+        Ok(CommandResult::success("Session and history reset"))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
+}
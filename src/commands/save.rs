@@ -1,18 +1,46 @@
 //! Save command implementation for saving files to the filesystem.
 //!
-//! This command provides functionality to save files to the filesystem
-//! with comprehensive validation and error handling. It ensures safe file
-//! operations and prevents unauthorized file access.
+//! This command writes the edit buffer to the filesystem with
+//! comprehensive validation and error handling. It ensures safe file
+//! operations and prevents unauthorized file access. With `--diff`, the
+//! changes an overwrite would make are shown before it is confirmed; with
+//! `--append`, the buffer is added to the end of the file instead.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::core::line_diff::unified_diff;
+use crate::core::security::sanitize_for_display;
+use crate::core::tokenizer::pasted_path;
+use crate::{ArgKind, CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+use std::fs;
+use std::path::Path;
+
+/// Default filename used when the buffer has none and no argument is given
+pub(crate) const DEFAULT_FILENAME: &str = "untitled.txt";
+
+/// Option that previews the changes to an existing file
+const DIFF: &str = "--diff";
+
+/// Option that appends to the file instead of overwriting it
+const APPEND: &str = "--append";
+
+/// How the buffer is written to the target file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SaveMode {
+    /// Replace the file's contents
+    Overwrite,
+    /// Replace the file's contents, showing the changes first
+    Diff,
+    /// Add the buffer after the file's contents
+    Append,
+}
 
 /// Save command for saving files to the filesystem
 ///
-/// This command handles file saving operations with security validation
-/// to prevent unauthorized file access and ensure safe file operations.
-/// It accepts an optional filename argument and defaults to "untitled.txt"
-/// if no filename is provided.
+/// This command writes the edit buffer with security validation to
+/// prevent unauthorized file access and ensure safe file operations.
+/// It accepts an optional filename argument and defaults to the buffer's
+/// filename, or "untitled.txt" if the buffer has none. Overwriting an
+/// existing file counts as destructive; appending to one does not.
 #[derive(Debug)]
 pub struct SaveCommand;
 
@@ -33,6 +61,57 @@ impl SaveCommand {
     pub fn new() -> Self {
         SaveCommand
     }
+
+    /// Split the arguments into the save mode and the filename arguments
+    fn parse_args(args: &[String]) -> (SaveMode, &[String]) {
+        match args.split_first() {
+            Some((first, rest)) if first == DIFF => (SaveMode::Diff, rest),
+            Some((first, rest)) if first == APPEND => (SaveMode::Append, rest),
+            _ => (SaveMode::Overwrite, args),
+        }
+    }
+
+    /// Get the filename to save to
+    ///
+    /// A filename pasted with its terminal quoting is unquoted, unless a
+    /// file exists under the name exactly as typed.
+    fn target_filename(args: &[String], context: &CliContext) -> String {
+        let (_, args) = Self::parse_args(args);
+        args.first()
+            .map(|arg| match pasted_path(arg) {
+                Some(pasted) if context.security().validate_path(arg).is_err() => pasted,
+                _ => arg.clone(),
+            })
+            .or_else(|| context.buffer().filename().map(str::to_string))
+            .unwrap_or_else(|| DEFAULT_FILENAME.to_string())
+    }
+
+    /// Append the buffer text to a file, creating it if needed
+    ///
+    /// The buffer keeps its filename and modified state, since the file
+    /// now holds more than the buffer does.
+    fn append(
+        filename: &str,
+        path: &Path,
+        text: &str,
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        let security = context.security();
+        security.validate_extension(path)?;
+        let existing = fs::metadata(path).map_or(0, |metadata| metadata.len());
+        security.validate_size(existing + text.len() as u64)?;
+
+        context.append_file(path, text.as_bytes())?;
+
+        let message = format!(
+            "Appended {} lines to {}",
+            context.buffer().len(),
+            sanitize_for_display(filename)
+        );
+        context.display().display_success(&message);
+
+        Ok(CommandResult::success_silent())
+    }
 }
 
 impl Command for SaveCommand {
@@ -41,15 +120,73 @@ impl Command for SaveCommand {
     }
 
     fn description(&self) -> &'static str {
-        "Save a file to the filesystem"
+        "Save the edit buffer to a file"
     }
 
     fn aliases(&self) -> Vec<&'static str> {
         vec!["s"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
-        // Save command can take 0 or 1 arguments (optional filename)
+    fn usage(&self) -> String {
+        format!("save [{DIFF}] [filename] | save {APPEND} <filename>")
+    }
+
+    fn is_destructive(&self, args: &[String], context: &CliContext) -> bool {
+        // Only overwriting an existing file loses data
+        if Self::parse_args(args).0 == SaveMode::Append {
+            return false;
+        }
+        let filename = Self::target_filename(args, context);
+        context
+            .security()
+            .validate_path(&filename)
+            .is_ok_and(|path| path.is_file())
+    }
+
+    fn preview(&self, args: &[String], context: &CliContext) -> Option<String> {
+        if Self::parse_args(args).0 != SaveMode::Diff {
+            return None;
+        }
+
+        // Compare against the file as it is on disk now
+        let filename = Self::target_filename(args, context);
+        let name = sanitize_for_display(&filename);
+        let security = context.security();
+        let path = security.validate_path(&filename).ok()?;
+        let size = fs::metadata(&path).ok()?.len();
+        if security.validate_size(size).is_err() {
+            return Some(format!("{name} is too large to compare"));
+        }
+        let on_disk = String::from_utf8_lossy(&fs::read(&path).ok()?).into_owned();
+
+        // Both sides may hold escape codes, so sanitize before coloring
+        let old: Vec<String> = on_disk.lines().map(sanitize_for_display).collect();
+        let new: Vec<String> = context
+            .buffer()
+            .lines()
+            .iter()
+            .map(|line| sanitize_for_display(line))
+            .collect();
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+        let diff = unified_diff(&name, "buffer", &old, &new, &context.theme());
+        if diff.is_empty() {
+            Some(format!("No differences from {name}"))
+        } else {
+            Some(diff.join("\n"))
+        }
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Save command can take an optional --diff and an optional filename,
+        // or --append and a filename
+        let (mode, args) = Self::parse_args(args);
+        if mode == SaveMode::Append && args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
         if args.len() > 1 {
             return Err(CliError::TooManyArguments {
                 expected: 1,
@@ -57,32 +194,53 @@ impl Command for SaveCommand {
             });
         }
 
-        let filename = if args.is_empty() {
-            "untitled.txt".to_string()
-        } else {
-            args[0].clone()
-        };
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let filename = Self::target_filename(args, context);
 
         // Validate filename
         if filename.trim().is_empty() {
             return Err(CliError::invalid_input("Filename cannot be empty"));
         }
 
-        // Simulate file saving with error handling
-        if filename.contains("..") {
-            return Err(CliError::invalid_input(
-                "Invalid filename: path traversal not allowed",
-            ));
+        let path = context.security().validate_write_path(&filename)?;
+        if path.is_dir() {
+            return Err(CliError::invalid_input(&format!(
+                "Cannot save over a directory: {filename}"
+            )));
         }
 
-        if filename.starts_with("/etc/") {
-            return Err(CliError::permission_denied(&format!(
-                "Cannot save to system directory: {filename}"
-            )));
+        let text = context
+            .buffer()
+            .text_with(context.preferences().line_ending);
+        if Self::parse_args(args).0 == SaveMode::Append {
+            return Self::append(&filename, &path, &text, context);
         }
+        context.security().validate_size(text.len() as u64)?;
+        context.write_file(&path, text.as_bytes())?;
+
+        let line_count = context.buffer().len();
+        let message = format!(
+            "Saved {line_count} lines to {}",
+            sanitize_for_display(&filename)
+        );
+        context.mark_saved(filename);
+        context.display().display_success(&message);
 
-        println!("Saving file: {filename}");
-        Ok(CommandResult::Continue)
+        Ok(CommandResult::success_silent())
     }
 
     fn subcommands(&self) -> Vec<Box<dyn Command>> {
@@ -91,4 +249,13 @@ impl Command for SaveCommand {
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, _index: usize) -> ArgKind {
+        // Every argument is a filename (flags are typed, not completed)
+        ArgKind::File
+    }
 }
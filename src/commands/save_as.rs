@@ -0,0 +1,121 @@
+//! Save-as command implementation for saving under a prompted filename.
+//!
+//! This command asks for the filename to save the edit buffer to, offering
+//! the buffer's current filename as the default, then runs `save` with the
+//! answer. Saving itself, including confirming an overwrite, is left to
+//! the `save` command.
+
+use super::save::DEFAULT_FILENAME;
+use crate::core::security::sanitize_for_display;
+use crate::core::tokenizer::join_tokens;
+use crate::{
+    ArgKind, ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory,
+    CommandResult,
+};
+
+/// Save-as command that prompts for the filename to save to
+///
+/// With a filename argument no prompt is shown. When input is not a
+/// terminal, the buffer's filename is only used without asking if `--yes`
+/// was given.
+#[derive(Debug)]
+pub struct SaveAsCommand;
+
+impl Default for SaveAsCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveAsCommand {
+    /// Creates a new SaveAsCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::save_as::SaveAsCommand;
+    /// let save_as_cmd = SaveAsCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        SaveAsCommand
+    }
+
+    /// Ask for the filename, returning `None` to keep the default
+    fn prompt_for_filename(context: &mut CliContext) -> CliResult<Option<String>> {
+        let default = context.buffer().filename().unwrap_or(DEFAULT_FILENAME);
+        let prompt = format!("Save as [{}]: ", sanitize_for_display(default));
+        context.display().write_text(&prompt);
+
+        let answer = context
+            .io_mut()
+            .read_line()?
+            .ok_or_else(|| CliError::invalid_input("No filename given"))?;
+        let answer = answer.trim();
+        Ok((!answer.is_empty()).then(|| answer.to_string()))
+    }
+}
+
+impl ArgumentValidator for SaveAsCommand {}
+
+impl Command for SaveAsCommand {
+    fn name(&self) -> &'static str {
+        "save-as"
+    }
+
+    fn description(&self) -> &'static str {
+        "Save the buffer under a filename asked for at a prompt"
+    }
+
+    fn usage(&self) -> String {
+        "save-as [filename]".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Save-as command takes an optional filename, skipping the prompt
+        self.validate_exact_or_range(args, 0, 1)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let filename = match args.first() {
+            Some(filename) => Some(filename.clone()),
+            None if context.io().input_is_terminal() => Self::prompt_for_filename(context)?,
+            None if context.preferences().assume_yes => None,
+            None => {
+                return Err(CliError::invalid_input(
+                    "save-as needs a filename when input is not a terminal (or --yes to use the buffer's)",
+                ));
+            }
+        };
+
+        // Without a filename, save picks the buffer's own
+        let mut line = vec!["save".to_string()];
+        line.extend(filename);
+        Ok(CommandResult::Batch {
+            lines: vec![join_tokens(&line)],
+            keep_going: false,
+        })
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, index: usize) -> ArgKind {
+        // The only argument is a filename
+        if index == 0 {
+            ArgKind::File
+        } else {
+            ArgKind::None
+        }
+    }
+}
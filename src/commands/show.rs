@@ -1,37 +1,103 @@
 //! Show command implementation for displaying current state.
 //!
 //! This command provides functionality to display the current state and
-//! configuration within different contexts (edit, view). It gives users
-//! insight into the current operational mode and settings.
+//! configuration within different menus (edit, view). It gives users
+//! insight into the current operational mode and settings. The menu is
+//! read from the command path. In the edit menu the buffer is listed too,
+//! sanitized so loaded contents cannot send escape codes to the terminal.
+//! `show --range <a> <b>` prints only lines `a` through `b` of the buffer,
+//! in any menu, without entering the show menu.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
+use super::lines::parse_line_number;
+use crate::core::security::sanitize_for_display;
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Option that prints a range of buffer lines instead of the state
+const RANGE: &str = "--range";
+
+/// Number buffer lines for display, sanitizing their contents
+///
+/// # Arguments
+/// * `lines` - The lines to show
+/// * `first` - The 1-based line number of the first of them
+pub(crate) fn number_lines(lines: &[String], first: usize) -> Vec<String> {
+    lines
+        .iter()
+        .zip(first..)
+        .map(|(line, number)| format!("{number:>4}  {}", sanitize_for_display(line)))
+        .collect()
+}
 
 /// Show command for displaying current state
 ///
 /// This command displays the current state and configuration for different
-/// contexts such as editing or viewing modes. It provides users with information
+/// menus such as editing or viewing modes. It provides users with information
 /// about the current operational state.
 #[derive(Debug)]
 pub struct ShowCommand {
-    context: String,
+    /// Help category, following the menu the command is offered in
+    category: CommandCategory,
+}
+
+impl Default for ShowCommand {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ShowCommand {
-    /// Creates a new ShowCommand with the specified context
+    /// Creates a new ShowCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::show::ShowCommand;
+    /// let show_cmd = ShowCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_category(CommandCategory::General)
+    }
+
+    /// Creates a new ShowCommand listed under the given help category
+    ///
+    /// The edit and view menus use this so the command is grouped with
+    /// the rest of their commands.
     ///
     /// # Arguments
-    /// * `context` - The context string (e.g., "edit", "view")
+    /// * `category` - The category of the menu offering the command
     ///
     /// # Examples
     /// ```
     /// use sm_menu::commands::show::ShowCommand;
-    /// let show_cmd = ShowCommand::new("edit");
+    /// use sm_menu::{Command, CommandCategory};
+    ///
+    /// let show_cmd = ShowCommand::with_category(CommandCategory::View);
+    /// assert_eq!(show_cmd.category(), CommandCategory::View);
     /// ```
-    pub fn new(context: &str) -> Self {
-        ShowCommand {
-            context: context.to_string(),
+    pub fn with_category(category: CommandCategory) -> Self {
+        ShowCommand { category }
+    }
+
+    /// Print lines `a` through `b` of the buffer
+    fn show_range(args: &[String], context: &mut CliContext) -> CliResult<CommandResult> {
+        let len = context.buffer().len();
+        if len == 0 {
+            return Err(CliError::invalid_input("The buffer is empty"));
         }
+        let first = parse_line_number(&args[1], len)?;
+        let last = parse_line_number(&args[2], len)?;
+        if first > last {
+            return Err(CliError::invalid_input(&format!(
+                "Range start {first} is after its end {last}"
+            )));
+        }
+
+        let lines = number_lines(&context.buffer().lines()[first - 1..last], first);
+        let display = context.display();
+        for line in &lines {
+            display.display_text(line);
+        }
+        Ok(CommandResult::success_silent())
     }
 }
 
@@ -41,40 +107,78 @@ impl Command for ShowCommand {
     }
 
     fn description(&self) -> &'static str {
-        match self.context.as_str() {
-            "edit" => "Display current edit state and configuration",
-            "view" => "Display current view state and configuration",
-            _ => "Display current state",
-        }
+        "Display current state and configuration"
     }
 
     fn aliases(&self) -> Vec<&'static str> {
         vec!["sh"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
-        // Show command takes no arguments
-        if !args.is_empty() {
-            return Err(CliError::TooManyArguments {
+    fn usage(&self) -> String {
+        format!("show [{RANGE} <a> <b>]")
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Show command takes no arguments, or a range of lines
+        match args {
+            [] => Ok(()),
+            [option, ..] if option == RANGE => match args.len() {
+                3 => Ok(()),
+                found if found < 3 => Err(CliError::TooFewArguments { expected: 3, found }),
+                found => Err(CliError::TooManyArguments { expected: 3, found }),
+            },
+            [option, ..] if option.starts_with("--") => Err(CliError::invalid_input(&format!(
+                "Unknown option: {option} (expected {RANGE})"
+            ))),
+            _ => Err(CliError::TooManyArguments {
                 expected: 0,
                 found: args.len(),
-            });
+            }),
+        }
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+        if !args.is_empty() {
+            return Self::show_range(args, context);
         }
 
-        match self.context.as_str() {
+        let buffer_lines = number_lines(context.buffer().lines(), 1);
+        let display = context.display();
+        display.display_text(&format!("Location: {}", path.join(" > ")));
+
+        // The enclosing menu is the path segment before this command
+        let menu = match path {
+            [.., menu, _] => menu.as_str(),
+            _ => "",
+        };
+        match menu {
             "edit" => {
-                println!("Displaying current edit state...");
-                println!("Edit mode: Active");
-                println!("Current selection: None");
+                display.display_text("Displaying current edit state...");
+                display.display_text("Edit mode: Active");
+                display.display_text("Current selection: None");
+                display.display_text(&format!("Buffer: {} lines", buffer_lines.len()));
+                for line in &buffer_lines {
+                    display.display_text(line);
+                }
             }
             "view" => {
-                println!("Displaying current view state...");
-                println!("View mode: Active");
-                println!("Current perspective: Default");
+                display.display_text("Displaying current view state...");
+                display.display_text("View mode: Active");
+                display.display_text("Current perspective: Default");
             }
             _ => {
-                println!("Displaying current state...");
-                println!("Status: Active");
+                display.display_text("Displaying current state...");
+                display.display_text("Status: Active");
             }
         }
 
@@ -87,4 +191,8 @@ impl Command for ShowCommand {
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        self.category
+    }
 }
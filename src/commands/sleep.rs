@@ -0,0 +1,122 @@
+//! Sleep command implementation for pacing scripts.
+//!
+//! This command waits for a number of seconds, for example between the
+//! steps of a scripted demo. Ctrl-C sets the interrupt flag while it
+//! waits, and the wait checks the flag as it goes, so it can be cut short
+//! without ending the session.
+
+use crate::core::interrupt::{catch_interrupts, sleep_interruptibly};
+use crate::{
+    ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory, CommandResult,
+};
+use std::time::Duration;
+
+/// Sleep command that waits for a number of seconds
+///
+/// The session loop makes this command available in every menu. It is
+/// hidden from listings, since it is meant for scripts.
+#[derive(Debug)]
+pub struct SleepCommand;
+
+impl Default for SleepCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SleepCommand {
+    /// Creates a new SleepCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::sleep::SleepCommand;
+    /// let sleep_cmd = SleepCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        SleepCommand
+    }
+
+    /// Parse a duration given in seconds, such as `2` or `0.25`
+    ///
+    /// # Returns
+    /// * `Ok(Duration)` - The duration to sleep for
+    /// * `Err(CliError)` - If the argument is not a non-negative number
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::sleep::SleepCommand;
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(SleepCommand::parse_duration("0.5").unwrap(), Duration::from_millis(500));
+    /// assert!(SleepCommand::parse_duration("-1").is_err());
+    /// ```
+    pub fn parse_duration(arg: &str) -> CliResult<Duration> {
+        arg.parse::<f64>()
+            .ok()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| {
+                CliError::invalid_input(&format!(
+                    "Invalid duration: {arg} (expected a non-negative number of seconds)"
+                ))
+            })
+    }
+}
+
+impl ArgumentValidator for SleepCommand {}
+
+impl Command for SleepCommand {
+    fn name(&self) -> &'static str {
+        "sleep"
+    }
+
+    fn description(&self) -> &'static str {
+        "Wait for a number of seconds"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["wait"]
+    }
+
+    fn usage(&self) -> String {
+        "sleep <seconds>".to_string()
+    }
+
+    fn hidden(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Sleep command takes exactly one duration
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+        self.validate_arg_count(args, 1)?;
+        Self::parse_duration(&args[0]).map(|_| ())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        _context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let duration = Self::parse_duration(&args[0])?;
+        let _guard = catch_interrupts()
+            .map_err(|e| CliError::execution_error_with_source("Could not catch Ctrl-C", e))?;
+        sleep_interruptibly(duration)?;
+        Ok(CommandResult::success_silent())
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
+}
@@ -0,0 +1,137 @@
+//! Stat command implementation for reporting file metadata.
+//!
+//! This command shows a file's size, modified time, permissions, and
+//! whether it holds text or binary data, so a file can be checked before
+//! it is loaded. It only reads the file. The path is validated against the
+//! working root like any other file operation.
+
+use crate::core::history_file::format_timestamp;
+use crate::core::security::{FileFormat, detect_format, sanitize_for_display};
+use crate::{
+    ArgKind, ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory,
+    CommandResult,
+};
+use std::fs::{self, Metadata};
+use std::time::UNIX_EPOCH;
+
+/// Stat command for showing the metadata of a file
+#[derive(Debug)]
+pub struct StatCommand;
+
+impl Default for StatCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatCommand {
+    /// Creates a new StatCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::stat::StatCommand;
+    /// let stat_cmd = StatCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        StatCommand
+    }
+
+    /// Format a file's permissions, in octal on Unix
+    #[cfg(unix)]
+    fn permissions(metadata: &Metadata) -> String {
+        use std::os::unix::fs::PermissionsExt;
+        format!("{:04o}", metadata.permissions().mode() & 0o7777)
+    }
+
+    /// Format a file's permissions, in octal on Unix
+    #[cfg(not(unix))]
+    fn permissions(metadata: &Metadata) -> String {
+        if metadata.permissions().readonly() {
+            "read-only".to_string()
+        } else {
+            "read-write".to_string()
+        }
+    }
+}
+
+impl ArgumentValidator for StatCommand {}
+
+impl Command for StatCommand {
+    fn name(&self) -> &'static str {
+        "stat"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show a file's size, modified time, permissions, and format"
+    }
+
+    fn usage(&self) -> String {
+        "stat <filename>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Stat command takes exactly one filename
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+        self.validate_arg_count(args, 1)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let path = context.security().validate_path(&args[0])?;
+        let metadata = fs::metadata(&path)?;
+        let modified = metadata.modified()?.duration_since(UNIX_EPOCH).map_or_else(
+            |_| "before 1970".to_string(),
+            |since| format_timestamp(since.as_secs()),
+        );
+
+        // The format needs the whole file, so files over the size limit
+        // are not read
+        let format = if metadata.is_dir() {
+            "directory".to_string()
+        } else if context.security().validate_size(metadata.len()).is_err() {
+            "not checked (file too large)".to_string()
+        } else {
+            match detect_format(&fs::read(&path)?) {
+                FileFormat::Text => "text".to_string(),
+                FileFormat::Binary => "binary".to_string(),
+            }
+        };
+
+        let display = context.display();
+        display.display_text(&format!("File: {}", sanitize_for_display(&args[0])));
+        display.display_text(&format!("Size: {} bytes", metadata.len()));
+        display.display_text(&format!("Modified: {modified}"));
+        display.display_text(&format!("Permissions: {}", Self::permissions(&metadata)));
+        display.display_text(&format!("Format: {format}"));
+
+        Ok(CommandResult::success_silent())
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, index: usize) -> ArgKind {
+        // The only argument is a filename
+        if index == 0 {
+            ArgKind::File
+        } else {
+            ArgKind::None
+        }
+    }
+}
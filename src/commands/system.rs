@@ -0,0 +1,76 @@
+//! System command implementation for session-wide operations.
+//!
+//! This command provides a submenu for operations that affect the whole
+//! session rather than a single file or the buffer, such as returning the
+//! session to a clean state.
+
+use super::base::{ExitCommand, InfoCommand};
+use super::reset::ResetCommand;
+use crate::{CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// System command handling "Reset", "Info", and "Exit"
+#[derive(Debug)]
+pub struct SystemCommand;
+
+impl Default for SystemCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemCommand {
+    /// Creates a new SystemCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::system::SystemCommand;
+    /// let system_cmd = SystemCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        SystemCommand
+    }
+}
+
+impl Command for SystemCommand {
+    fn name(&self) -> &'static str {
+        "system"
+    }
+
+    fn description(&self) -> &'static str {
+        "System operations: Reset, Info, Exit"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["sys"]
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - system command takes no arguments when used as menu
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(ResetCommand::new()),
+            Box::new(InfoCommand::new(self.name())),
+            Box::new(ExitCommand::new()),
+        ]
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
+}
@@ -0,0 +1,111 @@
+//! Touch command implementation for creating empty files.
+//!
+//! This command creates an empty file, or updates the modified time of a
+//! file that already exists. The contents of an existing file are never
+//! changed, so it runs without confirmation. The path is validated like a
+//! save target, so it cannot leave the working root.
+
+use crate::core::security::sanitize_for_display;
+use crate::{
+    ArgKind, ArgumentValidator, CliContext, CliError, CliResult, Command, CommandCategory,
+    CommandResult,
+};
+use std::fs::OpenOptions;
+use std::time::SystemTime;
+
+/// Touch command that creates a file or updates its modified time
+#[derive(Debug)]
+pub struct TouchCommand;
+
+impl Default for TouchCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TouchCommand {
+    /// Creates a new TouchCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::touch::TouchCommand;
+    /// let touch_cmd = TouchCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        TouchCommand
+    }
+}
+
+impl ArgumentValidator for TouchCommand {}
+
+impl Command for TouchCommand {
+    fn name(&self) -> &'static str {
+        "touch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Create an empty file, or update the modified time of an existing one"
+    }
+
+    fn usage(&self) -> String {
+        "touch <filename>".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Touch command takes exactly one filename
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+        self.validate_arg_count(args, 1)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let filename = &args[0];
+        let path = context.security().validate_write_path(filename)?;
+        if path.is_dir() {
+            return Err(CliError::invalid_input(&format!(
+                "Cannot touch a directory: {}",
+                sanitize_for_display(filename)
+            )));
+        }
+
+        // Appending leaves existing contents alone
+        let existed = path.exists();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        file.set_modified(SystemTime::now())?;
+
+        let name = sanitize_for_display(filename);
+        Ok(if existed {
+            CommandResult::success_fmt(format_args!("Updated the modified time of {name}"))
+        } else {
+            CommandResult::success_fmt(format_args!("Created empty file {name}"))
+        })
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
+
+    fn arg_completion(&self, index: usize) -> ArgKind {
+        // The only argument is a filename
+        if index == 0 {
+            ArgKind::File
+        } else {
+            ArgKind::None
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Tree command implementation for drawing the command hierarchy.
+//!
+//! This command draws the commands below the current menu as a tree, one
+//! name per line, joined by box-drawing connectors. Unlike `help`, it shows
+//! no descriptions, so the whole structure fits on one screen. It walks the
+//! tree with the shared traversal, so hidden commands are left out and a
+//! menu nested inside itself is drawn once without being descended into.
+
+use crate::{ArgumentValidator, CliContext, CliResult, Command, CommandResult, CommandTreeIter};
+
+/// Connectors for a child with more siblings below it, the last child, a
+/// level with more siblings to come, and a finished level
+type Connectors = [&'static str; 4];
+
+/// Connectors drawn with box-drawing characters
+const UNICODE_CONNECTORS: Connectors = ["├── ", "└── ", "│   ", "    "];
+
+/// Connectors for terminals that cannot show box-drawing characters
+const ASCII_CONNECTORS: Connectors = ["|-- ", "`-- ", "|   ", "    "];
+
+/// Draw the commands below a menu as a tree
+///
+/// The first line is the menu's name and each command below it gets a
+/// line of its own, in menu order.
+///
+/// # Arguments
+/// * `menu` - The menu to start from
+/// * `unicode` - Whether to draw with box-drawing characters rather than ascii
+///
+/// # Examples
+/// ```
+/// use sm_menu::commands::help::HelpCommand;
+/// use sm_menu::commands::tree::render_tree;
+///
+/// // The hidden `info` command is left out
+/// assert_eq!(render_tree(&HelpCommand::new(), false), "help\n`-- exit");
+/// ```
+pub fn render_tree(menu: &dyn Command, unicode: bool) -> String {
+    let [branch, last_branch, more, done] = if unicode {
+        UNICODE_CONNECTORS
+    } else {
+        ASCII_CONNECTORS
+    };
+    let paths: Vec<Vec<String>> = CommandTreeIter::new(menu).map(|(path, _)| path).collect();
+
+    let mut lines = vec![menu.name().to_string()];
+    // Whether each level drawn so far still has siblings to come
+    let mut open: Vec<bool> = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        let depth = path.len();
+        // Siblings follow a command's subtree, before anything shallower
+        let is_last = !paths[index + 1..]
+            .iter()
+            .take_while(|later| later.len() >= depth)
+            .any(|later| later.len() == depth);
+
+        open.truncate(depth - 1);
+        let mut line: String = open
+            .iter()
+            .map(|&has_more| if has_more { more } else { done })
+            .collect();
+        line.push_str(if is_last { last_branch } else { branch });
+        line.push_str(&path[depth - 1]);
+        lines.push(line);
+        open.push(!is_last);
+    }
+    lines.join("\n")
+}
+
+/// Tree command that draws the commands below the current menu
+///
+/// The session loop makes this command available in every menu.
+#[derive(Debug)]
+pub struct TreeCommand;
+
+impl Default for TreeCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreeCommand {
+    /// Creates a new TreeCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::tree::TreeCommand;
+    /// let tree_cmd = TreeCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        TreeCommand
+    }
+}
+
+impl ArgumentValidator for TreeCommand {}
+
+impl Command for TreeCommand {
+    fn name(&self) -> &'static str {
+        "tree"
+    }
+
+    fn description(&self) -> &'static str {
+        "Draw the commands below this menu as a tree"
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Tree command takes no arguments
+        self.validate_arg_count(args, 0)
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let (display, navigator) = context.display_with_navigator();
+        display.display_text(&render_tree(navigator.current(), display.is_unicode()));
+        Ok(CommandResult::success_silent())
+    }
+}
@@ -0,0 +1,144 @@
+//! Undo and redo command implementations for edit operations.
+//!
+//! These commands step backwards and forwards through the bounded history
+//! of edit buffer snapshots kept in the session context.
+
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+
+/// Undo command that reverts the most recent buffer edit
+#[derive(Debug)]
+pub struct UndoCommand;
+
+impl Default for UndoCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UndoCommand {
+    /// Creates a new UndoCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::undo::UndoCommand;
+    /// let undo_cmd = UndoCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        UndoCommand
+    }
+}
+
+impl Command for UndoCommand {
+    fn name(&self) -> &'static str {
+        "undo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Undo the last edit to the buffer"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["u"]
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - undo command takes no arguments
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        context.undo()?;
+        Ok(CommandResult::success("Undid last edit"))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
+}
+
+/// Redo command that re-applies the most recently undone buffer edit
+#[derive(Debug)]
+pub struct RedoCommand;
+
+impl Default for RedoCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RedoCommand {
+    /// Creates a new RedoCommand instance
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::undo::RedoCommand;
+    /// let redo_cmd = RedoCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        RedoCommand
+    }
+}
+
+impl Command for RedoCommand {
+    fn name(&self) -> &'static str {
+        "redo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Redo the last undone edit to the buffer"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["r"]
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // Validate arguments - redo command takes no arguments
+        if !args.is_empty() {
+            return Err(CliError::TooManyArguments {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        context.redo()?;
+        Ok(CommandResult::success("Redid last edit"))
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Edit
+    }
+}
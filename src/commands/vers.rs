@@ -2,10 +2,24 @@
 //!
 //! This command provides functionality to display the current version of the
 //! application along with other relevant build information. It uses compile-time
-//! environment variables to retrieve version information.
+//! environment variables to retrieve version information. With `--check` it
+//! compares that version against a latest version supplied offline, and
+//! with `--json` it prints the build information as one JSON object.
 
 use super::base::{ExitCommand, InfoCommand};
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::core::version::{Version, build_info};
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+use std::cmp::Ordering;
+use std::fs;
+
+/// Option that compares the running version against the latest one
+const CHECK: &str = "--check";
+
+/// Option that prints the build information as JSON
+const JSON: &str = "--json";
+
+/// Environment variable holding the latest available version
+pub const LATEST_VERSION_VAR: &str = "SM_MENU_LATEST";
 
 /// Vers command that shows version information
 ///
@@ -32,6 +46,54 @@ impl VersCommand {
     pub fn new() -> Self {
         VersCommand
     }
+
+    /// Describe how the running version compares to the latest one
+    ///
+    /// # Arguments
+    /// * `current` - The running version
+    /// * `latest` - The latest available version, as text
+    ///
+    /// # Returns
+    /// * `Ok(String)` - Whether an update is available
+    /// * `Err(CliError)` - `InvalidInput` if `latest` is not a valid version
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::vers::VersCommand;
+    /// use sm_menu::core::version::Version;
+    ///
+    /// let current: Version = "0.1.0".parse().unwrap();
+    /// let report = VersCommand::check_against(&current, "0.2.0").unwrap();
+    /// assert_eq!(report, "A newer version is available: 0.2.0 (running 0.1.0)");
+    /// ```
+    pub fn check_against(current: &Version, latest: &str) -> CliResult<String> {
+        let latest: Version = latest.parse()?;
+        Ok(match current.cmp(&latest) {
+            Ordering::Less => {
+                format!("A newer version is available: {latest} (running {current})")
+            }
+            Ordering::Equal => format!("Up to date: {current} is the latest version"),
+            Ordering::Greater => {
+                format!("Running {current}, which is newer than the latest known version {latest}")
+            }
+        })
+    }
+
+    /// Read the latest version from a file, or from `SM_MENU_LATEST`
+    fn latest_version(file: Option<&String>, context: &CliContext) -> CliResult<String> {
+        match file {
+            Some(file) => {
+                let path = context.security().validate_path(file)?;
+                let text = fs::read_to_string(&path)?;
+                Ok(text.lines().next().unwrap_or_default().to_string())
+            }
+            None => std::env::var(LATEST_VERSION_VAR).map_err(|_| {
+                CliError::invalid_input(&format!(
+                    "No latest version to check against: set {LATEST_VERSION_VAR} or give a file"
+                ))
+            }),
+        }
+    }
 }
 
 impl Command for VersCommand {
@@ -47,8 +109,39 @@ impl Command for VersCommand {
         vec!["v"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
-        // Validate arguments - vers command takes no arguments
+    fn usage(&self) -> String {
+        format!("vers [{CHECK} [file] | {JSON}]")
+    }
+
+    fn is_cacheable(&self, args: &[String]) -> bool {
+        // The build information is fixed; --check reads a file that may change
+        args.is_empty() || args == [JSON]
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // With --check, vers takes an optional file with the latest version
+        if args.first().is_some_and(|arg| arg == CHECK) {
+            if args.len() > 2 {
+                return Err(CliError::TooManyArguments {
+                    expected: 2,
+                    found: args.len(),
+                });
+            }
+            return Ok(());
+        }
+
+        // --json takes nothing after it
+        if args.first().is_some_and(|arg| arg == JSON) {
+            if args.len() > 1 {
+                return Err(CliError::TooManyArguments {
+                    expected: 1,
+                    found: args.len(),
+                });
+            }
+            return Ok(());
+        }
+
+        // Validate arguments - vers command otherwise takes no arguments
         if !args.is_empty() {
             return Err(CliError::TooManyArguments {
                 expected: 0,
@@ -56,10 +149,37 @@ impl Command for VersCommand {
             });
         }
 
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        if args.first().is_some_and(|arg| arg == JSON) {
+            context.display().display_text(&build_info().to_string());
+            return Ok(CommandResult::success_silent());
+        }
+        if !args.is_empty() {
+            let latest = Self::latest_version(args.get(1), context)?;
+            let report = Self::check_against(&Version::current(), &latest)?;
+            return Ok(CommandResult::success(report));
+        }
+
         let version = env!("CARGO_PKG_VERSION");
         let name = env!("CARGO_PKG_NAME");
 
-        println!("{name} > version {version}");
+        context
+            .display()
+            .display_text(&format!("{name} > version {version}"));
         Ok(CommandResult::Continue)
     }
 
@@ -69,4 +189,8 @@ impl Command for VersCommand {
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::File
+    }
 }
@@ -6,14 +6,17 @@
 
 use super::axis::AxisCommand;
 use super::base::{ExitCommand, InfoCommand};
+use super::goto::GotoCommand;
+use super::grep::GrepCommand;
 use super::show::ShowCommand;
-use crate::{CliError, CliResult, Command, CommandResult};
+use crate::{CliError, CliResult, Command, CommandCategory, CommandResult};
 
 /// View command handling "Axis", "Show", "Info", and "Exit"
 ///
 /// This command provides a submenu for viewing operations including
-/// axis configuration, state display, and help functionality. It creates
-/// context-aware subcommands that understand they are operating in view mode.
+/// axis configuration, state display, and help functionality. Its
+/// subcommands read their command path to know that they are operating
+/// in view mode.
 #[derive(Debug)]
 pub struct ViewCommand;
 
@@ -49,7 +52,7 @@ impl Command for ViewCommand {
         vec!["v"]
     }
 
-    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+    fn validate(&self, args: &[String]) -> CliResult<()> {
         // Validate arguments - view command takes no arguments when used as menu
         if !args.is_empty() {
             return Err(CliError::TooManyArguments {
@@ -58,15 +61,27 @@ impl Command for ViewCommand {
             });
         }
 
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
         Ok(CommandResult::Continue)
     }
 
     fn subcommands(&self) -> Vec<Box<dyn Command>> {
         vec![
-            Box::new(AxisCommand::new("view")),
-            Box::new(ShowCommand::new("view")),
+            Box::new(AxisCommand::with_category(CommandCategory::View)),
+            Box::new(ShowCommand::with_category(CommandCategory::View)),
+            Box::new(GotoCommand::new()),
+            Box::new(GrepCommand::with_category(CommandCategory::View)),
             Box::new(InfoCommand::new(self.name())),
             Box::new(ExitCommand::new()),
         ]
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::View
+    }
 }
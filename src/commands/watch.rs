@@ -0,0 +1,249 @@
+//! Watch command implementation for re-running a command periodically.
+//!
+//! Like the Unix `watch`, this command clears the screen and runs a command
+//! of the current menu again every interval, until Ctrl-C. Ctrl-C only
+//! sets the interrupt flag while the command is watched, so the loop stops
+//! cleanly between runs and the session carries on. Watching only makes
+//! sense with someone at the terminal, so scripts cannot run it.
+
+use super::available_commands;
+use crate::core::interrupt::{
+    catch_interrupts, clear_interrupt, interrupt_requested, sleep_interruptibly,
+};
+use crate::core::pipeline;
+use crate::core::tokenizer::join_tokens;
+use crate::{CliContext, CliError, CliResult, Command, CommandCategory, CommandResult};
+use std::fmt;
+use std::time::Duration;
+
+/// Waits out the interval between runs, for substituting in tests
+///
+/// Failing with [`CliError::Interrupted`] stops the watch cleanly; any
+/// other error ends it with that error.
+pub type Clock = Box<dyn FnMut(Duration) -> CliResult<()>>;
+
+/// Wait out an interval, stopping at once if Ctrl-C was pressed during
+/// the run before it
+fn wait_interruptibly(interval: Duration) -> CliResult<()> {
+    if interrupt_requested() {
+        clear_interrupt();
+        return Err(CliError::Interrupted);
+    }
+    sleep_interruptibly(interval)
+}
+
+/// Run `run` repeatedly, waiting with `clock` between runs
+///
+/// # Returns
+/// * `Ok(usize)` - The number of runs, once the clock is interrupted
+/// * `Err(CliError)` - The first error from a run or the clock, other
+///   than an interrupt
+///
+/// # Examples
+/// ```
+/// use sm_menu::CliError;
+/// use sm_menu::commands::watch::watch_loop;
+/// use std::time::Duration;
+///
+/// // A clock interrupted at its first wait stops the watch after one run
+/// let mut clock = |_| Err(CliError::Interrupted);
+/// let runs = watch_loop(Duration::from_secs(2), &mut clock, || Ok(())).unwrap();
+/// assert_eq!(runs, 1);
+/// ```
+pub fn watch_loop(
+    interval: Duration,
+    clock: &mut dyn FnMut(Duration) -> CliResult<()>,
+    mut run: impl FnMut() -> CliResult<()>,
+) -> CliResult<usize> {
+    let mut runs = 0;
+    loop {
+        run()?;
+        runs += 1;
+        match clock(interval) {
+            Ok(()) => {}
+            Err(CliError::Interrupted) => return Ok(runs),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Watch command that re-runs a command every interval until Ctrl-C
+///
+/// The session loop makes this command available in every menu.
+pub struct WatchCommand {
+    /// Waits between runs
+    clock: Clock,
+}
+
+impl fmt::Debug for WatchCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WatchCommand")
+    }
+}
+
+impl Default for WatchCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WatchCommand {
+    /// Creates a new WatchCommand that sleeps between runs
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::watch::WatchCommand;
+    /// let watch_cmd = WatchCommand::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_clock(Box::new(wait_interruptibly))
+    }
+
+    /// Creates a WatchCommand that waits between runs with `clock`
+    ///
+    /// Tests use this to stop the watch without waiting or pressing Ctrl-C.
+    pub fn with_clock(clock: Clock) -> Self {
+        WatchCommand { clock }
+    }
+
+    /// Parse an interval given in seconds, such as `2` or `0.5`
+    ///
+    /// # Returns
+    /// * `Ok(Duration)` - The time between runs
+    /// * `Err(CliError)` - If the argument is not a positive number
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::watch::WatchCommand;
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(WatchCommand::parse_interval("0.5").unwrap(), Duration::from_millis(500));
+    /// assert!(WatchCommand::parse_interval("0").is_err());
+    /// ```
+    pub fn parse_interval(arg: &str) -> CliResult<Duration> {
+        arg.parse::<f64>()
+            .ok()
+            .filter(|seconds| *seconds > 0.0)
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| {
+                CliError::invalid_input(&format!(
+                    "Invalid interval: {arg} (expected a positive number of seconds)"
+                ))
+            })
+    }
+
+    /// Split the arguments into the interval and the command line
+    fn parse_args<'a>(&self, args: &'a [String]) -> CliResult<(Duration, &'a [String])> {
+        let Some((interval, command)) = args.split_first().filter(|(_, rest)| !rest.is_empty())
+        else {
+            return Err(CliError::TooFewArguments {
+                expected: 2,
+                found: args.len(),
+            });
+        };
+        let interval = Self::parse_interval(interval)?;
+
+        // Guard against recursion - watch cannot watch itself
+        if self.matches(&command[0]) {
+            return Err(CliError::invalid_input("watch cannot watch itself"));
+        }
+        Ok((interval, command))
+    }
+
+    /// Show one run's result as the session loop would
+    ///
+    /// Results that would move around the menus or run more lines are
+    /// refused, since the loop cannot apply them.
+    fn show_result(name: &str, result: CommandResult, context: &mut CliContext) -> CliResult<()> {
+        match result {
+            CommandResult::Success(msg) => {
+                if !msg.is_empty() {
+                    context.display().display_success(&msg);
+                }
+                Ok(())
+            }
+            CommandResult::Continue => Ok(()),
+            CommandResult::GoUp | CommandResult::Quit | CommandResult::Batch { .. } => Err(
+                CliError::invalid_input(&format!("'{name}' cannot be watched")),
+            ),
+        }
+    }
+}
+
+impl Command for WatchCommand {
+    fn name(&self) -> &'static str {
+        "watch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Re-run a command every interval until Ctrl-C"
+    }
+
+    fn usage(&self) -> String {
+        "watch <seconds> <command> [args...]".to_string()
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        self.parse_args(args).map(|_| ())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        let (interval, command) = self.parse_args(args)?;
+
+        let siblings = available_commands(context.navigator().current());
+        let pipeline::Dispatch {
+            line,
+            command: mut cmd,
+            path,
+            args,
+            ..
+        } = pipeline::plan(
+            join_tokens(command),
+            &command[0],
+            &command[1..],
+            None,
+            context,
+            siblings,
+        )?;
+
+        // Only commands that run and show something can be repeated safely
+        let name = cmd.name();
+        if cmd.has_subcommands() || cmd.requires_tty() || cmd.is_destructive(&args, context) {
+            return Err(CliError::invalid_input(&format!(
+                "'{name}' cannot be watched"
+            )));
+        }
+        cmd.validate(&args)?;
+
+        let _guard = catch_interrupts()
+            .map_err(|e| CliError::execution_error_with_source("Could not catch Ctrl-C", e))?;
+        clear_interrupt();
+        let header = format!("Every {:.1}s: {line}", interval.as_secs_f64());
+        watch_loop(interval, &mut self.clock, || {
+            context.display().clear_screen()?;
+            context.display().display_text(&header);
+            context.display().display_text("");
+            let result = cmd.execute_with_context(&args, &path, context)?;
+            Self::show_result(name, result, context)
+        })?;
+
+        Ok(CommandResult::success_silent())
+    }
+
+    fn requires_tty(&self) -> bool {
+        true
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::System
+    }
+}
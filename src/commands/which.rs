@@ -0,0 +1,157 @@
+//! Which command implementation for debugging command resolution.
+//!
+//! This command reports what a token would resolve to in the current menu:
+//! the canonical command name, whether it matched by name, alias, or
+//! prefix, and the command's description. It goes through the same
+//! [`resolve_case`](crate::core::resolve::resolve_case) function and
+//! case preference the session loop uses, so its answer always matches
+//! what typing the token would run.
+//! With `--check`, it also validates arguments for the resolved command
+//! without running it.
+
+use super::menu::MenuCommand;
+use super::sleep::SleepCommand;
+use crate::core::resolve::{Resolution, resolve_case};
+use crate::{CliContext, CliError, CliResult, Command, CommandResult};
+
+/// Option that also validates arguments for the resolved command
+const CHECK: &str = "--check";
+
+/// Which command that explains how a token resolves in the current menu
+///
+/// The session loop makes this command available in every menu and
+/// constructs it with the commands of the menu it is run from.
+#[derive(Debug)]
+pub struct WhichCommand {
+    siblings: Vec<Box<dyn Command>>,
+}
+
+impl Default for WhichCommand {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl WhichCommand {
+    /// Creates a new WhichCommand for a menu
+    ///
+    /// # Arguments
+    /// * `siblings` - The commands of the menu `which` is run from
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::commands::RootCommand;
+    /// use sm_menu::commands::which::WhichCommand;
+    /// use sm_menu::Command;
+    ///
+    /// let which_cmd = WhichCommand::new(RootCommand.subcommands());
+    /// ```
+    pub fn new(siblings: Vec<Box<dyn Command>>) -> Self {
+        WhichCommand { siblings }
+    }
+
+    /// Describe what a token resolves to among this menu's commands
+    ///
+    /// The menu's commands are consumed by the resolution, so each
+    /// instance describes a single token; the session loop builds a fresh
+    /// instance for every input line.
+    ///
+    /// With `check_args`, the arguments are validated against the resolved
+    /// command and the outcome is appended.
+    fn describe(
+        &mut self,
+        token: &str,
+        check_args: Option<&[String]>,
+        case_sensitive: bool,
+    ) -> String {
+        let mut siblings = std::mem::take(&mut self.siblings);
+        siblings.push(Box::new(WhichCommand::default()));
+        siblings.push(Box::new(MenuCommand::default()));
+        siblings.push(Box::new(SleepCommand::new()));
+
+        match resolve_case(token, siblings, case_sensitive) {
+            Resolution::Found { command, kind } => {
+                let description = format!(
+                    "'{token}' resolves to '{}' by {kind} - {}",
+                    command.name(),
+                    command.description()
+                );
+                match check_args.map(|args| command.validate(args)) {
+                    None => description,
+                    Some(Ok(())) => format!("{description}\nArguments OK"),
+                    Some(Err(e)) => format!("{description}\nArguments rejected: {e}"),
+                }
+            }
+            Resolution::Ambiguous(candidates) => {
+                format!("'{token}' is ambiguous: could be {}", candidates.join(", "))
+            }
+            Resolution::NotFound => format!("'{token}' not found"),
+        }
+    }
+}
+
+impl Command for WhichCommand {
+    fn name(&self) -> &'static str {
+        "which"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show which command a name, alias, or prefix resolves to"
+    }
+
+    fn usage(&self) -> String {
+        format!("which [{CHECK}] <token> [args...]")
+    }
+
+    fn validate(&self, args: &[String]) -> CliResult<()> {
+        // With --check, the token may be followed by arguments to validate
+        if args.first().is_some_and(|arg| arg == CHECK) {
+            if args.len() < 2 {
+                return Err(CliError::TooFewArguments {
+                    expected: 2,
+                    found: args.len(),
+                });
+            }
+            return Ok(());
+        }
+
+        // Which command otherwise expects exactly one argument (the token to resolve)
+        if args.is_empty() {
+            return Err(CliError::TooFewArguments {
+                expected: 1,
+                found: 0,
+            });
+        }
+
+        if args.len() > 1 {
+            return Err(CliError::TooManyArguments {
+                expected: 1,
+                found: args.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.execute_with_context(args, &[], &mut CliContext::new())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.validate(args)?;
+
+        let case_sensitive = context.preferences().case_sensitive_commands;
+        let message = match args {
+            [check, token, rest @ ..] if check == CHECK => {
+                self.describe(token, Some(rest), case_sensitive)
+            }
+            _ => self.describe(&args[0], None, case_sensitive),
+        };
+        Ok(CommandResult::success(message))
+    }
+}
@@ -0,0 +1,237 @@
+//! User-defined command aliases for the CLI application.
+//!
+//! An alias maps a name to the text it stands for, such as `notes` to
+//! `load notes.txt`. Before a line is tokenized its first word is looked up, and
+//! if it names an alias it is replaced by the alias's text, keeping the
+//! rest of the line. The text may itself start with another alias, so
+//! expansion repeats, up to a depth limit and stopping at a cycle. Names of
+//! built-in commands are never expanded, so an alias cannot shadow them.
+
+use crate::core::command_tree::is_valid_command_name;
+use crate::core::error::{CliError, CliResult};
+use std::collections::BTreeMap;
+
+/// Aliases that can be expanded in turn before expansion gives up
+pub const DEFAULT_ALIAS_DEPTH: usize = 16;
+
+/// Aliases defined for a session, by name
+#[derive(Debug, Clone, PartialEq)]
+pub struct AliasTable {
+    /// The text each alias stands for, keyed by alias name
+    aliases: BTreeMap<String, String>,
+    /// Most aliases expanded for one line
+    max_depth: usize,
+}
+
+impl AliasTable {
+    /// Create an empty table with the default depth limit
+    pub fn new() -> Self {
+        AliasTable {
+            aliases: BTreeMap::new(),
+            max_depth: DEFAULT_ALIAS_DEPTH,
+        }
+    }
+
+    /// Define an alias, replacing any earlier one of the same name
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the alias was defined
+    /// * `Err(CliError)` - `InvalidInput` if the name cannot be typed as
+    ///   one word or the text is empty
+    pub fn define(&mut self, name: &str, text: &str) -> CliResult<()> {
+        if !is_valid_command_name(name) {
+            return Err(CliError::invalid_input(&format!(
+                "Alias name must be one word, got '{name}'"
+            )));
+        }
+        if text.trim().is_empty() {
+            return Err(CliError::invalid_input(&format!(
+                "Alias '{name}' needs a command to stand for"
+            )));
+        }
+        self.aliases
+            .insert(name.to_string(), text.trim().to_string());
+        Ok(())
+    }
+
+    /// Remove an alias, returning the text it stood for
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.aliases.remove(name)
+    }
+
+    /// Get the text an alias stands for
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.aliases.get(name).map(String::as_str)
+    }
+
+    /// Iterate over the aliases and their text, in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .map(|(name, text)| (name.as_str(), text.as_str()))
+    }
+
+    /// Check whether no aliases are defined
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Get the most aliases expanded for one line
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Set the most aliases expanded for one line
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Expand the alias at the start of a line, and any it leads to
+    ///
+    /// Alias names are matched in their exact case. Expansion stops at the
+    /// first word that is not an alias, or that `is_builtin` reports as a
+    /// built-in command name.
+    ///
+    /// # Arguments
+    /// * `line` - The input line
+    /// * `is_builtin` - Whether a word names a built-in command
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The expanded line, or the line unchanged if it does
+    ///   not start with an alias
+    /// * `Err(CliError)` - `InvalidInput` if an alias leads back to itself,
+    ///   or more than [`AliasTable::max_depth`] aliases are expanded
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::alias::AliasTable;
+    ///
+    /// let mut aliases = AliasTable::new();
+    /// aliases.define("notes", "load notes.txt").unwrap();
+    /// aliases.define("n", "notes").unwrap();
+    /// assert_eq!(aliases.expand("n", |_| false).unwrap(), "load notes.txt");
+    /// ```
+    pub fn expand(&self, line: &str, is_builtin: impl Fn(&str) -> bool) -> CliResult<String> {
+        let mut line = line.trim_start().to_string();
+        let mut chain: Vec<String> = Vec::new();
+
+        loop {
+            let (word, rest) = match line.split_once(char::is_whitespace) {
+                Some((word, rest)) => (word, Some(rest)),
+                None => (line.as_str(), None),
+            };
+            let Some(text) = self.aliases.get(word).filter(|_| !is_builtin(word)) else {
+                return Ok(line);
+            };
+
+            if chain.iter().any(|seen| seen == word) {
+                chain.push(word.to_string());
+                return Err(CliError::invalid_input(&format!(
+                    "alias cycle detected: {}",
+                    chain.join(" -> ")
+                )));
+            }
+            if chain.len() >= self.max_depth {
+                return Err(CliError::invalid_input(&format!(
+                    "alias expansion deeper than {} levels at '{word}'",
+                    self.max_depth
+                )));
+            }
+            chain.push(word.to_string());
+
+            line = match rest {
+                Some(rest) => format!("{text} {rest}"),
+                None => text.clone(),
+            };
+        }
+    }
+}
+
+impl Default for AliasTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(aliases: &[(&str, &str)]) -> AliasTable {
+        let mut table = AliasTable::new();
+        for (name, text) in aliases {
+            table.define(name, text).unwrap();
+        }
+        table
+    }
+
+    #[test]
+    fn test_expand_alias_chain() {
+        let aliases = table(&[("ll", "file ls"), ("l", "ll"), ("notes", "l notes")]);
+
+        assert_eq!(aliases.expand("ll", |_| false).unwrap(), "file ls");
+        assert_eq!(aliases.expand("l", |_| false).unwrap(), "file ls");
+        assert_eq!(
+            aliases.expand("notes -a", |_| false).unwrap(),
+            "file ls notes -a"
+        );
+        // A line that does not start with an alias is left alone
+        assert_eq!(aliases.expand("file ll", |_| false).unwrap(), "file ll");
+        assert_eq!(aliases.expand("LL", |_| false).unwrap(), "LL");
+    }
+
+    #[test]
+    fn test_expand_detects_cycles() {
+        let aliases = table(&[("a", "b x"), ("b", "a y"), ("self", "self")]);
+
+        for (line, chain) in [
+            ("a", "a -> b -> a"),
+            ("b 1", "b -> a -> b"),
+            ("self", "self -> self"),
+        ] {
+            match aliases.expand(line, |_| false) {
+                Err(CliError::InvalidInput(message)) => {
+                    assert_eq!(message, format!("alias cycle detected: {chain}"))
+                }
+                other => panic!("{line}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_expand_depth_limit() {
+        let mut aliases = table(&[("a1", "a2"), ("a2", "a3"), ("a3", "vers")]);
+        assert_eq!(aliases.expand("a1", |_| false).unwrap(), "vers");
+
+        aliases.set_max_depth(2);
+        assert!(matches!(
+            aliases.expand("a1", |_| false),
+            Err(CliError::InvalidInput(_))
+        ));
+        assert_eq!(aliases.expand("a2", |_| false).unwrap(), "vers");
+    }
+
+    #[test]
+    fn test_builtin_names_are_not_expanded() {
+        let aliases = table(&[("file", "edit"), ("f", "file ls")]);
+        let is_builtin = |word: &str| word == "file";
+
+        assert_eq!(aliases.expand("file", is_builtin).unwrap(), "file");
+        // An alias leading to a built-in name stops there
+        assert_eq!(aliases.expand("f", is_builtin).unwrap(), "file ls");
+    }
+
+    #[test]
+    fn test_define_rejects_bad_aliases() {
+        let mut aliases = AliasTable::new();
+        assert!(aliases.define("two words", "vers").is_err());
+        assert!(aliases.define("", "vers").is_err());
+        assert!(aliases.define("v", "  ").is_err());
+        assert!(aliases.is_empty());
+
+        aliases.define("v", " vers ").unwrap();
+        assert_eq!(aliases.get("v"), Some("vers"));
+        assert_eq!(aliases.remove("v").as_deref(), Some("vers"));
+        assert!(aliases.get("v").is_none());
+    }
+}
@@ -0,0 +1,166 @@
+//! Axis property storage for the edit and view menus.
+//!
+//! By default each menu keeps its own set of named axes in the session
+//! context; with the shared [`AxisScope`] every menu sees one set. An axis
+//! has an optional lower and upper bound; when both are set the lower
+//! bound must be below the upper one.
+
+use crate::core::error::{CliError, CliResult};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Key the axes of every menu are stored under when they are shared
+///
+/// It cannot be a menu name, since menu names are alphanumeric.
+pub const SHARED_AXES: &str = "*";
+
+/// Which menus see an axis that is configured in one of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AxisScope {
+    /// Each menu has its own axes
+    #[default]
+    Menu,
+    /// All menus share one set of axes
+    Shared,
+}
+
+impl AxisScope {
+    /// Get the key a menu's axes are stored under in this scope
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::axes::{AxisScope, SHARED_AXES};
+    ///
+    /// assert_eq!(AxisScope::Menu.store_key("edit"), "edit");
+    /// assert_eq!(AxisScope::Shared.store_key("edit"), SHARED_AXES);
+    /// ```
+    pub fn store_key(self, menu: &str) -> &str {
+        match self {
+            AxisScope::Menu => menu,
+            AxisScope::Shared => SHARED_AXES,
+        }
+    }
+}
+
+/// Bounds of a named axis
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Axis {
+    /// Lower bound, if set
+    pub min: Option<f64>,
+    /// Upper bound, if set
+    pub max: Option<f64>,
+}
+
+impl Axis {
+    /// Check that the axis bounds are consistent
+    ///
+    /// # Returns
+    /// * `Ok(())` - If at most one bound is set or `min < max`
+    /// * `Err(CliError)` - `InvalidInput` if `min >= max`
+    pub fn validate(&self) -> CliResult<()> {
+        if let (Some(min), Some(max)) = (self.min, self.max)
+            && min >= max
+        {
+            return Err(CliError::invalid_input(&format!(
+                "Axis min ({min}) must be less than max ({max})"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |value: Option<f64>| value.map_or("unset".to_string(), |v| v.to_string());
+        write!(f, "min {}, max {}", bound(self.min), bound(self.max))
+    }
+}
+
+/// Named axes of one menu, sorted by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AxisStore {
+    axes: BTreeMap<String, Axis>,
+}
+
+impl AxisStore {
+    /// Create an empty axis store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get an axis by name
+    pub fn get(&self, name: &str) -> Option<&Axis> {
+        self.axes.get(name)
+    }
+
+    /// Store an axis, validating its bounds first
+    ///
+    /// If the bounds are invalid the store is left unchanged.
+    pub fn set(&mut self, name: &str, axis: Axis) -> CliResult<()> {
+        axis.validate()?;
+        self.axes.insert(name.to_string(), axis);
+        Ok(())
+    }
+
+    /// Iterate over the axes in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Axis)> {
+        self.axes.iter().map(|(name, axis)| (name.as_str(), axis))
+    }
+
+    /// Get the number of stored axes
+    pub fn len(&self) -> usize {
+        self.axes.len()
+    }
+
+    /// Check if no axes are stored
+    pub fn is_empty(&self) -> bool {
+        self.axes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_validate() {
+        assert!(Axis::default().validate().is_ok());
+        assert!(
+            Axis {
+                min: Some(5.0),
+                max: None
+            }
+            .validate()
+            .is_ok()
+        );
+        assert!(
+            Axis {
+                min: Some(5.0),
+                max: Some(5.0)
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_store_rejects_invalid_axis() {
+        let mut store = AxisStore::new();
+        let valid = Axis {
+            min: Some(0.0),
+            max: Some(10.0),
+        };
+        store.set("x", valid).unwrap();
+
+        let invalid = Axis {
+            min: Some(10.0),
+            max: Some(0.0),
+        };
+        assert!(matches!(
+            store.set("x", invalid),
+            Err(CliError::InvalidInput(_))
+        ));
+        assert_eq!(store.get("x"), Some(&valid));
+        assert_eq!(store.len(), 1);
+    }
+}
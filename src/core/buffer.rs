@@ -0,0 +1,328 @@
+//! Edit buffer and undo history for the CLI application.
+//!
+//! This module provides the in-memory text buffer that edit operations act
+//! on, together with a bounded history of buffer snapshots used to undo and
+//! redo those operations.
+
+use crate::core::error::{CliError, CliResult};
+use std::collections::VecDeque;
+
+/// Line terminator style used when writing a buffer out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix style `\n`
+    Lf,
+    /// Windows style `\r\n`
+    CrLf,
+    /// Keep the style the buffer was loaded with
+    #[default]
+    Preserve,
+}
+
+impl LineEnding {
+    /// Detect the predominant line ending of some text
+    ///
+    /// Text without any line breaks is treated as [`LineEnding::Lf`].
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::buffer::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::detect("a\r\nb\r\n"), LineEnding::CrLf);
+    /// assert_eq!(LineEnding::detect("a\nb\n"), LineEnding::Lf);
+    /// ```
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+/// In-memory text buffer shared by the edit and view menus
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditBuffer {
+    /// Buffer contents, one entry per line
+    lines: Vec<String>,
+    /// Line ending of the text the buffer was filled from
+    line_ending: LineEnding,
+    /// File the buffer was loaded from or last saved to
+    filename: Option<String>,
+    /// Whether the buffer has changes that have not been saved
+    dirty: bool,
+}
+
+impl EditBuffer {
+    /// Create an empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a buffer holding the given text
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::buffer::EditBuffer;
+    ///
+    /// let buffer = EditBuffer::from_text("first\nsecond\n");
+    /// assert_eq!(buffer.lines(), ["first", "second"]);
+    /// ```
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            lines: text.lines().map(str::to_string).collect(),
+            line_ending: LineEnding::detect(text),
+            filename: None,
+            dirty: false,
+        }
+    }
+
+    /// Get the buffer lines
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Get mutable access to the buffer lines
+    ///
+    /// Callers are responsible for marking the buffer dirty.
+    pub fn lines_mut(&mut self) -> &mut Vec<String> {
+        &mut self.lines
+    }
+
+    /// Get the buffer contents joined with newlines
+    pub fn text(&self) -> String {
+        self.text_with(LineEnding::Lf)
+    }
+
+    /// Get the buffer contents joined with the given line ending
+    ///
+    /// [`LineEnding::Preserve`] uses the line ending the buffer was filled
+    /// from, or `\n` for a buffer that was never filled from text.
+    pub fn text_with(&self, line_ending: LineEnding) -> String {
+        let terminator = match (line_ending, self.line_ending) {
+            (LineEnding::CrLf, _) | (LineEnding::Preserve, LineEnding::CrLf) => "\r\n",
+            _ => "\n",
+        };
+
+        let mut text = self.lines.join(terminator);
+        if !self.lines.is_empty() {
+            text.push_str(terminator);
+        }
+        text
+    }
+
+    /// Replace the buffer contents with the given text
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.lines().map(str::to_string).collect();
+        self.line_ending = LineEnding::detect(text);
+    }
+
+    /// Get the line ending detected in the text the buffer was filled from
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Get the number of lines in the buffer
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Check if the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Get the file associated with the buffer
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Set the file associated with the buffer
+    pub fn set_filename(&mut self, filename: Option<String>) {
+        self.filename = filename;
+    }
+
+    /// Check if the buffer has unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Mark the buffer as having unsaved changes
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Mark the buffer as saved
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+}
+
+/// Bounded undo/redo history of buffer snapshots
+///
+/// Each mutating edit records the buffer as it was before the change.
+/// Recording a new snapshot clears the redo stack.
+#[derive(Debug, Clone, Default)]
+pub struct UndoHistory {
+    /// Snapshots that `undo` restores, oldest first
+    undo: VecDeque<EditBuffer>,
+    /// Snapshots that `redo` restores, most recent last
+    redo: Vec<EditBuffer>,
+}
+
+impl UndoHistory {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the buffer state before a mutation
+    ///
+    /// # Arguments
+    /// * `snapshot` - The buffer as it was before the change
+    /// * `depth` - Maximum number of undo steps to keep
+    pub fn record(&mut self, snapshot: EditBuffer, depth: usize) {
+        self.redo.clear();
+        if depth == 0 {
+            self.undo.clear();
+            return;
+        }
+
+        self.undo.push_back(snapshot);
+        while self.undo.len() > depth {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Restore the most recent snapshot into `current`
+    ///
+    /// The replaced state is kept so it can be redone.
+    pub fn undo(&mut self, current: &mut EditBuffer) -> CliResult<()> {
+        let previous = self
+            .undo
+            .pop_back()
+            .ok_or_else(|| CliError::other("nothing to undo"))?;
+        self.redo.push(std::mem::replace(current, previous));
+        Ok(())
+    }
+
+    /// Re-apply the most recently undone change into `current`
+    pub fn redo(&mut self, current: &mut EditBuffer) -> CliResult<()> {
+        let next = self
+            .redo
+            .pop()
+            .ok_or_else(|| CliError::other("nothing to redo"))?;
+        self.undo.push_back(std::mem::replace(current, next));
+        Ok(())
+    }
+
+    /// Check if there is a change to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Check if there is a change to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Discard all history
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_text_round_trip() {
+        let buffer = EditBuffer::from_text("one\ntwo\n");
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert!(!buffer.is_dirty());
+
+        assert_eq!(EditBuffer::new().text(), "");
+    }
+
+    #[test]
+    fn test_undo_redo_restores_snapshots() {
+        let mut history = UndoHistory::new();
+        let mut buffer = EditBuffer::from_text("one");
+
+        history.record(buffer.clone(), 10);
+        buffer.lines_mut().push("two".to_string());
+
+        history.undo(&mut buffer).unwrap();
+        assert_eq!(buffer.lines(), ["one"]);
+        assert!(history.can_redo());
+
+        history.redo(&mut buffer).unwrap();
+        assert_eq!(buffer.lines(), ["one", "two"]);
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn test_undo_empty_history() {
+        let mut history = UndoHistory::new();
+        let mut buffer = EditBuffer::new();
+
+        let result = history.undo(&mut buffer);
+        assert!(matches!(result, Err(CliError::Other(ref msg)) if msg == "nothing to undo"));
+        assert!(history.redo(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_record_clears_redo_and_respects_depth() {
+        let mut history = UndoHistory::new();
+        let mut buffer = EditBuffer::new();
+
+        for i in 0..5 {
+            history.record(buffer.clone(), 3);
+            buffer.lines_mut().push(i.to_string());
+        }
+
+        // Only the three most recent snapshots are kept
+        for _ in 0..3 {
+            history.undo(&mut buffer).unwrap();
+        }
+        assert_eq!(buffer.lines(), ["0", "1"]);
+        assert!(!history.can_undo());
+
+        // A new edit discards the redo stack
+        history.record(buffer.clone(), 3);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_line_ending_round_trip() {
+        let buffer = EditBuffer::from_text("one\r\ntwo\r\n");
+        assert_eq!(buffer.lines(), ["one", "two"]);
+        assert_eq!(buffer.line_ending(), LineEnding::CrLf);
+        assert_eq!(buffer.text_with(LineEnding::Preserve), "one\r\ntwo\r\n");
+        assert_eq!(buffer.text_with(LineEnding::Lf), "one\ntwo\n");
+
+        let buffer = EditBuffer::from_text("one\ntwo\n");
+        assert_eq!(buffer.line_ending(), LineEnding::Lf);
+        assert_eq!(buffer.text_with(LineEnding::CrLf), "one\r\ntwo\r\n");
+
+        // Mixed endings follow the majority
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\r\nb\nc\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("no breaks"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_preserve_on_new_buffer_uses_lf() {
+        let mut buffer = EditBuffer::new();
+        assert_eq!(buffer.line_ending(), LineEnding::Preserve);
+        assert_eq!(buffer.text_with(LineEnding::Preserve), "");
+
+        buffer.lines_mut().push("one".to_string());
+        assert_eq!(buffer.text_with(LineEnding::Preserve), "one\n");
+    }
+}
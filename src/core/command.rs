@@ -4,15 +4,25 @@
 //! It provides a consistent interface for command execution, help generation, and
 //! subcommand management.
 
+use crate::core::context::CliContext;
 use crate::core::error::CliResult;
+use std::fmt;
 
 /// Result of command execution
 ///
 /// This enum represents the different outcomes of executing a command,
 /// allowing the CLI framework to handle navigation and control flow.
+///
+/// Code that handles results should match every variant rather than use a
+/// wildcard arm, so that adding a variant is a compile error everywhere it
+/// needs handling. [`CommandResult::VARIANT_NAMES`] lists the variants for
+/// tests that check each one is covered.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CommandResult {
     /// Command executed successfully with an optional message
+    ///
+    /// A non-empty message is shown with the success icon; an empty one
+    /// means the command succeeded silently.
     Success(String),
     /// Return to parent menu
     GoUp,
@@ -20,18 +30,66 @@ pub enum CommandResult {
     Quit,
     /// Continue in current menu context
     Continue,
+    /// Dispatch each line through the normal input path, in order
+    Batch {
+        /// Command lines to dispatch
+        lines: Vec<String>,
+        /// Keep dispatching after a line fails
+        keep_going: bool,
+    },
 }
 
 impl CommandResult {
+    /// Names of every variant, in declaration order
+    pub const VARIANT_NAMES: [&'static str; 5] = ["Success", "GoUp", "Quit", "Continue", "Batch"];
+
+    /// Get the name of this result's variant
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CommandResult;
+    ///
+    /// assert_eq!(CommandResult::GoUp.variant_name(), "GoUp");
+    /// assert_eq!(CommandResult::success("done").variant_name(), "Success");
+    /// ```
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            CommandResult::Success(_) => "Success",
+            CommandResult::GoUp => "GoUp",
+            CommandResult::Quit => "Quit",
+            CommandResult::Continue => "Continue",
+            CommandResult::Batch { .. } => "Batch",
+        }
+    }
+
     /// Create a success result with a message
     pub fn success(msg: impl Into<String>) -> Self {
         CommandResult::Success(msg.into())
     }
 
+    /// Create a success result with a formatted message
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CommandResult;
+    ///
+    /// let line_number = 3;
+    /// let result = CommandResult::success_fmt(format_args!("Deleted line {line_number}"));
+    /// assert_eq!(result, CommandResult::success("Deleted line 3"));
+    /// ```
+    pub fn success_fmt(args: fmt::Arguments<'_>) -> Self {
+        CommandResult::Success(args.to_string())
+    }
+
     /// Create a success result with no message
     pub fn success_silent() -> Self {
         CommandResult::Success(String::new())
     }
+
+    /// Check whether this is a success result with nothing to show
+    pub fn is_silent(&self) -> bool {
+        matches!(self, CommandResult::Success(msg) if msg.is_empty())
+    }
 }
 
 /// Core trait that all CLI commands must implement
@@ -45,19 +103,107 @@ pub trait Command: std::fmt::Debug {
     /// Get the command description for help text
     fn description(&self) -> &'static str;
 
+    /// Check the shape of the arguments without running the command
+    ///
+    /// Validation covers argument counts and forms that can be checked
+    /// without side effects: no IO, and no reading or changing session
+    /// state. The session loop calls it before a command is confirmed or
+    /// run, and commands call it at the start of [`Command::execute`].
+    /// The default accepts any arguments.
+    fn validate(&self, _args: &[String]) -> CliResult<()> {
+        Ok(())
+    }
+
     /// Execute the command with given arguments
     fn execute(&mut self, args: &[String]) -> CliResult<CommandResult>;
 
+    /// Execute the command with access to the session context
+    ///
+    /// The main loop always calls this method. Commands that read or modify
+    /// session state (such as the edit buffer) override it; the default
+    /// simply delegates to [`Command::execute`].
+    ///
+    /// `path` is where the command sits in the menu tree: the names of the
+    /// menus entered so far followed by the command's own name, e.g.
+    /// `["view", "axis"]`. Commands use it to render breadcrumbs or to
+    /// behave differently depending on the menu they were run from.
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        _context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.execute(args)
+    }
+
+    /// Check if running the command with these arguments is destructive
+    ///
+    /// When `CliPreferences::confirm_destructive` is on, the session loop
+    /// asks the user to confirm before executing a destructive command, so
+    /// commands never prompt for confirmation themselves. The decision may
+    /// depend on the arguments and session state, e.g. `save` is only
+    /// destructive when it would overwrite an existing file.
+    fn is_destructive(&self, _args: &[String], _context: &CliContext) -> bool {
+        false
+    }
+
+    /// Describe what a destructive command would change
+    ///
+    /// The session loop shows the preview, if any, before confirming or
+    /// running a command that [`Command::is_destructive`] reports as
+    /// destructive. For example `save --diff` previews the lines an
+    /// overwrite would change.
+    fn preview(&self, _args: &[String], _context: &CliContext) -> Option<String> {
+        None
+    }
+
+    /// Check if a run with these arguments can be replayed instead of repeated
+    ///
+    /// Only read-only commands whose output depends on nothing but their
+    /// arguments should return true, such as `vers`. The session keeps a
+    /// bounded number of such runs, keyed by command path and arguments,
+    /// and replays a run's output and result when it is invoked again.
+    fn is_cacheable(&self, _args: &[String]) -> bool {
+        false
+    }
+
+    /// Check if the command only works with an interactive terminal
+    ///
+    /// Commands that read raw keystrokes, page output, or animate return
+    /// true. The session loop refuses to run them when the session input is
+    /// not a terminal (for example a piped script).
+    fn requires_tty(&self) -> bool {
+        false
+    }
+
+    /// Check if the command is left out of listings
+    ///
+    /// Hidden commands still run when typed, but menus, help, and
+    /// [`CommandTreeIter`](crate::core::CommandTreeIter) do not show them.
+    fn hidden(&self) -> bool {
+        false
+    }
+
     /// Get available subcommands (if any)
     fn subcommands(&self) -> Vec<Box<dyn Command>> {
         Vec::new()
     }
 
     /// Get command aliases (alternative names for the command)
+    ///
+    /// Every alias is matched when the command is typed.
     fn aliases(&self) -> Vec<&'static str> {
         Vec::new()
     }
 
+    /// Get the alias shown in bold in command listings
+    ///
+    /// This is the first alias, which may be longer than one letter (for
+    /// example `sh` for `show`).
+    fn primary_alias(&self) -> Option<&'static str> {
+        self.aliases().first().copied()
+    }
+
     /// Get detailed help text for the command
     fn help(&self) -> String {
         let aliases = self.aliases();
@@ -79,7 +225,19 @@ pub trait Command: std::fmt::Debug {
     }
 
     /// Check if the given name matches this command (name or alias)
+    ///
+    /// Matching ignores case; see [`Command::matches_case`] to require it.
     fn matches(&self, name: &str) -> bool {
+        self.matches_case(name, false)
+    }
+
+    /// Check if the given name matches this command, optionally in exact case
+    ///
+    /// Sessions pass the `case_sensitive_commands` preference.
+    fn matches_case(&self, name: &str, case_sensitive: bool) -> bool {
+        if case_sensitive {
+            return self.name() == name || self.aliases().contains(&name);
+        }
         let name_lower = name.to_lowercase();
         self.name().to_lowercase() == name_lower
             || self
@@ -97,6 +255,24 @@ pub trait Command: std::fmt::Debug {
     fn category(&self) -> CommandCategory {
         CommandCategory::General
     }
+
+    /// Get the kind of value the argument at `index` takes, for completion
+    ///
+    /// `index` counts the arguments after the command name, from 0. File
+    /// arguments are completed with the names in the working directory;
+    /// other arguments are not completed.
+    fn arg_completion(&self, _index: usize) -> ArgKind {
+        ArgKind::None
+    }
+}
+
+/// Kind of value an argument takes, which decides how it is completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// Not completed
+    None,
+    /// A path below the working directory
+    File,
 }
 
 /// Command categories for organizing help output
@@ -115,6 +291,15 @@ pub enum CommandCategory {
 }
 
 impl CommandCategory {
+    /// Every category, in the order grouped help lists them
+    pub const ALL: [CommandCategory; 5] = [
+        CommandCategory::General,
+        CommandCategory::File,
+        CommandCategory::Edit,
+        CommandCategory::View,
+        CommandCategory::System,
+    ];
+
     /// Get the display name for this category
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -134,6 +319,8 @@ impl CommandCategory {
 #[derive(Debug)]
 pub struct CommandRegistry {
     commands: Vec<Box<dyn Command>>,
+    /// Whether lookups require the exact case of a name or alias
+    case_sensitive: bool,
 }
 
 impl CommandRegistry {
@@ -141,38 +328,65 @@ impl CommandRegistry {
     pub fn new() -> Self {
         Self {
             commands: Vec::new(),
+            case_sensitive: false,
         }
     }
 
+    /// Require lookups to match a name or alias in its exact case
+    ///
+    /// Lookups ignore case unless this is set, as [`Command::matches`] does.
+    pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+        self.case_sensitive = case_sensitive;
+    }
+
     /// Register a command with the registry
     pub fn register(&mut self, command: Box<dyn Command>) {
         self.commands.push(command);
     }
 
-    /// Find a command by name or alias
+    /// Find a command by name or alias, including hidden commands
+    ///
+    /// This is the lookup for dispatch; use [`CommandRegistry::find_visible`]
+    /// for anything shown to the user.
     pub fn find_command(&self, name: &str) -> Option<&dyn Command> {
         self.commands
             .iter()
-            .find(|cmd| cmd.matches(name))
+            .find(|cmd| cmd.matches_case(name, self.case_sensitive))
             .map(|cmd| cmd.as_ref())
     }
 
-    /// Get all registered commands
+    /// Find a command by name or alias, skipping hidden commands
+    pub fn find_visible(&self, name: &str) -> Option<&dyn Command> {
+        self.visible_commands()
+            .find(|cmd| cmd.matches_case(name, self.case_sensitive))
+    }
+
+    /// Get all registered commands, including hidden commands
     pub fn commands(&self) -> &[Box<dyn Command>] {
         &self.commands
     }
 
-    /// Get commands organized by category
+    /// Get the registered commands that are not hidden, in registration order
+    pub fn visible_commands(&self) -> impl Iterator<Item = &dyn Command> {
+        self.commands
+            .iter()
+            .map(|cmd| cmd.as_ref())
+            .filter(|cmd| !cmd.hidden())
+    }
+
+    /// Get visible commands organized by category
+    ///
+    /// Hidden commands are left out, since categories organize help output.
     pub fn commands_by_category(
         &self,
     ) -> std::collections::HashMap<CommandCategory, Vec<&dyn Command>> {
         let mut categorized = std::collections::HashMap::new();
 
-        for command in &self.commands {
+        for command in self.visible_commands() {
             categorized
                 .entry(command.category())
                 .or_insert_with(Vec::new)
-                .push(command.as_ref());
+                .push(command);
         }
 
         categorized
@@ -185,6 +399,54 @@ impl Default for CommandRegistry {
     }
 }
 
+/// Parse a count typed by the user, from 0 up to `max`
+///
+/// Numbers too large for any integer type are reported as over the
+/// maximum rather than as non-numeric, so a runaway `repeat 99999999999999999999`
+/// gets the same answer as `repeat 5000`. Callers that need a positive
+/// count check for zero themselves.
+///
+/// # Returns
+/// * `Ok(usize)` - The count
+/// * `Err(CliError)` - `InvalidInput` if the argument is not a whole
+///   number, is negative, or is greater than `max`
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::command::parse_count;
+///
+/// assert_eq!(parse_count("12", 100).unwrap(), 12);
+/// assert!(parse_count("-1", 100).is_err());
+/// assert!(parse_count("101", 100).is_err());
+/// ```
+pub fn parse_count(arg: &str, max: usize) -> CliResult<usize> {
+    let digits = arg.trim();
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, digits.strip_prefix('+').unwrap_or(digits)),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(crate::core::error::CliError::invalid_input(&format!(
+            "Expected a whole number from 0 to {max}, got '{arg}'"
+        )));
+    }
+    if negative && digits.bytes().any(|b| b != b'0') {
+        return Err(crate::core::error::CliError::invalid_input(&format!(
+            "Count cannot be negative, got '{arg}'"
+        )));
+    }
+
+    digits
+        .parse::<usize>()
+        .ok()
+        .filter(|count| *count <= max)
+        .ok_or_else(|| {
+            crate::core::error::CliError::invalid_input(&format!(
+                "Count {digits} exceeds the maximum of {max}"
+            ))
+        })
+}
+
 /// Trait for commands that can validate their arguments
 pub trait ArgumentValidator {
     /// Validate the number of arguments
@@ -199,22 +461,33 @@ pub trait ArgumentValidator {
     }
 
     /// Validate the number of arguments is within a range
+    ///
+    /// A count outside `min..=max` is reported as `WrongArgumentCount`,
+    /// which carries the whole range.
     fn validate_arg_range(&self, args: &[String], min: usize, max: usize) -> CliResult<()> {
-        if args.len() < min {
-            return Err(crate::core::error::CliError::TooFewArguments {
-                expected: min,
-                found: args.len(),
-            });
-        }
-        if args.len() > max {
-            return Err(crate::core::error::CliError::TooManyArguments {
-                expected: max,
+        if !(min..=max).contains(&args.len()) {
+            return Err(crate::core::error::CliError::WrongArgumentCount {
+                min,
+                max,
                 found: args.len(),
             });
         }
         Ok(())
     }
 
+    /// Validate the number of arguments is exact, or within a range
+    ///
+    /// When `min` equals `max` this is [`ArgumentValidator::validate_arg_count`],
+    /// so existing callers keep their errors; otherwise it is
+    /// [`ArgumentValidator::validate_arg_range`].
+    fn validate_exact_or_range(&self, args: &[String], min: usize, max: usize) -> CliResult<()> {
+        if min == max {
+            self.validate_arg_count(args, min)
+        } else {
+            self.validate_arg_range(args, min, max)
+        }
+    }
+
     /// Validate that an argument is not empty
     fn validate_not_empty(&self, arg: &str, arg_name: &str) -> CliResult<()> {
         if arg.trim().is_empty() {
@@ -224,11 +497,21 @@ pub trait ArgumentValidator {
         }
         Ok(())
     }
+
+    /// Parse a numeric argument
+    fn parse_number<T: std::str::FromStr>(&self, arg: &str, arg_name: &str) -> CliResult<T> {
+        arg.trim().parse().map_err(|_| {
+            crate::core::error::CliError::invalid_input(&format!(
+                "{arg_name} must be a number, got '{arg}'"
+            ))
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::error::CliError;
 
     #[derive(Debug)]
     struct TestCommand {
@@ -288,6 +571,31 @@ mod tests {
         assert!(!cmd.matches("OTHER"));
     }
 
+    #[test]
+    fn test_command_matches_case_sensitive() {
+        let cmd = TestCommand {
+            name: "test",
+            description: "Test command",
+        };
+
+        assert!(cmd.matches_case("test", true));
+        assert!(cmd.matches_case("t", true));
+        assert!(!cmd.matches_case("TEST", true));
+        assert!(!cmd.matches_case("T", true));
+        assert!(cmd.matches_case("TEST", false));
+
+        let mut registry = CommandRegistry::new();
+        registry.register(Box::new(TestCommand {
+            name: "save",
+            description: "Save",
+        }));
+        assert!(registry.find_command("SAVE").is_some());
+        registry.set_case_sensitive(true);
+        assert!(registry.find_command("SAVE").is_none());
+        assert!(registry.find_visible("Save").is_none());
+        assert!(registry.find_command("save").is_some());
+    }
+
     #[test]
     fn test_command_help() {
         let cmd = TestCommand {
@@ -305,6 +613,29 @@ mod tests {
 
         let result = CommandResult::success_silent();
         assert_eq!(result, CommandResult::Success(String::new()));
+        assert!(result.is_silent());
+
+        let count = 2;
+        let result = CommandResult::success_fmt(format_args!("Loaded {count} lines"));
+        assert_eq!(result, CommandResult::success("Loaded 2 lines"));
+        assert!(!result.is_silent());
+        assert!(!CommandResult::Continue.is_silent());
+    }
+
+    #[test]
+    fn test_variant_names() {
+        let results = [
+            CommandResult::success_silent(),
+            CommandResult::GoUp,
+            CommandResult::Quit,
+            CommandResult::Continue,
+            CommandResult::Batch {
+                lines: Vec::new(),
+                keep_going: false,
+            },
+        ];
+        let names: Vec<_> = results.iter().map(CommandResult::variant_name).collect();
+        assert_eq!(names, CommandResult::VARIANT_NAMES);
     }
 
     #[test]
@@ -345,4 +676,115 @@ mod tests {
         assert!(registry.find_command("other").is_none());
         assert!(registry.find_command("OTHER").is_none());
     }
+
+    /// Command that runs when typed but is left out of listings
+    #[derive(Debug)]
+    struct SecretCommand;
+
+    impl Command for SecretCommand {
+        fn name(&self) -> &'static str {
+            "secret"
+        }
+
+        fn description(&self) -> &'static str {
+            "Hidden command"
+        }
+
+        fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+            Ok(CommandResult::success_silent())
+        }
+
+        fn hidden(&self) -> bool {
+            true
+        }
+
+        fn category(&self) -> CommandCategory {
+            CommandCategory::System
+        }
+    }
+
+    #[test]
+    fn test_command_registry_hidden_commands() {
+        let mut registry = CommandRegistry::new();
+        registry.register(Box::new(TestCommand {
+            name: "test",
+            description: "Test command",
+        }));
+        registry.register(Box::new(SecretCommand));
+
+        // Hidden commands can be dispatched but are not discoverable
+        assert!(registry.find_command("secret").is_some());
+        assert!(registry.find_visible("secret").is_none());
+        assert!(registry.find_visible("t").is_some());
+        assert_eq!(registry.commands().len(), 2);
+
+        let visible: Vec<_> = registry.visible_commands().map(|cmd| cmd.name()).collect();
+        assert_eq!(visible, ["test"]);
+        let categories = registry.commands_by_category();
+        assert!(!categories.contains_key(&CommandCategory::System));
+        assert_eq!(categories[&CommandCategory::General].len(), 1);
+    }
+
+    impl ArgumentValidator for TestCommand {}
+
+    #[test]
+    fn test_validate_arg_range_reports_range() {
+        let cmd = TestCommand {
+            name: "test",
+            description: "Test command",
+        };
+        let args = |n: usize| vec!["x".to_string(); n];
+
+        assert!(cmd.validate_arg_range(&args(1), 0, 2).is_ok());
+        assert_eq!(
+            cmd.validate_arg_range(&args(3), 0, 2),
+            Err(CliError::WrongArgumentCount {
+                min: 0,
+                max: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            cmd.validate_exact_or_range(&args(0), 1, 2),
+            Err(CliError::WrongArgumentCount {
+                min: 1,
+                max: 2,
+                found: 0
+            })
+        );
+        assert_eq!(
+            cmd.validate_exact_or_range(&args(2), 1, 1),
+            Err(CliError::TooManyArguments {
+                expected: 1,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("7", 10).unwrap(), 7);
+        assert_eq!(parse_count(" 10 ", 10).unwrap(), 10);
+        assert_eq!(parse_count("0", 10).unwrap(), 0);
+        assert_eq!(parse_count("+3", 10).unwrap(), 3);
+        assert_eq!(parse_count("-0", 10).unwrap(), 0);
+
+        for (arg, message) in [
+            ("-3", "Count cannot be negative, got '-3'"),
+            ("three", "Expected a whole number from 0 to 10, got 'three'"),
+            ("1.5", "Expected a whole number from 0 to 10, got '1.5'"),
+            ("", "Expected a whole number from 0 to 10, got ''"),
+            ("11", "Count 11 exceeds the maximum of 10"),
+            (
+                "99999999999999999999999",
+                "Count 99999999999999999999999 exceeds the maximum of 10",
+            ),
+        ] {
+            assert_eq!(
+                parse_count(arg, 10),
+                Err(CliError::invalid_input(message)),
+                "{arg:?}"
+            );
+        }
+    }
 }
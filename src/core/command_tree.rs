@@ -0,0 +1,200 @@
+//! Depth-first traversal of the command tree.
+//!
+//! Features that walk every command below a menu (search, listings,
+//! completions) share this traversal, so they agree on which commands are
+//! reachable and all stop at the same recursion guard.
+
+use crate::core::command::Command;
+use crate::core::error::{CliError, CliResult};
+
+/// Deepest menu level the traversal descends to
+pub const MAX_TREE_DEPTH: usize = 10;
+
+/// Iterator over the commands below a menu, in depth-first order
+///
+/// Each item is the command's path from the starting menu, e.g.
+/// `["file", "load"]`, together with the command itself. Commands are
+/// created fresh by [`Command::subcommands`], so the iterator hands out
+/// owned boxes rather than references.
+///
+/// Hidden commands are skipped along with everything below them. A
+/// command is listed but not descended into when its name is the starting
+/// menu's or already appears earlier in its path (a menu nested inside
+/// itself), or when the path is
+/// [`MAX_TREE_DEPTH`] levels deep, so iteration always terminates.
+#[derive(Debug)]
+pub struct CommandTreeIter {
+    /// Name of the menu the traversal started from
+    menu: &'static str,
+    /// Whether hidden commands are visited too
+    include_hidden: bool,
+    /// Commands still to visit, with the last one visited next
+    pending: Vec<(Vec<String>, Box<dyn Command>)>,
+}
+
+impl CommandTreeIter {
+    /// Start iterating over the commands below `menu`
+    ///
+    /// The menu itself is not yielded.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::RootCommand;
+    /// use sm_menu::core::CommandTreeIter;
+    ///
+    /// let paths: Vec<String> = CommandTreeIter::new(&RootCommand)
+    ///     .map(|(path, _)| path.join(" > "))
+    ///     .collect();
+    /// assert!(paths.contains(&"file > load".to_string()));
+    /// ```
+    pub fn new(menu: &dyn Command) -> Self {
+        Self::start(menu, false)
+    }
+
+    /// Start iterating over every command below `menu`, hidden ones included
+    pub fn with_hidden(menu: &dyn Command) -> Self {
+        Self::start(menu, true)
+    }
+
+    fn start(menu: &dyn Command, include_hidden: bool) -> Self {
+        let mut iter = CommandTreeIter {
+            menu: menu.name(),
+            include_hidden,
+            pending: Vec::new(),
+        };
+        iter.push_children(&[], menu);
+        iter
+    }
+
+    /// Queue the subcommands of `menu` to visit, keeping their order
+    fn push_children(&mut self, path: &[String], menu: &dyn Command) {
+        let include_hidden = self.include_hidden;
+        let children = menu
+            .subcommands()
+            .into_iter()
+            .filter(|cmd| include_hidden || !cmd.hidden())
+            .map(|cmd| {
+                let mut child_path = path.to_vec();
+                child_path.push(cmd.name().to_string());
+                (child_path, cmd)
+            });
+        let start = self.pending.len();
+        self.pending.extend(children);
+        self.pending[start..].reverse();
+    }
+}
+
+impl Iterator for CommandTreeIter {
+    type Item = (Vec<String>, Box<dyn Command>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, cmd) = self.pending.pop()?;
+
+        let (name, ancestors) = path.split_last().expect("paths are never empty");
+        let nested_in_itself = name == self.menu || ancestors.contains(name);
+        if !nested_in_itself && path.len() < MAX_TREE_DEPTH {
+            self.push_children(&path, cmd.as_ref());
+        }
+
+        Some((path, cmd))
+    }
+}
+
+/// Check that a command name or alias is one input token
+///
+/// A valid name is non-empty and has no whitespace or control characters,
+/// so it survives the session's whitespace tokenizing and can be typed.
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::command_tree::is_valid_command_name;
+///
+/// assert!(is_valid_command_name("load"));
+/// assert!(is_valid_command_name("?"));
+/// assert!(!is_valid_command_name("load file"));
+/// assert!(!is_valid_command_name(""));
+/// ```
+pub fn is_valid_command_name(name: &str) -> bool {
+    !name.is_empty() && !name.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Check the names and aliases of every command in a tree
+///
+/// The starting menu and all commands below it, hidden ones included, are
+/// checked with [`is_valid_command_name`].
+///
+/// # Returns
+/// * `Ok(())` - Every name and alias is valid
+/// * `Err(CliError)` - `InternalError` listing each offending name with the
+///   path to it
+pub fn validate_command_tree(root: &dyn Command) -> CliResult<()> {
+    let commands = std::iter::once((Vec::new(), root.name(), root.aliases())).chain(
+        CommandTreeIter::with_hidden(root).map(|(mut path, cmd)| {
+            path.pop();
+            (path, cmd.name(), cmd.aliases())
+        }),
+    );
+
+    let mut offenders = Vec::new();
+    for (parents, name, aliases) in commands {
+        for token in std::iter::once(name).chain(aliases) {
+            if !is_valid_command_name(token) {
+                let mut path = parents.clone();
+                path.push(format!("{token:?}"));
+                offenders.push(path.join(" > "));
+            }
+        }
+    }
+
+    if offenders.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::internal_error(&format!(
+            "invalid command names: {}",
+            offenders.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command::CommandResult;
+    use crate::core::error::CliResult;
+
+    const NAMES: [&str; 12] = [
+        "m0", "m1", "m2", "m3", "m4", "m5", "m6", "m7", "m8", "m9", "m10", "m11",
+    ];
+
+    /// Menu that always has one deeper menu with a new name
+    #[derive(Debug)]
+    struct Deep(usize);
+
+    impl Command for Deep {
+        fn name(&self) -> &'static str {
+            NAMES[self.0 % NAMES.len()]
+        }
+
+        fn description(&self) -> &'static str {
+            "Endless menu"
+        }
+
+        fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+            Ok(CommandResult::Continue)
+        }
+
+        fn subcommands(&self) -> Vec<Box<dyn Command>> {
+            vec![Box::new(Deep(self.0 + 1))]
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let paths: Vec<Vec<String>> = CommandTreeIter::new(&Deep(0))
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths.len(), MAX_TREE_DEPTH);
+        assert_eq!(paths.last().unwrap().len(), MAX_TREE_DEPTH);
+        assert_eq!(paths[1], ["m1", "m2"]);
+    }
+}
@@ -0,0 +1,469 @@
+//! Configuration file loading for the CLI application.
+//!
+//! Preferences can be set in a `config.toml` file. Only the part of TOML
+//! that preferences need is supported: one `key = value` pair per line,
+//! with booleans, non-negative integers, and quoted strings as values, and
+//! `#` comments. Keys that are not set keep their default values.
+
+use crate::core::axes::AxisScope;
+use crate::core::buffer::LineEnding;
+use crate::core::context::CliPreferences;
+use crate::core::error::{CliError, CliResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file inside the configuration directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Name of the history file inside the configuration directory
+pub const HISTORY_FILE_NAME: &str = "history";
+
+/// Name of the application's directory under the user configuration directory
+const CONFIG_DIR_NAME: &str = "sm-menu";
+
+/// Get the default configuration file path
+///
+/// This is `$XDG_CONFIG_HOME/sm-menu/config.toml`, falling back to
+/// `$HOME/.config/sm-menu/config.toml`. Returns `None` when neither
+/// variable is set.
+pub fn default_config_path() -> Option<PathBuf> {
+    default_config_path_from(
+        std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+        std::env::var_os("HOME").map(PathBuf::from),
+    )
+}
+
+/// Get the default history file path, next to the default configuration file
+pub fn default_history_path() -> Option<PathBuf> {
+    default_config_path().map(|path| path.with_file_name(HISTORY_FILE_NAME))
+}
+
+/// Build the default configuration file path from the given directories
+fn default_config_path_from(
+    xdg_config_home: Option<PathBuf>,
+    home: Option<PathBuf>,
+) -> Option<PathBuf> {
+    let config_home = xdg_config_home
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| home.join(".config")))?;
+    Some(config_home.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+impl CliPreferences {
+    /// Load preferences from a configuration file
+    ///
+    /// # Arguments
+    /// * `path` - The configuration file to read
+    ///
+    /// # Returns
+    /// * `Ok(CliPreferences)` - The defaults, overridden by the file's settings
+    /// * `Err(CliError)` - If the file cannot be read or is not valid
+    pub fn from_path(path: &Path) -> CliResult<Self> {
+        let text = fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => CliError::file_not_found(&path.display().to_string()),
+            _ => CliError::IoError(e),
+        })?;
+
+        Self::from_toml(&text).map_err(|e| match e {
+            CliError::InvalidFileFormat(msg) => {
+                CliError::InvalidFileFormat(format!("{}: {msg}", path.display()))
+            }
+            other => other,
+        })
+    }
+
+    /// Parse preferences from configuration text
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CliPreferences;
+    ///
+    /// let preferences = CliPreferences::from_toml("max_list_items = 10").unwrap();
+    /// assert_eq!(preferences.max_list_items, 10);
+    /// assert!(preferences.colored_prompt);
+    /// ```
+    pub fn from_toml(text: &str) -> CliResult<Self> {
+        let mut preferences = Self::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid =
+                |msg: &str| CliError::InvalidFileFormat(format!("line {}: {msg}", index + 1));
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected 'key = value'"))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "colored_prompt" => {
+                    preferences.colored_prompt =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "show_suggestions" => {
+                    preferences.show_suggestions =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "confirm_destructive" => {
+                    preferences.confirm_destructive =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "enable_recursive_menus" => {
+                    preferences.enable_recursive_menus =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "history_timestamps" => {
+                    preferences.history_timestamps =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "shortcut_hints" => {
+                    preferences.shortcut_hints =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "verbose" => {
+                    preferences.verbose =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "preserve_interrupted_line" => {
+                    preferences.preserve_interrupted_line =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "case_sensitive_commands" => {
+                    preferences.case_sensitive_commands =
+                        parse_bool(value).ok_or_else(|| invalid("expected true or false"))?
+                }
+                "max_list_items" => {
+                    preferences.max_list_items = value
+                        .parse()
+                        .map_err(|_| invalid("expected a non-negative integer"))?
+                }
+                "undo_depth" => {
+                    preferences.undo_depth = value
+                        .parse()
+                        .map_err(|_| invalid("expected a non-negative integer"))?
+                }
+                "io_retry_attempts" => {
+                    preferences.io_retry_attempts = value
+                        .parse()
+                        .ok()
+                        .filter(|&attempts| attempts >= 1)
+                        .ok_or_else(|| invalid("expected a positive integer"))?
+                }
+                "io_retry_backoff_ms" => {
+                    preferences.io_retry_backoff_ms = value
+                        .parse()
+                        .map_err(|_| invalid("expected a non-negative integer"))?
+                }
+                "line_ending" => {
+                    preferences.line_ending = match parse_string(value).as_deref() {
+                        Some("preserve") => LineEnding::Preserve,
+                        Some("lf") => LineEnding::Lf,
+                        Some("crlf") => LineEnding::CrLf,
+                        _ => return Err(invalid("expected \"preserve\", \"lf\", or \"crlf\"")),
+                    }
+                }
+                "axis_scope" => {
+                    preferences.axis_scope = match parse_string(value).as_deref() {
+                        Some("menu") => AxisScope::Menu,
+                        Some("shared") => AxisScope::Shared,
+                        _ => return Err(invalid("expected \"menu\" or \"shared\"")),
+                    }
+                }
+                _ => return Err(invalid(&format!("unknown setting '{key}'"))),
+            }
+        }
+
+        Ok(preferences)
+    }
+}
+
+impl CliPreferences {
+    /// Get each file setting as its configuration key and value
+    ///
+    /// Values are formatted as they are written in the configuration file.
+    /// `assume_yes`, `trace_errors`, `plain`, and `max_depth` are not file
+    /// settings and are not included.
+    pub(crate) fn settings(&self) -> [(&'static str, String); 15] {
+        let line_ending = match self.line_ending {
+            LineEnding::Preserve => "\"preserve\"",
+            LineEnding::Lf => "\"lf\"",
+            LineEnding::CrLf => "\"crlf\"",
+        };
+        let axis_scope = match self.axis_scope {
+            AxisScope::Menu => "\"menu\"",
+            AxisScope::Shared => "\"shared\"",
+        };
+        [
+            ("colored_prompt", self.colored_prompt.to_string()),
+            ("show_suggestions", self.show_suggestions.to_string()),
+            ("confirm_destructive", self.confirm_destructive.to_string()),
+            (
+                "enable_recursive_menus",
+                self.enable_recursive_menus.to_string(),
+            ),
+            ("history_timestamps", self.history_timestamps.to_string()),
+            ("shortcut_hints", self.shortcut_hints.to_string()),
+            ("verbose", self.verbose.to_string()),
+            (
+                "preserve_interrupted_line",
+                self.preserve_interrupted_line.to_string(),
+            ),
+            (
+                "case_sensitive_commands",
+                self.case_sensitive_commands.to_string(),
+            ),
+            ("max_list_items", self.max_list_items.to_string()),
+            ("undo_depth", self.undo_depth.to_string()),
+            ("io_retry_attempts", self.io_retry_attempts.to_string()),
+            ("io_retry_backoff_ms", self.io_retry_backoff_ms.to_string()),
+            ("line_ending", line_ending.to_string()),
+            ("axis_scope", axis_scope.to_string()),
+        ]
+    }
+
+    /// Describe the file settings that differ from other preferences
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CliPreferences;
+    ///
+    /// let old = CliPreferences::default();
+    /// let new = CliPreferences::from_toml("max_list_items = 10").unwrap();
+    /// assert_eq!(new.changes_from(&old), ["max_list_items: 50 -> 10"]);
+    /// ```
+    pub fn changes_from(&self, old: &CliPreferences) -> Vec<String> {
+        self.settings()
+            .into_iter()
+            .zip(old.settings())
+            .filter(|((_, new), (_, old))| new != old)
+            .map(|((key, new), (_, old))| format!("{key}: {old} -> {new}"))
+            .collect()
+    }
+
+    /// Write these preferences as a commented configuration file
+    ///
+    /// Every file setting is written, each after a comment describing it,
+    /// so the result reads back as the same preferences.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CliPreferences;
+    ///
+    /// let text = CliPreferences::default().to_toml();
+    /// assert!(text.contains("max_list_items = 50"));
+    /// assert_eq!(CliPreferences::from_toml(&text).unwrap(), CliPreferences::default());
+    /// ```
+    pub fn to_toml(&self) -> String {
+        let mut text = String::from("# sm-menu preferences\n");
+        for (key, value) in self.settings() {
+            text.push_str(&format!("\n# {}\n{key} = {value}\n", setting_comment(key)));
+        }
+        text
+    }
+}
+
+/// Write a configuration file with the default preferences
+///
+/// Missing parent directories are created. An existing file is only
+/// replaced when `force` is set.
+///
+/// # Arguments
+/// * `path` - The configuration file to write
+/// * `force` - Replace the file if it already exists
+///
+/// # Returns
+/// * `Ok(())` - If the file was written
+/// * `Err(CliError)` - If the file exists and `force` is not set, or it cannot be written
+pub fn write_default_config(path: &Path, force: bool) -> CliResult<()> {
+    if path.exists() && !force {
+        return Err(CliError::invalid_input(&format!(
+            "Configuration file already exists: {} (use --force to replace it)",
+            path.display()
+        )));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, CliPreferences::default().to_toml())?;
+    Ok(())
+}
+
+/// Get the comment written above a setting in a generated configuration file
+fn setting_comment(key: &str) -> &'static str {
+    match key {
+        "colored_prompt" => "Show the prompt and messages in color",
+        "show_suggestions" => "Suggest commands after an invalid command",
+        "confirm_destructive" => "Ask before destructive operations, such as overwriting a file",
+        "enable_recursive_menus" => "Offer a nested file menu inside the file menu",
+        "history_timestamps" => "Save a timestamp with each command in the history file",
+        "shortcut_hints" => "Show the shortcut hint before every prompt, not just the first few",
+        "verbose" => "Show extra info lines, such as when a menu is entered or left",
+        "preserve_interrupted_line" => {
+            "Keep a line abandoned with Ctrl-C as the start of the next prompt"
+        }
+        "case_sensitive_commands" => {
+            "Require command names and aliases in their exact case, so SAVE is not save"
+        }
+        "max_list_items" => "Maximum items to show in listings",
+        "undo_depth" => "Number of edits that can be undone",
+        "io_retry_attempts" => {
+            "Tries of a file read or write that fails transiently; 1 is no retry"
+        }
+        "io_retry_backoff_ms" => {
+            "Milliseconds before the first retry, doubled before each later one"
+        }
+        "line_ending" => "Line endings written by save: \"preserve\", \"lf\", or \"crlf\"",
+        "axis_scope" => "Axes kept per menu (\"menu\") or shared by edit and view (\"shared\")",
+        _ => "",
+    }
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parse a TOML boolean
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a double-quoted TOML string without escapes
+fn parse_string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    (!inner.contains('"') && !inner.contains('\\')).then(|| inner.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let preferences = CliPreferences::from_toml(
+            "# sm-menu preferences\n\
+             colored_prompt = false\n\
+             \n\
+             max_list_items = 10 # fewer rows\n\
+             line_ending = \"crlf\"\n\
+             preserve_interrupted_line = true\n\
+             case_sensitive_commands = true\n\
+             io_retry_attempts = 3\n\
+             axis_scope = \"shared\"\n",
+        )
+        .unwrap();
+
+        assert!(!preferences.colored_prompt);
+        assert!(preferences.preserve_interrupted_line);
+        assert!(preferences.case_sensitive_commands);
+        assert!(!CliPreferences::default().case_sensitive_commands);
+        assert_eq!(preferences.max_list_items, 10);
+        assert_eq!(preferences.io_retry_attempts, 3);
+        assert_eq!(preferences.axis_scope, AxisScope::Shared);
+        assert_eq!(preferences.line_ending, LineEnding::CrLf);
+        // Settings not in the file keep their defaults
+        assert_eq!(preferences.undo_depth, CliPreferences::default().undo_depth);
+    }
+
+    #[test]
+    fn test_from_toml_errors() {
+        for text in [
+            "colored_prompt",
+            "colored_prompt = yes",
+            "max_list_items = -1",
+            "io_retry_attempts = 0",
+            "line_ending = crlf",
+            "axis_scope = \"global\"",
+            "favourite_color = \"green\"",
+        ] {
+            assert!(
+                matches!(
+                    CliPreferences::from_toml(text),
+                    Err(CliError::InvalidFileFormat(_))
+                ),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_default_config() {
+        let dir = std::env::temp_dir().join(format!("sm_menu_init_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME);
+
+        write_default_config(&path, false).unwrap();
+        assert_eq!(
+            CliPreferences::from_path(&path).unwrap(),
+            CliPreferences::default()
+        );
+
+        // An existing file is only replaced with force
+        fs::write(&path, "undo_depth = 5\n").unwrap();
+        assert!(matches!(
+            write_default_config(&path, false),
+            Err(CliError::InvalidInput(_))
+        ));
+        assert_eq!(CliPreferences::from_path(&path).unwrap().undo_depth, 5);
+        write_default_config(&path, true).unwrap();
+        assert_eq!(
+            CliPreferences::from_path(&path).unwrap(),
+            CliPreferences::default()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_from_path() {
+        let dir = std::env::temp_dir().join(format!("sm_menu_config_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+
+        fs::write(&path, "undo_depth = 5\n").unwrap();
+        assert_eq!(CliPreferences::from_path(&path).unwrap().undo_depth, 5);
+
+        fs::write(&path, "undo_depth = many\n").unwrap();
+        let error = CliPreferences::from_path(&path).unwrap_err().to_string();
+        assert!(error.contains("config.toml: line 1"), "{error}");
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            CliPreferences::from_path(&path),
+            Err(CliError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_config_path() {
+        assert_eq!(
+            default_config_path_from(Some("/xdg".into()), Some("/home/u".into())),
+            Some(PathBuf::from("/xdg/sm-menu/config.toml"))
+        );
+        assert_eq!(
+            default_config_path_from(None, Some("/home/u".into())),
+            Some(PathBuf::from("/home/u/.config/sm-menu/config.toml"))
+        );
+        // A relative XDG_CONFIG_HOME is ignored, as the spec requires
+        assert_eq!(
+            default_config_path_from(Some("rel".into()), Some("/home/u".into())),
+            Some(PathBuf::from("/home/u/.config/sm-menu/config.toml"))
+        );
+        assert_eq!(default_config_path_from(None, None), None);
+    }
+}
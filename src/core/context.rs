@@ -3,8 +3,23 @@
 //! This module provides the context management system for the CLI application,
 //! handling navigation state, command history, and user session management.
 
-use crate::core::command::Command;
-use std::collections::VecDeque;
+use crate::commands::RootCommand;
+use crate::core::alias::AliasTable;
+use crate::core::axes::{AxisScope, AxisStore};
+use crate::core::buffer::{EditBuffer, LineEnding, UndoHistory};
+use crate::core::command::{ArgKind, Command};
+use crate::core::error::CliResult;
+use crate::core::file_io::{FileSystem, RetryPolicy, SessionFileSystem};
+use crate::core::history_file::HistoryEntry;
+use crate::core::io::SessionIo;
+use crate::core::listing::DirectoryListing;
+use crate::core::navigator::Navigator;
+use crate::core::security::SecurityContext;
+use crate::ui::DisplayManager;
+use crate::ui::theme::Theme;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Maximum number of commands to keep in history
 const MAX_HISTORY_SIZE: usize = 100;
@@ -15,90 +30,168 @@ const MAX_HISTORY_SIZE: usize = 100;
 /// navigation context, command history, and user preferences.
 #[derive(Debug)]
 pub struct CliContext {
-    /// Current navigation path (stack of menu names)
-    current_path: Vec<String>,
+    /// Entered menus and the navigation path of their names
+    navigator: Navigator,
     /// Whether the application is still running
     pub running: bool,
     /// Command history for user convenience
-    history: VecDeque<String>,
+    history: VecDeque<HistoryEntry>,
     /// Current history position (for history navigation)
     history_position: usize,
     /// User preferences
     preferences: CliPreferences,
+    /// Security boundary for file operations
+    security: SecurityContext,
+    /// Text buffer shared by edit operations
+    buffer: EditBuffer,
+    /// Undo/redo history for the buffer
+    undo_history: UndoHistory,
+    /// 1-based buffer line at the top of the viewer, moved by `goto`
+    viewport_top: usize,
+    /// Input handle for the session
+    io: SessionIo,
+    /// File system the file commands read and write through
+    file_system: SessionFileSystem,
+    /// Most recent directory listing, for referring to entries by number
+    last_listing: Option<DirectoryListing>,
+    /// Axes configured in each menu, keyed by menu name
+    axes: BTreeMap<String, AxisStore>,
+    /// User-defined aliases expanded before a line is dispatched
+    aliases: AliasTable,
+    /// Themed output for commands
+    display: DisplayManager,
+    /// Process exit code to report when the session ends
+    exit_code: u8,
+    /// Configuration file the preferences were read from, for reloading
+    config_path: Option<PathBuf>,
 }
 
 impl CliContext {
     /// Create a new CLI context with default settings
     pub fn new() -> Self {
+        Self::with_security(SecurityContext::default())
+    }
+
+    /// Create a new CLI context confined to the given security boundary
+    pub fn with_security(security: SecurityContext) -> Self {
         Self {
-            current_path: Vec::new(),
+            navigator: Navigator::new(Box::new(RootCommand)),
             running: true,
             history: VecDeque::new(),
             history_position: 0,
             preferences: CliPreferences::default(),
+            security,
+            buffer: EditBuffer::new(),
+            undo_history: UndoHistory::new(),
+            viewport_top: 1,
+            io: SessionIo::stdio(),
+            file_system: SessionFileSystem::default(),
+            last_listing: None,
+            axes: BTreeMap::new(),
+            aliases: AliasTable::new(),
+            display: DisplayManager::new(),
+            exit_code: 0,
+            config_path: None,
         }
     }
 
-    /// Push a new context level (enter submenu)
-    pub fn push_context(&mut self, name: String) {
-        self.current_path.push(name);
+    /// Get the entered menus and the navigation path
+    pub fn navigator(&self) -> &Navigator {
+        &self.navigator
     }
 
-    /// Pop the current context level (exit to parent menu)
-    pub fn pop_context(&mut self) -> Option<String> {
-        self.current_path.pop()
+    /// Get mutable access to the navigation state, to enter or leave menus
+    pub fn navigator_mut(&mut self) -> &mut Navigator {
+        &mut self.navigator
     }
 
     /// Get the current navigation path
     pub fn current_path(&self) -> &[String] {
-        &self.current_path
+        self.navigator.path()
     }
 
     /// Get the current depth in the navigation hierarchy
     pub fn depth(&self) -> usize {
-        self.current_path.len()
+        self.navigator.depth()
     }
 
     /// Check if we're at the root level
     pub fn is_root(&self) -> bool {
-        self.current_path.is_empty()
+        self.navigator.is_root()
     }
 
     /// Get the formatted prompt string
+    ///
+    /// Path segments and the separators between them are styled with the
+    /// session theme; with color off the prompt is plain text. A `*` before
+    /// the final `>` marks a buffer with unsaved changes, like an editor's
+    /// title bar.
     pub fn get_prompt(&self) -> String {
-        // Use Warp-like green color (24-bit color for better accuracy)
-        let green_color = if self.preferences.colored_prompt {
-            "\x1b[38;2;0;215;135m"
+        let theme = self.theme();
+        let brand = theme.paint(theme.brand, "sm-menu");
+        let marker = if self.buffer.is_dirty() {
+            theme.paint(theme.modified, "*")
         } else {
-            ""
-        };
-        let reset_color = if self.preferences.colored_prompt {
-            "\x1b[0m"
-        } else {
-            ""
+            String::new()
         };
 
-        if self.current_path.is_empty() {
-            format!("{green_color}sm-menu{reset_color} > ")
+        if self.navigator.is_root() {
+            format!("{brand}{marker} > ")
         } else {
+            let separator = format!(" {} ", theme.paint(theme.separator, ">"));
+            let path = self
+                .navigator
+                .path()
+                .iter()
+                .map(|segment| theme.paint(theme.path_segment, segment))
+                .collect::<Vec<_>>()
+                .join(&separator);
             format!(
-                "{}sm-menu{} ~ {} > ",
-                green_color,
-                reset_color,
-                self.current_path.join(" > ")
+                "{brand} {} {path}{marker} > ",
+                theme.paint(theme.separator, "~")
             )
         }
     }
 
+    /// Get the theme for the current color preference
+    pub fn theme(&self) -> Theme {
+        Theme::for_color(self.preferences.colored())
+    }
+
     /// Signal that the application should quit
     pub fn quit(&mut self) {
         self.running = false;
     }
 
-    /// Add a command to the history
+    /// Set the process exit code to report when the session ends
+    pub fn set_exit_code(&mut self, code: u8) {
+        self.exit_code = code;
+    }
+
+    /// Get the process exit code to report when the session ends (0 by default)
+    pub fn exit_code(&self) -> u8 {
+        self.exit_code
+    }
+
+    /// Add a command to the history, timestamped now
     pub fn add_to_history(&mut self, command: String) {
-        if !command.trim().is_empty() && self.history.back() != Some(&command) {
-            self.history.push_back(command);
+        self.push_history(HistoryEntry::now(command));
+    }
+
+    /// Add previously recorded entries (for example loaded from a file) to the history
+    pub fn extend_history(&mut self, entries: impl IntoIterator<Item = HistoryEntry>) {
+        for entry in entries {
+            self.push_history(entry);
+        }
+    }
+
+    fn push_history(&mut self, entry: HistoryEntry) {
+        let is_repeat = self
+            .history
+            .back()
+            .is_some_and(|last| last.command == entry.command);
+        if !entry.command.trim().is_empty() && !is_repeat {
+            self.history.push_back(entry);
 
             // Limit history size
             if self.history.len() > MAX_HISTORY_SIZE {
@@ -111,7 +204,7 @@ impl CliContext {
     }
 
     /// Get the command history
-    pub fn history(&self) -> &VecDeque<String> {
+    pub fn history(&self) -> &VecDeque<HistoryEntry> {
         &self.history
     }
 
@@ -119,7 +212,9 @@ impl CliContext {
     pub fn previous_command(&mut self) -> Option<&String> {
         if self.history_position > 0 {
             self.history_position -= 1;
-            self.history.get(self.history_position)
+            self.history
+                .get(self.history_position)
+                .map(|entry| &entry.command)
         } else {
             None
         }
@@ -129,18 +224,31 @@ impl CliContext {
     pub fn next_command(&mut self) -> Option<&String> {
         if self.history_position < self.history.len().saturating_sub(1) {
             self.history_position += 1;
-            self.history.get(self.history_position)
+            self.history
+                .get(self.history_position)
+                .map(|entry| &entry.command)
         } else {
             None
         }
     }
 
     /// Get command completions for the given prefix
+    ///
+    /// A prefix without whitespace completes command names, aliases, and
+    /// history lines. Once a command name is followed by whitespace, the
+    /// last word is an argument instead: arguments the command declares as
+    /// [`ArgKind::File`] complete to names in the working directory, and
+    /// other arguments get no completions. Argument completions are whole
+    /// lines, like history completions.
     pub fn get_completions(
         &self,
         prefix: &str,
         available_commands: &[Box<dyn Command>],
     ) -> Vec<String> {
+        if let Some((name, args)) = prefix.split_once(char::is_whitespace) {
+            return self.arg_completions(prefix, name, args, available_commands);
+        }
+
         let mut completions = Vec::new();
 
         // Add command name completions
@@ -158,7 +266,7 @@ impl CliContext {
         }
 
         // Add history completions
-        for command in &self.history {
+        for HistoryEntry { command, .. } in &self.history {
             if command.starts_with(prefix) && !completions.contains(command) {
                 completions.push(command.clone());
             }
@@ -169,6 +277,75 @@ impl CliContext {
         completions
     }
 
+    /// Complete the last word of `prefix` as an argument of the command `name`
+    fn arg_completions(
+        &self,
+        prefix: &str,
+        name: &str,
+        args: &str,
+        available_commands: &[Box<dyn Command>],
+    ) -> Vec<String> {
+        let case_sensitive = self.preferences.case_sensitive_commands;
+        let Some(cmd) = available_commands
+            .iter()
+            .find(|cmd| cmd.matches_case(name, case_sensitive))
+        else {
+            return Vec::new();
+        };
+        let words: Vec<&str> = args.split_whitespace().collect();
+        let (index, partial) = if args.ends_with(char::is_whitespace) || words.is_empty() {
+            (words.len(), "")
+        } else {
+            (words.len() - 1, words[words.len() - 1])
+        };
+        if cmd.arg_completion(index) != ArgKind::File {
+            return Vec::new();
+        }
+
+        let line = &prefix[..prefix.len() - partial.len()];
+        self.file_completions(partial)
+            .into_iter()
+            .map(|path| format!("{line}{path}"))
+            .collect()
+    }
+
+    /// List the paths below the working directory that start with `partial`
+    ///
+    /// The directory part of `partial` is validated like any other path, so
+    /// completion cannot list directories outside the root. Directories end
+    /// with `/`, and hidden names are only offered once `.` is typed.
+    fn file_completions(&self, partial: &str) -> Vec<String> {
+        let (dir, start) = match partial.rfind('/') {
+            Some(index) => partial.split_at(index + 1),
+            None => ("", partial),
+        };
+        let dir_path = if dir.is_empty() {
+            self.security.root().to_path_buf()
+        } else {
+            match self.security.validate_path(dir) {
+                Ok(path) => path,
+                Err(_) => return Vec::new(),
+            }
+        };
+        let Ok(entries) = std::fs::read_dir(dir_path) else {
+            return Vec::new();
+        };
+
+        let mut completions: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                if !name.starts_with(start) || (name.starts_with('.') && !start.starts_with('.')) {
+                    return None;
+                }
+                let slash = if entry.path().is_dir() { "/" } else { "" };
+                Some(format!("{dir}{name}{slash}"))
+            })
+            .collect();
+        completions.sort();
+        completions
+    }
+
     /// Get user preferences
     pub fn preferences(&self) -> &CliPreferences {
         &self.preferences
@@ -179,11 +356,254 @@ impl CliContext {
         &mut self.preferences
     }
 
+    /// Get the configuration file the preferences were read from
+    pub fn config_path(&self) -> Option<&Path> {
+        self.config_path.as_deref()
+    }
+
+    /// Set the configuration file `reload` reads preferences from
+    pub fn set_config_path(&mut self, path: Option<PathBuf>) {
+        self.config_path = path;
+    }
+
+    /// Get the security boundary for file operations
+    pub fn security(&self) -> &SecurityContext {
+        &self.security
+    }
+
+    /// Replace the security boundary for file operations
+    pub fn set_security(&mut self, security: SecurityContext) {
+        self.security = security;
+    }
+
+    /// Get the display manager commands use for themed output
+    ///
+    /// Colors follow the `colored_prompt` preference, so commands showing
+    /// messages through it match the prompt and the session's errors.
+    /// Suggestions after invalid commands follow `show_suggestions`. Plain
+    /// output also turns off unicode icons and progress bars.
+    pub fn display(&mut self) -> &DisplayManager {
+        self.display.set_colored(self.preferences.colored());
+        if self.preferences.plain {
+            self.display.set_unicode(false);
+            self.display.set_show_progress(false);
+        }
+        self.display
+            .set_show_suggestions(self.preferences.show_suggestions);
+        self.display.set_trace_errors(self.preferences.trace_errors);
+        &self.display
+    }
+
+    /// Get the display manager together with the navigation state
+    ///
+    /// For output that lists the current menu's commands, which needs both
+    /// at once.
+    pub fn display_with_navigator(&mut self) -> (&DisplayManager, &Navigator) {
+        self.display();
+        (&self.display, &self.navigator)
+    }
+
+    /// Get the display manager together with the session input handle
+    ///
+    /// For prompts that read their answer through the display, such as
+    /// [`DisplayManager::read_secret`].
+    pub fn display_with_io(&mut self) -> (&DisplayManager, &mut SessionIo) {
+        self.display();
+        (&self.display, &mut self.io)
+    }
+
+    /// Replace the display manager, for example to capture output
+    pub fn set_display(&mut self, display: DisplayManager) {
+        self.display = display;
+    }
+
+    /// Get the edit buffer
+    pub fn buffer(&self) -> &EditBuffer {
+        &self.buffer
+    }
+
+    /// Record that the buffer was written to a file
+    ///
+    /// Sets the buffer's filename and clears its dirty flag. The contents
+    /// are unchanged, so nothing is pushed onto the undo stack.
+    pub fn mark_saved(&mut self, filename: String) {
+        self.buffer.set_filename(Some(filename));
+        self.buffer.mark_clean();
+    }
+
+    /// Apply a mutating edit to the buffer
+    ///
+    /// The buffer state before the edit is pushed onto the undo stack and
+    /// the redo stack is cleared. If the edit fails the buffer is left
+    /// unchanged.
+    pub fn edit_buffer<R>(
+        &mut self,
+        edit: impl FnOnce(&mut EditBuffer) -> CliResult<R>,
+    ) -> CliResult<R> {
+        let snapshot = self.buffer.clone();
+        match edit(&mut self.buffer) {
+            Ok(result) => {
+                self.buffer.mark_dirty();
+                self.undo_history
+                    .record(snapshot, self.preferences.undo_depth);
+                Ok(result)
+            }
+            Err(e) => {
+                self.buffer = snapshot;
+                Err(e)
+            }
+        }
+    }
+
+    /// Replace the whole buffer (for example after loading a file)
+    ///
+    /// The previous buffer is pushed onto the undo stack. The new buffer's
+    /// dirty flag is kept as given.
+    pub fn replace_buffer(&mut self, buffer: EditBuffer) {
+        let previous = std::mem::replace(&mut self.buffer, buffer);
+        self.undo_history
+            .record(previous, self.preferences.undo_depth);
+        self.viewport_top = 1;
+    }
+
+    /// Get the 1-based buffer line at the top of the viewer
+    ///
+    /// The viewer starts at line 1, and returns there whenever the whole
+    /// buffer is replaced.
+    pub fn viewport_top(&self) -> usize {
+        self.viewport_top
+    }
+
+    /// Scroll the viewer so the given 1-based buffer line is at the top
+    pub fn set_viewport_top(&mut self, line: usize) {
+        self.viewport_top = line.max(1);
+    }
+
+    /// Replace the file system the file commands go through
+    ///
+    /// Sessions use [`StdFileSystem`](crate::core::file_io::StdFileSystem);
+    /// tests can substitute one that fails on purpose.
+    pub fn set_file_system(&mut self, file_system: Box<dyn FileSystem>) {
+        self.file_system = SessionFileSystem(file_system);
+    }
+
+    /// Get the retry policy for file operations from the preferences
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(
+            self.preferences.io_retry_attempts,
+            Duration::from_millis(self.preferences.io_retry_backoff_ms),
+        )
+    }
+
+    /// Read a whole file, retrying transient errors
+    pub fn read_file(&self, path: &Path) -> CliResult<Vec<u8>> {
+        self.retry_policy().run(|| self.file_system.0.read(path))
+    }
+
+    /// Create or replace a file, retrying transient errors
+    pub fn write_file(&self, path: &Path, contents: &[u8]) -> CliResult<()> {
+        self.retry_policy()
+            .run(|| self.file_system.0.write(path, contents))
+    }
+
+    /// Append to a file, trying only once
+    ///
+    /// Unlike a read or a whole-file write, an append is not safe to
+    /// repeat: one that timed out may already have written some of the
+    /// contents, and retrying would add them again.
+    pub fn append_file(&self, path: &Path, contents: &[u8]) -> CliResult<()> {
+        RetryPolicy::none().run(|| self.file_system.0.append(path, contents))
+    }
+
+    /// Undo the most recent buffer edit
+    pub fn undo(&mut self) -> CliResult<()> {
+        self.undo_history.undo(&mut self.buffer)
+    }
+
+    /// Redo the most recently undone buffer edit
+    pub fn redo(&mut self) -> CliResult<()> {
+        self.undo_history.redo(&mut self.buffer)
+    }
+
+    /// Get the most recent directory listing
+    pub fn last_listing(&self) -> Option<&DirectoryListing> {
+        self.last_listing.as_ref()
+    }
+
+    /// Remember a directory listing so its entries can be referred to by number
+    pub fn set_last_listing(&mut self, listing: DirectoryListing) {
+        self.last_listing = Some(listing);
+    }
+
+    /// Get the axes configured in a menu, if any have been
+    ///
+    /// With the shared `axis_scope` every menu gets the same axes.
+    pub fn axes(&self, menu: &str) -> Option<&AxisStore> {
+        self.axes.get(self.preferences.axis_scope.store_key(menu))
+    }
+
+    /// Iterate over the stored axes by menu name, in name order
+    ///
+    /// Shared axes are listed under [`SHARED_AXES`](crate::core::axes::SHARED_AXES).
+    pub fn axes_by_menu(&self) -> impl Iterator<Item = (&str, &AxisStore)> {
+        self.axes.iter().map(|(menu, store)| (menu.as_str(), store))
+    }
+
+    /// Get mutable access to the axes of a menu, creating an empty store
+    ///
+    /// With the shared `axis_scope` every menu gets the same axes.
+    pub fn axes_mut(&mut self, menu: &str) -> &mut AxisStore {
+        let key = self.preferences.axis_scope.store_key(menu);
+        self.axes.entry(key.to_string()).or_default()
+    }
+
+    /// Get the user-defined aliases
+    pub fn aliases(&self) -> &AliasTable {
+        &self.aliases
+    }
+
+    /// Get mutable access to the user-defined aliases, to define or remove them
+    pub fn aliases_mut(&mut self) -> &mut AliasTable {
+        &mut self.aliases
+    }
+
+    /// Get the session input handle
+    pub fn io(&self) -> &SessionIo {
+        &self.io
+    }
+
+    /// Get the mutable session input handle
+    pub fn io_mut(&mut self) -> &mut SessionIo {
+        &mut self.io
+    }
+
+    /// Replace the session input handle
+    pub fn set_io(&mut self, io: SessionIo) {
+        self.io = io;
+    }
+
     /// Reset the context to initial state
+    ///
+    /// The buffer and its undo history are emptied, every axis and the
+    /// last directory listing are dropped, and navigation returns to the
+    /// root menu. The command history is kept; see
+    /// [`CliContext::clear_history`]. Preferences, the security boundary,
+    /// and the session's input and output are not touched.
     pub fn reset(&mut self) {
-        self.current_path.clear();
+        self.navigator.reset();
         self.running = true;
         self.history_position = self.history.len();
+        self.buffer = EditBuffer::new();
+        self.undo_history.clear();
+        self.viewport_top = 1;
+        self.last_listing = None;
+        self.axes.clear();
+    }
+
+    /// Forget every command in the history
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_position = 0;
     }
 }
 
@@ -194,7 +614,7 @@ impl Default for CliContext {
 }
 
 /// User preferences for CLI behavior
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CliPreferences {
     /// Whether to use colored output
     pub colored_prompt: bool,
@@ -202,8 +622,52 @@ pub struct CliPreferences {
     pub show_suggestions: bool,
     /// Whether to confirm destructive operations
     pub confirm_destructive: bool,
+    /// Whether confirmations are answered yes without asking
+    pub assume_yes: bool,
+    /// Whether errors are shown with the chain of their causes
+    pub trace_errors: bool,
+    /// Whether all decorative output is off: color, unicode icons,
+    /// progress bars, shortcut hints, and the welcome and goodbye banners
+    pub plain: bool,
     /// Maximum number of items to show in listings
     pub max_list_items: usize,
+    /// Maximum number of edit operations that can be undone
+    pub undo_depth: usize,
+    /// Line ending used when saving the buffer
+    pub line_ending: LineEnding,
+    /// Whether axes are kept per menu or shared by all menus
+    pub axis_scope: AxisScope,
+    /// Whether the file menu offers a nested file menu
+    pub enable_recursive_menus: bool,
+    /// Whether timestamps are written to the persisted history
+    pub history_timestamps: bool,
+    /// Whether the shortcut hint is shown before every interactive prompt
+    pub shortcut_hints: bool,
+    /// Whether extra info lines are shown, such as when a menu is entered
+    pub verbose: bool,
+    /// Whether a line abandoned with Ctrl-C starts the next prompt, instead
+    /// of being discarded
+    pub preserve_interrupted_line: bool,
+    /// Whether command names and aliases must be typed in their exact case
+    pub case_sensitive_commands: bool,
+    /// Tries of a file read or write that fails transiently, including the
+    /// first; 1 turns retries off. Appends are never retried.
+    pub io_retry_attempts: u32,
+    /// Milliseconds to wait before the first retry, doubled after each one
+    pub io_retry_backoff_ms: u64,
+    /// Deepest menu level that can be entered, with the root at level 0
+    ///
+    /// `None` leaves only the built-in navigation depth limit.
+    pub max_depth: Option<usize>,
+}
+
+impl CliPreferences {
+    /// Check whether output is colored
+    ///
+    /// Color follows `colored_prompt`, unless plain output is on.
+    pub fn colored(&self) -> bool {
+        self.colored_prompt && !self.plain
+    }
 }
 
 impl Default for CliPreferences {
@@ -212,7 +676,22 @@ impl Default for CliPreferences {
             colored_prompt: true,
             show_suggestions: true,
             confirm_destructive: true,
+            assume_yes: false,
+            trace_errors: false,
+            plain: false,
             max_list_items: 50,
+            undo_depth: 50,
+            line_ending: LineEnding::Preserve,
+            axis_scope: AxisScope::Menu,
+            enable_recursive_menus: false,
+            history_timestamps: false,
+            shortcut_hints: false,
+            verbose: false,
+            preserve_interrupted_line: false,
+            case_sensitive_commands: false,
+            io_retry_attempts: 1,
+            io_retry_backoff_ms: 100,
+            max_depth: None,
         }
     }
 }
@@ -220,29 +699,43 @@ impl Default for CliPreferences {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::axis::AxisCommand;
+    use crate::commands::file::FileCommand;
+    use crate::commands::load::LoadCommand;
+    use crate::commands::save::SaveCommand;
+    use crate::commands::view::ViewCommand;
+    use crate::ui::theme::{strip_ansi, visible_width};
 
     #[test]
     fn test_context_creation() {
         let context = CliContext::new();
         assert!(context.running);
-        assert!(context.current_path.is_empty());
+        assert!(context.current_path().is_empty());
         assert!(context.is_root());
         assert_eq!(context.depth(), 0);
     }
 
+    #[test]
+    fn test_context_with_security() {
+        let root = std::env::temp_dir();
+        let security = SecurityContext::new(&root).unwrap();
+        let context = CliContext::with_security(security);
+        assert_eq!(context.security().root(), root.canonicalize().unwrap());
+    }
+
     #[test]
     fn test_context_navigation() {
         let mut context = CliContext::new();
 
         // Test pushing context
-        context.push_context("file".to_string());
+        context.navigator_mut().push(Box::new(FileCommand::new()));
         assert_eq!(context.current_path(), &["file"]);
         assert_eq!(context.depth(), 1);
         assert!(!context.is_root());
 
         // Test popping context
-        let popped = context.pop_context();
-        assert_eq!(popped, Some("file".to_string()));
+        let popped = context.navigator_mut().pop();
+        assert_eq!(popped.map(|menu| menu.name()), Some("file"));
         assert!(context.is_root());
         assert_eq!(context.depth(), 0);
     }
@@ -256,10 +749,10 @@ mod tests {
         assert_eq!(context.get_prompt(), "sm-menu > ");
 
         // Nested prompt
-        context.push_context("file".to_string());
+        context.navigator_mut().push(Box::new(FileCommand::new()));
         assert_eq!(context.get_prompt(), "sm-menu ~ file > ");
 
-        context.push_context("load".to_string());
+        context.navigator_mut().push(Box::new(LoadCommand::new()));
         assert_eq!(context.get_prompt(), "sm-menu ~ file > load > ");
     }
 
@@ -274,14 +767,98 @@ mod tests {
             "\x1b[38;2;0;215;135msm-menu\x1b[0m > "
         );
 
-        // Nested prompt with Warp-style green color
-        context.push_context("file".to_string());
+        // Each path segment is cyan and each separator dimmed
+        context.navigator_mut().push(Box::new(FileCommand::new()));
+        context.navigator_mut().push(Box::new(LoadCommand::new()));
         assert_eq!(
             context.get_prompt(),
-            "\x1b[38;2;0;215;135msm-menu\x1b[0m ~ file > "
+            "\x1b[38;2;0;215;135msm-menu\x1b[0m \x1b[2m~\x1b[0m \x1b[36mfile\x1b[0m \x1b[2m>\x1b[0m \x1b[36mload\x1b[0m > "
         );
     }
 
+    #[test]
+    fn test_colored_prompt_has_plain_width() {
+        let mut context = CliContext::new();
+        context.navigator_mut().push(Box::new(ViewCommand::new()));
+        context.navigator_mut().push(Box::new(AxisCommand::new()));
+
+        context.preferences.colored_prompt = false;
+        let plain = context.get_prompt();
+        context.preferences.colored_prompt = true;
+        let colored = context.get_prompt();
+
+        assert_eq!(strip_ansi(&colored), plain);
+        assert_eq!(visible_width(&colored), plain.chars().count());
+    }
+
+    #[test]
+    fn test_prompt_marks_unsaved_changes() {
+        let root = std::env::temp_dir().join(format!("sm_menu_prompt_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let mut context = CliContext::with_security(SecurityContext::new(&root).unwrap());
+        context.preferences.colored_prompt = false;
+        context.navigator_mut().push(Box::new(FileCommand::new()));
+
+        context
+            .edit_buffer(|buffer| {
+                buffer.lines_mut().push("hello".to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(context.get_prompt(), "sm-menu ~ file* > ");
+        context.navigator_mut().pop();
+        assert_eq!(context.get_prompt(), "sm-menu* > ");
+
+        // The marker is styled with color on and keeps the plain width
+        context.preferences.colored_prompt = true;
+        let colored = context.get_prompt();
+        assert!(colored.contains("\x1b[1;33m*\x1b[0m"), "{colored:?}");
+        assert_eq!(visible_width(&colored), "sm-menu* > ".len());
+
+        // Saving clears it
+        context.preferences.colored_prompt = false;
+        SaveCommand::new()
+            .execute_with_context(&["notes.txt".to_string()], &[], &mut context)
+            .unwrap();
+        assert!(!context.buffer().is_dirty());
+        assert_eq!(context.get_prompt(), "sm-menu > ");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_edit_then_undo_and_redo() {
+        let mut context = CliContext::new();
+
+        context
+            .edit_buffer(|buffer| {
+                buffer.lines_mut().push("hello".to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(context.buffer().lines(), ["hello"]);
+        assert!(context.buffer().is_dirty());
+
+        context.undo().unwrap();
+        assert!(context.buffer().is_empty());
+        assert!(!context.buffer().is_dirty());
+
+        context.redo().unwrap();
+        assert_eq!(context.buffer().lines(), ["hello"]);
+    }
+
+    #[test]
+    fn test_failed_edit_leaves_buffer_unchanged() {
+        let mut context = CliContext::new();
+
+        let result: CliResult<()> = context.edit_buffer(|buffer| {
+            buffer.lines_mut().push("partial".to_string());
+            Err(crate::core::error::CliError::other("failed"))
+        });
+        assert!(result.is_err());
+        assert!(context.buffer().is_empty());
+        assert!(context.undo().is_err());
+    }
+
     #[test]
     fn test_history_management() {
         let mut context = CliContext::new();
@@ -301,5 +878,37 @@ mod tests {
         context.add_to_history("".to_string());
         context.add_to_history("   ".to_string());
         assert_eq!(context.history().len(), 3);
+
+        context.clear_history();
+        assert!(context.history().is_empty());
+        assert!(context.previous_command().is_none());
+    }
+
+    #[test]
+    fn test_reset_clears_session_state() {
+        let mut context = CliContext::new();
+        context
+            .edit_buffer(|buffer| {
+                buffer.lines_mut().push("hello".to_string());
+                Ok(())
+            })
+            .unwrap();
+        context
+            .axes_mut("edit")
+            .set("x", crate::core::axes::Axis::default())
+            .unwrap();
+        context.set_viewport_top(4);
+        context.navigator_mut().push(Box::new(FileCommand::new()));
+        context.add_to_history("file".to_string());
+
+        context.reset();
+        assert!(context.buffer().is_empty());
+        assert!(!context.buffer().is_dirty());
+        assert!(context.undo().is_err());
+        assert!(context.axes("edit").is_none());
+        assert_eq!(context.viewport_top(), 1);
+        assert!(context.is_root());
+        // The history is only cleared on request
+        assert_eq!(context.history().len(), 1);
     }
 }
@@ -7,6 +7,7 @@
 use std::error::Error;
 use std::fmt;
 use std::io;
+use std::time::Duration;
 
 /// Result type for CLI operations
 pub type CliResult<T> = Result<T, CliError>;
@@ -19,6 +20,11 @@ pub type CliResult<T> = Result<T, CliError>;
 pub enum CliError {
     /// Invalid command name provided
     InvalidCommand(String),
+    /// Input matches more than one command in the current menu
+    AmbiguousCommand {
+        input: String,
+        candidates: Vec<String>,
+    },
     /// Invalid input format or content
     InvalidInput(String),
     /// IO operation failed
@@ -29,8 +35,17 @@ pub enum CliError {
     TooManyArguments { expected: usize, found: usize },
     /// Too few arguments provided to command
     TooFewArguments { expected: usize, found: usize },
-    /// Command execution failed
-    ExecutionError(String),
+    /// Argument count outside the accepted range
+    WrongArgumentCount {
+        min: usize,
+        max: usize,
+        found: usize,
+    },
+    /// Command execution failed, possibly because of an underlying error
+    ExecutionError {
+        message: String,
+        source: Option<Box<dyn Error + Send + Sync>>,
+    },
     /// Permission denied error
     PermissionDenied(String),
     /// File not found error
@@ -39,6 +54,10 @@ pub enum CliError {
     InvalidFileFormat(String),
     /// Operation interrupted by user
     Interrupted,
+    /// Reading input interrupted by user, e.g. Ctrl-C at the prompt
+    InputInterrupted,
+    /// No input arrived within the idle timeout
+    IdleTimeout(Duration),
     /// Terminal operation failed
     TerminalError(String),
     /// Internal error (should not happen in normal operation)
@@ -51,6 +70,11 @@ impl fmt::Display for CliError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             CliError::InvalidCommand(cmd) => write!(f, "Invalid command: '{cmd}'"),
+            CliError::AmbiguousCommand { input, candidates } => write!(
+                f,
+                "Ambiguous command: '{input}' could be {}",
+                candidates.join(", ")
+            ),
             CliError::InvalidInput(input) => write!(f, "Invalid input: {input}"),
             CliError::IoError(err) => write!(f, "IO error: {err}"),
             CliError::EmptyInput => write!(f, "Empty input provided"),
@@ -60,11 +84,27 @@ impl fmt::Display for CliError {
             CliError::TooFewArguments { expected, found } => {
                 write!(f, "Too few arguments: expected {expected}, found {found}")
             }
-            CliError::ExecutionError(msg) => write!(f, "Command execution failed: {msg}"),
+            CliError::WrongArgumentCount { min, max, found } => {
+                write!(f, "Wrong number of arguments: expected ")?;
+                match (min, max) {
+                    (1, 1) => write!(f, "1 argument")?,
+                    (min, max) if min == max => write!(f, "{min} arguments")?,
+                    (min, max) => write!(f, "{min} to {max} arguments")?,
+                }
+                write!(f, ", found {found}")
+            }
+            CliError::ExecutionError { message, .. } => {
+                write!(f, "Command execution failed: {message}")
+            }
             CliError::PermissionDenied(resource) => write!(f, "Permission denied: {resource}"),
             CliError::FileNotFound(path) => write!(f, "File not found: {path}"),
             CliError::InvalidFileFormat(details) => write!(f, "Invalid file format: {details}"),
             CliError::Interrupted => write!(f, "Operation interrupted by user"),
+            CliError::InputInterrupted => write!(f, "Input interrupted"),
+            CliError::IdleTimeout(timeout) => match timeout.as_secs() {
+                1 => write!(f, "No input for 1 second"),
+                seconds => write!(f, "No input for {seconds} seconds"),
+            },
             CliError::TerminalError(msg) => write!(f, "Terminal error: {msg}"),
             CliError::InternalError(msg) => {
                 write!(f, "Internal error: {msg} (please report this bug)")
@@ -78,11 +118,96 @@ impl Error for CliError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             CliError::IoError(err) => Some(err),
+            CliError::ExecutionError {
+                source: Some(source),
+                ..
+            } => Some(source.as_ref()),
             _ => None,
         }
     }
 }
 
+/// Errors are equal when they are the same variant with equal payloads
+///
+/// `io::Error` cannot be compared, so IO errors are equal when their
+/// [`io::ErrorKind`]s are, and the causes of execution errors are compared
+/// by their messages.
+///
+/// # Examples
+/// ```
+/// use sm_menu::CliError;
+/// use std::io;
+///
+/// assert_eq!(
+///     CliError::TooManyArguments { expected: 0, found: 1 },
+///     CliError::TooManyArguments { expected: 0, found: 1 }
+/// );
+/// assert_eq!(
+///     CliError::IoError(io::Error::new(io::ErrorKind::TimedOut, "first")),
+///     CliError::IoError(io::Error::new(io::ErrorKind::TimedOut, "second"))
+/// );
+/// ```
+impl PartialEq for CliError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CliError::InvalidCommand(a), CliError::InvalidCommand(b))
+            | (CliError::InvalidInput(a), CliError::InvalidInput(b))
+            | (CliError::PermissionDenied(a), CliError::PermissionDenied(b))
+            | (CliError::FileNotFound(a), CliError::FileNotFound(b))
+            | (CliError::InvalidFileFormat(a), CliError::InvalidFileFormat(b))
+            | (CliError::TerminalError(a), CliError::TerminalError(b))
+            | (CliError::InternalError(a), CliError::InternalError(b))
+            | (CliError::Other(a), CliError::Other(b)) => a == b,
+            (
+                CliError::AmbiguousCommand { input, candidates },
+                CliError::AmbiguousCommand {
+                    input: other_input,
+                    candidates: other_candidates,
+                },
+            ) => input == other_input && candidates == other_candidates,
+            (CliError::IoError(a), CliError::IoError(b)) => a.kind() == b.kind(),
+            (
+                CliError::TooManyArguments { expected, found },
+                CliError::TooManyArguments {
+                    expected: other_expected,
+                    found: other_found,
+                },
+            )
+            | (
+                CliError::TooFewArguments { expected, found },
+                CliError::TooFewArguments {
+                    expected: other_expected,
+                    found: other_found,
+                },
+            ) => expected == other_expected && found == other_found,
+            (
+                CliError::WrongArgumentCount { min, max, found },
+                CliError::WrongArgumentCount {
+                    min: other_min,
+                    max: other_max,
+                    found: other_found,
+                },
+            ) => min == other_min && max == other_max && found == other_found,
+            (
+                CliError::ExecutionError { message, source },
+                CliError::ExecutionError {
+                    message: other_message,
+                    source: other_source,
+                },
+            ) => {
+                message == other_message
+                    && source.as_ref().map(ToString::to_string)
+                        == other_source.as_ref().map(ToString::to_string)
+            }
+            (CliError::IdleTimeout(a), CliError::IdleTimeout(b)) => a == b,
+            (CliError::EmptyInput, CliError::EmptyInput)
+            | (CliError::Interrupted, CliError::Interrupted)
+            | (CliError::InputInterrupted, CliError::InputInterrupted) => true,
+            _ => false,
+        }
+    }
+}
+
 impl From<io::Error> for CliError {
     fn from(err: io::Error) -> Self {
         match err.kind() {
@@ -111,7 +236,36 @@ impl CliError {
 
     /// Create an execution error
     pub fn execution_error(msg: &str) -> Self {
-        CliError::ExecutionError(msg.to_string())
+        CliError::ExecutionError {
+            message: msg.to_string(),
+            source: None,
+        }
+    }
+
+    /// Create an execution error caused by another error
+    ///
+    /// The cause is kept as the error's [`Error::source`], so it can be
+    /// shown with `--trace-errors` instead of being folded into the message.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CliError;
+    /// use std::error::Error;
+    /// use std::io;
+    ///
+    /// let cause = io::Error::other("disk full");
+    /// let error = CliError::execution_error_with_source("Cannot write backup", cause);
+    /// assert_eq!(error.to_string(), "Command execution failed: Cannot write backup");
+    /// assert_eq!(error.source().unwrap().to_string(), "disk full");
+    /// ```
+    pub fn execution_error_with_source(
+        msg: &str,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        CliError::ExecutionError {
+            message: msg.to_string(),
+            source: Some(source.into()),
+        }
     }
 
     /// Create a file not found error
@@ -158,10 +312,13 @@ impl CliError {
             CliError::InvalidCommand(_) | CliError::InvalidInput(_) | CliError::EmptyInput => {
                 ErrorSeverity::Warning
             }
-            CliError::TooManyArguments { .. } | CliError::TooFewArguments { .. } => {
-                ErrorSeverity::Warning
-            }
-            CliError::ExecutionError(_)
+            CliError::AmbiguousCommand { .. } => ErrorSeverity::Warning,
+            // Pressing Ctrl-C at the prompt only abandons the line being typed
+            CliError::InputInterrupted | CliError::IdleTimeout(_) => ErrorSeverity::Warning,
+            CliError::TooManyArguments { .. }
+            | CliError::TooFewArguments { .. }
+            | CliError::WrongArgumentCount { .. } => ErrorSeverity::Warning,
+            CliError::ExecutionError { .. }
             | CliError::FileNotFound(_)
             | CliError::PermissionDenied(_) => ErrorSeverity::Error,
             CliError::InvalidFileFormat(_) | CliError::Interrupted => ErrorSeverity::Error,
@@ -172,14 +329,59 @@ impl CliError {
         }
     }
 
-    /// Get the emoji icon for this error type
+    /// Check whether this error is transient, so the operation may succeed
+    /// if tried again
+    ///
+    /// Only IO errors such as timeouts and dropped connections, which
+    /// network file systems report, are retryable. A user interrupt is not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CliError::IoError(err) => matches!(
+                err.kind(),
+                io::ErrorKind::TimedOut
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::ResourceBusy
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected
+            ),
+            _ => false,
+        }
+    }
+
+    /// Get the unicode (emoji) icon for this error type
+    ///
+    /// This is the unicode variant; see [`CliError::icon_ascii`] for terminals
+    /// without unicode support. Display code should pick between the two via
+    /// the display manager rather than calling either directly.
+    pub fn icon(&self) -> &'static str {
+        self.severity().icon()
+    }
+
+    /// Get the plain ascii icon for this error type
+    pub fn icon_ascii(&self) -> &'static str {
+        self.severity().icon_ascii()
+    }
+}
+
+impl ErrorSeverity {
+    /// Get the unicode (emoji) icon for this severity
     pub fn icon(&self) -> &'static str {
-        match self.severity() {
+        match self {
             ErrorSeverity::Warning => "⚠️",
             ErrorSeverity::Error => "❌",
             ErrorSeverity::Critical => "💥",
         }
     }
+
+    /// Get the plain ascii icon for this severity
+    pub fn icon_ascii(&self) -> &'static str {
+        match self {
+            ErrorSeverity::Warning => "!",
+            ErrorSeverity::Error => "X",
+            ErrorSeverity::Critical => "!!",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -209,11 +411,120 @@ mod tests {
         };
         assert_eq!(format!("{error}"), "Too few arguments: expected 2, found 1");
 
-        let error = CliError::ExecutionError("command failed".to_string());
+        let error = CliError::WrongArgumentCount {
+            min: 0,
+            max: 1,
+            found: 2,
+        };
+        assert_eq!(
+            format!("{error}"),
+            "Wrong number of arguments: expected 0 to 1 arguments, found 2"
+        );
+
+        let error = CliError::WrongArgumentCount {
+            min: 1,
+            max: 1,
+            found: 3,
+        };
+        assert_eq!(
+            format!("{error}"),
+            "Wrong number of arguments: expected 1 argument, found 3"
+        );
+
+        let error = CliError::execution_error("command failed");
         assert_eq!(
             format!("{error}"),
             "Command execution failed: command failed"
         );
+
+        let error = CliError::AmbiguousCommand {
+            input: "v".to_string(),
+            candidates: vec!["view".to_string(), "vers".to_string()],
+        };
+        assert_eq!(
+            format!("{error}"),
+            "Ambiguous command: 'v' could be view, vers"
+        );
+    }
+
+    #[test]
+    fn test_error_equality() {
+        assert_eq!(
+            CliError::TooManyArguments {
+                expected: 0,
+                found: 1
+            },
+            CliError::TooManyArguments {
+                expected: 0,
+                found: 1
+            }
+        );
+        assert_ne!(
+            CliError::TooManyArguments {
+                expected: 0,
+                found: 1
+            },
+            CliError::TooFewArguments {
+                expected: 0,
+                found: 1
+            }
+        );
+        assert_ne!(
+            CliError::WrongArgumentCount {
+                min: 1,
+                max: 2,
+                found: 3
+            },
+            CliError::WrongArgumentCount {
+                min: 1,
+                max: 3,
+                found: 3
+            }
+        );
+        assert_eq!(
+            CliError::invalid_input("bad"),
+            CliError::invalid_input("bad")
+        );
+        assert_ne!(
+            CliError::invalid_input("bad"),
+            CliError::invalid_command("bad")
+        );
+        assert_ne!(
+            CliError::invalid_input("bad"),
+            CliError::invalid_input("worse")
+        );
+        assert_eq!(CliError::EmptyInput, CliError::EmptyInput);
+        assert_ne!(CliError::Interrupted, CliError::InputInterrupted);
+        assert_eq!(
+            CliError::AmbiguousCommand {
+                input: "s".to_string(),
+                candidates: vec!["save".to_string(), "show".to_string()]
+            },
+            CliError::AmbiguousCommand {
+                input: "s".to_string(),
+                candidates: vec!["save".to_string(), "show".to_string()]
+            }
+        );
+
+        // IO errors compare by kind, whatever their messages
+        assert_eq!(
+            CliError::IoError(io::Error::new(io::ErrorKind::TimedOut, "slow")),
+            CliError::IoError(io::Error::new(io::ErrorKind::TimedOut, "very slow"))
+        );
+        assert_ne!(
+            CliError::IoError(io::Error::from(io::ErrorKind::TimedOut)),
+            CliError::IoError(io::Error::from(io::ErrorKind::WriteZero))
+        );
+
+        // Execution errors compare their causes by message
+        assert_eq!(
+            CliError::execution_error_with_source("failed", io::Error::other("disk full")),
+            CliError::execution_error_with_source("failed", io::Error::other("disk full"))
+        );
+        assert_ne!(
+            CliError::execution_error_with_source("failed", io::Error::other("disk full")),
+            CliError::execution_error("failed")
+        );
     }
 
     #[test]
@@ -223,7 +534,7 @@ mod tests {
             ErrorSeverity::Warning
         );
         assert_eq!(
-            CliError::ExecutionError("test".to_string()).severity(),
+            CliError::execution_error("test").severity(),
             ErrorSeverity::Error
         );
         assert_eq!(
@@ -232,10 +543,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_interruption_severity() {
+        assert_eq!(
+            CliError::InputInterrupted.severity(),
+            ErrorSeverity::Warning
+        );
+        assert_eq!(CliError::Interrupted.severity(), ErrorSeverity::Error);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(CliError::from(io::Error::from(io::ErrorKind::TimedOut)).is_retryable());
+        assert!(CliError::from(io::Error::from(io::ErrorKind::ConnectionReset)).is_retryable());
+        assert!(!CliError::from(io::Error::from(io::ErrorKind::NotFound)).is_retryable());
+        assert!(!CliError::from(io::Error::other("disk full")).is_retryable());
+        assert!(!CliError::Interrupted.is_retryable());
+        assert!(!CliError::execution_error("timed out").is_retryable());
+    }
+
     #[test]
     fn test_error_icons() {
         assert_eq!(CliError::InvalidCommand("test".to_string()).icon(), "⚠️");
-        assert_eq!(CliError::ExecutionError("test".to_string()).icon(), "❌");
+        assert_eq!(CliError::execution_error("test").icon(), "❌");
         assert_eq!(CliError::InternalError("test".to_string()).icon(), "💥");
     }
+
+    #[test]
+    fn test_error_icons_ascii() {
+        assert_eq!(
+            CliError::InvalidCommand("test".to_string()).icon_ascii(),
+            "!"
+        );
+        assert_eq!(CliError::execution_error("test").icon_ascii(), "X");
+        assert_eq!(
+            CliError::InternalError("test".to_string()).icon_ascii(),
+            "!!"
+        );
+    }
 }
@@ -0,0 +1,181 @@
+//! File access for commands, with bounded retries of transient errors.
+//!
+//! Commands that read or write whole files go through the session's
+//! [`FileSystem`] instead of calling `std::fs` directly, so tests can put a
+//! failing file system in its place. Network and NFS mounts occasionally
+//! fail an operation that works when tried again; a [`RetryPolicy`] repeats
+//! an operation whose error [`CliError::is_retryable`] says is transient,
+//! waiting longer before each attempt. Retries are off by default.
+
+use crate::core::error::{CliError, CliResult};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// File operations the file commands perform
+pub trait FileSystem {
+    /// Read a whole file
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Create or replace a file with the given contents
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Add contents to the end of a file, creating it if needed
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+}
+
+/// File system backed by `std::fs`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(contents)
+    }
+}
+
+/// How often, and how patiently, a file operation is tried
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::file_io::RetryPolicy;
+/// use std::io;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(3, Duration::ZERO);
+/// let mut failures = 2;
+/// let value = policy
+///     .run(|| {
+///         if failures > 0 {
+///             failures -= 1;
+///             return Err(io::ErrorKind::TimedOut.into());
+///         }
+///         Ok(7)
+///     })
+///     .unwrap();
+/// assert_eq!(value, 7);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 means no retries
+    pub attempts: u32,
+    /// Wait before the first retry, doubled before each one after it
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Create a policy of `attempts` tries in total
+    ///
+    /// A count of 0 is treated as 1, so the operation always runs once.
+    pub fn new(attempts: u32, backoff: Duration) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            backoff,
+        }
+    }
+
+    /// Create a policy that tries each operation once
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// Run an operation, retrying transient failures
+    ///
+    /// Errors that are not retryable are returned at once. When every
+    /// attempt fails, the last error is returned.
+    pub fn run<T>(&self, mut operation: impl FnMut() -> io::Result<T>) -> CliResult<T> {
+        let mut wait = self.backoff;
+        for attempt in 1.. {
+            match operation().map_err(CliError::from) {
+                Err(e) if e.is_retryable() && attempt < self.attempts => {
+                    thread::sleep(wait);
+                    wait = wait.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+        unreachable!("the attempts loop only ends by returning")
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// Boxed file system a session uses
+pub(crate) struct SessionFileSystem(pub(crate) Box<dyn FileSystem>);
+
+impl fmt::Debug for SessionFileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SessionFileSystem")
+    }
+}
+
+impl Default for SessionFileSystem {
+    fn default() -> Self {
+        Self(Box::new(StdFileSystem))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Run an operation that fails `failures` times with `kind` before succeeding
+    fn flaky(policy: RetryPolicy, failures: u32, kind: io::ErrorKind) -> (CliResult<u32>, u32) {
+        let calls = Cell::new(0);
+        let result = policy.run(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() <= failures {
+                Err(kind.into())
+            } else {
+                Ok(calls.get())
+            }
+        });
+        (result, calls.get())
+    }
+
+    #[test]
+    fn test_retry_policy_retries_transient_errors() {
+        let policy = RetryPolicy::new(3, Duration::ZERO);
+        let (result, calls) = flaky(policy, 2, io::ErrorKind::TimedOut);
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+
+        let (result, calls) = flaky(policy, 3, io::ErrorKind::TimedOut);
+        assert!(matches!(result, Err(CliError::IoError(_))));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_retry_policy_returns_other_errors_at_once() {
+        let policy = RetryPolicy::new(5, Duration::ZERO);
+        let (result, calls) = flaky(policy, 1, io::ErrorKind::NotFound);
+        assert!(matches!(result, Err(CliError::FileNotFound(_))));
+        assert_eq!(calls, 1);
+
+        // The default policy tries once
+        let (result, calls) = flaky(RetryPolicy::default(), 1, io::ErrorKind::TimedOut);
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(RetryPolicy::new(0, Duration::ZERO).attempts, 1);
+    }
+}
@@ -0,0 +1,275 @@
+//! Command history entries and their persisted form.
+//!
+//! History is stored one command per line. A line may start with an
+//! ISO-8601 UTC timestamp and a tab (`2024-06-01T10:00:00Z\tsave x`); a
+//! line without one is a legacy entry and is loaded without a time.
+
+use crate::core::error::{CliError, CliResult};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds in a day
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// One command in the history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The command line as typed
+    pub command: String,
+    /// When the command was entered, in seconds since the Unix epoch
+    pub timestamp: Option<u64>,
+}
+
+impl HistoryEntry {
+    /// Create an entry for a command entered now
+    pub fn now(command: String) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|elapsed| elapsed.as_secs());
+        Self { command, timestamp }
+    }
+
+    /// Parse a persisted history line
+    ///
+    /// A line whose text before the first tab is not a valid timestamp is
+    /// taken whole as the command, so legacy plain lines still load.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::history_file::HistoryEntry;
+    ///
+    /// let entry = HistoryEntry::parse_line("2024-06-01T10:00:00Z\tsave x");
+    /// assert_eq!(entry.command, "save x");
+    /// assert_eq!(entry.timestamp, Some(1_717_236_000));
+    ///
+    /// let legacy = HistoryEntry::parse_line("save x");
+    /// assert_eq!(legacy.timestamp, None);
+    /// ```
+    pub fn parse_line(line: &str) -> Self {
+        if let Some((stamp, command)) = line.split_once('\t')
+            && let Some(timestamp) = parse_timestamp(stamp)
+        {
+            return Self {
+                command: command.to_string(),
+                timestamp: Some(timestamp),
+            };
+        }
+
+        Self {
+            command: line.to_string(),
+            timestamp: None,
+        }
+    }
+
+    /// Format the entry as a persisted history line
+    ///
+    /// The timestamp is written only when `with_timestamp` is set and the
+    /// entry has one.
+    pub fn to_line(&self, with_timestamp: bool) -> String {
+        match self.timestamp {
+            Some(timestamp) if with_timestamp => {
+                format!("{}\t{}", format_timestamp(timestamp), self.command)
+            }
+            _ => self.command.clone(),
+        }
+    }
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp / SECONDS_PER_DAY);
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parse a `YYYY-MM-DDTHH:MM:SSZ` timestamp into seconds since the Unix epoch
+///
+/// Returns `None` for any other form, or for dates before 1970.
+pub fn parse_timestamp(text: &str) -> Option<u64> {
+    let bytes = text.as_bytes();
+    if bytes.len() != 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || bytes[10] != b'T'
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+        || bytes[19] != b'Z'
+    {
+        return None;
+    }
+
+    let field = |range: std::ops::Range<usize>| -> Option<u64> {
+        let digits = text.get(range)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if year < 1970 || !(1..=12).contains(&month) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    // Reject days that do not exist in the month, such as February 30
+    let days = days_from_civil(year, month, day)?;
+    if civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+
+    Some(days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second)
+}
+
+/// Count the days from 1970-01-01 to a date on or after it
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    if day == 0 {
+        return None;
+    }
+    // Years start in March so the leap day falls at the end
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+/// Convert days since 1970-01-01 to a `(year, month, day)` date
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Load history entries from a file
+///
+/// A missing file is an empty history. Blank lines are skipped.
+pub fn load_history(path: &Path) -> CliResult<Vec<HistoryEntry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(CliError::IoError(e)),
+    };
+
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(HistoryEntry::parse_line)
+        .collect())
+}
+
+/// Write history entries to a file, one per line
+///
+/// # Arguments
+/// * `path` - The history file to write
+/// * `entries` - The entries to write, oldest first
+/// * `with_timestamps` - Whether to write each entry's timestamp
+pub fn save_history<'a>(
+    path: &Path,
+    entries: impl IntoIterator<Item = &'a HistoryEntry>,
+    with_timestamps: bool,
+) -> CliResult<()> {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&entry.to_line(with_timestamps));
+        text.push('\n');
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_format_and_parse() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_717_236_000), "2024-06-01T10:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+
+        for timestamp in [0, 951_782_400, 1_717_236_000, 4_102_444_799] {
+            assert_eq!(
+                parse_timestamp(&format_timestamp(timestamp)),
+                Some(timestamp)
+            );
+        }
+
+        for text in [
+            "2024-06-01 10:00:00Z",
+            "2024-06-01T10:00:00",
+            "2024-13-01T10:00:00Z",
+            "2023-02-29T10:00:00Z",
+            "2024-06-01T24:00:00Z",
+            "1969-12-31T23:59:59Z",
+            "+024-06-01T10:00:00Z",
+        ] {
+            assert_eq!(parse_timestamp(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_timestamped_entry_round_trip() {
+        let entry = HistoryEntry {
+            command: "save x".to_string(),
+            timestamp: Some(1_717_236_000),
+        };
+        let line = entry.to_line(true);
+        assert_eq!(line, "2024-06-01T10:00:00Z\tsave x");
+        assert_eq!(HistoryEntry::parse_line(&line), entry);
+
+        // Without timestamps the line is plain
+        assert_eq!(entry.to_line(false), "save x");
+    }
+
+    #[test]
+    fn test_legacy_plain_entry() {
+        let entry = HistoryEntry::parse_line("load notes.txt");
+        assert_eq!(entry.command, "load notes.txt");
+        assert_eq!(entry.timestamp, None);
+
+        // A tab after something that is not a timestamp belongs to the command
+        let entry = HistoryEntry::parse_line("note\tkeep");
+        assert_eq!(entry.command, "note\tkeep");
+        assert_eq!(entry.timestamp, None);
+    }
+
+    #[test]
+    fn test_save_and_load_history() {
+        let dir = std::env::temp_dir().join(format!("sm_menu_history_{}", std::process::id()));
+        let path = dir.join("history");
+        let entries = vec![
+            HistoryEntry::parse_line("legacy"),
+            HistoryEntry::parse_line("2024-06-01T10:00:00Z\tsave x"),
+        ];
+
+        save_history(&path, &entries, true).unwrap();
+        assert_eq!(load_history(&path).unwrap(), entries);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(load_history(&path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+//! Interrupt flag for blocking operations.
+//!
+//! Commands that block for a while check this process-wide flag as they
+//! go, so a Ctrl-C handler (or another thread) can ask them to stop with
+//! [`request_interrupt`]. A stopped operation fails with
+//! [`CliError::Interrupted`]. While a [`catch_interrupts`] guard is held,
+//! Ctrl-C at the terminal requests an interrupt instead of ending the
+//! program.
+
+use crate::core::error::{CliError, CliResult};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest time a blocking operation goes without checking the flag
+pub const INTERRUPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Set when an interrupt has been requested and not yet handled
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Ask the running blocking operation to stop
+///
+/// Only sets a flag, so it is safe to call from a signal handler.
+pub fn request_interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Check whether an interrupt has been requested
+pub fn interrupt_requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Forget any interrupt requested so far
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// Sleep for a duration, stopping early if an interrupt is requested
+///
+/// An interrupt requested before the sleep started is forgotten, so it
+/// cannot cut short an operation it was not meant for.
+///
+/// # Returns
+/// * `Ok(())` - The full duration passed
+/// * `Err(CliError::Interrupted)` - If an interrupt was requested first
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::interrupt::sleep_interruptibly;
+/// use std::time::Duration;
+///
+/// assert!(sleep_interruptibly(Duration::from_millis(1)).is_ok());
+/// ```
+pub fn sleep_interruptibly(duration: Duration) -> CliResult<()> {
+    clear_interrupt();
+    let deadline = Instant::now() + duration;
+    loop {
+        if INTERRUPTED.swap(false, Ordering::SeqCst) {
+            return Err(CliError::Interrupted);
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(());
+        }
+        thread::sleep(remaining.min(INTERRUPT_POLL_INTERVAL));
+    }
+}
+
+/// Make Ctrl-C request an interrupt, until the guard is dropped
+///
+/// The handler only sets the interrupt flag, so the operation decides
+/// where to stop. Dropping the guard puts back the previous handling. On
+/// platforms other than Unix and Windows, Ctrl-C is left alone.
+///
+/// # Returns
+/// * `Ok(InterruptGuard)` - Ctrl-C is caught while the guard is held
+/// * `Err(io::Error)` - If the handler could not be installed
+pub fn catch_interrupts() -> io::Result<InterruptGuard> {
+    #[cfg(unix)]
+    let guard = InterruptGuard {
+        previous: sigint::install()?,
+    };
+    #[cfg(windows)]
+    let guard = InterruptGuard {
+        _handler: console_ctrl::install()?,
+    };
+    #[cfg(not(any(unix, windows)))]
+    let guard = InterruptGuard { _private: () };
+    Ok(guard)
+}
+
+/// Guard that puts back the previous Ctrl-C handling when dropped
+#[derive(Debug)]
+pub struct InterruptGuard {
+    #[cfg(unix)]
+    previous: usize,
+    #[cfg(windows)]
+    _handler: console_ctrl::Handler,
+    #[cfg(not(any(unix, windows)))]
+    _private: (),
+}
+
+#[cfg(unix)]
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        sigint::restore(self.previous);
+    }
+}
+
+/// SIGINT handling through the C library
+#[cfg(unix)]
+mod sigint {
+    use std::ffi::c_int;
+    use std::io;
+
+    /// SIGINT has the same number on every Unix
+    const SIGINT: c_int = 2;
+    /// Returned by `signal` when it fails
+    const SIG_ERR: usize = usize::MAX;
+
+    unsafe extern "C" {
+        fn signal(signum: c_int, handler: usize) -> usize;
+    }
+
+    /// Only stores to an atomic, which is safe in a signal handler
+    extern "C" fn on_sigint(_signum: c_int) {
+        super::request_interrupt();
+    }
+
+    /// Install the handler, returning the one it replaced
+    pub(super) fn install() -> io::Result<usize> {
+        let handler = on_sigint as extern "C" fn(c_int) as usize;
+        // SAFETY: the handler is async-signal-safe and lives for the whole
+        // program
+        match unsafe { signal(SIGINT, handler) } {
+            SIG_ERR => Err(io::Error::last_os_error()),
+            previous => Ok(previous),
+        }
+    }
+
+    /// Put back a handler returned by `install`
+    pub(super) fn restore(previous: usize) {
+        // SAFETY: `previous` was the handler in place before `install`
+        unsafe {
+            signal(SIGINT, previous);
+        }
+    }
+}
+
+/// Ctrl-C handling through the Windows console API
+#[cfg(windows)]
+mod console_ctrl {
+    use std::io;
+
+    const CTRL_C_EVENT: u32 = 0;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+    }
+
+    /// Runs on a thread of its own, so it only sets the flag
+    unsafe extern "system" fn on_ctrl(ctrl_type: u32) -> i32 {
+        if ctrl_type == CTRL_C_EVENT {
+            super::request_interrupt();
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Registered handler, removed on drop
+    #[derive(Debug)]
+    pub(super) struct Handler {
+        _private: (),
+    }
+
+    /// Register the handler ahead of the default one
+    pub(super) fn install() -> io::Result<Handler> {
+        // SAFETY: the handler lives for the whole program
+        if unsafe { SetConsoleCtrlHandler(Some(on_ctrl), 1) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Handler { _private: () })
+    }
+
+    impl Drop for Handler {
+        fn drop(&mut self) {
+            // SAFETY: removes the handler registered by `install`
+            unsafe {
+                SetConsoleCtrlHandler(Some(on_ctrl), 0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes the tests, since they share the process-wide flag
+    static FLAG_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_sleep_stops_on_interrupt() {
+        let _lock = FLAG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let interrupter = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            request_interrupt();
+        });
+
+        let started = Instant::now();
+        let result = sleep_interruptibly(Duration::from_secs(10));
+        interrupter.join().unwrap();
+
+        assert!(matches!(result, Err(CliError::Interrupted)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        // The interrupt is handled, so it does not affect the next sleep
+        assert!(!interrupt_requested());
+        assert!(sleep_interruptibly(Duration::from_millis(1)).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sigint_interrupts_sleep_while_caught() {
+        unsafe extern "C" {
+            fn kill(pid: i32, signum: i32) -> i32;
+        }
+
+        let _lock = FLAG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let guard = catch_interrupts().unwrap();
+        let interrupter = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            // SAFETY: sends SIGINT to this process, which the guard catches
+            unsafe { kill(std::process::id() as i32, 2) }
+        });
+
+        let started = Instant::now();
+        let result = sleep_interruptibly(Duration::from_secs(10));
+        assert_eq!(interrupter.join().unwrap(), 0);
+        drop(guard);
+
+        assert_eq!(result, Err(CliError::Interrupted));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!interrupt_requested());
+    }
+}
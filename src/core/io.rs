@@ -0,0 +1,527 @@
+//! Input handling for CLI sessions.
+//!
+//! This module provides the injectable input handle a session reads from.
+//! The main loop and commands both go through it instead of touching
+//! `std::io::stdin()` directly, so tests can feed input from memory.
+//!
+//! Standard input can also be read with an idle timeout. A thread then
+//! reads stdin and hands the bytes over a channel, so each wait for input
+//! can give up once [`IdleTimer`] says the window has passed.
+
+use crate::core::error::{CliError, CliResult};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Cursor, IsTerminal, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default maximum length of one input line, in bytes (64 KiB)
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Input handle for a CLI session
+pub struct SessionIo {
+    /// Source of input lines
+    input: Box<dyn BufRead>,
+    /// Whether the input is an interactive terminal
+    input_is_terminal: bool,
+    /// Whether prompts read the input a key at a time, with line editing
+    line_editing: bool,
+    /// Longest line `read_line` accepts, in bytes
+    max_line_length: usize,
+    /// Longest wait for input before `read_line` gives up
+    idle_timeout: Option<Duration>,
+    /// Where each line `read_line` returns is copied, such as a transcript
+    echo: Option<Box<dyn Write>>,
+}
+
+impl SessionIo {
+    /// Create a session input handle reading from the process stdin
+    pub fn stdio() -> Self {
+        let stdin = io::stdin();
+        let input_is_terminal = stdin.is_terminal();
+        let mut io = Self::from_reader(BufReader::new(stdin), input_is_terminal);
+        io.line_editing = input_is_terminal;
+        io
+    }
+
+    /// Create a session input handle reading from the process stdin, giving
+    /// up when no input arrives within `timeout`
+    ///
+    /// Stdin is read by a background thread from then on, so nothing else
+    /// may read it directly.
+    pub fn stdio_with_idle_timeout(timeout: Duration) -> Self {
+        let input_is_terminal = io::stdin().is_terminal();
+        let reader = TimedReader::spawn_stdin(timeout);
+        let mut io = Self::from_reader(BufReader::new(reader), input_is_terminal);
+        io.idle_timeout = Some(timeout);
+        io
+    }
+
+    /// Create a session input handle from any buffered reader
+    ///
+    /// # Arguments
+    /// * `input` - The reader to take input from
+    /// * `input_is_terminal` - Whether the reader should be treated as a TTY
+    pub fn from_reader(input: impl BufRead + 'static, input_is_terminal: bool) -> Self {
+        Self {
+            input: Box::new(input),
+            input_is_terminal,
+            line_editing: false,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            idle_timeout: None,
+            echo: None,
+        }
+    }
+
+    /// Create a non-terminal session input handle over in-memory bytes
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::io::SessionIo;
+    ///
+    /// let mut io = SessionIo::from_bytes("edit\n");
+    /// assert_eq!(io.read_line().unwrap(), Some("edit\n".to_string()));
+    /// assert_eq!(io.read_line().unwrap(), None);
+    /// ```
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self::from_reader(Cursor::new(bytes.into()), false)
+    }
+
+    /// Check if the input is an interactive terminal
+    pub fn input_is_terminal(&self) -> bool {
+        self.input_is_terminal
+    }
+
+    /// Check if prompts read the input a key at a time, with line editing
+    ///
+    /// Only the process's own terminal is read this way, since editing
+    /// puts the terminal into raw mode, and not when an idle timeout hands
+    /// stdin to a reading thread. Other input, including readers treated as
+    /// a terminal, is read a line at a time.
+    pub fn line_editing(&self) -> bool {
+        self.line_editing
+    }
+
+    /// Get the longest line `read_line` accepts, in bytes
+    pub fn max_line_length(&self) -> usize {
+        self.max_line_length
+    }
+
+    /// Get the idle timeout, if input is read with one
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// Set the longest line `read_line` accepts, in bytes
+    pub fn set_max_line_length(&mut self, max_line_length: usize) {
+        self.max_line_length = max_line_length;
+    }
+
+    /// Copy each line read from now on to `echo`, returning the previous copy
+    ///
+    /// A transcript uses this to log what was typed after each prompt, as
+    /// the terminal showed it. `None` stops copying.
+    pub fn set_echo(&mut self, echo: Option<Box<dyn Write>>) -> Option<Box<dyn Write>> {
+        std::mem::replace(&mut self.echo, echo)
+    }
+
+    /// Read a single line of input
+    ///
+    /// At most the maximum line length is buffered. A longer line is
+    /// discarded up to its terminator and reported as an error, so the
+    /// next call reads the following line and memory use stays bounded
+    /// even when the input is an accidental binary pipe.
+    ///
+    /// # Returns
+    /// * `Ok(Some(line))` - The line read, including its terminator
+    /// * `Ok(None)` - The input is exhausted
+    /// * `Err(CliError)` - `InvalidInput` if the line is too long, contains
+    ///   a NUL byte, or is not valid UTF-8, `InputInterrupted` if the read
+    ///   was interrupted, `IdleTimeout` if no input arrived within the idle
+    ///   timeout, or any other error from reading
+    pub fn read_line(&mut self) -> CliResult<Option<String>> {
+        let mut line = Vec::new();
+        let mut too_long = false;
+
+        loop {
+            let idle_timeout = self.idle_timeout;
+            let available = self.input.fill_buf().map_err(|e| match e.kind() {
+                io::ErrorKind::Interrupted => CliError::InputInterrupted,
+                io::ErrorKind::TimedOut => CliError::IdleTimeout(idle_timeout.unwrap_or_default()),
+                _ => CliError::from(e),
+            })?;
+            if available.is_empty() {
+                break;
+            }
+
+            let (chunk, found_end) = match available.iter().position(|&b| b == b'\n') {
+                Some(end) => (&available[..=end], true),
+                None => (available, false),
+            };
+            let used = chunk.len();
+            if !too_long {
+                if line.len() + used > self.max_line_length {
+                    too_long = true;
+                    line.clear();
+                } else {
+                    line.extend_from_slice(chunk);
+                }
+            }
+            self.input.consume(used);
+
+            if found_end {
+                break;
+            }
+        }
+
+        if too_long {
+            return Err(CliError::invalid_input(&format!(
+                "input too long (maximum: {} bytes)",
+                self.max_line_length
+            )));
+        }
+        if line.is_empty() {
+            return Ok(None);
+        }
+        if line.contains(&0) {
+            return Err(CliError::invalid_input("input contains a NUL byte"));
+        }
+
+        let line = String::from_utf8(line)
+            .map_err(|_| CliError::invalid_input("input is not valid UTF-8"))?;
+        if let Some(echo) = &mut self.echo {
+            // Failures to copy are ignored, like the transcript's own
+            let terminator = if line.ends_with('\n') { "" } else { "\n" };
+            let _ = write!(echo, "{line}{terminator}");
+            let _ = echo.flush();
+        }
+        Ok(Some(line))
+    }
+
+    /// Read a single byte of input
+    ///
+    /// For input read a key at a time, such as from a terminal in raw mode.
+    ///
+    /// # Returns
+    /// * `Ok(Some(byte))` - The byte read
+    /// * `Ok(None)` - The input is exhausted
+    /// * `Err(CliError)` - `InputInterrupted` if the read was interrupted,
+    ///   `IdleTimeout` if no input arrived within the idle timeout, or any
+    ///   other error from reading
+    pub fn read_byte(&mut self) -> CliResult<Option<u8>> {
+        let idle_timeout = self.idle_timeout;
+        let available = self.input.fill_buf().map_err(|e| match e.kind() {
+            io::ErrorKind::Interrupted => CliError::InputInterrupted,
+            io::ErrorKind::TimedOut => CliError::IdleTimeout(idle_timeout.unwrap_or_default()),
+            _ => CliError::from(e),
+        })?;
+        let byte = available.first().copied();
+        if byte.is_some() {
+            self.input.consume(1);
+        }
+        Ok(byte)
+    }
+
+    /// Read all remaining input as text
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of bytes to accept
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The remaining input
+    /// * `Err(CliError)` - If reading failed, the input is not valid UTF-8,
+    ///   or it exceeds `limit` bytes
+    pub fn read_to_string(&mut self, limit: u64) -> CliResult<String> {
+        let mut bytes = Vec::new();
+        (&mut self.input)
+            .take(limit.saturating_add(1))
+            .read_to_end(&mut bytes)?;
+
+        if bytes.len() as u64 > limit {
+            return Err(CliError::execution_error(&format!(
+                "Input too large (maximum: {limit} bytes)"
+            )));
+        }
+
+        String::from_utf8(bytes)
+            .map_err(|_| CliError::InvalidFileFormat("input is not valid UTF-8".to_string()))
+    }
+}
+
+impl Default for SessionIo {
+    fn default() -> Self {
+        Self::stdio()
+    }
+}
+
+impl fmt::Debug for SessionIo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionIo")
+            .field("input_is_terminal", &self.input_is_terminal)
+            .field("line_editing", &self.line_editing)
+            .field("max_line_length", &self.max_line_length)
+            .field("idle_timeout", &self.idle_timeout)
+            .field("echo", &self.echo.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Deadline bookkeeping for an idle timeout
+///
+/// The window restarts each time the session starts waiting for input, so
+/// time spent running commands does not count as idle time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleTimer {
+    /// Length of the idle window
+    timeout: Duration,
+    /// When the current wait gives up
+    deadline: Instant,
+}
+
+impl IdleTimer {
+    /// Create a timer whose first window starts at `now`
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::io::IdleTimer;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let start = Instant::now();
+    /// let timer = IdleTimer::new(Duration::from_secs(30), start);
+    /// assert!(!timer.is_expired(start + Duration::from_secs(29)));
+    /// assert!(timer.is_expired(start + Duration::from_secs(30)));
+    /// ```
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        IdleTimer {
+            timeout,
+            deadline: now + timeout,
+        }
+    }
+
+    /// Start a new window at `now`
+    pub fn restart(&mut self, now: Instant) {
+        self.deadline = now + self.timeout;
+    }
+
+    /// Get when the current window ends
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Get how long is left of the current window at `now`
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.deadline.saturating_duration_since(now)
+    }
+
+    /// Check whether the current window has passed at `now`
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.remaining(now).is_zero()
+    }
+}
+
+/// Reader over bytes sent from a reading thread, with an idle timeout
+///
+/// Each read is a new wait for input, which fails with
+/// `io::ErrorKind::TimedOut` once the idle window passes.
+struct TimedReader {
+    /// Chunks read by the thread; an error ends the input after it
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    /// Bytes received but not yet read
+    pending: Vec<u8>,
+    /// Idle window of the current wait
+    timer: IdleTimer,
+}
+
+impl TimedReader {
+    /// Start a thread reading stdin and return a reader over its output
+    fn spawn_stdin(timeout: Duration) -> Self {
+        let (sender, chunks) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stdin = io::stdin();
+            let mut buf = [0u8; 4096];
+            loop {
+                let chunk = match stdin.read(&mut buf) {
+                    // End of input drops the sender, which ends the reader
+                    Ok(0) => break,
+                    Ok(read) => Ok(buf[..read].to_vec()),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = chunk.is_err();
+                if sender.send(chunk).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Self::new(chunks, timeout)
+    }
+
+    /// Create a reader over chunks from any sender
+    fn new(chunks: Receiver<io::Result<Vec<u8>>>, timeout: Duration) -> Self {
+        TimedReader {
+            chunks,
+            pending: Vec::new(),
+            timer: IdleTimer::new(timeout, Instant::now()),
+        }
+    }
+}
+
+impl Read for TimedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            self.timer.restart(Instant::now());
+            loop {
+                let left = self.timer.remaining(Instant::now());
+                if left.is_zero() {
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+                match self.chunks.recv_timeout(left) {
+                    Ok(chunk) => {
+                        self.pending = chunk?;
+                        break;
+                    }
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => return Ok(0),
+                }
+            }
+        }
+
+        let used = self.pending.len().min(buf.len());
+        buf[..used].copy_from_slice(&self.pending[..used]);
+        self.pending.drain(..used);
+        Ok(used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_lines_until_exhausted() {
+        let mut io = SessionIo::from_bytes("one\ntwo");
+        assert!(!io.input_is_terminal());
+        assert_eq!(io.read_line().unwrap(), Some("one\n".to_string()));
+        assert_eq!(io.read_line().unwrap(), Some("two".to_string()));
+        assert_eq!(io.read_line().unwrap(), None);
+    }
+
+    #[test]
+    fn test_echo_copies_each_line_read() {
+        let echo = crate::ui::CapturedOutput::default();
+        let mut io = SessionIo::from_bytes("one\ntwo");
+        assert!(io.set_echo(Some(Box::new(echo.clone()))).is_none());
+        io.read_line().unwrap();
+        io.read_line().unwrap();
+        assert!(io.set_echo(None).is_some());
+        io.read_line().unwrap();
+
+        assert_eq!(echo.text(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_read_line_rejects_over_length_line() {
+        let mut io = SessionIo::from_bytes(format!("{}\nedit\n", "x".repeat(20)));
+        io.set_max_line_length(10);
+
+        assert!(matches!(io.read_line(), Err(CliError::InvalidInput(_))));
+        // The rest of the long line is discarded, not read as the next line
+        assert_eq!(io.read_line().unwrap(), Some("edit\n".to_string()));
+        assert_eq!(io.read_line().unwrap(), None);
+
+        // The terminator counts towards the limit
+        let mut io = SessionIo::from_bytes("123456789\n12345678901");
+        io.set_max_line_length(10);
+        assert_eq!(io.read_line().unwrap(), Some("123456789\n".to_string()));
+        assert!(io.read_line().is_err());
+        assert_eq!(SessionIo::from_bytes("").max_line_length(), 64 * 1024);
+    }
+
+    #[test]
+    fn test_read_line_rejects_nul_and_invalid_utf8() {
+        let mut io = SessionIo::from_bytes(b"ed\0it\n\xff\nquit\n".to_vec());
+        assert!(matches!(io.read_line(), Err(CliError::InvalidInput(_))));
+        assert!(matches!(io.read_line(), Err(CliError::InvalidInput(_))));
+        assert_eq!(io.read_line().unwrap(), Some("quit\n".to_string()));
+    }
+
+    /// Reader whose first read is interrupted
+    struct InterruptedOnce(bool);
+
+    impl Read for InterruptedOnce {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if std::mem::replace(&mut self.0, true) {
+                let line = b"quit\n";
+                buf[..line.len()].copy_from_slice(line);
+                Ok(line.len())
+            } else {
+                Err(io::ErrorKind::Interrupted.into())
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_line_reports_input_interruption() {
+        let mut io = SessionIo::from_reader(BufReader::new(InterruptedOnce(false)), true);
+        let error = io.read_line().unwrap_err();
+        assert!(matches!(error, CliError::InputInterrupted));
+        assert_eq!(error.severity(), crate::core::error::ErrorSeverity::Warning);
+        assert_eq!(io.read_line().unwrap(), Some("quit\n".to_string()));
+    }
+
+    #[test]
+    fn test_idle_timer_windows() {
+        let start = Instant::now();
+        let mut timer = IdleTimer::new(Duration::from_secs(10), start);
+        assert_eq!(timer.deadline(), start + Duration::from_secs(10));
+        assert_eq!(
+            timer.remaining(start + Duration::from_secs(4)),
+            Duration::from_secs(6)
+        );
+        assert!(!timer.is_expired(start + Duration::from_secs(9)));
+        assert!(timer.is_expired(start + Duration::from_secs(10)));
+        assert_eq!(
+            timer.remaining(start + Duration::from_secs(60)),
+            Duration::ZERO
+        );
+
+        // A new wait gets the whole window again
+        timer.restart(start + Duration::from_secs(60));
+        assert!(!timer.is_expired(start + Duration::from_secs(65)));
+        assert!(timer.is_expired(start + Duration::from_secs(70)));
+    }
+
+    #[test]
+    fn test_read_line_times_out_without_input() {
+        let (sender, chunks) = mpsc::channel();
+        let timeout = Duration::from_millis(20);
+        let reader = TimedReader::new(chunks, timeout);
+        let mut io = SessionIo::from_reader(BufReader::new(reader), true);
+        io.idle_timeout = Some(timeout);
+
+        sender.send(Ok(b"edit\n".to_vec())).unwrap();
+        assert_eq!(io.read_line().unwrap(), Some("edit\n".to_string()));
+        assert!(matches!(io.read_line(), Err(CliError::IdleTimeout(t)) if t == timeout));
+
+        // Input that arrives later is still read, and a closed sender ends it
+        sender.send(Ok(b"quit\n".to_vec())).unwrap();
+        drop(sender);
+        assert_eq!(io.read_line().unwrap(), Some("quit\n".to_string()));
+        assert_eq!(io.read_line().unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_to_string_limits() {
+        let mut io = SessionIo::from_bytes("hello");
+        assert_eq!(io.read_to_string(5).unwrap(), "hello");
+
+        let mut io = SessionIo::from_bytes("hello");
+        assert!(io.read_to_string(4).is_err());
+
+        let mut io = SessionIo::from_bytes(vec![0xff, 0xfe]);
+        assert!(matches!(
+            io.read_to_string(10),
+            Err(CliError::InvalidFileFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,458 @@
+//! Minimal JSON values for machine-readable input and output.
+//!
+//! This module covers what the CLI exchanges as JSON: a value type that
+//! prints itself compactly or indented, and a strict parser for it. It is
+//! not a general serialization framework; callers convert their own types
+//! to and from [`JsonValue`].
+
+use crate::core::error::{CliError, CliResult};
+use std::fmt;
+
+/// Deepest nesting of arrays and objects the parser accepts
+const MAX_JSON_DEPTH: usize = 64;
+
+/// A JSON value
+///
+/// Object members keep the order they were written or parsed in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// A number, always held as a float
+    Number(f64),
+    /// A string
+    String(String),
+    /// An array of values
+    Array(Vec<JsonValue>),
+    /// An object, as key and value pairs
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Build a string value
+    pub fn string(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+
+    /// Parse a complete JSON document
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::json::JsonValue;
+    ///
+    /// let value = JsonValue::parse(r#"{"lines": ["a", "b"], "dirty": false}"#).unwrap();
+    /// assert_eq!(value.get("dirty"), Some(&JsonValue::Bool(false)));
+    /// assert_eq!(value.to_string(), r#"{"lines":["a","b"],"dirty":false}"#);
+    /// ```
+    ///
+    /// # Returns
+    /// * `Ok(JsonValue)` - The parsed value
+    /// * `Err(CliError)` - `InvalidFileFormat` naming the byte offset of the
+    ///   first problem
+    pub fn parse(text: &str) -> CliResult<Self> {
+        let mut parser = Parser { text, pos: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.pos != text.len() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    /// Get an object member by key
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a string, if it is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a number, if it is one
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Format the value with two-space indentation, one member per line
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        let pad = |depth: usize| "  ".repeat(depth);
+        match self {
+            JsonValue::Array(items) if !items.is_empty() => {
+                out.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    out.push_str(&pad(indent + 1));
+                    item.write_pretty(out, indent + 1);
+                    out.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&pad(indent));
+                out.push(']');
+            }
+            JsonValue::Object(members) if !members.is_empty() => {
+                out.push_str("{\n");
+                for (index, (key, value)) in members.iter().enumerate() {
+                    out.push_str(&format!("{}{}: ", pad(indent + 1), quote(key)));
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(if index + 1 < members.len() {
+                        ",\n"
+                    } else {
+                        "\n"
+                    });
+                }
+                out.push_str(&pad(indent));
+                out.push('}');
+            }
+            other => out.push_str(&other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for JsonValue {
+    /// Format the value compactly, with no whitespace
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{value}"),
+            // JSON has no NaN or infinity
+            JsonValue::Number(value) if !value.is_finite() => write!(f, "null"),
+            JsonValue::Number(value) => write!(f, "{value}"),
+            JsonValue::String(value) => write!(f, "{}", quote(value)),
+            JsonValue::Array(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(members) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{value}", quote(key))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Quote a string as a JSON string literal
+///
+/// The result is also a valid TOML basic string and YAML double-quoted
+/// string, so the other export formats use it too.
+pub fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Recursive-descent parser over a JSON document
+struct Parser<'a> {
+    text: &'a str,
+    /// Byte offset of the next character
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> CliError {
+        CliError::InvalidFileFormat(format!("JSON at byte {}: {msg}", self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> CliResult<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> CliResult<JsonValue> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: JsonValue) -> CliResult<JsonValue> {
+        if self.text[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn number(&mut self) -> CliResult<JsonValue> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        let text = &self.text[start..self.pos];
+        // Rust accepts forms JSON does not, such as "1.", ".5", and "01"
+        let digits = text.strip_prefix('-').unwrap_or(text).as_bytes();
+        let leading_zero = digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit();
+        let well_formed = !text.ends_with('.')
+            && !text.contains(".e")
+            && !text.contains(".E")
+            && digits.first().is_some_and(u8::is_ascii_digit)
+            && !leading_zero;
+        match text.parse::<f64>() {
+            Ok(value) if well_formed && value.is_finite() => Ok(JsonValue::Number(value)),
+            _ => {
+                self.pos = start;
+                Err(self.error(&format!("invalid number '{text}'")))
+            }
+        }
+    }
+
+    fn string(&mut self) -> CliResult<String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let ch = rest
+                .chars()
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += ch.len_utf8();
+            match ch {
+                '"' => return Ok(out),
+                '\\' => out.push(self.escape()?),
+                c if c.is_control() => return Err(self.error("control character in string")),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> CliResult<char> {
+        let code = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.pos += 1;
+        Ok(match code {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4()?;
+                if (0xD800..0xDC00).contains(&high) {
+                    // A surrogate pair spells one character outside the BMP
+                    if !self.text[self.pos..].starts_with("\\u") {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let low = self.hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                    char::from_u32(code).ok_or_else(|| self.error("invalid escape"))?
+                } else {
+                    char::from_u32(high).ok_or_else(|| self.error("unpaired surrogate"))?
+                }
+            }
+            _ => return Err(self.error("invalid escape")),
+        })
+    }
+
+    fn hex4(&mut self) -> CliResult<u32> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid \\u escape"))
+    }
+
+    fn array(&mut self, depth: usize) -> CliResult<JsonValue> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> CliResult<JsonValue> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':')?;
+            let value = self.value(depth + 1)?;
+            members.push((key, value));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        let value = JsonValue::parse(
+            r#" { "n": -1.5e2, "s": "a\"b\\c\u00e9\ud83d\ude00", "a": [true, null, []], "o": {} } "#,
+        )
+        .unwrap();
+        assert_eq!(value.get("n").and_then(JsonValue::as_f64), Some(-150.0));
+        assert_eq!(
+            value.get("s").and_then(JsonValue::as_str),
+            Some("a\"b\\cé😀")
+        );
+        assert_eq!(
+            value.get("a"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Bool(true),
+                JsonValue::Null,
+                JsonValue::Array(Vec::new()),
+            ]))
+        );
+        assert_eq!(value.get("o"), Some(&JsonValue::Object(Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        for text in [
+            "",
+            "{",
+            "[1,]",
+            "01",
+            "-",
+            "{\"a\" 1}",
+            "tru",
+            "01x",
+            "1.",
+            ".5",
+            "\"\\x\"",
+            "\"a\nb\"",
+            "\"\\ud800\"",
+            "1 2",
+            "{a: 1}",
+        ] {
+            assert!(
+                matches!(JsonValue::parse(text), Err(CliError::InvalidFileFormat(_))),
+                "{text:?}"
+            );
+        }
+        let deep = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        assert!(JsonValue::parse(&deep).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_and_pretty() {
+        let value = JsonValue::Object(vec![
+            (
+                "lines".to_string(),
+                JsonValue::Array(vec![JsonValue::string("tab\there")]),
+            ),
+            ("empty".to_string(), JsonValue::Array(Vec::new())),
+            ("max".to_string(), JsonValue::Number(2.5)),
+        ]);
+        assert_eq!(JsonValue::parse(&value.to_string()).unwrap(), value);
+        assert_eq!(JsonValue::parse(&value.pretty()).unwrap(), value);
+        assert_eq!(
+            value.pretty(),
+            "{\n  \"lines\": [\n    \"tab\\there\"\n  ],\n  \"empty\": [],\n  \"max\": 2.5\n}"
+        );
+        assert_eq!(quote("\u{1}"), "\"\\u0001\"");
+    }
+}
@@ -0,0 +1,262 @@
+//! Line-based diffs between two versions of a text.
+//!
+//! This module compares two sequences of lines and renders the changes as
+//! a unified diff, with added and removed lines colored by the theme. It is
+//! meant for showing what an overwrite would change, not for producing
+//! patches.
+
+use crate::ui::theme::Theme;
+
+/// Number of unchanged lines shown around each change
+pub const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Largest comparison table (old lines times new lines) computed exactly
+///
+/// Beyond this, the differing middle of the texts is shown as removed and
+/// then added in full, which keeps memory use bounded for large files.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// One line of a diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    /// Line present in both texts
+    Unchanged(&'a str),
+    /// Line only in the new text
+    Added(&'a str),
+    /// Line only in the old text
+    Removed(&'a str),
+}
+
+/// Compare two sequences of lines
+///
+/// The result lists every line of both texts in order, with a longest
+/// common subsequence kept as unchanged.
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::line_diff::{DiffLine, diff_lines};
+///
+/// let diff = diff_lines(&["a", "b"], &["a", "c"]);
+/// assert_eq!(
+///     diff,
+///     [DiffLine::Unchanged("a"), DiffLine::Removed("b"), DiffLine::Added("c")]
+/// );
+/// ```
+pub fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    // Common leading and trailing lines need no table
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut diff: Vec<DiffLine<'a>> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Unchanged(line))
+        .collect();
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_DIFF_CELLS {
+        diff.extend(old_middle.iter().map(|line| DiffLine::Removed(line)));
+        diff.extend(new_middle.iter().map(|line| DiffLine::Added(line)));
+    } else {
+        diff.extend(diff_middle(old_middle, new_middle));
+    }
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Unchanged(line)),
+    );
+    diff
+}
+
+/// Diff two sequences with a longest-common-subsequence table
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    // lengths[i][j] is the LCS length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0usize; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    diff
+}
+
+/// Render the changes between two texts as a unified diff
+///
+/// # Arguments
+/// * `old_name` - Label for the old text in the `---` header
+/// * `new_name` - Label for the new text in the `+++` header
+/// * `old` - Lines of the old text
+/// * `new` - Lines of the new text
+/// * `theme` - Colors for added, removed, and hunk header lines
+///
+/// # Returns
+/// The diff lines, or an empty vector when the texts are the same
+pub fn unified_diff(
+    old_name: &str,
+    new_name: &str,
+    old: &[&str],
+    new: &[&str],
+    theme: &Theme,
+) -> Vec<String> {
+    let diff = diff_lines(old, new);
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Unchanged(_)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut output = vec![
+        theme.paint(theme.diff_removed, &format!("--- {old_name}")),
+        theme.paint(theme.diff_added, &format!("+++ {new_name}")),
+    ];
+
+    // Group changes whose context would touch or overlap into one hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &index in &changed {
+        let start = index.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (index + DIFF_CONTEXT_LINES + 1).min(diff.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        // Line numbers are 1-based positions in each text
+        let old_start = count_lines(&diff[..start], false) + 1;
+        let new_start = count_lines(&diff[..start], true) + 1;
+        let old_count = count_lines(&diff[start..end], false);
+        let new_count = count_lines(&diff[start..end], true);
+        output.push(theme.paint(
+            theme.diff_hunk,
+            &format!(
+                "@@ -{} +{} @@",
+                hunk_range(old_start, old_count),
+                hunk_range(new_start, new_count)
+            ),
+        ));
+
+        for line in &diff[start..end] {
+            output.push(match line {
+                DiffLine::Unchanged(text) => format!(" {text}"),
+                DiffLine::Added(text) => theme.paint(theme.diff_added, &format!("+{text}")),
+                DiffLine::Removed(text) => theme.paint(theme.diff_removed, &format!("-{text}")),
+            });
+        }
+    }
+
+    output
+}
+
+/// Count the lines of the old (or new) text in part of a diff
+fn count_lines(diff: &[DiffLine<'_>], new: bool) -> usize {
+    diff.iter()
+        .filter(|line| match line {
+            DiffLine::Unchanged(_) => true,
+            DiffLine::Added(_) => new,
+            DiffLine::Removed(_) => !new,
+        })
+        .count()
+}
+
+/// Format a hunk range as `start,count`, where an empty range starts one early
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start - 1),
+        1 => start.to_string(),
+        _ => format!("{start},{count}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines_added_removed_unchanged() {
+        let old = ["one", "two", "three", "four"];
+        let new = ["one", "three", "four", "five"];
+        assert_eq!(
+            diff_lines(&old, &new),
+            [
+                DiffLine::Unchanged("one"),
+                DiffLine::Removed("two"),
+                DiffLine::Unchanged("three"),
+                DiffLine::Unchanged("four"),
+                DiffLine::Added("five"),
+            ]
+        );
+
+        assert_eq!(diff_lines(&old, &old).len(), old.len());
+        assert_eq!(diff_lines(&[], &["new"]), [DiffLine::Added("new")]);
+        assert_eq!(diff_lines(&["old"], &[]), [DiffLine::Removed("old")]);
+    }
+
+    #[test]
+    fn test_unified_diff_plain() {
+        let old = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+        let new = ["a", "B", "c", "d", "e", "f", "g", "h", "i", "j", "k"];
+        let theme = Theme::monochrome();
+        assert_eq!(
+            unified_diff("old.txt", "buffer", &old, &new, &theme),
+            [
+                "--- old.txt",
+                "+++ buffer",
+                "@@ -1,5 +1,5 @@",
+                " a",
+                "-b",
+                "+B",
+                " c",
+                " d",
+                " e",
+                "@@ -8,3 +8,4 @@",
+                " h",
+                " i",
+                " j",
+                "+k",
+            ]
+        );
+        assert!(unified_diff("a", "b", &old, &old, &theme).is_empty());
+    }
+
+    #[test]
+    fn test_unified_diff_colored() {
+        let theme = Theme::colored();
+        let diff = unified_diff("old", "new", &["same", "gone"], &["same", "new"], &theme);
+        assert_eq!(diff[2], theme.paint(theme.diff_hunk, "@@ -1,2 +1,2 @@"));
+        assert_eq!(diff[3], " same");
+        assert_eq!(diff[4], "\x1b[31m-gone\x1b[0m");
+        assert_eq!(diff[5], "\x1b[32m+new\x1b[0m");
+    }
+}
@@ -0,0 +1,153 @@
+//! Directory listing support for file commands.
+//!
+//! This module reads a directory into a sorted, numbered listing that can
+//! be shown one page at a time. The most recent listing is kept in the
+//! session context so later commands can refer to entries by number.
+
+use crate::core::error::{CliError, CliResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sorted snapshot of a directory's entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryListing {
+    /// Directory that was listed
+    dir: PathBuf,
+    /// Entry names, sorted, with a trailing `/` on directories
+    entries: Vec<String>,
+}
+
+/// One page of a directory listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingPage<'a> {
+    /// 1-based page number
+    pub number: usize,
+    /// Total number of pages
+    pub total_pages: usize,
+    /// 1-based listing index of the first entry on this page
+    pub first_index: usize,
+    /// Entries on this page
+    pub entries: &'a [String],
+}
+
+impl DirectoryListing {
+    /// Read and sort the entries of a directory
+    pub fn read(dir: &Path) -> CliResult<Self> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let mut name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() {
+                name.push('/');
+            }
+            entries.push(name);
+        }
+        entries.sort();
+
+        Ok(Self::from_entries(dir, entries))
+    }
+
+    /// Build a listing from already-collected entry names
+    pub fn from_entries(dir: &Path, entries: Vec<String>) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            entries,
+        }
+    }
+
+    /// Get the directory that was listed
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get all entries in listing order
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Get an entry by its 1-based listing number
+    pub fn entry(&self, number: usize) -> Option<&str> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.entries.get(index))
+            .map(String::as_str)
+    }
+
+    /// Get the number of pages at the given page size
+    pub fn page_count(&self, per_page: usize) -> usize {
+        self.entries.len().div_ceil(per_page.max(1)).max(1)
+    }
+
+    /// Get a page of entries
+    ///
+    /// # Arguments
+    /// * `number` - The 1-based page number
+    /// * `per_page` - Maximum entries per page
+    ///
+    /// # Returns
+    /// * `Ok(ListingPage)` - The requested page
+    /// * `Err(CliError)` - If the page number is out of range
+    pub fn page(&self, number: usize, per_page: usize) -> CliResult<ListingPage<'_>> {
+        let per_page = per_page.max(1);
+        let total_pages = self.page_count(per_page);
+        if number == 0 || number > total_pages {
+            return Err(CliError::invalid_input(&format!(
+                "Page {number} out of range (1-{total_pages})"
+            )));
+        }
+
+        let start = (number - 1) * per_page;
+        let end = (start + per_page).min(self.entries.len());
+        Ok(ListingPage {
+            number,
+            total_pages,
+            first_index: start + 1,
+            entries: &self.entries[start..end],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(count: usize) -> DirectoryListing {
+        let entries = (1..=count).map(|i| format!("file{i:03}.txt")).collect();
+        DirectoryListing::from_entries(Path::new("."), entries)
+    }
+
+    #[test]
+    fn test_pagination() {
+        let listing = numbered(120);
+        assert_eq!(listing.page_count(50), 3);
+
+        let page = listing.page(1, 50).unwrap();
+        assert_eq!(page.entries.len(), 50);
+        assert_eq!(page.first_index, 1);
+
+        let page = listing.page(3, 50).unwrap();
+        assert_eq!(page.entries.len(), 20);
+        assert_eq!(page.first_index, 101);
+        assert_eq!(page.entries[0], "file101.txt");
+        assert_eq!(page.total_pages, 3);
+
+        assert!(listing.page(0, 50).is_err());
+        assert!(listing.page(4, 50).is_err());
+    }
+
+    #[test]
+    fn test_empty_listing_has_one_page() {
+        let listing = numbered(0);
+        assert_eq!(listing.page_count(50), 1);
+        assert!(listing.page(1, 50).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_entry_lookup() {
+        let listing = numbered(5);
+        assert_eq!(listing.entry(1), Some("file001.txt"));
+        assert_eq!(listing.entry(5), Some("file005.txt"));
+        assert_eq!(listing.entry(0), None);
+        assert_eq!(listing.entry(6), None);
+    }
+}
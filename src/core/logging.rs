@@ -0,0 +1,102 @@
+//! Structured log records for applications embedding the menu engine.
+//!
+//! With the `logging` feature, the engine describes what it does as log
+//! records, independent of what the user sees on screen: command
+//! resolution at debug level, menu navigation at info level, rejected
+//! file paths at warn level, and failed commands at error level. A host
+//! installs a [`Logger`] once with [`set_logger`] and forwards the records
+//! to its own logging, such as a `log` crate backend. Without a logger the
+//! records are dropped.
+//!
+//! The project uses only the standard library, so this small facade takes
+//! the place of the `log` crate rather than depending on it.
+
+use crate::core::error::{CliError, CliResult};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Importance of a log record, from most to least severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// A command failed
+    Error,
+    /// Something was refused or may need attention
+    Warn,
+    /// A change in the session, such as entering a menu
+    Info,
+    /// Details useful when tracing how input was handled
+    Debug,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One log record
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    level: Level,
+    target: &'static str,
+    args: fmt::Arguments<'a>,
+}
+
+impl<'a> Record<'a> {
+    /// Get the record's level
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Get the module the record comes from, such as `sm_menu::app`
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+
+    /// Get the record's message
+    pub fn args(&self) -> &fmt::Arguments<'a> {
+        &self.args
+    }
+}
+
+/// Receiver of the engine's log records
+pub trait Logger: Send + Sync {
+    /// Handle one record
+    fn log(&self, record: &Record<'_>);
+}
+
+/// Logger installed by the host
+static LOGGER: OnceLock<Box<dyn Logger>> = OnceLock::new();
+
+/// Install the logger that receives the engine's records
+///
+/// The logger is global to the process and can only be set once.
+///
+/// # Returns
+/// * `Ok(())` - If the logger was installed
+/// * `Err(CliError)` - If a logger was already installed
+pub fn set_logger(logger: Box<dyn Logger>) -> CliResult<()> {
+    LOGGER
+        .set(logger)
+        .map_err(|_| CliError::invalid_input("A logger is already set"))
+}
+
+/// Send a record to the installed logger, if there is one
+///
+/// The engine goes through the crate's `log_record!` macro, which compiles
+/// to nothing without the `logging` feature.
+pub(crate) fn emit(level: Level, target: &'static str, args: fmt::Arguments<'_>) {
+    if let Some(logger) = LOGGER.get() {
+        logger.log(&Record {
+            level,
+            target,
+            args,
+        });
+    }
+}
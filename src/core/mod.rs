@@ -4,13 +4,55 @@
 //! functionality for the CLI application including error handling, command
 //! abstractions, and context management.
 
+pub mod alias;
+pub mod axes;
+pub mod buffer;
 pub mod command;
+pub mod command_tree;
+pub mod config;
 pub mod context;
 pub mod error;
+pub mod file_io;
+pub mod history_file;
+pub mod interrupt;
+pub mod io;
+pub mod json;
+pub mod line_diff;
+pub mod listing;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod navigator;
+pub mod options;
+pub mod pipeline;
+pub mod resolve;
 pub mod security;
+pub mod snapshot;
+pub mod tokenizer;
+pub mod version;
 
 // Re-export commonly used types
-pub use command::{ArgumentValidator, Command, CommandCategory, CommandResult};
+pub use alias::AliasTable;
+pub use axes::{Axis, AxisStore};
+pub use buffer::{EditBuffer, LineEnding, UndoHistory};
+pub use command::{
+    ArgKind, ArgumentValidator, Command, CommandCategory, CommandResult, parse_count,
+};
+pub use command_tree::{CommandTreeIter, validate_command_tree};
 pub use context::{CliContext, CliPreferences};
 pub use error::{CliError, CliResult};
-pub use security::{sanitize_for_display, validate_file_path, validate_file_size};
+pub use file_io::{FileSystem, RetryPolicy, StdFileSystem};
+pub use history_file::HistoryEntry;
+pub use io::SessionIo;
+pub use json::JsonValue;
+pub use listing::{DirectoryListing, ListingPage};
+pub use navigator::Navigator;
+pub use options::{ConfigSource, StartupOptions};
+pub use pipeline::{Dispatch, process_input};
+pub use resolve::{MatchKind, Resolution, resolve, resolve_case, suggest};
+pub use security::{
+    FileFormat, SecurityContext, SymlinkPolicy, detect_format, sanitize_for_display,
+    validate_against_root, validate_file_path, validate_file_size, validate_write_target,
+};
+pub use snapshot::{SessionSnapshot, SnapshotFormat};
+pub use tokenizer::{Redirect, Token, join_tokens, split_input, split_redirect, tokenize_quoted};
+pub use version::Version;
@@ -0,0 +1,162 @@
+//! Menu navigation state for the CLI application.
+//!
+//! The [`Navigator`] owns both the stack of entered menus and the path of
+//! their names shown in the prompt. Every change goes through it, so the
+//! two cannot drift apart.
+
+use crate::core::command::Command;
+
+/// Stack of entered menus and the matching navigation path
+///
+/// The root menu is always at the bottom of the stack and is not part of
+/// the path, so the path has one name for each menu entered below it.
+#[derive(Debug)]
+pub struct Navigator {
+    /// Menus entered so far, root first
+    menus: Vec<Box<dyn Command>>,
+    /// Names of the entered menus, not including the root
+    path: Vec<String>,
+}
+
+impl Navigator {
+    /// Create a navigator at the given root menu
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::RootCommand;
+    /// use sm_menu::core::navigator::Navigator;
+    /// use sm_menu::commands::file::FileCommand;
+    ///
+    /// let mut navigator = Navigator::new(Box::new(RootCommand));
+    /// navigator.push(Box::new(FileCommand::new()));
+    /// assert_eq!(navigator.path(), ["file"]);
+    /// assert_eq!(navigator.entered().len(), 1);
+    /// ```
+    pub fn new(root: Box<dyn Command>) -> Self {
+        Navigator {
+            menus: vec![root],
+            path: Vec::new(),
+        }
+    }
+
+    /// Enter a menu below the current one
+    pub fn push(&mut self, menu: Box<dyn Command>) {
+        log_record!(Info, "Entered menu '{}'", menu.name());
+        self.path.push(menu.name().to_string());
+        self.menus.push(menu);
+    }
+
+    /// Leave the current menu for its parent
+    ///
+    /// Returns `None` at the root menu, which is never left.
+    pub fn pop(&mut self) -> Option<Box<dyn Command>> {
+        let name = self.path.pop()?;
+        log_record!(Info, "Left menu '{name}'");
+        self.menus.pop()
+    }
+
+    /// Return to the root menu
+    pub fn reset(&mut self) {
+        if !self.path.is_empty() {
+            log_record!(Info, "Returned to the root menu");
+        }
+        self.menus.truncate(1);
+        self.path.clear();
+    }
+
+    /// Replace the root menu, leaving every entered menu
+    pub fn set_root(&mut self, root: Box<dyn Command>) {
+        self.menus = vec![root];
+        self.path.clear();
+    }
+
+    /// Get the menu the session is currently in
+    pub fn current(&self) -> &dyn Command {
+        self.menus
+            .last()
+            .expect("the navigator always has a root menu")
+            .as_ref()
+    }
+
+    /// Get every menu on the stack, root first
+    pub fn menus(&self) -> &[Box<dyn Command>] {
+        &self.menus
+    }
+
+    /// Get the menus entered below the root
+    pub fn entered(&self) -> &[Box<dyn Command>] {
+        &self.menus[1..]
+    }
+
+    /// Get the names of the entered menus
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Get the number of menus entered below the root
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Check whether the session is at the root menu
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::RootCommand;
+    use crate::commands::edit::EditCommand;
+    use crate::commands::file::FileCommand;
+
+    fn assert_in_sync(navigator: &Navigator) {
+        assert_eq!(navigator.path().len(), navigator.entered().len());
+        assert_eq!(navigator.menus().len(), navigator.depth() + 1);
+        for (name, menu) in navigator.path().iter().zip(navigator.entered()) {
+            assert_eq!(name, menu.name());
+        }
+    }
+
+    #[test]
+    fn test_push_and_pop_keep_path_in_sync() {
+        let mut navigator = Navigator::new(Box::new(RootCommand));
+        assert!(navigator.is_root());
+        assert_eq!(navigator.current().name(), "root");
+
+        navigator.push(Box::new(FileCommand::new()));
+        assert_in_sync(&navigator);
+        navigator.push(Box::new(EditCommand::new()));
+        assert_in_sync(&navigator);
+        assert_eq!(navigator.path(), ["file", "edit"]);
+        assert_eq!(navigator.current().name(), "edit");
+
+        assert_eq!(navigator.pop().unwrap().name(), "edit");
+        assert_in_sync(&navigator);
+        assert_eq!(navigator.pop().unwrap().name(), "file");
+        assert_in_sync(&navigator);
+
+        // The root is never popped
+        assert!(navigator.pop().is_none());
+        assert_in_sync(&navigator);
+        assert_eq!(navigator.current().name(), "root");
+    }
+
+    #[test]
+    fn test_reset_and_set_root() {
+        let mut navigator = Navigator::new(Box::new(RootCommand));
+        navigator.push(Box::new(FileCommand::new()));
+        navigator.push(Box::new(EditCommand::new()));
+
+        navigator.reset();
+        assert_in_sync(&navigator);
+        assert!(navigator.is_root());
+        assert_eq!(navigator.current().name(), "root");
+
+        navigator.push(Box::new(FileCommand::new()));
+        navigator.set_root(Box::new(EditCommand::new()));
+        assert_in_sync(&navigator);
+        assert_eq!(navigator.current().name(), "edit");
+    }
+}
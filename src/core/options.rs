@@ -0,0 +1,696 @@
+//! Startup option parsing for the CLI application.
+//!
+//! This module parses the command-line flags given to `sm-menu` at launch
+//! and turns them into the settings used to build the session, such as the
+//! security boundary for file operations and the user preferences.
+
+use crate::core::config::{default_config_path, default_history_path, write_default_config};
+use crate::core::context::CliPreferences;
+use crate::core::error::{CliError, CliResult};
+use crate::core::io::SessionIo;
+use crate::core::security::SecurityContext;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Environment variable naming the default working directory
+pub const WORKDIR_VAR: &str = "SM_MENU_WORKDIR";
+
+/// Where startup preferences are read from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ConfigSource {
+    /// The default configuration file, if it exists
+    #[default]
+    Default,
+    /// A configuration file given with `--config <file>`, which must exist
+    File(PathBuf),
+    /// No configuration file (`--config none` or `--no-config`)
+    Disabled,
+}
+
+/// Options parsed from the command line at startup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StartupOptions {
+    /// Directory that file operations are confined to (`--root <dir>` or
+    /// `--workdir <dir>`)
+    pub root: Option<PathBuf>,
+    /// Answer yes to every confirmation without asking (`--yes`)
+    pub assume_yes: bool,
+    /// Where preferences are read from (`--config`, `--no-config`)
+    pub config: ConfigSource,
+    /// File to read commands from instead of standard input (`--script <file>`)
+    pub script: Option<PathBuf>,
+    /// Suppress the summary at the end of a script run (`--quiet`)
+    pub quiet: bool,
+    /// Print each scripted command line to stderr before it runs (`--echo`)
+    pub echo: bool,
+    /// Stop a script at its first failed line, warnings included (`--strict`)
+    pub strict: bool,
+    /// Emit machine-readable JSON output (`--json`)
+    pub json: bool,
+    /// File to append a plain-text session transcript to (`--log <file>`)
+    pub log: Option<PathBuf>,
+    /// File to write a CSV row of timing for each command to (`--profile <file>`)
+    pub profile: Option<PathBuf>,
+    /// Write a default configuration file and exit (`--init`)
+    pub init: bool,
+    /// Print the build information as JSON and exit (`--version-json`)
+    pub version_json: bool,
+    /// Let `--init` replace an existing configuration file (`--force`)
+    pub force: bool,
+    /// Show the causes of errors below them (`--trace-errors`)
+    pub trace_errors: bool,
+    /// Turn off color, icons, progress bars, hints, and banners (`--plain`)
+    pub plain: bool,
+    /// Quit when no input arrives for this long (`--idle-timeout <secs>`)
+    pub idle_timeout: Option<Duration>,
+    /// Read JSON requests and answer with JSON responses (`--commands-from-stdin`)
+    pub commands_from_stdin: bool,
+    /// Deepest menu level navigation may enter (`--max-depth <n>`)
+    pub max_depth: Option<usize>,
+    /// Command to run once instead of a session, with its menu path
+    pub command: Vec<String>,
+    /// File a one-shot command's output is written to (`--output <file>`)
+    pub output: Option<PathBuf>,
+}
+
+impl StartupOptions {
+    /// Parse startup options from command-line arguments
+    ///
+    /// # Arguments
+    /// * `args` - The arguments following the program name
+    ///
+    /// # Returns
+    /// * `Ok(StartupOptions)` - The parsed options
+    /// * `Err(CliError)` - If an option is unknown or missing its value
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::options::StartupOptions;
+    ///
+    /// let options = StartupOptions::parse(["--root", "/srv/data"]).unwrap();
+    /// assert_eq!(options.root.unwrap().to_str(), Some("/srv/data"));
+    /// ```
+    pub fn parse<I, S>(args: I) -> CliResult<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut options = StartupOptions::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+
+            match flag.as_str() {
+                "--root" | "--workdir" => {
+                    let value = inline_value.or_else(|| args.next()).ok_or_else(|| {
+                        CliError::invalid_input(&format!("{flag} requires a directory"))
+                    })?;
+                    options.root = Some(PathBuf::from(value));
+                }
+                "--yes" | "-y" if inline_value.is_none() => options.assume_yes = true,
+                "--config" => {
+                    let value = inline_value.or_else(|| args.next()).ok_or_else(|| {
+                        CliError::invalid_input("--config requires a file or 'none'")
+                    })?;
+                    // Disabling config wins over any --config file, whatever the order
+                    if options.config != ConfigSource::Disabled {
+                        options.config = match value.as_str() {
+                            "none" => ConfigSource::Disabled,
+                            _ => ConfigSource::File(PathBuf::from(value)),
+                        };
+                    }
+                }
+                "--no-config" if inline_value.is_none() => options.config = ConfigSource::Disabled,
+                "--script" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| CliError::invalid_input("--script requires a file"))?;
+                    options.script = Some(PathBuf::from(value));
+                }
+                "--log" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| CliError::invalid_input("--log requires a file"))?;
+                    options.log = Some(PathBuf::from(value));
+                }
+                "--profile" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| CliError::invalid_input("--profile requires a file"))?;
+                    options.profile = Some(PathBuf::from(value));
+                }
+                "--quiet" | "-q" if inline_value.is_none() => options.quiet = true,
+                "--echo" if inline_value.is_none() => options.echo = true,
+                "--strict" if inline_value.is_none() => options.strict = true,
+                "--json" if inline_value.is_none() => options.json = true,
+                "--init" if inline_value.is_none() => options.init = true,
+                "--version-json" if inline_value.is_none() => options.version_json = true,
+                "--force" if inline_value.is_none() => options.force = true,
+                "--trace-errors" if inline_value.is_none() => options.trace_errors = true,
+                "--plain" if inline_value.is_none() => options.plain = true,
+                "--commands-from-stdin" if inline_value.is_none() => {
+                    options.commands_from_stdin = true
+                }
+                "--idle-timeout" => {
+                    let value = inline_value.or_else(|| args.next()).ok_or_else(|| {
+                        CliError::invalid_input("--idle-timeout requires a number of seconds")
+                    })?;
+                    let seconds = value.parse::<u64>().ok().filter(|&s| s > 0).ok_or_else(|| {
+                        CliError::invalid_input(&format!(
+                            "Invalid idle timeout: {value} (expected a positive number of seconds)"
+                        ))
+                    })?;
+                    options.idle_timeout = Some(Duration::from_secs(seconds));
+                }
+                "--max-depth" => {
+                    let value = inline_value.or_else(|| args.next()).ok_or_else(|| {
+                        CliError::invalid_input("--max-depth requires a number of levels")
+                    })?;
+                    let depth = value.parse::<usize>().map_err(|_| {
+                        CliError::invalid_input(&format!(
+                            "Invalid max depth: {value} (expected a non-negative number of levels)"
+                        ))
+                    })?;
+                    options.max_depth = Some(depth);
+                }
+                "--output" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| CliError::invalid_input("--output requires a file"))?;
+                    options.output = Some(PathBuf::from(value));
+                }
+                // The first word that is not an option starts a one-shot
+                // command; the words after it are its own, even if they
+                // look like options
+                _ if !flag.starts_with('-') => {
+                    options.command.push(flag);
+                    options.command.extend(args.by_ref());
+                }
+                _ => {
+                    return Err(CliError::invalid_input(&format!("Unknown option: {flag}")));
+                }
+            }
+        }
+
+        if options.output.is_some() && options.command.is_empty() {
+            return Err(CliError::invalid_input(
+                "--output needs a command to run, as in: sm-menu --output <file> <command>",
+            ));
+        }
+        if !options.command.is_empty() && (options.script.is_some() || options.commands_from_stdin)
+        {
+            return Err(CliError::invalid_input(
+                "A command cannot be combined with --script or --commands-from-stdin",
+            ));
+        }
+
+        Ok(options)
+    }
+
+    /// Parse startup options from the process arguments
+    pub fn from_env() -> CliResult<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Build the security context described by these options
+    ///
+    /// Uses the `--root` (or `--workdir`) directory when given, then the
+    /// `SM_MENU_WORKDIR` directory, and otherwise the current working
+    /// directory.
+    pub fn security_context(&self) -> CliResult<SecurityContext> {
+        self.security_context_from(std::env::var_os(WORKDIR_VAR).map(PathBuf::from))
+    }
+
+    /// Build the security context, given the `SM_MENU_WORKDIR` directory
+    ///
+    /// An empty variable counts as unset. The chosen directory must exist
+    /// and be readable.
+    pub fn security_context_from(
+        &self,
+        env_workdir: Option<PathBuf>,
+    ) -> CliResult<SecurityContext> {
+        let env_workdir = env_workdir.filter(|dir| !dir.as_os_str().is_empty());
+        let (root, source) = match (&self.root, &env_workdir) {
+            (Some(root), _) => (root, "--root"),
+            (None, Some(dir)) => (dir, WORKDIR_VAR),
+            (None, None) => return SecurityContext::from_cwd(),
+        };
+
+        let security = SecurityContext::new(root).map_err(|e| {
+            CliError::invalid_input(&format!("Working directory from {source}: {e}"))
+        })?;
+        std::fs::read_dir(security.root()).map_err(|e| {
+            CliError::invalid_input(&format!(
+                "Working directory from {source} is not readable: {}: {e}",
+                root.display()
+            ))
+        })?;
+        Ok(security)
+    }
+
+    /// Build the session input described by these options
+    ///
+    /// Reads the `--script` file when given, otherwise standard input. A
+    /// script is never treated as a terminal, and the idle timeout only
+    /// applies to standard input.
+    pub fn session_io(&self) -> CliResult<SessionIo> {
+        match &self.script {
+            Some(path) => {
+                let file = File::open(path).map_err(|e| match e.kind() {
+                    std::io::ErrorKind::NotFound => {
+                        CliError::file_not_found(&path.display().to_string())
+                    }
+                    _ => CliError::IoError(e),
+                })?;
+                Ok(SessionIo::from_reader(BufReader::new(file), false))
+            }
+            None => Ok(match self.idle_timeout {
+                Some(timeout) => SessionIo::stdio_with_idle_timeout(timeout),
+                None => SessionIo::stdio(),
+            }),
+        }
+    }
+
+    /// Check whether the session is typed by a person
+    ///
+    /// Script runs, one-shot commands, and the JSON request protocol are
+    /// not: they show no banner and do not clear the screen.
+    pub fn is_interactive(&self) -> bool {
+        self.script.is_none() && !self.commands_from_stdin && self.command.is_empty()
+    }
+
+    /// Get the file the session history is loaded from and saved to
+    ///
+    /// History is only persisted for interactive runs that use the user's
+    /// configuration, so script runs, JSON request runs, and `--no-config`
+    /// runs leave it alone.
+    pub fn history_path(&self) -> Option<PathBuf> {
+        if !self.is_interactive() || self.config == ConfigSource::Disabled {
+            return None;
+        }
+        default_history_path()
+    }
+
+    /// Get the configuration file these options read preferences from
+    ///
+    /// Returns `None` when configuration is disabled or there is no default
+    /// configuration path.
+    pub fn config_path(&self) -> Option<PathBuf> {
+        self.config_path_from(default_config_path())
+    }
+
+    /// Get the configuration file, given the default configuration path
+    pub fn config_path_from(&self, default: Option<PathBuf>) -> Option<PathBuf> {
+        match &self.config {
+            ConfigSource::Default => default,
+            ConfigSource::File(path) => Some(path.clone()),
+            ConfigSource::Disabled => None,
+        }
+    }
+
+    /// Write a default configuration file for `--init`
+    ///
+    /// The file is written where these options read preferences from, so
+    /// `--config <file>` picks another file.
+    ///
+    /// # Returns
+    /// * `Ok(PathBuf)` - The file that was written
+    /// * `Err(CliError)` - If there is no configuration path, or the file
+    ///   exists and `--force` was not given
+    pub fn init_config(&self) -> CliResult<PathBuf> {
+        self.init_config_from(default_config_path())
+    }
+
+    /// Write a default configuration file, given the default configuration path
+    pub fn init_config_from(&self, default: Option<PathBuf>) -> CliResult<PathBuf> {
+        let path = self.config_path_from(default).ok_or_else(|| {
+            CliError::invalid_input("--init needs a configuration file; use --config <file>")
+        })?;
+        write_default_config(&path, self.force)?;
+        Ok(path)
+    }
+
+    /// Build the session preferences described by these options
+    ///
+    /// Reads the configuration file, if any, and applies the preference
+    /// flags on top of it.
+    pub fn preferences(&self) -> CliResult<CliPreferences> {
+        self.preferences_from(default_config_path())
+    }
+
+    /// Build the session preferences, given the default configuration path
+    ///
+    /// A missing default configuration file is not an error; a missing
+    /// `--config` file is. With configuration disabled no file is read and
+    /// the defaults are used.
+    pub fn preferences_from(&self, default: Option<PathBuf>) -> CliResult<CliPreferences> {
+        let explicit = matches!(self.config, ConfigSource::File(_));
+        let mut preferences = match self.config_path_from(default) {
+            Some(path) if explicit || path.exists() => CliPreferences::from_path(&path)?,
+            _ => CliPreferences::default(),
+        };
+
+        preferences.assume_yes = self.assume_yes;
+        preferences.trace_errors = self.trace_errors;
+        preferences.plain = self.plain;
+        preferences.max_depth = self.max_depth;
+        Ok(preferences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_no_options() {
+        let options = StartupOptions::parse(Vec::<String>::new()).unwrap();
+        assert_eq!(options, StartupOptions::default());
+    }
+
+    #[test]
+    fn test_parse_root() {
+        let options = StartupOptions::parse(["--root", "/tmp/box"]).unwrap();
+        assert_eq!(options.root, Some(PathBuf::from("/tmp/box")));
+
+        let options = StartupOptions::parse(["--root=/tmp/box"]).unwrap();
+        assert_eq!(options.root, Some(PathBuf::from("/tmp/box")));
+    }
+
+    #[test]
+    fn test_parse_yes() {
+        let options = StartupOptions::parse(["--yes", "--root", "/tmp/box"]).unwrap();
+        assert!(options.assume_yes);
+        assert!(StartupOptions::parse(["-y"]).unwrap().assume_yes);
+        assert!(StartupOptions::parse(["--yes=no"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_trace_errors() {
+        let options = StartupOptions::parse(["--trace-errors"]).unwrap();
+        assert!(options.trace_errors);
+        assert!(!StartupOptions::default().trace_errors);
+        assert!(StartupOptions::parse(["--trace-errors=yes"]).is_err());
+
+        let preferences = options.preferences_from(None).unwrap();
+        assert!(preferences.trace_errors);
+    }
+
+    #[test]
+    fn test_parse_strict() {
+        assert!(StartupOptions::parse(["--strict"]).unwrap().strict);
+        assert!(!StartupOptions::default().strict);
+        assert!(StartupOptions::parse(["--strict=yes"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let options = StartupOptions::parse(["--profile", "times.csv"]).unwrap();
+        assert_eq!(options.profile, Some(PathBuf::from("times.csv")));
+        let options = StartupOptions::parse(["--profile=times.csv"]).unwrap();
+        assert_eq!(options.profile, Some(PathBuf::from("times.csv")));
+        assert!(StartupOptions::parse(["--profile"]).is_err());
+    }
+
+    #[test]
+    fn test_workdir_precedence() {
+        let base = std::env::temp_dir().join(format!("sm_menu_workdir_{}", std::process::id()));
+        let (flag_dir, env_dir) = (base.join("flag"), base.join("env"));
+        std::fs::create_dir_all(&flag_dir).unwrap();
+        std::fs::create_dir_all(&env_dir).unwrap();
+
+        // The environment variable is used without a flag
+        let options = StartupOptions::default();
+        let security = options
+            .security_context_from(Some(env_dir.clone()))
+            .unwrap();
+        assert_eq!(security.root(), env_dir.canonicalize().unwrap());
+
+        // --workdir, like --root, overrides it
+        let flag = flag_dir.to_str().unwrap();
+        for name in ["--workdir", "--root"] {
+            let options = StartupOptions::parse([name, flag]).unwrap();
+            let security = options
+                .security_context_from(Some(env_dir.clone()))
+                .unwrap();
+            assert_eq!(security.root(), flag_dir.canonicalize().unwrap(), "{name}");
+        }
+        assert!(StartupOptions::parse(["--workdir"]).is_err());
+
+        // An empty variable is ignored
+        let security = options.security_context_from(Some(PathBuf::new())).unwrap();
+        assert_eq!(
+            security.root(),
+            std::env::current_dir().unwrap().canonicalize().unwrap()
+        );
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_workdir_must_exist() {
+        let missing = std::env::temp_dir().join("sm_menu_no_such_workdir");
+        let error = StartupOptions::default()
+            .security_context_from(Some(missing.clone()))
+            .unwrap_err();
+        assert!(matches!(error, CliError::InvalidInput(_)));
+        assert!(
+            error
+                .to_string()
+                .contains("Working directory from SM_MENU_WORKDIR"),
+            "{error}"
+        );
+
+        let options = StartupOptions::parse(["--workdir", missing.to_str().unwrap()]).unwrap();
+        let error = options.security_context_from(None).unwrap_err();
+        assert!(error.to_string().contains("from --root"), "{error}");
+    }
+
+    #[test]
+    fn test_parse_plain() {
+        let options = StartupOptions::parse(["--plain"]).unwrap();
+        assert!(options.plain);
+        assert!(StartupOptions::parse(["--plain=yes"]).is_err());
+
+        let preferences = options.preferences_from(None).unwrap();
+        assert!(preferences.plain);
+        assert!(!preferences.colored());
+        assert!(CliPreferences::default().colored());
+    }
+
+    #[test]
+    fn test_parse_commands_from_stdin() {
+        let options = StartupOptions::parse(["--commands-from-stdin"]).unwrap();
+        assert!(options.commands_from_stdin);
+        assert!(!options.is_interactive());
+        assert_eq!(options.history_path(), None);
+        assert!(StartupOptions::default().is_interactive());
+        assert!(StartupOptions::parse(["--commands-from-stdin=yes"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_idle_timeout() {
+        let options = StartupOptions::parse(["--idle-timeout", "300"]).unwrap();
+        assert_eq!(options.idle_timeout, Some(Duration::from_secs(300)));
+        let options = StartupOptions::parse(["--idle-timeout=5"]).unwrap();
+        assert_eq!(options.idle_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(StartupOptions::default().idle_timeout, None);
+
+        for args in [
+            vec!["--idle-timeout"],
+            vec!["--idle-timeout", "0"],
+            vec!["--idle-timeout", "-5"],
+            vec!["--idle-timeout", "soon"],
+        ] {
+            assert!(StartupOptions::parse(args.clone()).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_max_depth() {
+        let options = StartupOptions::parse(["--max-depth", "2"]).unwrap();
+        assert_eq!(options.max_depth, Some(2));
+        assert_eq!(options.preferences_from(None).unwrap().max_depth, Some(2));
+        assert_eq!(
+            StartupOptions::parse(["--max-depth=0"]).unwrap().max_depth,
+            Some(0)
+        );
+        assert_eq!(StartupOptions::default().max_depth, None);
+
+        for args in [
+            &["--max-depth"][..],
+            &["--max-depth", "-1"],
+            &["--max-depth", "deep"],
+        ] {
+            assert!(matches!(
+                StartupOptions::parse(args.iter().copied()),
+                Err(CliError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_one_shot_command() {
+        let options =
+            StartupOptions::parse(["--root", "/tmp/box", "file", "save", "--force"]).unwrap();
+        assert_eq!(options.root, Some(PathBuf::from("/tmp/box")));
+        assert_eq!(options.command, ["file", "save", "--force"]);
+        assert!(!options.is_interactive());
+        assert_eq!(options.history_path(), None);
+
+        let options = StartupOptions::parse(["--output=out.txt", "file", "vers"]).unwrap();
+        assert_eq!(options.output, Some(PathBuf::from("out.txt")));
+        assert_eq!(options.command, ["file", "vers"]);
+
+        for args in [
+            &["--output", "out.txt"][..],
+            &["--output"],
+            &["--script", "setup.sm", "file"],
+            &["--commands-from-stdin", "file"],
+        ] {
+            assert!(matches!(
+                StartupOptions::parse(args.iter().copied()),
+                Err(CliError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let options = StartupOptions::parse(["--config", "/etc/sm.toml"]).unwrap();
+        assert_eq!(
+            options.config,
+            ConfigSource::File(PathBuf::from("/etc/sm.toml"))
+        );
+
+        for args in [
+            vec!["--config", "none"],
+            vec!["--config=none"],
+            vec!["--no-config"],
+            vec!["--no-config", "--config", "/etc/sm.toml"],
+            vec!["--config", "/etc/sm.toml", "--no-config"],
+        ] {
+            let options = StartupOptions::parse(args.clone()).unwrap();
+            assert_eq!(options.config, ConfigSource::Disabled, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_no_config_ignores_default_config_file() {
+        let dir = std::env::temp_dir().join(format!("sm_menu_no_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let default = dir.join("config.toml");
+        std::fs::write(&default, "max_list_items = 5\n").unwrap();
+
+        let options = StartupOptions::parse(Vec::<String>::new()).unwrap();
+        let preferences = options.preferences_from(Some(default.clone())).unwrap();
+        assert_eq!(preferences.max_list_items, 5);
+
+        for args in [["--no-config"], ["--config=none"]] {
+            let options = StartupOptions::parse(args).unwrap();
+            assert_eq!(options.config_path_from(Some(default.clone())), None);
+            let preferences = options.preferences_from(Some(default.clone())).unwrap();
+            assert_eq!(preferences, CliPreferences::default());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_config_files() {
+        let missing = std::env::temp_dir().join("sm_menu_missing_config.toml");
+
+        // A missing default file falls back to the defaults
+        let options = StartupOptions::default();
+        assert!(options.preferences_from(Some(missing.clone())).is_ok());
+
+        // A missing file named with --config is an error
+        let options = StartupOptions::parse(["--config", missing.to_str().unwrap()]).unwrap();
+        assert!(matches!(
+            options.preferences_from(None),
+            Err(CliError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_script_flags() {
+        let options =
+            StartupOptions::parse(["--script", "run.sm", "--quiet", "--json", "--echo"]).unwrap();
+        assert_eq!(options.script, Some(PathBuf::from("run.sm")));
+        assert!(options.quiet);
+        assert!(options.json);
+        assert!(options.echo);
+        assert!(StartupOptions::parse(["-q"]).unwrap().quiet);
+        assert!(StartupOptions::parse(["--echo=yes"]).is_err());
+        assert!(StartupOptions::parse(["--script"]).is_err());
+
+        let options = StartupOptions::parse(["--log=session.log"]).unwrap();
+        assert_eq!(options.log, Some(PathBuf::from("session.log")));
+        assert!(StartupOptions::parse(["--log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_version_json() {
+        assert!(
+            StartupOptions::parse(["--version-json"])
+                .unwrap()
+                .version_json
+        );
+        assert!(!StartupOptions::default().version_json);
+        assert!(StartupOptions::parse(["--version-json=1"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_init() {
+        let options = StartupOptions::parse(["--init", "--force"]).unwrap();
+        assert!(options.init);
+        assert!(options.force);
+        assert!(StartupOptions::parse(["--init=yes"]).is_err());
+
+        let options = StartupOptions::parse(["--init", "--no-config"]).unwrap();
+        assert!(matches!(
+            options.init_config_from(None),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_history_path_skipped_for_scripts_and_no_config() {
+        assert_eq!(
+            StartupOptions::parse(["--no-config"])
+                .unwrap()
+                .history_path(),
+            None
+        );
+        assert_eq!(
+            StartupOptions::parse(["--script", "run.sm"])
+                .unwrap()
+                .history_path(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            StartupOptions::parse(["--root"]),
+            Err(CliError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            StartupOptions::parse(["--config"]),
+            Err(CliError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            StartupOptions::parse(["--bogus"]),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+}
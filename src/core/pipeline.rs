@@ -0,0 +1,312 @@
+//! The input pipeline, from a typed line to the command it would run.
+//!
+//! A line goes through these stages before anything runs:
+//!
+//! 1. History references: `!!` is replaced by the previous command line
+//! 2. Aliases: a user-defined alias at the start of the line is expanded
+//! 3. Tokenizing: the line is split into words, with quotes grouping them
+//! 4. Redirection: a trailing `> file` or `>> file` is split off
+//! 5. Resolution: the first word is resolved among the menu's commands
+//!
+//! [`process_input`] runs every stage and returns the resulting
+//! [`Dispatch`] without running the command or changing the session, so
+//! the pipeline can be checked end to end on its own. The session loop
+//! goes through the same stages. Middleware added to an
+//! [`App`](crate::App) runs before them and is not part of the pipeline,
+//! since it may have side effects.
+
+use crate::core::command::Command;
+use crate::core::context::CliContext;
+use crate::core::error::{CliError, CliResult};
+use crate::core::history_file::HistoryEntry;
+use crate::core::resolve::resolve_case;
+use crate::core::tokenizer::{Redirect, split_redirect, tokenize_quoted};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// History reference standing for the previous command line
+pub const PREVIOUS_COMMAND: &str = "!!";
+
+/// Plan for running one input line, made without running it
+pub struct Dispatch {
+    /// The line after history references and aliases are expanded
+    pub line: String,
+    /// The command name as typed, before resolution
+    pub token: String,
+    /// The command the name resolves to
+    pub command: Box<dyn Command>,
+    /// Path to the command: the current menu path followed by its name
+    pub path: Vec<String>,
+    /// Arguments for the command
+    pub args: Vec<String>,
+    /// Where the command's output goes instead of the display, if anywhere
+    pub redirect: Option<Redirect>,
+}
+
+impl fmt::Debug for Dispatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dispatch")
+            .field("line", &self.line)
+            .field("token", &self.token)
+            .field("path", &self.path)
+            .field("args", &self.args)
+            .field("redirect", &self.redirect)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Plans are equal when they run the same line the same way
+///
+/// The command itself is not compared; the last element of the path
+/// names it.
+impl PartialEq for Dispatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line
+            && self.token == other.token
+            && self.path == other.path
+            && self.args == other.args
+            && self.redirect == other.redirect
+    }
+}
+
+/// Work out what an input line would run, without running it
+///
+/// # Arguments
+/// * `line` - The input line, without its terminator
+/// * `context` - The session, for its history, aliases, preferences, and
+///   current menu path
+/// * `siblings` - The commands available in the current menu
+///
+/// # Returns
+/// * `Ok(Dispatch)` - The plan for running the line
+/// * `Err(CliError)` - `EmptyInput` if the line has no words, or the error
+///   from the first stage that fails: an unknown history reference or
+///   alias cycle, an unclosed quote, a bad redirection, or a command name
+///   that is unknown or ambiguous
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::pipeline::process_input;
+/// use sm_menu::{CliContext, Command, RootCommand};
+///
+/// let context = CliContext::new();
+/// let dispatch = process_input("fi > out.txt", &context, RootCommand.subcommands()).unwrap();
+/// assert_eq!(dispatch.path, ["file"]);
+/// assert_eq!(dispatch.redirect.unwrap().target, "out.txt");
+/// ```
+pub fn process_input(
+    line: &str,
+    context: &CliContext,
+    siblings: Vec<Box<dyn Command>>,
+) -> CliResult<Dispatch> {
+    let (line, parts, redirect) = split_line(line, context, &siblings)?;
+    match parts.split_first() {
+        Some((token, args)) => plan(line, token, args, redirect, context, siblings),
+        None => Err(CliError::EmptyInput),
+    }
+}
+
+/// Run the stages before resolution: expand the line, tokenize it, and
+/// split off its redirection
+///
+/// # Returns
+/// The expanded line, its words, and the redirection, if any.
+pub(crate) fn split_line(
+    line: &str,
+    context: &CliContext,
+    siblings: &[Box<dyn Command>],
+) -> CliResult<(String, Vec<String>, Option<Redirect>)> {
+    let line = expand_history(line, context.history())?;
+    let line = expand_aliases(&line, context, siblings)?;
+    let (parts, redirect) = split_redirect(tokenize_quoted(&line)?)?;
+    Ok((line, parts, redirect))
+}
+
+/// Resolve a command name among the menu's commands and plan to run it
+pub(crate) fn plan(
+    line: String,
+    token: &str,
+    args: &[String],
+    redirect: Option<Redirect>,
+    context: &CliContext,
+    siblings: Vec<Box<dyn Command>>,
+) -> CliResult<Dispatch> {
+    let case_sensitive = context.preferences().case_sensitive_commands;
+    let command = resolve_case(token, siblings, case_sensitive).into_command(token)?;
+
+    let mut path = context.current_path().to_vec();
+    path.push(command.name().to_string());
+    Ok(Dispatch {
+        line,
+        token: token.to_string(),
+        command,
+        path,
+        args: args.to_vec(),
+        redirect,
+    })
+}
+
+/// Expand a user-defined alias at the start of a line
+///
+/// Names and aliases of the menu's commands are built in, so they are
+/// never expanded.
+fn expand_aliases(
+    line: &str,
+    context: &CliContext,
+    siblings: &[Box<dyn Command>],
+) -> CliResult<String> {
+    let aliases = context.aliases();
+    if aliases.is_empty() {
+        return Ok(line.to_string());
+    }
+    let case_sensitive = context.preferences().case_sensitive_commands;
+    aliases.expand(line, |word| {
+        siblings
+            .iter()
+            .any(|cmd| cmd.matches_case(word, case_sensitive))
+    })
+}
+
+/// Replace each `!!` word in a line with the previous command line
+///
+/// A `!!` is only a reference when it is a whole word outside quotes, so
+/// `echo '!!'` and `wow!!` are left alone. The previous command is the
+/// most recent history entry. Replacing is idempotent, since the previous
+/// command was itself expanded before it was recorded.
+///
+/// # Returns
+/// * `Ok(String)` - The line with its references replaced
+/// * `Err(CliError)` - `InvalidInput` if the line has a reference and
+///   the history is empty
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::HistoryEntry;
+/// use sm_menu::core::pipeline::expand_history;
+/// use std::collections::VecDeque;
+///
+/// let history = VecDeque::from([HistoryEntry::now("file ls".to_string())]);
+/// assert_eq!(expand_history("!! -a", &history).unwrap(), "file ls -a");
+/// assert_eq!(expand_history("echo '!!'", &history).unwrap(), "echo '!!'");
+/// ```
+pub fn expand_history(line: &str, history: &VecDeque<HistoryEntry>) -> CliResult<String> {
+    let mut expanded = String::with_capacity(line.len());
+    let mut quote = None;
+    let mut at_word_start = true;
+    let mut chars = line.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match quote {
+            Some(open) => {
+                if c == open {
+                    quote = None;
+                }
+                expanded.push(c);
+                continue;
+            }
+            None if c.is_whitespace() => {
+                at_word_start = true;
+                expanded.push(c);
+                continue;
+            }
+            None => {}
+        }
+
+        let rest = &line[index..];
+        if at_word_start && (c == '"' || c == '\'') {
+            quote = Some(c);
+            expanded.push(c);
+        } else if at_word_start
+            && rest.starts_with(PREVIOUS_COMMAND)
+            && rest[PREVIOUS_COMMAND.len()..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+        {
+            let previous = history.back().ok_or_else(|| {
+                CliError::invalid_input(&format!("{PREVIOUS_COMMAND}: no previous command"))
+            })?;
+            expanded.push_str(&previous.command);
+            chars.next();
+        } else if c == '\\' {
+            // An escaped character, such as a space, stays in the word
+            expanded.push(c);
+            if let Some((_, escaped)) = chars.next() {
+                expanded.push(escaped);
+            }
+        } else {
+            expanded.push(c);
+        }
+        at_word_start = false;
+    }
+
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::RootCommand;
+
+    fn history(lines: &[&str]) -> VecDeque<HistoryEntry> {
+        lines
+            .iter()
+            .map(|line| HistoryEntry::now(line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_history_references() {
+        let history = history(&["edit", "file ls"]);
+        let expand = |line| expand_history(line, &history).unwrap();
+
+        assert_eq!(expand("!!"), "file ls");
+        assert_eq!(expand("  !!  > out.txt"), "  file ls  > out.txt");
+        assert_eq!(expand("echo !! !!"), "echo file ls file ls");
+        // Only whole words outside quotes are references
+        assert_eq!(expand("echo wow!! !!x"), "echo wow!! !!x");
+        assert_eq!(expand(r#"echo "!! is" '!!'"#), r#"echo "!! is" '!!'"#);
+        assert_eq!(expand(r"echo \!!"), r"echo \!!");
+
+        assert_eq!(
+            expand_history("!!", &VecDeque::new()),
+            Err(CliError::invalid_input("!!: no previous command"))
+        );
+        assert_eq!(expand_history("ls", &VecDeque::new()).unwrap(), "ls");
+    }
+
+    #[test]
+    fn test_process_input_plans_without_running() {
+        let mut context = CliContext::new();
+        context.add_to_history("fi".to_string());
+
+        let dispatch = process_input("!! >> log.txt", &context, RootCommand.subcommands()).unwrap();
+        assert_eq!(dispatch.line, "fi >> log.txt");
+        assert_eq!(dispatch.token, "fi");
+        assert_eq!(dispatch.path, ["file"]);
+        assert!(dispatch.args.is_empty());
+        assert_eq!(
+            dispatch.redirect,
+            Some(Redirect {
+                target: "log.txt".to_string(),
+                append: true,
+            })
+        );
+        // Nothing was entered or recorded
+        assert!(context.current_path().is_empty());
+        assert_eq!(context.history().len(), 1);
+
+        assert_eq!(
+            process_input("   ", &context, RootCommand.subcommands()),
+            Err(CliError::EmptyInput)
+        );
+        assert!(matches!(
+            process_input("re", &context, RootCommand.subcommands()),
+            Err(CliError::AmbiguousCommand { .. })
+        ));
+        assert!(matches!(
+            process_input("load 'open", &context, RootCommand.subcommands()),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+//! Command resolution for the CLI application.
+//!
+//! This module decides which command an input token names among the
+//! commands of a menu. The session loop and the `which` command share it,
+//! so `which` always reports what typing the token would actually run.
+
+use crate::core::command::Command;
+use crate::core::error::{CliError, CliResult};
+use std::fmt;
+
+/// How a token matched the command it resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The token is the command's name
+    Name,
+    /// The token is one of the command's aliases
+    Alias,
+    /// The token is the start of the command's name
+    Prefix,
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchKind::Name => write!(f, "name"),
+            MatchKind::Alias => write!(f, "alias"),
+            MatchKind::Prefix => write!(f, "prefix"),
+        }
+    }
+}
+
+/// Outcome of resolving a token against a menu's commands
+#[derive(Debug)]
+pub enum Resolution {
+    /// The token names exactly one command
+    Found {
+        /// The command the token resolves to
+        command: Box<dyn Command>,
+        /// How the token matched
+        kind: MatchKind,
+    },
+    /// The token matches several commands equally well
+    Ambiguous(Vec<String>),
+    /// The token matches no command
+    NotFound,
+}
+
+impl Resolution {
+    /// Turn the resolution into the command to run
+    ///
+    /// # Returns
+    /// * `Ok(command)` - The command the token resolves to
+    /// * `Err(CliError)` - `AmbiguousCommand` or `InvalidCommand` otherwise
+    pub fn into_command(self, token: &str) -> CliResult<Box<dyn Command>> {
+        match self {
+            Resolution::Found { command, .. } => Ok(command),
+            Resolution::Ambiguous(candidates) => Err(CliError::AmbiguousCommand {
+                input: token.to_string(),
+                candidates,
+            }),
+            Resolution::NotFound => Err(CliError::invalid_command(token)),
+        }
+    }
+}
+
+/// Resolve a token against a menu's commands
+///
+/// Matching is case-insensitive and tried in order of strength: an exact
+/// name, then an alias, then a prefix of a name. The first kind that
+/// matches decides; if it matches more than one command, the token is
+/// ambiguous rather than resolving to whichever command comes first.
+///
+/// # Arguments
+/// * `token` - The command token typed by the user
+/// * `siblings` - The commands available in the current menu
+pub fn resolve(token: &str, siblings: Vec<Box<dyn Command>>) -> Resolution {
+    resolve_case(token, siblings, false)
+}
+
+/// Resolve a token against a menu's commands, optionally in exact case
+///
+/// With `case_sensitive`, names, aliases, and prefixes only match in the
+/// case they are written, so `SAVE` does not resolve to `save`. Sessions
+/// pass the `case_sensitive_commands` preference; see [`resolve`].
+///
+/// # Examples
+/// ```
+/// use sm_menu::commands::RootCommand;
+/// use sm_menu::core::resolve::{Resolution, resolve_case};
+/// use sm_menu::Command;
+///
+/// let resolution = resolve_case("FILE", RootCommand.subcommands(), true);
+/// assert!(matches!(resolution, Resolution::NotFound));
+/// ```
+pub fn resolve_case(
+    token: &str,
+    siblings: Vec<Box<dyn Command>>,
+    case_sensitive: bool,
+) -> Resolution {
+    let token = if case_sensitive {
+        token.to_string()
+    } else {
+        token.to_lowercase()
+    };
+    let mut siblings = siblings;
+
+    for kind in [MatchKind::Name, MatchKind::Alias, MatchKind::Prefix] {
+        let (mut found, rest): (Vec<_>, Vec<_>) = siblings
+            .into_iter()
+            .partition(|cmd| matches_as(cmd.as_ref(), &token, kind, case_sensitive));
+        match found.len() {
+            0 => siblings = rest,
+            1 => {
+                return Resolution::Found {
+                    command: found.remove(0),
+                    kind,
+                };
+            }
+            _ => {
+                return Resolution::Ambiguous(
+                    found.iter().map(|cmd| cmd.name().to_string()).collect(),
+                );
+            }
+        }
+    }
+
+    Resolution::NotFound
+}
+
+/// Suggest the command a mistyped token was probably meant to be
+///
+/// The suggestion is the visible command whose name is closest to the
+/// token by edit distance, as long as it is at most two edits away and
+/// closer than the token's own length. Ties go to the earlier command.
+///
+/// # Examples
+/// ```
+/// use sm_menu::commands::RootCommand;
+/// use sm_menu::core::resolve::suggest;
+/// use sm_menu::Command;
+///
+/// assert_eq!(suggest("flie", &RootCommand.subcommands()), Some("file"));
+/// assert_eq!(suggest("xyzzy", &RootCommand.subcommands()), None);
+/// ```
+pub fn suggest(token: &str, siblings: &[Box<dyn Command>]) -> Option<&'static str> {
+    let token = token.to_lowercase();
+    let limit = 2.min(token.chars().count().saturating_sub(1));
+    siblings
+        .iter()
+        .filter(|cmd| !cmd.hidden())
+        .map(|cmd| {
+            (
+                edit_distance(&token, &cmd.name().to_lowercase()),
+                cmd.name(),
+            )
+        })
+        .filter(|&(distance, _)| distance <= limit)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, name)| name)
+}
+
+/// Count the single-character insertions, deletions, and substitutions
+/// needed to turn one string into another
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Check if a token matches a command in the given way
+///
+/// The token is lowercase unless matching is case-sensitive.
+fn matches_as(cmd: &dyn Command, token: &str, kind: MatchKind, case_sensitive: bool) -> bool {
+    let fold = |text: &str| {
+        if case_sensitive {
+            text.to_string()
+        } else {
+            text.to_lowercase()
+        }
+    };
+    let name = fold(cmd.name());
+    match kind {
+        MatchKind::Name => name == token,
+        MatchKind::Alias => cmd.aliases().iter().any(|alias| fold(alias) == token),
+        MatchKind::Prefix => !token.is_empty() && name.starts_with(token),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command::CommandResult;
+
+    #[derive(Debug)]
+    struct TestCommand {
+        name: &'static str,
+        aliases: Vec<&'static str>,
+    }
+
+    impl Command for TestCommand {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn description(&self) -> &'static str {
+            "Test command"
+        }
+
+        fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+            Ok(CommandResult::success_silent())
+        }
+
+        fn aliases(&self) -> Vec<&'static str> {
+            self.aliases.clone()
+        }
+    }
+
+    fn menu() -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(TestCommand {
+                name: "save",
+                aliases: vec!["s"],
+            }),
+            Box::new(TestCommand {
+                name: "show",
+                aliases: vec!["sh"],
+            }),
+            Box::new(TestCommand {
+                name: "load",
+                aliases: vec!["l"],
+            }),
+        ]
+    }
+
+    fn resolved(resolution: Resolution) -> (&'static str, MatchKind) {
+        match resolution {
+            Resolution::Found { command, kind } => (command.name(), kind),
+            other => panic!("Expected a match, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_name() {
+        assert_eq!(resolved(resolve("save", menu())), ("save", MatchKind::Name));
+        assert_eq!(resolved(resolve("LOAD", menu())), ("load", MatchKind::Name));
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        assert_eq!(resolved(resolve("sh", menu())), ("show", MatchKind::Alias));
+        assert_eq!(resolved(resolve("s", menu())), ("save", MatchKind::Alias));
+    }
+
+    #[test]
+    fn test_resolve_prefix() {
+        assert_eq!(resolved(resolve("lo", menu())), ("load", MatchKind::Prefix));
+        assert_eq!(resolved(resolve("sa", menu())), ("save", MatchKind::Prefix));
+    }
+
+    #[test]
+    fn test_resolve_ambiguous_prefix() {
+        let siblings: Vec<Box<dyn Command>> = vec![
+            Box::new(TestCommand {
+                name: "save",
+                aliases: vec![],
+            }),
+            Box::new(TestCommand {
+                name: "show",
+                aliases: vec![],
+            }),
+        ];
+        match resolve("s", siblings) {
+            Resolution::Ambiguous(candidates) => assert_eq!(candidates, ["save", "show"]),
+            other => panic!("Expected Ambiguous, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_miss() {
+        assert!(matches!(resolve("quit", menu()), Resolution::NotFound));
+        assert!(matches!(
+            resolve("quit", menu()).into_command("quit"),
+            Err(CliError::InvalidCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_suggest_close_names() {
+        assert_eq!(suggest("sav", &menu()), Some("save"));
+        assert_eq!(suggest("LAOD", &menu()), Some("load"));
+        assert_eq!(suggest("shwo", &menu()), Some("show"));
+        assert_eq!(suggest("quit", &menu()), None);
+        // A single character is never close enough to suggest anything
+        assert_eq!(suggest("x", &menu()), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -3,40 +3,177 @@
 //! This module provides security functions to prevent common vulnerabilities
 //! such as path traversal attacks and terminal escape code injection.
 
-use crate::core::error::CliError;
 use crate::core::CliResult;
+use crate::core::error::CliError;
 use std::path::{Path, PathBuf};
 
 /// Maximum file size allowed for loading (100MB)
 pub const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
 
-/// Validate and sanitize a file path to prevent path traversal attacks
+/// How symbolic links are treated during path validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Follow symbolic links as long as the resolved target stays inside the root
+    #[default]
+    FollowWithinRoot,
+    /// Reject any path that is itself a symbolic link
+    Deny,
+}
+
+/// Security boundary applied to all file operations
+///
+/// The context carries the directory that file access is confined to along
+/// with the limits that apply inside it. It is built once at startup (from
+/// `--root <dir>` or the current working directory) and passed to the
+/// validation functions, so nothing in the security layer needs to read
+/// global process state.
+#[derive(Debug, Clone)]
+pub struct SecurityContext {
+    /// Canonical directory that all paths must resolve inside
+    root: PathBuf,
+    /// How symbolic links are handled
+    pub symlink_policy: SymlinkPolicy,
+    /// Maximum file size in bytes
+    pub max_size: u64,
+    /// Allowed file extensions (without the dot); `None` allows any extension
+    pub allowed_extensions: Option<Vec<String>>,
+}
+
+impl SecurityContext {
+    /// Create a security context confined to the given root directory
+    ///
+    /// # Arguments
+    /// * `root` - The directory to confine file operations to
+    ///
+    /// # Returns
+    /// * `Ok(SecurityContext)` - A context rooted at the canonical form of `root`
+    /// * `Err(CliError)` - If the root does not exist or is not a directory
+    pub fn new(root: impl AsRef<Path>) -> CliResult<Self> {
+        let root = root.as_ref();
+        let canonical = root.canonicalize().map_err(|e| {
+            CliError::invalid_input(&format!("Invalid root directory '{}': {e}", root.display()))
+        })?;
+
+        if !canonical.is_dir() {
+            return Err(CliError::invalid_input(&format!(
+                "Root is not a directory: {}",
+                root.display()
+            )));
+        }
+
+        Ok(Self::with_canonical_root(canonical))
+    }
+
+    /// Create a security context confined to the current working directory
+    pub fn from_cwd() -> CliResult<Self> {
+        let cwd = std::env::current_dir().map_err(|e| {
+            CliError::execution_error(&format!("Cannot determine working directory: {e}"))
+        })?;
+        Self::new(cwd)
+    }
+
+    /// Build a context from an already-canonical root with default limits
+    fn with_canonical_root(root: PathBuf) -> Self {
+        Self {
+            root,
+            symlink_policy: SymlinkPolicy::default(),
+            max_size: MAX_FILE_SIZE,
+            allowed_extensions: None,
+        }
+    }
+
+    /// Get the root directory file operations are confined to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Validate a file path against this context
+    ///
+    /// Convenience wrapper around [`validate_against_root`].
+    pub fn validate_path(&self, path_str: &str) -> CliResult<PathBuf> {
+        validate_against_root(path_str, self).inspect_err(|e| log_rejected(path_str, e))
+    }
+
+    /// Validate a path that is about to be written
+    ///
+    /// Convenience wrapper around [`validate_write_target`].
+    pub fn validate_write_path(&self, path_str: &str) -> CliResult<PathBuf> {
+        validate_write_target(path_str, self).inspect_err(|e| log_rejected(path_str, e))
+    }
+
+    /// Check a file size against this context's limit
+    pub fn validate_size(&self, size: u64) -> CliResult<()> {
+        if size > self.max_size {
+            Err(CliError::execution_error(&format!(
+                "File too large: {size} bytes (maximum: {} bytes)",
+                self.max_size
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check a path's extension against the allowlist (if one is configured)
+    pub fn validate_extension(&self, path: &Path) -> CliResult<()> {
+        let Some(allowed) = &self.allowed_extensions else {
+            return Ok(());
+        };
+
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+
+        if allowed
+            .iter()
+            .any(|ext| ext.eq_ignore_ascii_case(extension))
+        {
+            Ok(())
+        } else {
+            Err(CliError::permission_denied(&format!(
+                "File extension not allowed: '{extension}'"
+            )))
+        }
+    }
+}
+
+/// Log a path the security boundary refused
+///
+/// A file that does not exist is an ordinary failure, not a refusal.
+fn log_rejected(path_str: &str, error: &CliError) {
+    if !matches!(error, CliError::FileNotFound(_)) {
+        log_record!(Warn, "Rejected path '{path_str}': {error}");
+    }
+}
+
+impl Default for SecurityContext {
+    /// Confine to the current working directory, or "." if it cannot be resolved
+    fn default() -> Self {
+        Self::from_cwd().unwrap_or_else(|_| Self::with_canonical_root(PathBuf::from(".")))
+    }
+}
+
+/// Validate a file path against a security context
+///
+/// This is the core path check. It touches no global process state: relative
+/// paths are resolved against the context's root rather than the working
+/// directory, so it can be tested deterministically against any directory.
 ///
 /// # Arguments
 /// * `path_str` - The file path string to validate
+/// * `security` - The boundary and limits to validate against
 ///
 /// # Returns
 /// * `Ok(PathBuf)` - The canonical, validated path
-/// * `Err(CliError)` - If the path is invalid or traverses outside working directory
+/// * `Err(CliError)` - If the path is invalid or escapes the root
 ///
 /// # Security
 /// This function prevents:
 /// - Path traversal attacks using ".." components
-/// - Access to files outside the current working directory
-/// - Invalid or non-existent paths
-///
-/// # Examples
-/// ```
-/// use sm_menu::core::security::validate_file_path;
-///
-/// // Valid path
-/// let result = validate_file_path("data.txt");
-///
-/// // Invalid path (path traversal)
-/// let result = validate_file_path("../../../etc/passwd");
-/// assert!(result.is_err());
-/// ```
-pub fn validate_file_path(path_str: &str) -> CliResult<PathBuf> {
+/// - Absolute paths and symbolic links resolving outside the root
+/// - Symbolic links entirely when the policy is [`SymlinkPolicy::Deny`]
+/// - Extensions outside the configured allowlist
+pub fn validate_against_root(path_str: &str, security: &SecurityContext) -> CliResult<PathBuf> {
     // Check for empty path
     if path_str.trim().is_empty() {
         return Err(CliError::invalid_input("File path cannot be empty"));
@@ -53,23 +190,31 @@ pub fn validate_file_path(path_str: &str) -> CliResult<PathBuf> {
         }
     }
 
-    // Get current working directory
-    let cwd = std::env::current_dir()
-        .map_err(|e| CliError::execution_error(&format!("Cannot determine working directory: {}", e)))?;
-
     // Resolve the full path
     let full_path = if path.is_absolute() {
         path.to_path_buf()
     } else {
-        cwd.join(path)
+        security.root.join(path)
     };
 
+    if security.symlink_policy == SymlinkPolicy::Deny
+        && full_path
+            .symlink_metadata()
+            .is_ok_and(|meta| meta.file_type().is_symlink())
+    {
+        return Err(CliError::permission_denied(
+            "Symbolic links are not allowed",
+        ));
+    }
+
+    security.validate_extension(&full_path)?;
+
     // Canonicalize to resolve any symbolic links and normalize the path
     // Note: This requires the file to exist
     match full_path.canonicalize() {
         Ok(canonical_path) => {
-            // Ensure the canonical path is still within the working directory
-            if !canonical_path.starts_with(&cwd) {
+            // Ensure the canonical path is still within the root
+            if !canonical_path.starts_with(&security.root) {
                 return Err(CliError::permission_denied(
                     "Access outside working directory is not allowed",
                 ));
@@ -78,21 +223,117 @@ pub fn validate_file_path(path_str: &str) -> CliResult<PathBuf> {
         }
         Err(e) => {
             // File doesn't exist - validate the path would be safe if it did exist
-            // Check if the parent directory is within cwd
-            if let Some(parent) = full_path.parent() {
-                if !parent.starts_with(&cwd) {
-                    return Err(CliError::permission_denied(
-                        "Access outside working directory is not allowed",
-                    ));
-                }
+            // Check if the parent directory is within the root
+            if let Some(parent) = full_path.parent()
+                && !parent.starts_with(&security.root)
+            {
+                return Err(CliError::permission_denied(
+                    "Access outside working directory is not allowed",
+                ));
             }
 
             // Return the original error (file not found)
-            Err(CliError::file_not_found(&format!("{}: {}", path_str, e)))
+            Err(CliError::file_not_found(&format!("{path_str}: {e}")))
         }
     }
 }
 
+/// Validate a path that is about to be written against a security context
+///
+/// Unlike [`validate_against_root`], the target does not need to exist yet.
+/// An existing target is validated as usual; for a new file, its parent
+/// directory must exist and resolve inside the root, and the name must not
+/// be a symbolic link to a missing file.
+///
+/// # Arguments
+/// * `path_str` - The file path string to validate
+/// * `security` - The boundary and limits to validate against
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The validated path, canonical up to the file name
+/// * `Err(CliError)` - If the path is invalid, escapes the root, or its
+///   parent directory does not exist
+pub fn validate_write_target(path_str: &str, security: &SecurityContext) -> CliResult<PathBuf> {
+    match validate_against_root(path_str, security) {
+        Err(CliError::FileNotFound(_)) => {}
+        result => return result,
+    }
+
+    let path = Path::new(path_str);
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| CliError::invalid_input(&format!("Not a file path: {path_str}")))?;
+
+    // Resolve the parent of the new file; a bare file name lives in the root
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            if parent.is_absolute() {
+                parent.to_path_buf()
+            } else {
+                security.root.join(parent)
+            }
+        }
+        _ => security.root.clone(),
+    };
+    let canonical_parent = parent
+        .canonicalize()
+        .map_err(|e| CliError::file_not_found(&format!("{}: {e}", parent.display())))?;
+
+    if !canonical_parent.starts_with(&security.root) {
+        return Err(CliError::permission_denied(
+            "Access outside working directory is not allowed",
+        ));
+    }
+
+    // A dangling link does not resolve, so it looks like a new file; a
+    // write would follow it to wherever it points
+    let target = canonical_parent.join(file_name);
+    if target
+        .symlink_metadata()
+        .is_ok_and(|meta| meta.file_type().is_symlink())
+    {
+        return Err(CliError::permission_denied(
+            "Writing through a symbolic link to a missing file is not allowed",
+        ));
+    }
+
+    Ok(target)
+}
+
+/// Validate and sanitize a file path to prevent path traversal attacks
+///
+/// This is a thin wrapper that builds a [`SecurityContext`] rooted at the
+/// current working directory and calls [`validate_against_root`]. Use that
+/// function directly to confine paths to a different root.
+///
+/// # Arguments
+/// * `path_str` - The file path string to validate
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The canonical, validated path
+/// * `Err(CliError)` - If the path is invalid or traverses outside working directory
+///
+/// # Security
+/// This function prevents:
+/// - Path traversal attacks using ".." components
+/// - Access to files outside the current working directory
+/// - Invalid or non-existent paths
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::security::validate_file_path;
+///
+/// // Valid path
+/// let result = validate_file_path("data.txt");
+///
+/// // Invalid path (path traversal)
+/// let result = validate_file_path("../../../etc/passwd");
+/// assert!(result.is_err());
+/// ```
+pub fn validate_file_path(path_str: &str) -> CliResult<PathBuf> {
+    validate_against_root(path_str, &SecurityContext::from_cwd()?)
+}
+
 /// Sanitize a string for safe display in the terminal
 ///
 /// Removes control characters (except newline and tab) that could
@@ -136,17 +377,74 @@ pub fn sanitize_for_display(s: &str) -> String {
 pub fn validate_file_size(size: u64) -> CliResult<()> {
     if size > MAX_FILE_SIZE {
         Err(CliError::execution_error(&format!(
-            "File too large: {} bytes (maximum: {} bytes)",
-            size, MAX_FILE_SIZE
+            "File too large: {size} bytes (maximum: {MAX_FILE_SIZE} bytes)"
         )))
     } else {
         Ok(())
     }
 }
 
+/// Kind of file contents, as far as loading into the edit buffer goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// UTF-8 text
+    Text,
+    /// Anything else, such as images, archives, or executables
+    Binary,
+}
+
+/// Number of leading bytes checked by [`detect_format`]
+pub const FORMAT_SNIFF_LEN: usize = 8192;
+
+/// Detect whether file contents are text or binary
+///
+/// Contents are binary if their leading bytes contain a NUL byte or the
+/// whole is not valid UTF-8. Only data that loads as text is left, but
+/// text can still carry escape codes, so it must still be sanitized
+/// before display.
+///
+/// # Arguments
+/// * `bytes` - The file contents
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::security::{FileFormat, detect_format};
+///
+/// assert_eq!(detect_format(b"hello\n"), FileFormat::Text);
+/// assert_eq!(detect_format(b"\x7fELF\x02\x01\0\0"), FileFormat::Binary);
+/// ```
+pub fn detect_format(bytes: &[u8]) -> FileFormat {
+    let head = &bytes[..bytes.len().min(FORMAT_SNIFF_LEN)];
+    if head.contains(&0) || std::str::from_utf8(bytes).is_err() {
+        FileFormat::Binary
+    } else {
+        FileFormat::Text
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
+
+    /// Temporary directory removed when dropped
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("sm-menu-security-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempRoot(dir)
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
 
     #[test]
     fn test_sanitize_for_display() {
@@ -165,6 +463,17 @@ mod tests {
         assert!(output.contains("World"));
     }
 
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(b""), FileFormat::Text);
+        assert_eq!(detect_format("caf\u{e9}\r\n".as_bytes()), FileFormat::Text);
+        // Escape codes are text; display sanitizes them
+        assert_eq!(detect_format(b"\x1b[31mred\n"), FileFormat::Text);
+
+        assert_eq!(detect_format(b"PNG\0\0\0"), FileFormat::Binary);
+        assert_eq!(detect_format(&[0xff, 0xfe, b'a']), FileFormat::Binary);
+    }
+
     #[test]
     fn test_validate_file_size() {
         // Small files should be fine
@@ -193,4 +502,204 @@ mod tests {
         assert!(validate_file_path("../../etc/passwd").is_err());
         assert!(validate_file_path("subdir/../../../file.txt").is_err());
     }
+
+    #[test]
+    fn test_security_context_in_root_allowed() {
+        let temp = TempRoot::new("in-root");
+        fs::write(temp.0.join("data.txt"), "hello").unwrap();
+
+        let security = SecurityContext::new(&temp.0).unwrap();
+        let path = security.validate_path("data.txt").unwrap();
+        assert!(path.starts_with(security.root()));
+        assert!(path.ends_with("data.txt"));
+    }
+
+    #[test]
+    fn test_security_context_out_of_root_denied() {
+        let temp = TempRoot::new("out-of-root");
+        let root = temp.0.join("root");
+        let outside = temp.0.join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+
+        let security = SecurityContext::new(&root).unwrap();
+        let target = outside.join("secret.txt");
+        let result = security.validate_path(target.to_str().unwrap());
+        assert!(matches!(result, Err(CliError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn test_security_context_invalid_root() {
+        let temp = TempRoot::new("invalid-root");
+        fs::write(temp.0.join("file.txt"), "not a dir").unwrap();
+
+        assert!(SecurityContext::new(temp.0.join("missing")).is_err());
+        assert!(SecurityContext::new(temp.0.join("file.txt")).is_err());
+    }
+
+    #[test]
+    fn test_security_context_limits() {
+        let temp = TempRoot::new("limits");
+        fs::write(temp.0.join("notes.txt"), "notes").unwrap();
+        fs::write(temp.0.join("image.bin"), "bytes").unwrap();
+
+        let mut security = SecurityContext::new(&temp.0).unwrap();
+        security.max_size = 10;
+        assert!(security.validate_size(10).is_ok());
+        assert!(security.validate_size(11).is_err());
+
+        security.allowed_extensions = Some(vec!["txt".to_string()]);
+        assert!(security.validate_path("notes.txt").is_ok());
+        assert!(matches!(
+            security.validate_path("image.bin"),
+            Err(CliError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_against_root_traversal() {
+        let temp = TempRoot::new("against-traversal");
+        fs::create_dir_all(temp.0.join("sub")).unwrap();
+        fs::write(temp.0.join("sub/file.txt"), "data").unwrap();
+        let security = SecurityContext::new(&temp.0).unwrap();
+
+        assert!(validate_against_root("sub/file.txt", &security).is_ok());
+        assert!(matches!(
+            validate_against_root("sub/../sub/file.txt", &security),
+            Err(CliError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            validate_against_root("../file.txt", &security),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_against_root_absolute_paths() {
+        let temp = TempRoot::new("against-absolute");
+        let root = temp.0.join("root");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("inside.txt"), "inside").unwrap();
+        fs::write(temp.0.join("outside.txt"), "outside").unwrap();
+        let security = SecurityContext::new(&root).unwrap();
+
+        let inside = security.root().join("inside.txt");
+        assert_eq!(
+            validate_against_root(inside.to_str().unwrap(), &security).unwrap(),
+            inside
+        );
+
+        let outside = temp.0.join("outside.txt");
+        assert!(matches!(
+            validate_against_root(outside.to_str().unwrap(), &security),
+            Err(CliError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_against_root_missing_file() {
+        let temp = TempRoot::new("against-missing");
+        let security = SecurityContext::new(&temp.0).unwrap();
+
+        assert!(matches!(
+            validate_against_root("missing.txt", &security),
+            Err(CliError::FileNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_against_root_symlink_escape() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempRoot::new("against-symlink");
+        let root = temp.0.join("root");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(temp.0.join("secret.txt"), "secret").unwrap();
+        fs::write(root.join("real.txt"), "real").unwrap();
+        symlink(temp.0.join("secret.txt"), root.join("escape.txt")).unwrap();
+        symlink(root.join("real.txt"), root.join("alias.txt")).unwrap();
+
+        let mut security = SecurityContext::new(&root).unwrap();
+
+        // Links resolving outside the root are denied, links inside are followed
+        assert!(matches!(
+            validate_against_root("escape.txt", &security),
+            Err(CliError::PermissionDenied(_))
+        ));
+        assert!(
+            validate_against_root("alias.txt", &security)
+                .unwrap()
+                .ends_with("real.txt")
+        );
+
+        // With the deny policy even in-root links are rejected
+        security.symlink_policy = SymlinkPolicy::Deny;
+        assert!(matches!(
+            validate_against_root("alias.txt", &security),
+            Err(CliError::PermissionDenied(_))
+        ));
+        assert!(validate_against_root("real.txt", &security).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_write_target_dangling_symlink() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempRoot::new("write-dangling");
+        let root = temp.0.join("root");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(temp.0.join("outside")).unwrap();
+        symlink(temp.0.join("outside/pwned.txt"), root.join("evil.txt")).unwrap();
+        symlink(root.join("later.txt"), root.join("inside.txt")).unwrap();
+
+        let security = SecurityContext::new(&root).unwrap();
+        for name in ["evil.txt", "inside.txt"] {
+            assert!(
+                matches!(
+                    security.validate_write_path(name),
+                    Err(CliError::PermissionDenied(_))
+                ),
+                "{name}"
+            );
+        }
+        assert!(!temp.0.join("outside/pwned.txt").exists());
+    }
+
+    #[test]
+    fn test_validate_write_target() {
+        let temp = TempRoot::new("write-target");
+        let security = SecurityContext::new(&temp.0).unwrap();
+        fs::create_dir(temp.0.join("sub")).unwrap();
+        fs::write(temp.0.join("existing.txt"), "data").unwrap();
+
+        // Existing and new files inside the root are allowed
+        let root = temp.0.canonicalize().unwrap();
+        assert_eq!(
+            security.validate_write_path("existing.txt").unwrap(),
+            root.join("existing.txt")
+        );
+        assert_eq!(
+            security.validate_write_path("new.txt").unwrap(),
+            root.join("new.txt")
+        );
+        assert_eq!(
+            security.validate_write_path("sub/new.txt").unwrap(),
+            root.join("sub").join("new.txt")
+        );
+
+        // Missing parents, traversal, and paths outside the root are rejected
+        assert!(matches!(
+            security.validate_write_path("missing/new.txt"),
+            Err(CliError::FileNotFound(_))
+        ));
+        assert!(security.validate_write_path("../new.txt").is_err());
+        let outside = std::env::temp_dir().join("sm-menu-outside-new.txt");
+        assert!(matches!(
+            security.validate_write_path(&outside.to_string_lossy()),
+            Err(CliError::PermissionDenied(_))
+        ));
+    }
 }
@@ -0,0 +1,396 @@
+//! Snapshots of session state for export.
+//!
+//! A [`SessionSnapshot`] captures what a session has built up (the edit
+//! buffer, the axes of each menu, and the preferences) and is written out
+//! by a [`SnapshotFormat`]. JSON snapshots can be read back, so tools can
+//! round-trip them.
+
+use crate::core::axes::{Axis, AxisStore};
+use crate::core::context::{CliContext, CliPreferences};
+use crate::core::error::{CliError, CliResult};
+use crate::core::json::{JsonValue, quote};
+use std::collections::BTreeMap;
+
+/// Session state at one point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSnapshot {
+    /// File the buffer was loaded from or saved to, if any
+    pub filename: Option<String>,
+    /// Lines of the edit buffer
+    pub lines: Vec<String>,
+    /// Axes of each menu that has any, keyed by menu name
+    pub axes: BTreeMap<String, AxisStore>,
+    /// Session preferences
+    pub preferences: CliPreferences,
+}
+
+impl SessionSnapshot {
+    /// Capture the state of a session
+    pub fn capture(context: &CliContext) -> Self {
+        SessionSnapshot {
+            filename: context.buffer().filename().map(str::to_string),
+            lines: context.buffer().lines().to_vec(),
+            axes: context
+                .axes_by_menu()
+                .filter(|(_, store)| !store.is_empty())
+                .map(|(menu, store)| (menu.to_string(), store.clone()))
+                .collect(),
+            preferences: context.preferences().clone(),
+        }
+    }
+
+    /// Convert the snapshot to a JSON value
+    ///
+    /// Unset axis bounds are left out, and preferences hold the
+    /// configuration file settings.
+    pub fn to_json(&self) -> JsonValue {
+        let filename = self
+            .filename
+            .as_deref()
+            .map_or(JsonValue::Null, JsonValue::string);
+        let lines = self.lines.iter().map(|line| JsonValue::string(line));
+        let axes = self.axes.iter().map(|(menu, store)| {
+            let store = store
+                .iter()
+                .map(|(name, axis)| (name.to_string(), axis_to_json(axis)));
+            (menu.clone(), JsonValue::Object(store.collect()))
+        });
+        // Setting values are TOML booleans, integers, and simple strings,
+        // which are also JSON
+        let preferences = self.preferences.settings().into_iter().map(|(key, value)| {
+            let value = JsonValue::parse(&value).expect("settings are valid JSON");
+            (key.to_string(), value)
+        });
+
+        JsonValue::Object(vec![
+            ("filename".to_string(), filename),
+            ("lines".to_string(), JsonValue::Array(lines.collect())),
+            ("axes".to_string(), JsonValue::Object(axes.collect())),
+            (
+                "preferences".to_string(),
+                JsonValue::Object(preferences.collect()),
+            ),
+        ])
+    }
+
+    /// Rebuild a snapshot from the JSON written by [`SessionSnapshot::to_json`]
+    ///
+    /// # Returns
+    /// * `Ok(SessionSnapshot)` - The snapshot
+    /// * `Err(CliError)` - `InvalidFileFormat` if a member is missing or has
+    ///   the wrong type, or `InvalidInput` if an axis is invalid
+    pub fn from_json(value: &JsonValue) -> CliResult<Self> {
+        let invalid = |msg: &str| CliError::InvalidFileFormat(format!("snapshot: {msg}"));
+
+        let filename = match value.get("filename") {
+            None | Some(JsonValue::Null) => None,
+            Some(JsonValue::String(name)) => Some(name.clone()),
+            Some(_) => return Err(invalid("'filename' must be a string or null")),
+        };
+
+        let lines = match value.get("lines") {
+            Some(JsonValue::Array(items)) => items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid("'lines' must hold only strings"))?,
+            _ => return Err(invalid("'lines' must be an array")),
+        };
+
+        let mut axes = BTreeMap::new();
+        match value.get("axes") {
+            None => {}
+            Some(JsonValue::Object(menus)) => {
+                for (menu, store_value) in menus {
+                    let JsonValue::Object(entries) = store_value else {
+                        return Err(invalid(&format!("axes of '{menu}' must be an object")));
+                    };
+                    let mut store = AxisStore::new();
+                    for (name, axis) in entries {
+                        store.set(
+                            name,
+                            axis_from_json(axis).ok_or_else(|| {
+                                invalid(&format!("axis '{name}' must have numeric min and max"))
+                            })?,
+                        )?;
+                    }
+                    axes.insert(menu.clone(), store);
+                }
+            }
+            Some(_) => return Err(invalid("'axes' must be an object")),
+        }
+
+        // Preferences go through the configuration parser, so they are
+        // checked exactly like a config file
+        let preferences = match value.get("preferences") {
+            None => CliPreferences::default(),
+            Some(JsonValue::Object(settings)) => {
+                let toml: String = settings
+                    .iter()
+                    .map(|(key, value)| format!("{key} = {value}\n"))
+                    .collect();
+                CliPreferences::from_toml(&toml)?
+            }
+            Some(_) => return Err(invalid("'preferences' must be an object")),
+        };
+
+        Ok(SessionSnapshot {
+            filename,
+            lines,
+            axes,
+            preferences,
+        })
+    }
+}
+
+fn axis_to_json(axis: &Axis) -> JsonValue {
+    let bounds = [("min", axis.min), ("max", axis.max)];
+    JsonValue::Object(
+        bounds
+            .into_iter()
+            .filter_map(|(key, bound)| Some((key.to_string(), JsonValue::Number(bound?))))
+            .collect(),
+    )
+}
+
+fn axis_from_json(value: &JsonValue) -> Option<Axis> {
+    let bound = |key: &str| match value.get(key) {
+        None => Some(None),
+        Some(bound) => bound.as_f64().map(Some),
+    };
+    match value {
+        JsonValue::Object(_) => Some(Axis {
+            min: bound("min")?,
+            max: bound("max")?,
+        }),
+        _ => None,
+    }
+}
+
+/// Text format a snapshot can be written in
+pub trait SnapshotFormat {
+    /// Name of the format, as typed by the user
+    fn name(&self) -> &'static str;
+
+    /// Write a snapshot in this format
+    fn serialize(&self, snapshot: &SessionSnapshot) -> String;
+}
+
+/// Indented JSON, readable by [`SessionSnapshot::from_json`]
+#[derive(Debug)]
+pub struct JsonFormat;
+
+impl SnapshotFormat for JsonFormat {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn serialize(&self, snapshot: &SessionSnapshot) -> String {
+        format!("{}\n", snapshot.to_json().pretty())
+    }
+}
+
+/// TOML, with preferences and each axis in its own table
+#[derive(Debug)]
+pub struct TomlFormat;
+
+impl SnapshotFormat for TomlFormat {
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+
+    fn serialize(&self, snapshot: &SessionSnapshot) -> String {
+        let mut out = String::new();
+        if let Some(filename) = &snapshot.filename {
+            out.push_str(&format!("filename = {}\n", quote(filename)));
+        }
+        let lines: Vec<String> = snapshot.lines.iter().map(|line| quote(line)).collect();
+        out.push_str(&format!("lines = [{}]\n", lines.join(", ")));
+
+        out.push_str("\n[preferences]\n");
+        for (key, value) in snapshot.preferences.settings() {
+            out.push_str(&format!("{key} = {value}\n"));
+        }
+
+        for (menu, store) in &snapshot.axes {
+            for (name, axis) in store.iter() {
+                out.push_str(&format!("\n[axes.{}.{}]\n", toml_key(menu), toml_key(name)));
+                for (key, bound) in [("min", axis.min), ("max", axis.max)] {
+                    if let Some(bound) = bound {
+                        out.push_str(&format!("{key} = {}\n", toml_float(bound)));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// YAML, in block style with double-quoted strings
+#[derive(Debug)]
+pub struct YamlFormat;
+
+impl SnapshotFormat for YamlFormat {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn serialize(&self, snapshot: &SessionSnapshot) -> String {
+        let mut out = String::new();
+        match &snapshot.filename {
+            Some(filename) => out.push_str(&format!("filename: {}\n", quote(filename))),
+            None => out.push_str("filename: null\n"),
+        }
+
+        if snapshot.lines.is_empty() {
+            out.push_str("lines: []\n");
+        } else {
+            out.push_str("lines:\n");
+            for line in &snapshot.lines {
+                out.push_str(&format!("  - {}\n", quote(line)));
+            }
+        }
+
+        if snapshot.axes.is_empty() {
+            out.push_str("axes: {}\n");
+        } else {
+            out.push_str("axes:\n");
+            for (menu, store) in &snapshot.axes {
+                out.push_str(&format!("  {}:\n", quote(menu)));
+                for (name, axis) in store.iter() {
+                    out.push_str(&format!("    {}:\n", quote(name)));
+                    for (key, bound) in [("min", axis.min), ("max", axis.max)] {
+                        let bound = bound.map_or("null".to_string(), toml_float);
+                        out.push_str(&format!("      {key}: {bound}\n"));
+                    }
+                }
+            }
+        }
+
+        out.push_str("preferences:\n");
+        for (key, value) in snapshot.preferences.settings() {
+            out.push_str(&format!("  {key}: {value}\n"));
+        }
+        out
+    }
+}
+
+/// Get every snapshot format, in the order they are listed to users
+pub fn snapshot_formats() -> Vec<Box<dyn SnapshotFormat>> {
+    vec![
+        Box::new(JsonFormat),
+        Box::new(TomlFormat),
+        Box::new(YamlFormat),
+    ]
+}
+
+/// Find a snapshot format by name, ignoring case
+///
+/// # Returns
+/// * `Ok(format)` - The named format
+/// * `Err(CliError)` - `InvalidInput` listing the known formats
+pub fn find_format(name: &str) -> CliResult<Box<dyn SnapshotFormat>> {
+    let mut formats = snapshot_formats();
+    let known: Vec<&str> = formats.iter().map(|format| format.name()).collect();
+    let message = format!("Unknown format '{name}' (expected {})", known.join(", "));
+    formats
+        .iter()
+        .position(|format| format.name().eq_ignore_ascii_case(name))
+        .map(|index| formats.swap_remove(index))
+        .ok_or_else(|| CliError::invalid_input(&message))
+}
+
+/// Quote a TOML key unless it is a bare key
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare { key.to_string() } else { quote(key) }
+}
+
+/// Format a float so TOML and YAML read it back as a float
+fn toml_float(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains(['.', 'e', 'E']) {
+        text
+    } else {
+        format!("{text}.0")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated() -> SessionSnapshot {
+        let mut store = AxisStore::new();
+        store
+            .set(
+                "x",
+                Axis {
+                    min: Some(0.0),
+                    max: Some(10.0),
+                },
+            )
+            .unwrap();
+        store
+            .set(
+                "y axis",
+                Axis {
+                    min: None,
+                    max: Some(-2.5),
+                },
+            )
+            .unwrap();
+        SessionSnapshot {
+            filename: Some("notes.txt".to_string()),
+            lines: vec!["one".to_string(), "say \"hi\"".to_string()],
+            axes: BTreeMap::from([("edit".to_string(), store)]),
+            preferences: CliPreferences {
+                max_list_items: 7,
+                ..CliPreferences::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let snapshot = populated();
+        let text = JsonFormat.serialize(&snapshot);
+        let parsed = SessionSnapshot::from_json(&JsonValue::parse(&text).unwrap()).unwrap();
+        assert_eq!(parsed, snapshot);
+
+        let broken = JsonValue::parse(r#"{"lines": [1]}"#).unwrap();
+        assert!(matches!(
+            SessionSnapshot::from_json(&broken),
+            Err(CliError::InvalidFileFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_toml_and_yaml_output() {
+        let snapshot = populated();
+        let toml = TomlFormat.serialize(&snapshot);
+        assert!(
+            toml.starts_with("filename = \"notes.txt\"\nlines = [\"one\", \"say \\\"hi\\\"\"]\n")
+        );
+        assert!(toml.contains("\n[preferences]\ncolored_prompt = true\n"));
+        assert!(toml.contains("\n[axes.edit.x]\nmin = 0.0\nmax = 10.0\n"));
+        assert!(toml.contains("\n[axes.edit.\"y axis\"]\nmax = -2.5\n"));
+
+        let yaml = YamlFormat.serialize(&snapshot);
+        assert!(yaml.contains("lines:\n  - \"one\"\n"));
+        assert!(yaml.contains("  \"edit\":\n    \"x\":\n      min: 0.0\n      max: 10.0\n"));
+        assert!(yaml.contains("\n  max_list_items: 7\n"));
+    }
+
+    #[test]
+    fn test_find_format() {
+        assert_eq!(find_format("JSON").unwrap().name(), "json");
+        assert!(matches!(
+            find_format("xml"),
+            Err(CliError::InvalidInput(msg)) if msg.contains("json, toml, yaml")
+        ));
+    }
+}
@@ -0,0 +1,450 @@
+//! Input tokenizing for the CLI application.
+//!
+//! This module splits an input line into the command name and its
+//! arguments. Whitespace separates tokens, and a pair of double or single
+//! quotes opened at the start of a token groups text containing whitespace
+//! into one token. A quote inside a token is literal, as in a shell, so
+//! names like `say"hi".txt` need no escaping. Every line is
+//! tokenized and then normalized the same way before dispatch, so commands
+//! never see empty or padded arguments. A trailing `> file` or `>> file`
+//! is split off as an output redirection.
+
+use crate::core::error::{CliError, CliResult};
+
+/// Split an input line into tokens
+///
+/// A quote only opens a group at the start of a token, or right after a
+/// `>` or `>>` redirection operator; anywhere else it is a literal
+/// character. The grouping quotes are not part of the token, and text
+/// right after the closing quote continues it, so `"my notes".txt` is
+/// `my notes.txt`. Inside a pair of one kind of quote, the other kind is
+/// literal. Outside quotes, whitespace
+/// after a backslash does not end the token; both are kept, for commands
+/// taking paths to unescape with [`pasted_path`].
+///
+/// # Arguments
+/// * `input` - The input line
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The tokens, before normalization
+/// * `Err(CliError)` - If a quote is not closed
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::tokenizer::tokenize;
+///
+/// let tokens = tokenize(r#"load "my notes.txt""#).unwrap();
+/// assert_eq!(tokens, ["load", "my notes.txt"]);
+/// ```
+pub fn tokenize(input: &str) -> CliResult<Vec<String>> {
+    tokenize_quoted(input).map(|tokens| tokens.into_iter().map(|token| token.text).collect())
+}
+
+/// Token of an input line, knowing which part of it was typed unquoted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Token {
+    /// Text of the token, without its grouping quotes
+    pub text: String,
+    /// Length in bytes of the start of `text` that was outside quotes
+    pub unquoted_len: usize,
+}
+
+impl Token {
+    /// Get the start of the token that was outside quotes
+    pub fn unquoted_prefix(&self) -> &str {
+        &self.text[..self.unquoted_len]
+    }
+
+    /// Trim the token as [`normalize_tokens`] does, or drop it if empty
+    fn normalized(self) -> Option<Token> {
+        let trimmed = self.text.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let leading = self.text.len() - self.text.trim_start().len();
+        let unquoted_len = self.unquoted_len.saturating_sub(leading).min(trimmed.len());
+        Some(Token {
+            text: trimmed.to_string(),
+            unquoted_len,
+        })
+    }
+}
+
+/// Split an input line into tokens as [`tokenize`] does, recording for each
+/// one how much of its start was typed outside quotes
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::tokenizer::tokenize_quoted;
+///
+/// let tokens = tokenize_quoted(r#">"my log.txt" ">""#).unwrap();
+/// assert_eq!(tokens[0].unquoted_prefix(), ">");
+/// assert_eq!(tokens[1].unquoted_prefix(), "");
+/// ```
+pub fn tokenize_quoted(input: &str) -> CliResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    // Whether the current token has started, so `""` still yields a token
+    let mut in_token = false;
+    // Length of the current token when its first quote opened
+    let mut quoted_from: Option<usize> = None;
+    let mut quote: Option<char> = None;
+    // Whether the last character was a backslash that escapes the next one
+    let mut escaping = false;
+
+    let mut finish = |text: String, quoted_from: Option<usize>| {
+        let unquoted_len = quoted_from.unwrap_or(text.len());
+        tokens.push(Token { text, unquoted_len });
+    };
+    for c in input.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, c) if c.is_whitespace() && escaping => current.push(c),
+            // A redirection operator is its own token in a shell, so a
+            // quote after it starts the target
+            (None, '"' | '\'') if !in_token || current == ">" || current == ">>" => {
+                quote = Some(c);
+                quoted_from.get_or_insert(current.len());
+                in_token = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_token {
+                    finish(std::mem::take(&mut current), quoted_from.take());
+                    in_token = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+        escaping = quote.is_none() && c == '\\' && !escaping;
+    }
+
+    if let Some(open) = quote {
+        return Err(CliError::invalid_input(&format!(
+            "Unterminated {open} quote in input"
+        )));
+    }
+    if in_token {
+        finish(current, quoted_from);
+    }
+    Ok(tokens)
+}
+
+/// Trim each token and drop the tokens that are empty after trimming
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::tokenizer::normalize_tokens;
+///
+/// let tokens = vec!["load".to_string(), " file.txt ".to_string(), String::new()];
+/// assert_eq!(normalize_tokens(tokens), ["load", "file.txt"]);
+/// ```
+pub fn normalize_tokens(tokens: Vec<String>) -> Vec<String> {
+    tokens
+        .into_iter()
+        .filter_map(|token| {
+            let trimmed = token.trim();
+            match trimmed.len() {
+                0 => None,
+                len if len == token.len() => Some(token),
+                _ => Some(trimmed.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Tokenize an input line and normalize the tokens
+///
+/// This is the step applied to every line before dispatch.
+pub fn split_input(input: &str) -> CliResult<Vec<String>> {
+    tokenize(input).map(normalize_tokens)
+}
+
+/// Undo the quoting a terminal adds to a pasted or dropped file path
+///
+/// A surrounding pair of matching quotes that survived tokenizing, as in
+/// `'/home/me/my file.txt'`, is removed, and each `\ ` becomes a space.
+/// Quotes that do not wrap the whole path are left alone. Returns `None`
+/// when there is nothing to undo, so the caller can prefer the path as
+/// typed when a file by that name exists.
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::tokenizer::pasted_path;
+///
+/// assert_eq!(pasted_path("'my file.txt'").as_deref(), Some("my file.txt"));
+/// assert_eq!(pasted_path(r"my\ file.txt").as_deref(), Some("my file.txt"));
+/// assert_eq!(pasted_path("it's.txt"), None);
+/// ```
+pub fn pasted_path(token: &str) -> Option<String> {
+    let unquoted = ['"', '\'']
+        .into_iter()
+        .find_map(|q| {
+            token
+                .strip_prefix(q)
+                .and_then(|rest| rest.strip_suffix(q))
+                .filter(|inner| !inner.contains(q))
+        })
+        .unwrap_or(token);
+    let path = unquoted.replace("\\ ", " ");
+    (path != token).then_some(path)
+}
+
+/// Output redirection split off the end of an input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// File the command's output goes to
+    pub target: String,
+    /// Whether output is added to the end of the file (`>>`) instead of
+    /// replacing it (`>`)
+    pub append: bool,
+}
+
+/// Split a trailing `> file` or `>> file` off the tokens, normalizing them
+///
+/// The operator may be separated from the file or attached to it, as in
+/// `>out.txt`. Only an operator typed outside quotes counts, so `">"` and
+/// `">quoted line"` are ordinary words, as in a shell.
+///
+/// # Returns
+/// * `Ok((tokens, redirect))` - The remaining tokens and the redirection, if any
+/// * `Err(CliError)` - If the line ends with an operator but no file
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::tokenizer::{Redirect, split_redirect, tokenize_quoted};
+///
+/// let tokens = tokenize_quoted("vers >> log.txt").unwrap();
+/// let (tokens, redirect) = split_redirect(tokens).unwrap();
+/// assert_eq!(tokens, ["vers"]);
+/// assert_eq!(redirect, Some(Redirect { target: "log.txt".to_string(), append: true }));
+///
+/// let (tokens, redirect) = split_redirect(tokenize_quoted(r#"echo ">""#).unwrap()).unwrap();
+/// assert_eq!((tokens, redirect), (vec!["echo".to_string(), ">".to_string()], None));
+/// ```
+pub fn split_redirect(tokens: Vec<Token>) -> CliResult<(Vec<String>, Option<Redirect>)> {
+    // The operator at the start of a token: whether it appends, and its length
+    let operator = |token: &Token| {
+        let prefix = token.unquoted_prefix();
+        if prefix.starts_with(">>") {
+            Some((true, 2))
+        } else if prefix.starts_with('>') {
+            Some((false, 1))
+        } else {
+            None
+        }
+    };
+    // Whether a token is an operator and nothing else
+    let bare_operator = |token: &Token| operator(token).filter(|(_, len)| *len == token.text.len());
+
+    let mut tokens: Vec<Token> = tokens.into_iter().filter_map(Token::normalized).collect();
+    let Some(last) = tokens.last() else {
+        return Ok((Vec::new(), None));
+    };
+    if bare_operator(last).is_some() {
+        return Err(CliError::invalid_input(&format!(
+            "No file to redirect to after '{}'",
+            last.text
+        )));
+    }
+
+    let count = tokens.len();
+    let redirect = if let Some((append, _)) = count
+        .checked_sub(2)
+        .and_then(|index| bare_operator(&tokens[index]))
+    {
+        let target = tokens.pop().unwrap_or_default().text;
+        tokens.pop();
+        Redirect { target, append }
+    } else if let Some((append, len)) = operator(last) {
+        let target = last.text[len..].to_string();
+        tokens.pop();
+        Redirect { target, append }
+    } else {
+        return Ok((tokens.into_iter().map(|token| token.text).collect(), None));
+    };
+    let tokens = tokens.into_iter().map(|token| token.text).collect();
+    Ok((tokens, Some(redirect)))
+}
+
+/// Join tokens into an input line that tokenizes back to the same tokens
+///
+/// Tokens containing whitespace, starting with a quote or a `>`, or ending
+/// in a backslash are quoted, so none is taken for a redirection. Quotes later in a token are literal, so such a
+/// token is written as it is. A token needing quotes that contains both
+/// kinds of quote cannot be written back exactly.
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::tokenizer::{join_tokens, tokenize};
+///
+/// let tokens = vec!["append".to_string(), "two words".to_string()];
+/// assert_eq!(join_tokens(&tokens), r#"append "two words""#);
+/// assert_eq!(tokenize(&join_tokens(&tokens)).unwrap(), tokens);
+/// ```
+pub fn join_tokens(tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .map(|token| {
+            // A trailing backslash would escape the space joining the next token
+            let plain = !token.is_empty()
+                && !token.ends_with('\\')
+                && !token.starts_with(['"', '\'', '>'])
+                && !token.chars().any(char::is_whitespace);
+            if plain {
+                token.clone()
+            } else if token.contains('"') {
+                format!("'{token}'")
+            } else {
+                format!("\"{token}\"")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_whitespace_and_quotes() {
+        assert_eq!(
+            tokenize("  load   file.txt  ").unwrap(),
+            ["load", "file.txt"]
+        );
+        assert_eq!(
+            tokenize(r#"append "two  words" 'it''s' "say 'hi'""#).unwrap(),
+            ["append", "two  words", "it's'", "say 'hi'"]
+        );
+        assert_eq!(tokenize(r#"load """#).unwrap(), ["load", ""]);
+        assert!(tokenize("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_quotes_only_group_at_token_start() {
+        // An embedded quote is literal, as in a shell
+        assert_eq!(
+            tokenize(r#"load say"hi".txt"#).unwrap(),
+            ["load", r#"say"hi".txt"#]
+        );
+        assert_eq!(tokenize(r#"a"b c"d"#).unwrap(), [r#"a"b"#, r#"c"d"#]);
+        assert_eq!(tokenize("load it's.txt").unwrap(), ["load", "it's.txt"]);
+
+        // A quoted start is grouped and the rest of the token follows it
+        assert_eq!(
+            tokenize(r#"load "quoted name".txt"#).unwrap(),
+            ["load", "quoted name.txt"]
+        );
+        assert_eq!(tokenize(r#"append "a b c""#).unwrap(), ["append", "a b c"]);
+        assert_eq!(tokenize(r#"'a b'"c d""#).unwrap(), [r#"a b"c"#, r#"d""#]);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_escaped_whitespace() {
+        assert_eq!(
+            tokenize(r"load my\ notes\ file.txt").unwrap(),
+            ["load", r"my\ notes\ file.txt"]
+        );
+        // An escaped backslash does not escape the space after it
+        assert_eq!(tokenize(r"a\\ b").unwrap(), [r"a\\", "b"]);
+        assert_eq!(tokenize(r"'a\' b").unwrap(), [r"a\", "b"]);
+    }
+
+    #[test]
+    fn test_pasted_path() {
+        assert_eq!(
+            pasted_path(r#""/home/me/my file.txt""#).as_deref(),
+            Some("/home/me/my file.txt")
+        );
+        assert_eq!(
+            pasted_path(r"/home/me/my\ file.txt").as_deref(),
+            Some("/home/me/my file.txt")
+        );
+        // Quotes inside a name or not wrapping it all are part of the name
+        assert_eq!(pasted_path("'a'b'"), None);
+        assert_eq!(pasted_path("'notes.txt"), None);
+        assert_eq!(pasted_path(r#""'"#), None);
+        assert_eq!(pasted_path("plain.txt"), None);
+    }
+
+    #[test]
+    fn test_tokenize_unterminated_quote() {
+        assert!(matches!(
+            tokenize(r#"load "file.txt"#),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_join_tokens_round_trips() {
+        for tokens in [
+            vec!["save", "out.txt"],
+            vec!["append", "two words", "it's", r#"say "hi""#],
+            vec!["load", r"dir\", "next"],
+            vec!["load", r#"say"hi".txt"#, "'lead", r#""lead"#],
+            vec!["echo", ">", ">>x"],
+        ] {
+            let tokens: Vec<String> = tokens.into_iter().map(String::from).collect();
+            assert_eq!(tokenize(&join_tokens(&tokens)).unwrap(), tokens);
+        }
+    }
+
+    #[test]
+    fn test_split_redirect() {
+        let split = |line: &str| split_redirect(tokenize_quoted(line).unwrap());
+
+        let (tokens, redirect) = split("vers > out.txt").unwrap();
+        assert_eq!(tokens, ["vers"]);
+        assert_eq!(
+            redirect,
+            Some(Redirect {
+                target: "out.txt".to_string(),
+                append: false
+            })
+        );
+
+        let (tokens, redirect) = split(r#"history >>"my log.txt""#).unwrap();
+        assert_eq!(tokens, ["history"]);
+        assert_eq!(
+            redirect,
+            Some(Redirect {
+                target: "my log.txt".to_string(),
+                append: true
+            })
+        );
+
+        let (tokens, redirect) = split("append a>b").unwrap();
+        assert_eq!(tokens, ["append", "a>b"]);
+        assert_eq!(redirect, None);
+
+        // Quoted text is never an operator
+        for line in [
+            r#"append ">x""#,
+            r#"echo ">""#,
+            r#"echo "a" ">" b"#,
+            r#"echo '>>' b"#,
+        ] {
+            let (tokens, redirect) = split(line).unwrap();
+            assert_eq!(tokens, split_input(line).unwrap(), "{line}");
+            assert_eq!(redirect, None, "{line}");
+        }
+        let (tokens, redirect) = split(r#"echo ">" > ">out.txt""#).unwrap();
+        assert_eq!(tokens, ["echo", ">"]);
+        assert_eq!(redirect.unwrap().target, ">out.txt");
+
+        assert!(matches!(split("vers >"), Err(CliError::InvalidInput(_))));
+        assert!(matches!(split("vers >>"), Err(CliError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_split_input_normalizes() {
+        assert_eq!(
+            split_input(r#"load " file.txt " "" '  '"#).unwrap(),
+            ["load", "file.txt"]
+        );
+    }
+}
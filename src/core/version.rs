@@ -0,0 +1,252 @@
+//! Semantic version parsing and comparison.
+//!
+//! Versions follow semver: `MAJOR.MINOR.PATCH`, optionally followed by a
+//! `-pre.release` tag and `+build` metadata. Build metadata is ignored when
+//! comparing, and a pre-release sorts before the release it precedes.
+//! [`build_info`] describes the running binary with the metadata the build
+//! script embeds.
+
+use crate::core::error::{CliError, CliResult};
+use crate::core::history_file::format_timestamp;
+use crate::core::json::JsonValue;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed semantic version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    /// Major version
+    pub major: u64,
+    /// Minor version
+    pub minor: u64,
+    /// Patch version
+    pub patch: u64,
+    /// Dot-separated pre-release identifiers, empty for a release
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    /// Get the version this binary was built as
+    pub fn current() -> Self {
+        env!("CARGO_PKG_VERSION")
+            .parse()
+            .expect("Cargo package version is valid semver")
+    }
+}
+
+impl FromStr for Version {
+    type Err = CliError;
+
+    /// Parse a version such as `1.2.3`, `v1.2.3`, or `1.2.3-rc.1+build.5`
+    fn from_str(text: &str) -> CliResult<Self> {
+        let malformed = || CliError::invalid_input(&format!("Malformed version: '{text}'"));
+
+        let trimmed = text.trim();
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+        let without_build = trimmed
+            .split_once('+')
+            .map_or(trimmed, |(version, _)| version);
+        let (core, pre) = match without_build.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (without_build, None),
+        };
+
+        let numbers = core
+            .split('.')
+            .map(parse_number)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(malformed)?;
+        let [major, minor, patch] = numbers[..] else {
+            return Err(malformed());
+        };
+
+        let pre = match pre {
+            Some(pre) => {
+                let identifiers: Vec<String> = pre.split('.').map(str::to_string).collect();
+                let valid = identifiers.iter().all(|id| {
+                    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                });
+                if !valid {
+                    return Err(malformed());
+                }
+                identifiers
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+/// Describe the running binary as a JSON object
+///
+/// The object has the package `name` and `version`, the short git `commit`
+/// it was built from, and the `built` time as an ISO-8601 UTC timestamp.
+/// `commit` is `null` when the build had no git checkout to read.
+///
+/// # Examples
+/// ```
+/// use sm_menu::core::version::build_info;
+///
+/// let info = build_info();
+/// assert_eq!(info.get("name").and_then(|v| v.as_str()), Some("sm-menu"));
+/// ```
+pub fn build_info() -> JsonValue {
+    let optional = |value: Option<String>| value.map_or(JsonValue::Null, |v| JsonValue::string(&v));
+    let built = option_env!("SM_MENU_BUILT")
+        .and_then(|seconds| seconds.parse().ok())
+        .map(format_timestamp);
+    JsonValue::Object(vec![
+        (
+            "name".to_string(),
+            JsonValue::string(env!("CARGO_PKG_NAME")),
+        ),
+        (
+            "version".to_string(),
+            JsonValue::string(env!("CARGO_PKG_VERSION")),
+        ),
+        (
+            "commit".to_string(),
+            optional(option_env!("SM_MENU_COMMIT").map(str::to_string)),
+        ),
+        ("built".to_string(), optional(built)),
+    ])
+}
+
+/// Parse a numeric version component, rejecting signs and leading zeros
+fn parse_number(part: &str) -> Option<u64> {
+    let digits_only = !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !digits_only || (part.len() > 1 && part.starts_with('0')) {
+        return None;
+    }
+    part.parse().ok()
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                // A release is newer than any of its pre-releases
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare pre-release identifiers by semver precedence
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            // Numeric identifiers sort before alphanumeric ones
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(text: &str) -> Version {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            version("1.2.3"),
+            Version {
+                major: 1,
+                minor: 2,
+                patch: 3,
+                pre: Vec::new()
+            }
+        );
+        assert_eq!(version("v1.2.3+build.7"), version("1.2.3"));
+        assert_eq!(version("1.2.3-rc.1").pre, ["rc", "1"]);
+        assert_eq!(version("1.2.3-rc.1").to_string(), "1.2.3-rc.1");
+        assert_eq!(Version::current().to_string(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        for text in [
+            "",
+            "1.2",
+            "1.2.3.4",
+            "1.x.3",
+            "01.2.3",
+            "-1.2.3",
+            "1.2.3-",
+            "1.2.3-a..b",
+        ] {
+            assert!(
+                matches!(text.parse::<Version>(), Err(CliError::InvalidInput(_))),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_info() {
+        let info = build_info();
+        let keys: Vec<&str> = match &info {
+            JsonValue::Object(members) => members.iter().map(|(key, _)| key.as_str()).collect(),
+            other => panic!("not an object: {other}"),
+        };
+        assert_eq!(keys, ["name", "version", "commit", "built"]);
+        assert_eq!(
+            info.get("version").and_then(JsonValue::as_str),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        let built = info.get("built").and_then(JsonValue::as_str).unwrap();
+        assert!(built.ends_with('Z') && built.len() == 20, "{built}");
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(version("0.1.0") < version("0.2.0"));
+        assert!(version("1.10.0") > version("1.9.9"));
+        assert_eq!(
+            version("1.2.3").cmp(&version("1.2.3+other")),
+            Ordering::Equal
+        );
+        assert!(version("1.0.0-alpha") < version("1.0.0"));
+        assert!(version("1.0.0-alpha") < version("1.0.0-alpha.1"));
+        assert!(version("1.0.0-alpha.1") < version("1.0.0-alpha.beta"));
+        assert!(version("1.0.0-beta.2") < version("1.0.0-beta.11"));
+        assert!(version("1.0.0-rc.1") < version("1.0.0"));
+    }
+}
@@ -1,8 +1,31 @@
+/// Emit a log record at the given level, with the `logging` feature
+///
+/// Without the feature nothing is logged and the message is never built.
+macro_rules! log_record {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "logging")]
+        $crate::core::logging::emit(
+            $crate::core::logging::Level::$level,
+            module_path!(),
+            format_args!($($arg)+),
+        );
+        #[cfg(not(feature = "logging"))]
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+pub mod app;
 pub mod commands;
 pub mod core;
 pub mod ui;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 // Re-export for tests
+pub use app::{App, Middleware, SessionStats};
 pub use commands::*;
 pub use core::*;
 pub use ui::*;
@@ -10,6 +33,7 @@ pub use ui::*;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::file::FileCommand;
 
     #[test]
     fn test_cli_context_creation() {
@@ -26,12 +50,12 @@ mod tests {
         context.preferences_mut().colored_prompt = false;
 
         // Test pushing context
-        context.push_context("file".to_string());
+        context.navigator_mut().push(Box::new(FileCommand::new()));
         assert_eq!(context.current_path(), vec!["file"]);
         assert_eq!(context.get_prompt(), "sm-menu ~ file > ");
 
         // Test popping context
-        context.pop_context();
+        context.navigator_mut().pop();
         assert!(context.current_path().is_empty());
         assert_eq!(context.get_prompt(), "sm-menu > ");
     }
@@ -59,7 +83,7 @@ mod tests {
         };
         assert_eq!(format!("{error}"), "Too few arguments: expected 2, found 1");
 
-        let error = CliError::ExecutionError("command failed".to_string());
+        let error = CliError::execution_error("command failed");
         assert_eq!(
             format!("{error}"),
             "Command execution failed: command failed"
@@ -1,21 +1,17 @@
-use sm_menu::ui::DisplayManager;
-use sm_menu::{CliContext, CliError, CliResult, Command, CommandResult};
-use std::io::{self, BufRead, Write};
+use sm_menu::core::history_file::{load_history, save_history};
+use sm_menu::core::version::build_info;
+use sm_menu::ui::{Profile, TerminalUtils, Transcript};
+use sm_menu::{
+    App, CliContext, CliError, CliPreferences, CliResult, SecurityContext, SessionIo,
+    StartupOptions,
+};
+use std::io::{self, Write};
 use std::panic;
 use std::process;
 
-mod commands;
-use commands::RootCommand;
-
-/// Maximum navigation depth to prevent stack overflow
-const MAX_NAVIGATION_DEPTH: usize = 10;
-
 /// Clear the terminal screen using ANSI escape codes
 fn clear_terminal() -> CliResult<()> {
-    // ANSI escape code to clear screen and move cursor to top-left
-    print!("\x1b[2J\x1b[H");
-    io::stdout()
-        .flush()
+    TerminalUtils::clear_screen()
         .map_err(|e| CliError::terminal_error(&format!("Failed to clear terminal: {e}")))?;
     Ok(())
 }
@@ -24,203 +20,184 @@ fn main() -> CliResult<()> {
     // Set up panic handler for graceful error handling
     setup_panic_handler();
 
-    // Set up signal handlers for graceful shutdown
-    setup_signal_handlers();
+    // Ctrl-C keeps its default behavior of ending the program, except
+    // while a blocking command such as sleep or watch catches it with
+    // core::interrupt::catch_interrupts() to stop early
 
-    // Clear the terminal screen
-    if let Err(e) = clear_terminal() {
-        eprintln!("Warning: {e}");
+    // Parse startup options, build the security boundary, load the
+    // preferences, and open the input before touching the terminal
+    let options = match StartupOptions::from_env() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(2);
+        }
+    };
+
+    // --version-json describes the binary for build pipelines and exits
+    if options.version_json {
+        println!("{}", build_info());
+        return Ok(());
     }
 
-    println!("\n\tWelcome to sm-menu!\n");
+    // --init writes a default configuration file and exits, without
+    // reading the file it may replace
+    if options.init {
+        match options.init_config() {
+            Ok(path) => {
+                println!("Wrote default configuration to {}", path.display());
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                process::exit(2);
+            }
+        }
+    }
 
-    let mut context = CliContext::new();
-    let mut command_stack: Vec<Box<dyn Command>> = vec![Box::new(RootCommand)];
+    let session = match open_session(&options) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(2);
+        }
+    };
+
+    // A script or JSON request run leaves the screen alone, and so does
+    // plain output, which has no escape sequences
+    if options.is_interactive()
+        && !options.plain
+        && let Err(e) = clear_terminal()
+    {
+        eprintln!("Warning: {e}");
+    }
 
     // Main application loop with comprehensive error handling
-    let result = run_main_loop(&mut context, &mut command_stack);
-
-    // Perform graceful shutdown
-    graceful_shutdown();
-
-    result
-}
-
-/// Set up panic handler for better error reporting
-fn setup_panic_handler() {
-    panic::set_hook(Box::new(|panic_info| {
-        eprintln!("\nFatal error occurred!");
-        eprintln!("Error: {panic_info}");
-        eprintln!("Please report this bug to the developers.");
-        process::exit(1);
-    }));
-}
+    let mut context = CliContext::with_security(session.security);
+    *context.preferences_mut() = session.preferences;
+    context.set_config_path(options.config_path());
+    context.set_io(session.io);
+
+    // Pick up the history of earlier sessions
+    let history_path = options.history_path();
+    if let Some(path) = &history_path {
+        match load_history(path) {
+            Ok(entries) => context.extend_history(entries),
+            Err(e) => eprintln!("Warning: could not load history: {e}"),
+        }
+    }
 
-/// Set up signal handlers for graceful shutdown (placeholder for now)
-fn setup_signal_handlers() {
-    // In a real implementation, you would set up signal handlers here
-    // For now, we'll rely on Rust's default behavior
-}
+    let mut app = App::new(context);
+    if let Some(transcript) = session.transcript {
+        app.set_transcript(transcript);
+    }
+    if let Some(profile) = session.profile {
+        app.set_profile(profile);
+    }
+    app.set_echo(options.echo);
+    app.set_strict(options.strict);
+    if options.is_interactive() {
+        app.welcome();
+    }
 
-/// Main application loop with comprehensive error handling
-fn run_main_loop(
-    context: &mut CliContext,
-    command_stack: &mut Vec<Box<dyn Command>>,
-) -> CliResult<()> {
-    while context.running {
-        // Display prompt and read input
-        match display_flashing_prompt_and_read_input(context) {
-            Ok(input) => {
-                let input = input.trim();
-                if input.is_empty() {
-                    // Show available commands when user presses enter with no input
-                    show_available_commands(command_stack);
-                    continue;
-                }
-
-                // Handle the input with comprehensive error handling
-                if let Err(e) = handle_input(input, command_stack, context) {
-                    display_error(&e, command_stack);
-                }
-            }
-            Err(CliError::Interrupted) => {
-                println!("\nOperation interrupted. Type 'quit' to exit.");
-                continue;
-            }
-            Err(e) => {
-                eprintln!("Error reading input: {e}");
-                // Don't break on IO errors, try to continue
-                continue;
-            }
+    // A one-shot command runs on its own; its failure is reported on
+    // stderr, so an --output file only ever holds the command's output
+    if !options.command.is_empty() {
+        let result = match &options.output {
+            Some(path) => app.set_output_file(&path.to_string_lossy()),
+            None => Ok(()),
+        }
+        .and_then(|()| app.run_once(&options.command));
+        graceful_shutdown(&mut app, false);
+        if let Err(e) = result {
+            eprintln!("Error: {e}");
+            process::exit(1);
         }
+        return match app.context().exit_code() {
+            0 => Ok(()),
+            code => process::exit(code.into()),
+        };
     }
 
-    Ok(())
-}
+    let result = if options.commands_from_stdin {
+        app.run_json_requests()
+    } else {
+        app.run()
+    };
+
+    if let Some(path) = &history_path {
+        let context = app.context();
+        let timestamps = context.preferences().history_timestamps;
+        if let Err(e) = save_history(path, context.history(), timestamps) {
+            eprintln!("Warning: could not save history: {e}");
+        }
+    }
 
-/// Display prompt and read input (simplified without flashing animation)
-fn display_flashing_prompt_and_read_input(context: &CliContext) -> CliResult<String> {
-    let base_prompt = context.get_prompt();
-    let question_mark = "? ";
-
-    // Display static prompt
-    print!("{base_prompt}{question_mark}");
-    io::stdout()
-        .flush()
-        .map_err(|e| CliError::terminal_error(&format!("Failed to display prompt: {e}")))?;
-
-    // Read input directly
-    let stdin = io::stdin();
-    let mut input = String::new();
-    match stdin.lock().read_line(&mut input) {
-        Ok(_) => Ok(input),
-        Err(e) => {
-            let cli_error = match e.kind() {
-                std::io::ErrorKind::Interrupted => CliError::Interrupted,
-                _ => CliError::from(e),
-            };
-            Err(cli_error)
+    // Summarize a script run on stderr, so piped stdout stays clean
+    if options.script.is_some() && !options.quiet {
+        let stats = app.stats();
+        if options.json {
+            eprintln!("{}", stats.summary_json());
+        } else {
+            eprintln!("{}", stats.summary());
         }
     }
-}
 
-/// Show available commands when user presses enter with no input
-fn show_available_commands(command_stack: &[Box<dyn Command>]) {
-    if let Some(_current_command) = command_stack.last() {
-        println!();
-        let display_manager = DisplayManager::new();
-        display_manager.display_available_commands(command_stack);
-        println!();
+    // Perform graceful shutdown; JSON responses are the only output of a
+    // JSON request run
+    graceful_shutdown(&mut app, !options.commands_from_stdin);
+
+    result?;
+    match app.context().exit_code() {
+        0 => Ok(()),
+        code => process::exit(code.into()),
     }
 }
 
-/// Display error with appropriate formatting
-fn display_error(error: &CliError, command_stack: &[Box<dyn Command>]) {
-    let display_manager = DisplayManager::new();
-    display_manager.display_error(error, command_stack);
+/// Everything a session is built from, opened before touching the terminal
+struct Session {
+    security: SecurityContext,
+    preferences: CliPreferences,
+    io: SessionIo,
+    transcript: Option<Transcript>,
+    profile: Option<Profile>,
 }
 
-/// Perform graceful shutdown
-fn graceful_shutdown() {
-    println!("\nThank you for using sm-menu!");
-    // Ensure stdout is flushed before exit
-    let _ = io::stdout().flush();
+/// Build the security boundary, preferences, input, transcript, and profile
+/// for a session
+fn open_session(options: &StartupOptions) -> CliResult<Session> {
+    Ok(Session {
+        security: options.security_context()?,
+        preferences: options.preferences()?,
+        io: options.session_io()?,
+        transcript: options.log.as_deref().map(Transcript::open).transpose()?,
+        profile: options
+            .profile
+            .as_deref()
+            .map(Profile::create)
+            .transpose()?,
+    })
 }
 
-fn handle_input(
-    input: &str,
-    command_stack: &mut Vec<Box<dyn Command>>,
-    context: &mut CliContext,
-) -> CliResult<()> {
-    let parts: Vec<String> = input.split_whitespace().map(|s| s.to_string()).collect();
-    if parts.is_empty() {
-        return Err(CliError::EmptyInput);
-    }
+/// Set up panic handler for better error reporting
+///
+/// The terminal is restored first, so a panic while in raw mode or with
+/// the cursor hidden does not leave the user's shell unusable.
+fn setup_panic_handler() {
+    panic::set_hook(Box::new(|panic_info| {
+        let _ = TerminalUtils::restore_terminal();
+        eprintln!("\nFatal error occurred!");
+        eprintln!("Error: {panic_info}");
+        eprintln!("Please report this bug to the developers.");
+        process::exit(1);
+    }));
+}
 
-    let command_name = &parts[0];
-    let args = &parts[1..];
-
-    // Get current command level with error handling
-    let current_command = command_stack
-        .last()
-        .ok_or_else(|| CliError::internal_error("Empty command stack"))?;
-    let subcommands = current_command.subcommands();
-
-    // Find matching command (by name or alias)
-    let found_command = subcommands
-        .into_iter()
-        .find(|cmd| cmd.matches(command_name));
-
-    match found_command {
-        Some(mut cmd) => {
-            // Execute the command with proper error handling
-            match cmd.execute(args) {
-                Ok(result) => {
-                    match result {
-                        CommandResult::Success(msg) => {
-                            if !msg.is_empty() {
-                                println!("{msg}");
-                            }
-                        }
-                        CommandResult::Continue => {
-                            // If command has subcommands, enter that submenu
-                            if cmd.has_subcommands() {
-                                // Check for maximum navigation depth
-                                if command_stack.len() >= MAX_NAVIGATION_DEPTH {
-                                    return Err(CliError::execution_error(
-                                        "Maximum navigation depth reached. Use 'exit' to go back.",
-                                    ));
-                                }
-                                context.push_context(cmd.name().to_string());
-                                command_stack.push(cmd);
-                            }
-                        }
-                        CommandResult::GoUp => {
-                            // Return to parent menu
-                            if command_stack.len() > 1 {
-                                command_stack.pop();
-                                context.pop_context();
-                            } else {
-                                // Already at root level
-                                println!("Already at root level.");
-                            }
-                        }
-                        CommandResult::Quit => {
-                            context.quit();
-                        }
-                    }
-                }
-                Err(e) => {
-                    // Return the error to be handled by the caller
-                    return Err(e);
-                }
-            }
-        }
-        None => {
-            // Command not found - this is now an error
-            return Err(CliError::invalid_command(command_name));
-        }
+/// Perform graceful shutdown, saying goodbye if asked to
+fn graceful_shutdown(app: &mut App, say_goodbye: bool) {
+    if say_goodbye {
+        app.goodbye();
     }
-
-    Ok(())
+    // Ensure stdout is flushed before exit
+    let _ = io::stdout().flush();
 }
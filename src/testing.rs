@@ -0,0 +1,209 @@
+//! Isolated sessions for testing commands.
+//!
+//! This module is only built with the `testing` feature. A [`TestHarness`]
+//! owns a scratch directory and a captured output buffer, and builds
+//! contexts and apps confined to them. Nothing depends on the process's
+//! working directory or environment, so tests using separate harnesses can
+//! run in parallel.
+
+use crate::app::App;
+use crate::core::{CliContext, CliResult, SecurityContext, SessionIo};
+pub use crate::ui::CapturedOutput;
+use crate::ui::DisplayManager;
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of harnesses created so far, to keep their directories apart
+static HARNESS_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Scratch working root and captured output for one test
+///
+/// The directory is created empty and removed when the harness is dropped.
+/// Contexts built by the harness are confined to it, have color turned off,
+/// show command messages in the captured output, and read session input
+/// only from what the test supplies.
+///
+/// # Examples
+/// ```
+/// use sm_menu::testing::TestHarness;
+///
+/// let harness = TestHarness::new("doc-example").unwrap();
+/// harness.write_file("notes.txt", "one\ntwo\n");
+///
+/// let mut app = harness.app("");
+/// app.handle_input("file").unwrap();
+/// app.handle_input("load notes.txt").unwrap();
+/// assert_eq!(harness.output(), "OK Loaded 2 lines from notes.txt\n");
+/// ```
+#[derive(Debug)]
+pub struct TestHarness {
+    /// Scratch directory the session is confined to
+    root: PathBuf,
+    /// Where command messages are written
+    output: CapturedOutput,
+}
+
+impl TestHarness {
+    /// Create a harness with a new, empty scratch directory
+    ///
+    /// # Arguments
+    /// * `name` - Label included in the directory name, for debugging
+    ///
+    /// # Returns
+    /// * `Ok(TestHarness)` - The harness
+    /// * `Err(CliError)` - If the directory could not be created
+    pub fn new(name: &str) -> CliResult<Self> {
+        let count = HARNESS_COUNT.fetch_add(1, Ordering::Relaxed);
+        let root = std::env::temp_dir().join(format!(
+            "sm-menu-harness-{name}-{}-{count}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root)?;
+        Ok(TestHarness {
+            root,
+            output: CapturedOutput::default(),
+        })
+    }
+
+    /// Get the scratch directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the path of a file in the scratch directory
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    /// Write a file in the scratch directory
+    ///
+    /// # Panics
+    /// If the file cannot be written.
+    pub fn write_file(&self, name: &str, contents: &str) {
+        fs::write(self.path(name), contents).expect("failed to write test file");
+    }
+
+    /// Read a file from the scratch directory
+    ///
+    /// # Panics
+    /// If the file cannot be read.
+    pub fn read_file(&self, name: &str) -> String {
+        fs::read_to_string(self.path(name)).expect("failed to read test file")
+    }
+
+    /// Build a context confined to the scratch directory
+    ///
+    /// # Arguments
+    /// * `input` - Session input, read as if piped from a script
+    pub fn context(&self, input: &str) -> CliContext {
+        let security = SecurityContext::new(&self.root).expect("harness root is a valid directory");
+        let mut context = CliContext::with_security(security);
+        context.preferences_mut().colored_prompt = false;
+        context.set_display(DisplayManager::with_writer(
+            false,
+            false,
+            Box::new(self.output.clone()),
+        ));
+        context.set_io(SessionIo::from_bytes(input));
+        context
+    }
+
+    /// Build an app whose context is confined to the scratch directory
+    ///
+    /// # Arguments
+    /// * `input` - Session input, read as if piped from a script
+    pub fn app(&self, input: &str) -> App {
+        App::new(self.context(input))
+    }
+
+    /// Run a whole session through the app's input loop
+    ///
+    /// Each input line is read and handled by [`App::run`] as in a script
+    /// run, and the menu path after each command line is recorded, so tests
+    /// can check navigation end to end. Blank lines are not recorded.
+    ///
+    /// # Arguments
+    /// * `input` - Session input, one command per line
+    ///
+    /// # Returns
+    /// * `Ok(SessionRun)` - The app after the loop ended, and the steps
+    /// * `Err(CliError)` - If the loop itself failed
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::testing::TestHarness;
+    ///
+    /// let harness = TestHarness::new("session-example").unwrap();
+    /// let run = harness.run_session("file\nexit\n").unwrap();
+    /// assert_eq!(run.paths(), [vec!["file"], vec![]]);
+    /// ```
+    pub fn run_session(&self, input: &str) -> CliResult<SessionRun> {
+        let mut app = self.app(input);
+
+        // Middleware sees each command line before it runs, so the path
+        // it finds is the one left by the line before
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let record = Rc::clone(&seen);
+        app.add_middleware(move |line, context| {
+            record
+                .borrow_mut()
+                .push((line.to_string(), context.current_path().to_vec()));
+            Ok(None)
+        });
+        app.run()?;
+
+        let seen = seen.take();
+        let after = seen
+            .iter()
+            .skip(1)
+            .map(|(_, path)| path.clone())
+            .chain([app.context().current_path().to_vec()]);
+        let steps = seen
+            .iter()
+            .map(|(line, _)| line.clone())
+            .zip(after)
+            .collect();
+        Ok(SessionRun { app, steps })
+    }
+
+    /// Get the captured output handle, to share with other writers
+    pub fn captured(&self) -> CapturedOutput {
+        self.output.clone()
+    }
+
+    /// Get the command messages written so far
+    pub fn output(&self) -> String {
+        self.output.text()
+    }
+
+    /// Discard the command messages written so far
+    pub fn clear_output(&self) {
+        self.output.clear();
+    }
+}
+
+/// A session run by [`TestHarness::run_session`]
+#[derive(Debug)]
+pub struct SessionRun {
+    /// The app after its loop ended, to inspect the final state
+    pub app: App,
+    /// Each command line, with the menu path after it ran
+    pub steps: Vec<(String, Vec<String>)>,
+}
+
+impl SessionRun {
+    /// Get the menu path after each command line, in order
+    pub fn paths(&self) -> Vec<Vec<String>> {
+        self.steps.iter().map(|(_, path)| path.clone()).collect()
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
@@ -0,0 +1,281 @@
+//! ANSI escape sequences used by the CLI interface.
+//!
+//! Every escape sequence the interface writes is defined here, so the bytes
+//! are spelled out once and checked by the tests below. Fixed styles are
+//! constants, which the `const` color theme can use; sequences that take a
+//! parameter are built by functions. Keys read from a terminal in raw mode
+//! arrive as the same kind of sequences; [`split_key`] finds where each one
+//! ends, for every reader of raw keys to share.
+
+/// Control Sequence Introducer that starts every sequence here
+const CSI: &str = "\x1b[";
+
+/// Reset all text styling
+pub const RESET: &str = "\x1b[0m";
+/// Bold text
+pub const BOLD: &str = "\x1b[1m";
+/// Dim text
+pub const DIM: &str = "\x1b[2m";
+/// Red text
+pub const RED: &str = "\x1b[31m";
+/// Green text
+pub const GREEN: &str = "\x1b[32m";
+/// Cyan text
+pub const CYAN: &str = "\x1b[36m";
+/// Bold red text
+pub const BOLD_RED: &str = "\x1b[1;31m";
+/// Bold green text
+pub const BOLD_GREEN: &str = "\x1b[1;32m";
+/// Bold yellow text
+pub const BOLD_YELLOW: &str = "\x1b[1;33m";
+/// Bold blue text
+pub const BOLD_BLUE: &str = "\x1b[1;34m";
+/// Bold magenta text
+pub const BOLD_MAGENTA: &str = "\x1b[1;35m";
+/// Bold cyan text
+pub const BOLD_CYAN: &str = "\x1b[1;36m";
+/// Warp-like green of the prompt brand, as 24-bit color `rgb_fg(0, 215, 135)`
+pub const BRAND_GREEN: &str = "\x1b[38;2;0;215;135m";
+
+/// Build a Select Graphic Rendition sequence from its parameters
+///
+/// `sgr("1;32")` is bold green, and `sgr("0")` resets all styling.
+pub fn sgr(code: &str) -> String {
+    format!("{CSI}{code}m")
+}
+
+/// Build the sequence for a 24-bit foreground color
+///
+/// The theme needs its colors as constants, so [`BRAND_GREEN`] is spelled
+/// out and checked against this function in the tests.
+#[allow(dead_code)]
+pub fn rgb_fg(r: u8, g: u8, b: u8) -> String {
+    sgr(&format!("38;2;{r};{g};{b}"))
+}
+
+/// Wrap text in bold, resetting the styling after it
+pub fn bold(text: &str) -> String {
+    format!("{BOLD}{text}{RESET}")
+}
+
+/// Clear the screen and move the cursor to the top-left corner
+pub fn clear_screen() -> &'static str {
+    "\x1b[2J\x1b[H"
+}
+
+/// Clear the whole row the cursor is on
+pub fn clear_line() -> &'static str {
+    "\x1b[2K"
+}
+
+/// Clear from the cursor to the end of the screen
+pub fn clear_below() -> &'static str {
+    "\x1b[J"
+}
+
+/// Hide the cursor
+pub fn hide_cursor() -> &'static str {
+    "\x1b[?25l"
+}
+
+/// Show the cursor
+pub fn show_cursor() -> &'static str {
+    "\x1b[?25h"
+}
+
+/// Save the cursor position
+pub fn save_cursor() -> &'static str {
+    "\x1b[s"
+}
+
+/// Restore the cursor position saved by [`save_cursor`]
+pub fn restore_cursor() -> &'static str {
+    "\x1b[u"
+}
+
+/// Move the cursor to a 1-based column and row
+pub fn cursor_to(x: usize, y: usize) -> String {
+    format!("{CSI}{y};{x}H")
+}
+
+/// Move the cursor up `rows` rows
+pub fn cursor_up(rows: usize) -> String {
+    format!("{CSI}{rows}A")
+}
+
+/// Move the cursor left `columns` columns
+pub fn cursor_left(columns: usize) -> String {
+    format!("{CSI}{columns}D")
+}
+
+/// Escape byte that starts every key sequence
+const ESC: u8 = 0x1b;
+
+/// Key at the front of raw terminal input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawKey {
+    /// A character, including control characters such as Enter
+    Char(char),
+    /// A control sequence such as an arrow key, named by its final byte
+    Sequence(u8),
+    /// Esc on its own
+    Escape,
+    /// Bytes that do not form a key, such as invalid UTF-8
+    Other,
+}
+
+/// Get the length of the key at the front of raw terminal input
+///
+/// A control sequence (`ESC [`) runs through its parameter bytes to a final
+/// byte from `@` to `~`, so `\x1b[3~` (Delete) is one key; an `ESC O`
+/// sequence is three bytes. Returns `None` if the input ends partway
+/// through a key, which includes a lone `ESC` that may start a sequence.
+pub fn key_len(bytes: &[u8]) -> Option<usize> {
+    match bytes {
+        [] | [ESC] | [ESC, b'O'] => None,
+        [ESC, b'[', rest @ ..] => rest
+            .iter()
+            .position(|byte| (0x40..=0x7e).contains(byte))
+            .map(|end| end + 3),
+        [ESC, b'O', _, ..] => Some(3),
+        [ESC, ..] => Some(1),
+        [lead, ..] => {
+            let width = match lead {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            (bytes.len() >= width).then_some(width)
+        }
+    }
+}
+
+/// Split the key at the front of a non-empty chunk of raw terminal input
+///
+/// Returns the key and the number of bytes it took. A key cut off by the
+/// end of the chunk takes the rest of it, as [`RawKey::Other`], except for
+/// a lone `ESC`, which is the Esc key.
+pub fn split_key(bytes: &[u8]) -> (RawKey, usize) {
+    let Some(len) = key_len(bytes) else {
+        let key = if bytes == [ESC] {
+            RawKey::Escape
+        } else {
+            RawKey::Other
+        };
+        return (key, bytes.len());
+    };
+    match &bytes[..len] {
+        [ESC] => (RawKey::Escape, 1),
+        [ESC, .., last] => (RawKey::Sequence(*last), len),
+        key => match std::str::from_utf8(key).ok().and_then(|s| s.chars().next()) {
+            Some(c) => (RawKey::Char(c), len),
+            None => (RawKey::Other, 1),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Split a whole chunk into keys
+    fn split_all(mut bytes: &[u8]) -> Vec<RawKey> {
+        let mut keys = Vec::new();
+        while !bytes.is_empty() {
+            let (key, used) = split_key(bytes);
+            keys.push(key);
+            bytes = &bytes[used..];
+        }
+        keys
+    }
+
+    #[test]
+    fn test_split_key_consumes_whole_sequences() {
+        assert_eq!(
+            split_all("\x1b[3~\x1b[1;5Ca\x1bOB\x1bx\u{e9}\r".as_bytes()),
+            [
+                RawKey::Sequence(b'~'),
+                RawKey::Sequence(b'C'),
+                RawKey::Char('a'),
+                RawKey::Sequence(b'B'),
+                RawKey::Escape,
+                RawKey::Char('x'),
+                RawKey::Char('\u{e9}'),
+                RawKey::Char('\r'),
+            ]
+        );
+        assert_eq!(split_all(b"\x1b"), [RawKey::Escape]);
+        assert_eq!(split_all(b"\x1b[12"), [RawKey::Other]);
+        assert_eq!(
+            split_all(b"\xc3a\xff"),
+            [RawKey::Other, RawKey::Char('a'), RawKey::Other]
+        );
+    }
+
+    #[test]
+    fn test_key_len_waits_for_the_end_of_a_key() {
+        for partial in [&b"\x1b"[..], b"\x1b[", b"\x1b[3", b"\x1bO", b"\xe2\x82"] {
+            assert_eq!(key_len(partial), None, "{partial:?}");
+        }
+        assert_eq!(key_len(b"\x1b[3~x"), Some(4));
+        assert_eq!(key_len("\u{20ac}".as_bytes()), Some(3));
+        assert_eq!(key_len(b"\x1bx"), Some(1));
+    }
+
+    #[test]
+    fn test_sgr_builds_select_graphic_rendition() {
+        assert_eq!(sgr("0"), RESET);
+        assert_eq!(sgr("1"), BOLD);
+        assert_eq!(sgr("1;32"), BOLD_GREEN);
+        assert_eq!(sgr("1;36").as_bytes(), b"\x1b[1;36m");
+    }
+
+    #[test]
+    fn test_style_constants_match_sgr() {
+        let styles = [
+            (DIM, "2"),
+            (RED, "31"),
+            (GREEN, "32"),
+            (CYAN, "36"),
+            (BOLD_RED, "1;31"),
+            (BOLD_YELLOW, "1;33"),
+            (BOLD_BLUE, "1;34"),
+            (BOLD_MAGENTA, "1;35"),
+            (BOLD_CYAN, "1;36"),
+        ];
+        for (style, code) in styles {
+            assert_eq!(style, sgr(code), "SGR {code}");
+        }
+    }
+
+    #[test]
+    fn test_rgb_fg_builds_24_bit_color() {
+        assert_eq!(rgb_fg(0, 215, 135), BRAND_GREEN);
+        assert_eq!(rgb_fg(255, 0, 7).as_bytes(), b"\x1b[38;2;255;0;7m");
+    }
+
+    #[test]
+    fn test_bold_resets_after_text() {
+        assert_eq!(bold("Sh"), "\x1b[1mSh\x1b[0m");
+        assert!(bold("").ends_with(RESET));
+    }
+
+    #[test]
+    fn test_screen_and_cursor_sequences() {
+        assert_eq!(clear_screen().as_bytes(), b"\x1b[2J\x1b[H");
+        assert_eq!(clear_line().as_bytes(), b"\x1b[2K");
+        assert_eq!(clear_below().as_bytes(), b"\x1b[J");
+        assert_eq!(hide_cursor().as_bytes(), b"\x1b[?25l");
+        assert_eq!(show_cursor().as_bytes(), b"\x1b[?25h");
+        assert_eq!(save_cursor().as_bytes(), b"\x1b[s");
+        assert_eq!(restore_cursor().as_bytes(), b"\x1b[u");
+    }
+
+    #[test]
+    fn test_cursor_motions_take_counts() {
+        assert_eq!(cursor_to(3, 7), "\x1b[7;3H");
+        assert_eq!(cursor_up(4), "\x1b[4A");
+        assert_eq!(cursor_left(2), "\x1b[2D");
+    }
+}
@@ -4,55 +4,245 @@
 //! for the CLI application, including error display, help formatting,
 //! and terminal management.
 
-use crate::core::error::ErrorSeverity;
-use crate::core::{CliError, Command};
+use super::ansi;
+use super::line_buffer::{LineEvent, LineKey, LineReader};
+use super::transcript::Transcript;
+use crate::core::error::{CliResult, ErrorSeverity};
+use crate::core::io::SessionIo;
+use crate::core::{CliError, Command, CommandCategory, suggest};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
 use std::io::{self, Write};
+use std::process::{self, Stdio};
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 // Color constants
-const COLOR_WARNING: &str = "\x1b[1;33m";  // Yellow
-const COLOR_ERROR: &str = "\x1b[1;31m";    // Red
-const COLOR_CRITICAL: &str = "\x1b[1;35m"; // Magenta
-const COLOR_CYAN: &str = "\x1b[1;36m";     // Cyan
-const COLOR_RESET: &str = "\x1b[0m";
+const COLOR_SUCCESS: &str = ansi::BOLD_GREEN;
+const COLOR_WARNING: &str = ansi::BOLD_YELLOW;
+const COLOR_ERROR: &str = ansi::BOLD_RED;
+const COLOR_CRITICAL: &str = ansi::BOLD_MAGENTA;
+const COLOR_INFO: &str = ansi::BOLD_BLUE;
+const COLOR_CYAN: &str = ansi::BOLD_CYAN;
+const COLOR_RESET: &str = ansi::RESET;
+
+// Icon constants (unicode, ascii)
+const ICON_SUCCESS: (&str, &str) = ("✓", "OK");
+const ICON_WARNING: (&str, &str) = ("⚠", "WARNING");
+const ICON_INFO: (&str, &str) = ("ℹ", "INFO");
+
+/// Shown instead of the command list in a menu with no visible commands
+const EMPTY_MENU_MESSAGE: &str = "No commands available here. Use 'exit' to go back.";
+
+/// Terminal settings saved by `enable_raw_mode`, restored on exit or panic
+static SAVED_TTY_STATE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Shortest time between two progress bar redraws (about 30 per second)
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_millis(33);
 
 // Terminal size constants
 const DEFAULT_TERMINAL_WIDTH: usize = 80;
 const DEFAULT_TERMINAL_HEIGHT: usize = 24;
 
 /// Display manager for handling CLI output formatting
+///
+/// Success, warning, info, and plain text messages go to the manager's
+/// writer, which is standard output unless another writer is given.
 pub struct DisplayManager {
     /// Whether to use colored output
     colored: bool,
     /// Whether to use unicode symbols
     unicode: bool,
+    /// Whether invalid commands are followed by suggestions
+    show_suggestions: bool,
+    /// Whether errors are followed by the chain of their causes
+    trace_errors: bool,
+    /// Whether progress bars are drawn
+    show_progress: bool,
+    /// Where messages are written
+    writer: RefCell<Box<dyn Write>>,
+    /// Where errors are written, standard error unless a transcript tees it
+    error_writer: RefCell<Box<dyn Write>>,
 }
 
 impl DisplayManager {
     /// Create a new display manager with default settings
     pub fn new() -> Self {
-        Self {
-            colored: true,
-            unicode: true,
-        }
+        Self::with_options(true, true)
     }
 
     /// Create a display manager with specific settings
     pub fn with_options(colored: bool, unicode: bool) -> Self {
-        Self { colored, unicode }
+        Self::with_writer(colored, unicode, Box::new(PrintWriter))
     }
 
-    /// Display an error with appropriate formatting
-    pub fn display_error(&self, error: &CliError, command_stack: &[Box<dyn Command>]) {
-        let icon = if self.unicode {
-            error.icon()
-        } else {
-            match error.severity() {
-                ErrorSeverity::Warning => "!",
-                ErrorSeverity::Error => "X",
-                ErrorSeverity::Critical => "!!",
+    /// Create a display manager that writes messages to the given writer
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::DisplayManager;
+    ///
+    /// let display = DisplayManager::with_writer(false, false, Box::new(Vec::new()));
+    /// display.display_success("Saved");
+    /// ```
+    pub fn with_writer(colored: bool, unicode: bool, writer: Box<dyn Write>) -> Self {
+        Self {
+            colored,
+            unicode,
+            show_suggestions: true,
+            trace_errors: false,
+            show_progress: true,
+            writer: RefCell::new(writer),
+            error_writer: RefCell::new(Box::new(EprintWriter)),
+        }
+    }
+
+    /// Check whether output is colored
+    pub fn is_colored(&self) -> bool {
+        self.colored
+    }
+
+    /// Turn colored output on or off
+    pub fn set_colored(&mut self, colored: bool) {
+        self.colored = colored;
+    }
+
+    /// Check whether icons use unicode symbols
+    pub fn is_unicode(&self) -> bool {
+        self.unicode
+    }
+
+    /// Switch icons and progress bars between unicode and ascii
+    pub fn set_unicode(&mut self, unicode: bool) {
+        self.unicode = unicode;
+    }
+
+    /// Check whether progress bars are drawn
+    pub fn shows_progress(&self) -> bool {
+        self.show_progress
+    }
+
+    /// Turn drawing progress bars on or off
+    ///
+    /// With progress off, bars still count their updates but write nothing.
+    pub fn set_show_progress(&mut self, show_progress: bool) {
+        self.show_progress = show_progress;
+    }
+
+    /// Check whether invalid commands are followed by suggestions
+    pub fn shows_suggestions(&self) -> bool {
+        self.show_suggestions
+    }
+
+    /// Turn the suggestions after invalid commands on or off
+    pub fn set_show_suggestions(&mut self, show_suggestions: bool) {
+        self.show_suggestions = show_suggestions;
+    }
+
+    /// Check whether errors are followed by the chain of their causes
+    pub fn traces_errors(&self) -> bool {
+        self.trace_errors
+    }
+
+    /// Turn showing the causes of errors on or off
+    pub fn set_trace_errors(&mut self, trace_errors: bool) {
+        self.trace_errors = trace_errors;
+    }
+
+    /// Send messages to another writer, returning the one replaced
+    ///
+    /// This is how output redirection points a command's messages at a
+    /// file for one input line, putting the previous writer back after.
+    pub fn replace_writer(&self, writer: Box<dyn Write>) -> Box<dyn Write> {
+        self.writer.replace(writer)
+    }
+
+    /// Copy everything shown from now on into a transcript
+    ///
+    /// Both the message writer and the error writer are wrapped in a tee,
+    /// see [`Transcript::tee`]. A writer put in place later with
+    /// [`Self::replace_writer`], such as a redirection's file, is not
+    /// copied, since its output does not reach the screen either.
+    pub fn set_transcript(&self, transcript: &Transcript) {
+        for writer in [&self.writer, &self.error_writer] {
+            let mut writer = writer.borrow_mut();
+            let output = std::mem::replace(&mut *writer, Box::new(io::sink()));
+            *writer = transcript.tee(output);
+        }
+    }
+
+    /// Write one line to the writer, ignoring write failures
+    fn write_line(&self, line: &str) {
+        let mut writer = self.writer.borrow_mut();
+        let _ = writeln!(writer, "{line}");
+        let _ = writer.flush();
+    }
+
+    /// Write text to the writer without ending the line, ignoring write
+    /// failures
+    ///
+    /// The session loop writes prompts with this, so the answer is typed on
+    /// the same line.
+    pub fn write_text(&self, text: &str) {
+        let mut writer = self.writer.borrow_mut();
+        let _ = write!(writer, "{text}");
+        let _ = writer.flush();
+    }
+
+    /// Pick the unicode or ascii form of an icon
+    ///
+    /// This is the single place where the unicode setting decides which
+    /// icon is shown.
+    fn icon(&self, (unicode, ascii): (&'static str, &'static str)) -> &'static str {
+        if self.unicode { unicode } else { ascii }
+    }
+
+    /// Get the icon for an error, respecting the unicode setting
+    pub fn error_icon(&self, error: &CliError) -> &'static str {
+        self.icon((error.icon(), error.icon_ascii()))
+    }
+
+    /// Format an error as the lines shown for it, without color
+    ///
+    /// The first line is the error itself. When error tracing is on, each
+    /// underlying cause follows on an indented `caused by:` line.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::CliError;
+    /// use sm_menu::ui::DisplayManager;
+    /// use std::io;
+    ///
+    /// let mut display = DisplayManager::with_options(false, false);
+    /// display.set_trace_errors(true);
+    /// let error = CliError::execution_error_with_source("Backup failed", io::Error::other("disk full"));
+    /// assert_eq!(
+    ///     display.format_error(&error),
+    ///     ["X Command execution failed: Backup failed", "  caused by: disk full"]
+    /// );
+    /// ```
+    pub fn format_error(&self, error: &CliError) -> Vec<String> {
+        let mut lines = vec![format!("{} {error}", self.error_icon(error))];
+        if self.trace_errors {
+            let mut cause = error.source();
+            while let Some(err) = cause {
+                lines.push(format!("  caused by: {err}"));
+                cause = err.source();
             }
-        };
+        }
+        lines
+    }
 
+    /// Display an error with appropriate formatting
+    ///
+    /// When suggestions are on, an invalid command is followed by the
+    /// closest command name, if one is close enough, and an invalid or
+    /// ambiguous command by the list of the current menu's commands. When
+    /// they are off, only the error line is shown. The error's causes are
+    /// shown below it when error tracing is on, see [`Self::format_error`].
+    pub fn display_error(&self, error: &CliError, command_stack: &[Box<dyn Command>]) {
         let color = if self.colored {
             match error.severity() {
                 ErrorSeverity::Warning => COLOR_WARNING,
@@ -65,106 +255,132 @@ impl DisplayManager {
 
         let reset = if self.colored { COLOR_RESET } else { "" };
 
-        eprintln!("{color}{icon} {error}{reset}");
+        {
+            let mut writer = self.error_writer.borrow_mut();
+            for line in self.format_error(error) {
+                let _ = writeln!(writer, "{color}{line}{reset}");
+            }
+            let _ = writer.flush();
+        }
+
+        if !self.show_suggestions {
+            return;
+        }
+
+        if let (CliError::InvalidCommand(token), Some(menu)) = (error, command_stack.last())
+            && let Some(name) = suggest(token, &menu.subcommands())
+        {
+            self.write_line(&format!("Did you mean '{name}'?"));
+        }
 
-        // Show available commands for invalid command errors
-        if matches!(error, CliError::InvalidCommand(_)) {
+        // Show available commands for invalid or ambiguous command errors
+        if matches!(
+            error,
+            CliError::InvalidCommand(_) | CliError::AmbiguousCommand { .. }
+        ) {
             self.display_available_commands(command_stack);
         }
     }
 
     /// Display available commands in a formatted list
+    ///
+    /// A menu whose commands are all hidden, or a command without any,
+    /// shows how to go back instead of an empty list.
     pub fn display_available_commands(&self, command_stack: &[Box<dyn Command>]) {
         if let Some(current_command) = command_stack.last() {
+            // Skip hidden commands such as info
             let subcommands = current_command.subcommands();
-            if !subcommands.is_empty() {
-                for cmd in subcommands {
-                    // Skip the info command - it's a secret command
-                    if cmd.name() == "info" {
-                        continue;
-                    }
-
-                    let aliases = cmd.aliases();
-                    let alias_text = if aliases.is_empty() {
-                        String::new()
-                    } else {
-                        let uppercase_aliases: Vec<String> =
-                            aliases.iter().map(|a| a.to_uppercase()).collect();
-                        format!(" ({})", uppercase_aliases.join(", "))
-                    };
-
-                    let formatted_name = if self.colored {
-                        format!("{}{}{}", COLOR_CYAN, self.format_command_name(cmd.name()), COLOR_RESET)
-                    } else {
-                        self.format_command_name(cmd.name())
-                    };
-
-                    println!("  {}{} - {}", formatted_name, alias_text, cmd.description());
-                }
+            let mut visible = subcommands.iter().filter(|cmd| !cmd.hidden()).peekable();
+            if visible.peek().is_none() {
+                self.display_info(EMPTY_MENU_MESSAGE);
+                return;
+            }
+            for cmd in visible {
+                self.write_line(&self.command_line(cmd.as_ref()));
+            }
+        }
+    }
+
+    /// Display commands under a header for each of their categories
+    ///
+    /// Categories are listed in [`CommandCategory::ALL`] order, and those
+    /// without visible commands are left out.
+    pub fn display_commands_by_category(&self, commands: &[Box<dyn Command>]) {
+        for category in CommandCategory::ALL {
+            let mut listed = commands
+                .iter()
+                .filter(|cmd| !cmd.hidden() && cmd.category() == category)
+                .peekable();
+            if listed.peek().is_none() {
+                continue;
+            }
+
+            self.write_line(&format!("{}:", category.display_name()));
+            for cmd in listed {
+                self.write_line(&self.command_line(cmd.as_ref()));
             }
         }
     }
 
+    /// Format a command's listing line, with its aliases and description
+    fn command_line(&self, cmd: &dyn Command) -> String {
+        let aliases = cmd.aliases();
+        let alias_text = if aliases.is_empty() {
+            String::new()
+        } else {
+            let uppercase_aliases: Vec<String> = aliases.iter().map(|a| a.to_uppercase()).collect();
+            format!(" ({})", uppercase_aliases.join(", "))
+        };
+
+        let formatted_name = if self.colored {
+            format!(
+                "{}{}{}",
+                COLOR_CYAN,
+                self.format_command_with_alias(cmd.name(), cmd.primary_alias()),
+                COLOR_RESET
+            )
+        } else {
+            cmd.name().to_string()
+        };
+
+        format!("  {}{} - {}", formatted_name, alias_text, cmd.description())
+    }
+
     /// Display help information for a command
+    ///
+    /// Shows the command's description, aliases, and usage, followed by
+    /// its visible subcommands.
     pub fn display_help(&self, command: &dyn Command) {
         let name = command.name();
-        let description = command.description();
         let aliases = command.aliases();
-        let subcommands = command.subcommands();
 
         // Header
         if self.colored {
-            println!("\x1b[1;32m{}\x1b[0m", name.to_uppercase());
-            println!("{}", "=".repeat(name.len()));
+            self.write_line(&format!(
+                "{COLOR_SUCCESS}{}{COLOR_RESET}",
+                name.to_uppercase()
+            ));
         } else {
-            println!("{}", name.to_uppercase());
-            println!("{}", "=".repeat(name.len()));
+            self.write_line(&name.to_uppercase());
         }
+        self.write_line(&"=".repeat(name.len()));
 
-        // Description
-        println!("{description}");
-
-        // Aliases
+        self.write_line(command.description());
         if !aliases.is_empty() {
-            println!("\nAliases: {}", aliases.join(", "));
+            self.write_line(&format!("\nAliases: {}", aliases.join(", ")));
         }
-
-        // Usage
-        println!("\nUsage: {}", command.usage());
-
-        // Subcommands
+        self.write_line(&format!("\nUsage: {}", command.usage()));
+
+        // Skip hidden subcommands such as info
+        let subcommands: Vec<_> = command
+            .subcommands()
+            .into_iter()
+            .filter(|cmd| !cmd.hidden())
+            .collect();
         if !subcommands.is_empty() {
-            println!("\nSubcommands:");
-            for subcmd in subcommands {
-                // Skip the info command - it's a secret command
-                if subcmd.name() == "info" {
-                    continue;
-                }
-
-                let sub_aliases = subcmd.aliases();
-                let alias_text = if sub_aliases.is_empty() {
-                    String::new()
-                } else {
-                    let uppercase_aliases: Vec<String> =
-                        sub_aliases.iter().map(|a| a.to_uppercase()).collect();
-                    format!(" ({})", uppercase_aliases.join(", "))
-                };
-
-                let formatted_name = if self.colored {
-                    format!(
-                        "\x1b[1;36m{}\x1b[0m",
-                        self.format_command_name(subcmd.name())
-                    )
-                } else {
-                    self.format_command_name(subcmd.name())
-                };
-
-                println!(
-                    "  {}{} - {}",
-                    formatted_name,
-                    alias_text,
-                    subcmd.description()
-                );
+            self.write_line("\nSubcommands:");
+            for subcmd in &subcommands {
+                self.write_line(&self.command_line(subcmd.as_ref()));
             }
         }
     }
@@ -172,57 +388,269 @@ impl DisplayManager {
     /// Display a success message
     pub fn display_success(&self, message: &str) {
         if !message.is_empty() {
-            let icon = if self.unicode { "✓" } else { "OK" };
-            let color = if self.colored { "\x1b[1;32m" } else { "" };
-            let reset = if self.colored { "\x1b[0m" } else { "" };
+            let icon = self.icon(ICON_SUCCESS);
+            let color = if self.colored { COLOR_SUCCESS } else { "" };
+            let reset = if self.colored { COLOR_RESET } else { "" };
 
-            println!("{color}{icon} {message}{reset}");
+            self.write_line(&format!("{color}{icon} {message}{reset}"));
         }
     }
 
     /// Display a warning message
     pub fn display_warning(&self, message: &str) {
-        let icon = if self.unicode { "⚠" } else { "WARNING" };
-        let color = if self.colored { "\x1b[1;33m" } else { "" };
-        let reset = if self.colored { "\x1b[0m" } else { "" };
+        let icon = self.icon(ICON_WARNING);
+        let color = if self.colored { COLOR_WARNING } else { "" };
+        let reset = if self.colored { COLOR_RESET } else { "" };
 
-        println!("{color}{icon} {message}{reset}");
+        self.write_line(&format!("{color}{icon} {message}{reset}"));
     }
 
     /// Display an informational message
     pub fn display_info(&self, message: &str) {
-        let icon = if self.unicode { "ℹ" } else { "INFO" };
-        let color = if self.colored { "\x1b[1;34m" } else { "" };
-        let reset = if self.colored { "\x1b[0m" } else { "" };
+        let icon = self.icon(ICON_INFO);
+        let color = if self.colored { COLOR_INFO } else { "" };
+        let reset = if self.colored { COLOR_RESET } else { "" };
+
+        self.write_line(&format!("{color}{icon} {message}{reset}"));
+    }
 
-        println!("{color}{icon} {message}{reset}");
+    /// Display a plain message without an icon or color
+    pub fn display_text(&self, message: &str) {
+        self.write_line(message);
     }
 
     /// Clear the terminal screen
     pub fn clear_screen(&self) -> io::Result<()> {
-        print!("\x1b[2J\x1b[H");
-        io::stdout().flush()
+        TerminalUtils::clear_screen()
     }
 
-    /// Format a command name with bold alias character
-    pub fn format_command_with_alias(&self, name: &str, alias: Option<&str>) -> String {
-        if let Some(alias_char) = alias {
-            if let Some(first_char) = name.chars().next() {
-                if alias_char.starts_with(first_char) && self.colored {
-                    // Bold the first character if it matches the alias
-                    format!("\x1b[1m{}\x1b[0m{}", first_char, &name[1..])
-                } else {
-                    name.to_string()
+    /// Prompt for a secret, such as a passphrase, and read it without echo
+    ///
+    /// From a terminal, echo is turned off while the secret is typed and
+    /// turned back on afterwards, whether reading succeeds, fails, or is
+    /// cancelled with Ctrl-C. Other input is read as a normal line.
+    ///
+    /// # Arguments
+    /// * `prompt` - Text shown before the secret is typed
+    /// * `io` - The session input to read from
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The secret, without its line terminator
+    /// * `Err(CliError)` - `InvalidInput` if no secret was entered,
+    ///   `InputInterrupted` if Ctrl-C was pressed, `TerminalError` if echo
+    ///   could not be turned off, or any error from reading
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::core::io::SessionIo;
+    /// use sm_menu::ui::DisplayManager;
+    ///
+    /// let display = DisplayManager::with_writer(false, false, Box::new(Vec::new()));
+    /// let mut io = SessionIo::from_bytes("hunter2\n");
+    /// assert_eq!(display.read_secret("Passphrase: ", &mut io).unwrap(), "hunter2");
+    /// ```
+    pub fn read_secret(&self, prompt: &str, io: &mut SessionIo) -> CliResult<String> {
+        self.read_secret_with(prompt, io, TerminalUtils::disable_echo)
+    }
+
+    /// Read a secret, turning echo off with `disable_echo` when the input is
+    /// a terminal
+    ///
+    /// Echo stays off until the guard `disable_echo` returns is dropped.
+    fn read_secret_with<G>(
+        &self,
+        prompt: &str,
+        io: &mut SessionIo,
+        disable_echo: impl FnOnce() -> io::Result<G>,
+    ) -> CliResult<String> {
+        self.write_text(prompt);
+
+        if !io.input_is_terminal() {
+            // A secret is never copied to the transcript
+            let echo = io.set_echo(None);
+            let line = io.read_line();
+            io.set_echo(echo);
+            let line = line?.ok_or_else(|| CliError::invalid_input("No secret entered"))?;
+            return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+        }
+
+        let secret = {
+            let _echo_off = disable_echo()
+                .map_err(|e| CliError::terminal_error(&format!("Failed to turn off echo: {e}")))?;
+            read_keys(io)
+        };
+        // The key that ended the secret was not echoed either
+        self.write_text("\n");
+        secret
+    }
+
+    /// Prompt for a command line and read it with line editing
+    ///
+    /// The terminal is in raw mode while the line is typed, so the keys of
+    /// [`LineKey`] edit it and Ctrl-C reaches `reader` as a key instead of
+    /// ending the program. The line is redrawn after every key. Ctrl-D on
+    /// an empty line ends the input, and Ctrl-P on an empty line is
+    /// submitted as itself, so the session can open its picker.
+    ///
+    /// # Arguments
+    /// * `prompt` - Text shown before the line
+    /// * `reader` - Line reader kept across prompts, which decides what
+    ///   Ctrl-C does and may start the line with abandoned text
+    /// * `io` - The session input to read keys from
+    ///
+    /// # Returns
+    /// * `Ok(Some(line))` - The line typed, ending with `\n`
+    /// * `Ok(None)` - Input ended, or Ctrl-C was pressed twice in a row
+    /// * `Err(CliError)` - `InputInterrupted` if Ctrl-C abandoned the line,
+    ///   `InvalidInput` if it grew past the maximum line length,
+    ///   `TerminalError` if raw mode could not be turned on, or any error
+    ///   from reading
+    pub fn read_edited_line(
+        &self,
+        prompt: &str,
+        reader: &mut LineReader,
+        io: &mut SessionIo,
+    ) -> CliResult<Option<String>> {
+        self.read_edited_line_with(prompt, reader, io, TerminalUtils::enable_raw_mode)
+    }
+
+    /// Read an edited line, with the terminal in raw mode until the guard
+    /// `enable_raw_mode` returns is dropped
+    fn read_edited_line_with<G>(
+        &self,
+        prompt: &str,
+        reader: &mut LineReader,
+        io: &mut SessionIo,
+        enable_raw_mode: impl FnOnce() -> io::Result<G>,
+    ) -> CliResult<Option<String>> {
+        let event = {
+            let _raw_mode = enable_raw_mode()
+                .map_err(|e| CliError::terminal_error(&format!("Failed to enter raw mode: {e}")))?;
+            self.write_text(&reader.line().redraw(prompt));
+            self.edit_line(prompt, reader, io)?
+        };
+
+        match event {
+            LineEvent::Submitted(line) => {
+                // Raw mode did not echo the Enter that ended the line
+                self.write_text("\n");
+                Ok(Some(line + "\n"))
+            }
+            LineEvent::Interrupted => Err(CliError::InputInterrupted),
+            LineEvent::Editing | LineEvent::Exit => {
+                self.write_text("\n");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Apply keys to the line until it is submitted, abandoned, or the
+    /// input ends, which is reported as [`LineEvent::Exit`]
+    fn edit_line(
+        &self,
+        prompt: &str,
+        reader: &mut LineReader,
+        io: &mut SessionIo,
+    ) -> CliResult<LineEvent> {
+        const CTRL_D: u8 = 0x04;
+        const CTRL_P: u8 = 0x10;
+
+        loop {
+            let Some(bytes) = read_key_bytes(io)? else {
+                return Ok(LineEvent::Exit);
+            };
+            if reader.line().is_empty() {
+                match bytes[..] {
+                    [CTRL_D] => return Ok(LineEvent::Exit),
+                    [CTRL_P] => return Ok(LineEvent::Submitted("\x10".to_string())),
+                    _ => {}
                 }
-            } else {
-                name.to_string()
             }
-        } else {
-            name.to_string()
+
+            for key in LineKey::decode_all(&bytes) {
+                let event = reader.handle_key(key);
+                if event != LineEvent::Editing {
+                    return Ok(event);
+                }
+            }
+            if reader.line().text().len() > io.max_line_length() {
+                return Err(CliError::invalid_input(&format!(
+                    "input too long (maximum: {} bytes)",
+                    io.max_line_length()
+                )));
+            }
+            self.write_text(&reader.line().redraw(prompt));
         }
     }
 
-    /// Format a command name with bold characters for display
+    /// Format a command name with its alias in bold
+    ///
+    /// When colored and the alias begins the name, that part of the name is
+    /// bold and its first letter capitalized, so `show` with the alias `sh`
+    /// is shown as a bold `Sh` followed by `ow`. Otherwise the name is
+    /// returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::DisplayManager;
+    ///
+    /// let dm = DisplayManager::with_options(true, true);
+    /// assert_eq!(dm.format_command_with_alias("show", Some("sh")), "\x1b[1mSh\x1b[0mow");
+    /// assert_eq!(dm.format_command_with_alias("help", Some("?")), "help");
+    /// ```
+    pub fn format_command_with_alias(&self, name: &str, alias: Option<&str>) -> String {
+        let prefix_len = alias.filter(|_| self.colored).and_then(|alias| {
+            let prefix = name.get(..alias.len())?;
+            (!alias.is_empty() && prefix.eq_ignore_ascii_case(alias)).then_some(alias.len())
+        });
+        match prefix_len {
+            Some(len) => {
+                let mut chars = name[..len].chars();
+                let first: String = chars
+                    .next()
+                    .into_iter()
+                    .flat_map(char::to_uppercase)
+                    .collect();
+                format!("{}{}", ansi::bold(&(first + chars.as_str())), &name[len..])
+            }
+            None => name.to_string(),
+        }
+    }
+
+    /// Build the hint listing the shortcut letters of a menu's commands
+    ///
+    /// A command's shortcut is an alias that is the first letter of its
+    /// name. The letter is bold when colored, and bracketed otherwise.
+    /// Returns `None` when no visible command has a shortcut.
+    pub fn shortcut_hint(&self, commands: &[Box<dyn Command>]) -> Option<String> {
+        let shortcuts: Vec<String> = commands
+            .iter()
+            .filter(|cmd| !cmd.hidden())
+            .filter_map(|cmd| {
+                let name = cmd.name();
+                let first = name.chars().next()?;
+                cmd.aliases()
+                    .iter()
+                    .any(|alias| alias.chars().eq([first]))
+                    .then(|| {
+                        let rest = &name[first.len_utf8()..];
+                        if self.colored {
+                            format!("{}{rest}", ansi::bold(&first.to_string()))
+                        } else {
+                            format!("[{first}]{rest}")
+                        }
+                    })
+            })
+            .collect();
+
+        (!shortcuts.is_empty()).then(|| format!("Shortcuts: {}", shortcuts.join(" ")))
+    }
+
+    /// Format a built-in command name with bold characters for display
+    ///
+    /// Knows the built-in commands by name. Listings use
+    /// [`DisplayManager::format_command_with_alias`] with the command's
+    /// [`Command::primary_alias`] instead, which works for any command.
     pub fn format_command_name(&self, name: &str) -> String {
         if !self.colored {
             return name.to_string();
@@ -232,81 +660,104 @@ impl DisplayManager {
         match name.to_lowercase().as_str() {
             "file" => {
                 // Bold the 'F' in 'File' (alias: f)
-                "\x1b[1mF\x1b[0mile".to_string()
+                format!("{}ile", ansi::bold("F")).to_string()
             }
             "edit" => {
                 // Bold the 'E' in 'Edit' (alias: e)
-                "\x1b[1mE\x1b[0mdit".to_string()
+                format!("{}dit", ansi::bold("E")).to_string()
             }
             "view" => {
                 // Bold the 'V' in 'View' (alias: v)
-                "\x1b[1mV\x1b[0miew".to_string()
+                format!("{}iew", ansi::bold("V")).to_string()
             }
             "help" => {
                 // Bold the 'H' in 'Help' (alias: h)
-                "\x1b[1mH\x1b[0melp".to_string()
+                format!("{}elp", ansi::bold("H")).to_string()
             }
             "quit" => {
                 // Bold the 'Q' in 'Quit' (alias: q)
-                "\x1b[1mQ\x1b[0muit".to_string()
+                format!("{}uit", ansi::bold("Q")).to_string()
             }
             "info" => {
                 // Bold the 'I' in 'Info' (alias: i)
-                "\x1b[1mI\x1b[0mnfo".to_string()
+                format!("{}nfo", ansi::bold("I")).to_string()
             }
             "load" => {
                 // Bold the 'L' in 'Load' (alias: l)
-                "\x1b[1mL\x1b[0moad".to_string()
+                format!("{}oad", ansi::bold("L")).to_string()
             }
             "save" => {
                 // Bold the 'S' in 'Save' (alias: s)
-                "\x1b[1mS\x1b[0mave".to_string()
+                format!("{}ave", ansi::bold("S")).to_string()
             }
             "exit" => {
                 // Bold the 'E' in 'Exit' (alias: e)
-                "\x1b[1mE\x1b[0mxit".to_string()
+                format!("{}xit", ansi::bold("E")).to_string()
             }
             "axis" => {
                 // Bold the 'A' in 'Axis' (alias: a)
-                "\x1b[1mA\x1b[0mxis".to_string()
+                format!("{}xis", ansi::bold("A")).to_string()
             }
             "show" => {
-                // Bold the 'S' in 'Show' (alias: sh)
-                "\x1b[1mS\x1b[0mhow".to_string()
+                // Bold the 'Sh' in 'Show' (alias: sh)
+                format!("{}ow", ansi::bold("Sh")).to_string()
             }
             "vers" => {
                 // Bold the 'V' in 'Vers' (alias: v)
-                "\x1b[1mV\x1b[0mers".to_string()
+                format!("{}ers", ansi::bold("V")).to_string()
+            }
+            "undo" => {
+                // Bold the 'U' in 'Undo' (alias: u)
+                format!("{}ndo", ansi::bold("U")).to_string()
+            }
+            "redo" => {
+                // Bold the 'R' in 'Redo' (alias: r)
+                format!("{}edo", ansi::bold("R")).to_string()
             }
             _ => name.to_string(),
         }
     }
 
     /// Display a progress indicator
+    ///
+    /// Every call redraws the bar. For frequent updates use
+    /// [`DisplayManager::start_progress`], which throttles redraws.
     pub fn display_progress(&self, message: &str, current: usize, total: usize) {
-        let percentage = if total > 0 {
-            (current * 100) / total
-        } else {
-            0
-        };
-
-        let bar_width = 30;
-        let filled = (percentage * bar_width) / 100;
-        let empty = bar_width - filled;
+        if !self.show_progress {
+            return;
+        }
+        print!("{}", render_progress(self.unicode, message, current, total));
+        io::stdout().flush().unwrap_or(());
+    }
 
-        let bar = if self.unicode {
-            format!("{}{}", "█".repeat(filled), "░".repeat(empty))
-        } else {
-            format!("{}{}", "=".repeat(filled), "-".repeat(empty))
-        };
+    /// Start a throttled progress bar on stdout
+    pub fn start_progress(&self, message: &str, total: usize) -> ProgressBar<io::Stdout> {
+        self.start_progress_to(message, total, io::stdout())
+    }
 
-        print!("\r{message}: [{bar}] {percentage}% ({current}/{total})");
-        io::stdout().flush().unwrap_or(());
+    /// Start a throttled progress bar on the given writer
+    pub fn start_progress_to<W: Write>(
+        &self,
+        message: &str,
+        total: usize,
+        writer: W,
+    ) -> ProgressBar<W> {
+        ProgressBar {
+            writer,
+            message: message.to_string(),
+            total,
+            unicode: self.unicode,
+            visible: self.show_progress,
+            last_draw: None,
+            draws: 0,
+        }
     }
 
     /// Finish progress display
     pub fn finish_progress(&self) {
-        println!();
+        if self.show_progress {
+            println!();
+        }
     }
 }
 
@@ -316,6 +767,214 @@ impl Default for DisplayManager {
     }
 }
 
+/// Writer that goes through `print!`, so test harnesses capture it
+struct PrintWriter;
+
+impl Write for PrintWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        print!("{}", String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+/// Writer that keeps what was written, shared between clones
+///
+/// Handing a clone to [`DisplayManager::replace_writer`] collects what a
+/// command shows, for example to send it somewhere else or check it in a
+/// test.
+///
+/// # Examples
+/// ```
+/// use sm_menu::ui::{CapturedOutput, DisplayManager};
+///
+/// let output = CapturedOutput::default();
+/// let display = DisplayManager::with_writer(false, false, Box::new(output.clone()));
+/// display.display_text("hello");
+/// assert_eq!(output.text(), "hello\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CapturedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl CapturedOutput {
+    /// Get everything written so far, as text
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+
+    /// Discard everything written so far
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+impl Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer that goes through `eprint!`, so test harnesses capture it
+struct EprintWriter;
+
+impl Write for EprintWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        eprint!("{}", String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+impl fmt::Debug for DisplayManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DisplayManager")
+            .field("colored", &self.colored)
+            .field("unicode", &self.unicode)
+            .field("show_suggestions", &self.show_suggestions)
+            .field("trace_errors", &self.trace_errors)
+            .field("show_progress", &self.show_progress)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Render a progress bar line, starting with a carriage return
+fn render_progress(unicode: bool, message: &str, current: usize, total: usize) -> String {
+    let percentage = (current * 100).checked_div(total).unwrap_or(0).min(100);
+
+    let bar_width = 30;
+    let filled = (percentage * bar_width) / 100;
+    let empty = bar_width - filled;
+
+    let bar = if unicode {
+        format!("{}{}", "█".repeat(filled), "░".repeat(empty))
+    } else {
+        format!("{}{}", "=".repeat(filled), "-".repeat(empty))
+    };
+
+    format!("\r{message}: [{bar}] {percentage}% ({current}/{total})")
+}
+
+/// Read a secret typed a key at a time, until Enter
+///
+/// Backspace removes the last character, Ctrl-C cancels, and Ctrl-D on an
+/// empty secret ends the input.
+fn read_keys(io: &mut SessionIo) -> CliResult<String> {
+    const CTRL_C: u8 = 0x03;
+    const CTRL_D: u8 = 0x04;
+    const BACKSPACE: u8 = 0x08;
+    const DELETE: u8 = 0x7f;
+
+    let mut secret = Vec::new();
+    loop {
+        match io.read_byte()? {
+            None | Some(b'\r' | b'\n') => break,
+            Some(CTRL_C) => return Err(CliError::InputInterrupted),
+            Some(CTRL_D) if secret.is_empty() => break,
+            Some(BACKSPACE | DELETE) => {
+                // Remove the continuation bytes of the last character too
+                while secret.pop().is_some_and(|byte| byte & 0xc0 == 0x80) {}
+            }
+            Some(byte) => secret.push(byte),
+        }
+        if secret.len() > io.max_line_length() {
+            return Err(CliError::invalid_input(&format!(
+                "input too long (maximum: {} bytes)",
+                io.max_line_length()
+            )));
+        }
+    }
+
+    if secret.is_empty() {
+        return Err(CliError::invalid_input("No secret entered"));
+    }
+    String::from_utf8(secret).map_err(|_| CliError::invalid_input("input is not valid UTF-8"))
+}
+
+/// Read the bytes of one key from raw terminal input
+///
+/// A key is one character, which may take several bytes of UTF-8, or an
+/// escape sequence such as an arrow key. Returns `None` when the input
+/// ends.
+fn read_key_bytes(io: &mut SessionIo) -> CliResult<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    while ansi::key_len(&bytes).is_none() {
+        match io.read_byte()? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+    Ok((!bytes.is_empty()).then_some(bytes))
+}
+
+/// Progress bar that limits how often it redraws
+///
+/// Updates arriving faster than about 30 per second are skipped, except
+/// the final one at or past the total, which is always drawn.
+pub struct ProgressBar<W: Write> {
+    /// Where the bar is drawn
+    writer: W,
+    /// Label shown before the bar
+    message: String,
+    /// Value that counts as complete
+    total: usize,
+    /// Whether to draw the bar with unicode blocks
+    unicode: bool,
+    /// Whether the bar is written at all
+    visible: bool,
+    /// When the bar was last drawn
+    last_draw: Option<Instant>,
+    /// Number of times the bar has been drawn
+    draws: usize,
+}
+
+impl<W: Write> ProgressBar<W> {
+    /// Report progress, redrawing the bar unless it was drawn too recently
+    pub fn update(&mut self, current: usize) {
+        let complete = current >= self.total;
+        let due = self
+            .last_draw
+            .is_none_or(|last| last.elapsed() >= PROGRESS_REDRAW_INTERVAL);
+        if complete || due {
+            self.draw(current);
+        }
+    }
+
+    /// Get the number of times the bar has been drawn
+    pub fn draws(&self) -> usize {
+        self.draws
+    }
+
+    /// End the progress display, moving to the next line
+    pub fn finish(mut self) -> W {
+        if self.visible {
+            let _ = writeln!(self.writer);
+            let _ = self.writer.flush();
+        }
+        self.writer
+    }
+
+    fn draw(&mut self, current: usize) {
+        if self.visible {
+            let line = render_progress(self.unicode, &self.message, current, self.total);
+            let _ = write!(self.writer, "{line}");
+            let _ = self.writer.flush();
+        }
+        self.last_draw = Some(Instant::now());
+        self.draws += 1;
+    }
+}
+
 /// Terminal utilities for low-level terminal operations
 pub struct TerminalUtils;
 
@@ -339,40 +998,204 @@ impl TerminalUtils {
         DEFAULT_TERMINAL_HEIGHT
     }
 
+    /// Clear the screen and move the cursor to the top-left corner
+    pub fn clear_screen() -> io::Result<()> {
+        print!("{}", ansi::clear_screen());
+        io::stdout().flush()
+    }
+
     /// Move cursor to position
     pub fn move_cursor(x: usize, y: usize) -> io::Result<()> {
-        print!("\x1b[{y};{x}H");
+        print!("{}", ansi::cursor_to(x, y));
         io::stdout().flush()
     }
 
     /// Hide cursor
     pub fn hide_cursor() -> io::Result<()> {
-        print!("\x1b[?25l");
+        print!("{}", ansi::hide_cursor());
         io::stdout().flush()
     }
 
     /// Show cursor
     pub fn show_cursor() -> io::Result<()> {
-        print!("\x1b[?25h");
+        print!("{}", ansi::show_cursor());
         io::stdout().flush()
     }
 
     /// Save cursor position
     pub fn save_cursor() -> io::Result<()> {
-        print!("\x1b[s");
+        print!("{}", ansi::save_cursor());
         io::stdout().flush()
     }
 
     /// Restore cursor position
     pub fn restore_cursor() -> io::Result<()> {
-        print!("\x1b[u");
+        print!("{}", ansi::restore_cursor());
         io::stdout().flush()
     }
+
+    /// Put the terminal into raw mode (no line buffering, no echo)
+    ///
+    /// The previous settings are saved so that [`TerminalUtils::restore_terminal`]
+    /// can put them back even if the program panics. They are also restored
+    /// when the returned guard is dropped.
+    pub fn enable_raw_mode() -> io::Result<RawModeGuard> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        *saved_tty_state() = Some(saved.trim().to_string());
+        Ok(RawModeGuard { _private: () })
+    }
+
+    /// Leave raw mode, if it is active
+    pub fn disable_raw_mode() -> io::Result<()> {
+        let saved = saved_tty_state().take();
+        match saved {
+            Some(settings) => stty(&[&settings]).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Check if raw mode is active
+    pub fn is_raw_mode() -> bool {
+        saved_tty_state().is_some()
+    }
+
+    /// Stop the terminal echoing what is typed, until the guard is dropped
+    ///
+    /// Input is also passed on a key at a time, with Enter read as `\r` and
+    /// Ctrl-C read as a key rather than ending the program, so echo can
+    /// always be turned back on. On Unix the terminal is put into raw mode;
+    /// on Windows the console's echo, line, and Ctrl-C processing are
+    /// turned off.
+    pub fn disable_echo() -> io::Result<EchoGuard> {
+        #[cfg(windows)]
+        let guard = EchoGuard {
+            _console: console::disable_echo()?,
+        };
+        #[cfg(not(windows))]
+        let guard = EchoGuard {
+            _raw_mode: Self::enable_raw_mode()?,
+        };
+        Ok(guard)
+    }
+
+    /// Write the sequences that reset styling and show the cursor
+    pub fn write_restore_sequences(out: &mut impl Write) -> io::Result<()> {
+        // Reset text styling (SGR) and show the cursor
+        write!(out, "{}{}", ansi::RESET, ansi::show_cursor())?;
+        out.flush()
+    }
+
+    /// Return the terminal to a usable state
+    ///
+    /// Leaves raw mode, resets text styling, and shows the cursor. This is
+    /// safe to call at any time, including from a panic hook.
+    pub fn restore_terminal() -> io::Result<()> {
+        let raw_mode = Self::disable_raw_mode();
+        Self::write_restore_sequences(&mut io::stdout())?;
+        raw_mode
+    }
+}
+
+/// Guard that leaves raw mode when dropped
+#[derive(Debug)]
+pub struct RawModeGuard {
+    _private: (),
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = TerminalUtils::disable_raw_mode();
+    }
+}
+
+/// Guard that turns terminal echo back on when dropped
+#[derive(Debug)]
+pub struct EchoGuard {
+    #[cfg(not(windows))]
+    _raw_mode: RawModeGuard,
+    #[cfg(windows)]
+    _console: console::SavedMode,
+}
+
+/// Console mode changes through the Windows API
+#[cfg(windows)]
+mod console {
+    use std::ffi::c_void;
+    use std::io;
+
+    const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+    const ENABLE_PROCESSED_INPUT: u32 = 0x0001;
+    const ENABLE_LINE_INPUT: u32 = 0x0002;
+    const ENABLE_ECHO_INPUT: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetStdHandle(std_handle: u32) -> *mut c_void;
+        fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+    }
+
+    /// Console input mode from before echo was turned off, put back on drop
+    #[derive(Debug)]
+    pub(super) struct SavedMode {
+        handle: *mut c_void,
+        mode: u32,
+    }
+
+    /// Turn off echo, line input, and Ctrl-C processing for console input
+    pub(super) fn disable_echo() -> io::Result<SavedMode> {
+        let disabled = ENABLE_ECHO_INPUT | ENABLE_LINE_INPUT | ENABLE_PROCESSED_INPUT;
+        // SAFETY: the handle comes from GetStdHandle and `mode` outlives the
+        // call that writes it
+        unsafe {
+            let handle = GetStdHandle(STD_INPUT_HANDLE);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0
+                || SetConsoleMode(handle, mode & !disabled) == 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(SavedMode { handle, mode })
+        }
+    }
+
+    impl Drop for SavedMode {
+        fn drop(&mut self) {
+            // SAFETY: the handle was valid when the mode was saved
+            unsafe {
+                SetConsoleMode(self.handle, self.mode);
+            }
+        }
+    }
+}
+
+/// Lock the saved terminal settings, even if a panic poisoned the lock
+fn saved_tty_state() -> std::sync::MutexGuard<'static, Option<String>> {
+    SAVED_TTY_STATE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Run `stty` against the terminal on stdin and return its output
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = process::Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
 
     #[test]
     fn test_display_manager_creation() {
@@ -394,6 +1217,29 @@ mod tests {
 
         let result = dm.format_command_with_alias("test", None);
         assert_eq!(result, "test");
+
+        let dm = DisplayManager::with_options(true, true);
+        let result = dm.format_command_with_alias("help", Some("h"));
+        assert_eq!(result, "\x1b[1mH\x1b[0melp");
+
+        // Only an alias that begins the name is bolded
+        let result = dm.format_command_with_alias("history", Some("hist"));
+        assert_eq!(result, "\x1b[1mHist\x1b[0mory");
+        let result = dm.format_command_with_alias("list", Some("ls"));
+        assert_eq!(result, "list");
+        let result = dm.format_command_with_alias("sh", Some("show"));
+        assert_eq!(result, "sh");
+    }
+
+    #[test]
+    fn test_listing_bolds_primary_alias() {
+        let show = crate::commands::show::ShowCommand::new();
+        assert_eq!(show.primary_alias(), Some("sh"));
+
+        let dm = DisplayManager::with_options(true, true);
+        assert!(dm.command_line(&show).starts_with(&format!(
+            "  {COLOR_CYAN}\x1b[1mSh\x1b[0mow{COLOR_RESET} (SH)"
+        )));
     }
 
     #[test]
@@ -432,11 +1278,17 @@ mod tests {
         assert_eq!(result, "\x1b[1mA\x1b[0mxis");
 
         let result = dm.format_command_name("show");
-        assert_eq!(result, "\x1b[1mS\x1b[0mhow");
+        assert_eq!(result, "\x1b[1mSh\x1b[0mow");
 
         let result = dm.format_command_name("vers");
         assert_eq!(result, "\x1b[1mV\x1b[0mers");
 
+        let result = dm.format_command_name("undo");
+        assert_eq!(result, "\x1b[1mU\x1b[0mndo");
+
+        let result = dm.format_command_name("redo");
+        assert_eq!(result, "\x1b[1mR\x1b[0medo");
+
         // Test case insensitive matching
         let result = dm.format_command_name("View");
         assert_eq!(result, "\x1b[1mV\x1b[0miew");
@@ -457,9 +1309,226 @@ mod tests {
         assert_eq!(result, "file");
     }
 
+    #[test]
+    fn test_trace_errors_shows_cause_chain() {
+        let error = CliError::execution_error_with_source(
+            "Cannot write backup",
+            io::Error::new(io::ErrorKind::StorageFull, "no space left on device"),
+        );
+        let mut display = DisplayManager::with_options(false, false);
+        assert!(!display.traces_errors());
+        assert_eq!(
+            display.format_error(&error),
+            ["X Command execution failed: Cannot write backup"]
+        );
+
+        display.set_trace_errors(true);
+        assert_eq!(
+            display.format_error(&error),
+            [
+                "X Command execution failed: Cannot write backup",
+                "  caused by: no space left on device",
+            ]
+        );
+
+        // Errors without a cause are unchanged
+        let plain = CliError::execution_error("test");
+        assert_eq!(display.format_error(&plain).len(), 1);
+    }
+
+    #[test]
+    fn test_ascii_mode_icons() {
+        let errors = [
+            CliError::InvalidCommand("test".to_string()),
+            CliError::execution_error("test"),
+            CliError::InternalError("test".to_string()),
+        ];
+
+        let ascii = DisplayManager::with_options(false, false);
+        for error in &errors {
+            assert!(ascii.error_icon(error).is_ascii());
+        }
+        for icon in [ICON_SUCCESS, ICON_WARNING, ICON_INFO] {
+            assert!(ascii.icon(icon).is_ascii());
+        }
+
+        let unicode = DisplayManager::with_options(false, true);
+        for error in &errors {
+            assert_eq!(unicode.error_icon(error), error.icon());
+        }
+        assert_eq!(unicode.icon(ICON_SUCCESS), "✓");
+    }
+
     #[test]
     fn test_terminal_utils() {
         assert_eq!(TerminalUtils::get_width(), DEFAULT_TERMINAL_WIDTH);
         assert_eq!(TerminalUtils::get_height(), DEFAULT_TERMINAL_HEIGHT);
     }
+
+    #[test]
+    fn test_progress_bar_throttles_redraws() {
+        let dm = DisplayManager::with_options(false, false);
+        let total = 100_000;
+        let start = Instant::now();
+        let mut bar = dm.start_progress_to("Loading", total, Vec::new());
+        for current in 1..=total {
+            bar.update(current);
+        }
+        let elapsed = start.elapsed();
+
+        // At most one draw per interval, plus the first and the final draw
+        let bound = (elapsed.as_millis() / PROGRESS_REDRAW_INTERVAL.as_millis()) as usize + 2;
+        assert!(bar.draws() <= bound, "{} draws in {elapsed:?}", bar.draws());
+
+        let output = String::from_utf8(bar.finish()).unwrap();
+        assert!(output.ends_with("100% (100000/100000)\n"), "{output}");
+    }
+
+    #[test]
+    fn test_hidden_progress_bar_writes_nothing() {
+        let mut dm = DisplayManager::with_options(false, true);
+        dm.set_show_progress(false);
+        let mut bar = dm.start_progress_to("Saving", 10, Vec::new());
+        bar.update(10);
+        assert_eq!(bar.draws(), 1);
+        assert!(bar.finish().is_empty());
+    }
+
+    #[test]
+    fn test_progress_bar_first_update_draws() {
+        let dm = DisplayManager::with_options(false, true);
+        let mut bar = dm.start_progress_to("Saving", 10, Vec::new());
+        bar.update(1);
+        bar.update(2);
+        assert_eq!(bar.draws(), 1);
+        let output = String::from_utf8(bar.finish()).unwrap();
+        assert!(output.starts_with("\rSaving: [███░"), "{output}");
+    }
+
+    /// Guard that records when it is dropped
+    struct RecordDrop(Rc<Cell<bool>>);
+
+    impl Drop for RecordDrop {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    #[test]
+    fn test_read_secret_reads_line_without_terminal() {
+        let display = DisplayManager::with_writer(false, false, Box::new(Vec::new()));
+        let mut io = SessionIo::from_bytes("s3cret phrase\r\nnext\n");
+
+        // Echo is left alone when the input is not a terminal
+        let secret = display.read_secret_with("Passphrase: ", &mut io, || -> io::Result<()> {
+            panic!("echo must not be changed")
+        });
+        assert_eq!(secret.unwrap(), "s3cret phrase");
+        assert_eq!(io.read_line().unwrap(), Some("next\n".to_string()));
+        assert!(matches!(
+            display.read_secret("Passphrase: ", &mut io),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_secret_restores_echo() {
+        let display = DisplayManager::with_writer(false, false, Box::new(Vec::new()));
+        let read = |input: &'static [u8]| {
+            let restored = Rc::new(Cell::new(false));
+            let guard = RecordDrop(Rc::clone(&restored));
+            let mut io = SessionIo::from_reader(input, true);
+            let secret = display.read_secret_with("Passphrase: ", &mut io, || Ok(guard));
+            assert!(restored.get(), "echo left off after {input:?}");
+            secret
+        };
+
+        // Backspace removes a whole character
+        assert_eq!(read(b"pass\xc3\xa9\x7f\x7fs\r").unwrap(), "pass");
+        // Ctrl-C, bad input, and failing to turn echo off all restore it
+        assert_eq!(read(b"pa\x03ss\r"), Err(CliError::InputInterrupted));
+        assert!(matches!(read(b"\xff\r"), Err(CliError::InvalidInput(_))));
+        assert!(matches!(read(b"\r"), Err(CliError::InvalidInput(_))));
+
+        let mut io = SessionIo::from_reader(&b"pass\r"[..], true);
+        let failed = display.read_secret_with("Passphrase: ", &mut io, || -> io::Result<()> {
+            Err(io::Error::other("not a tty"))
+        });
+        assert!(matches!(failed, Err(CliError::TerminalError(_))));
+    }
+
+    #[test]
+    fn test_read_edited_line_applies_keys_in_raw_mode() {
+        let output = CapturedOutput::default();
+        let display = DisplayManager::with_writer(false, false, Box::new(output.clone()));
+        let mut reader = LineReader::new(false);
+        let read = |input: &'static [u8], reader: &mut LineReader| {
+            let restored = Rc::new(Cell::new(false));
+            let guard = RecordDrop(Rc::clone(&restored));
+            let mut io = SessionIo::from_reader(input, true);
+            let line = display.read_edited_line_with("> ", reader, &mut io, || Ok(guard));
+            assert!(restored.get(), "raw mode left on after {input:?}");
+            line
+        };
+
+        // Arrows, Backspace, Home, End and a multi-byte character edit the line,
+        // and Delete, which has no binding, leaves no bytes behind
+        assert_eq!(
+            read(
+                b"lod\x1b[3~\x1b[Da\x1b[C x\x7f\xc3\xa9\x01\x05\r",
+                &mut reader
+            )
+            .unwrap(),
+            Some("load \u{e9}\n".to_string())
+        );
+        let drawn = output.text();
+        assert!(drawn.starts_with("\r\x1b[2K> \r\x1b[2K> l"), "{drawn:?}");
+        assert!(drawn.ends_with("\r\x1b[2K> load \u{e9}\n"), "{drawn:?}");
+
+        // Ctrl-C abandons the line, and a second one right after exits
+        assert_eq!(
+            read(b"ls\x03", &mut reader),
+            Err(CliError::InputInterrupted)
+        );
+        assert_eq!(read(b"\x03", &mut reader), Ok(None));
+
+        // Ctrl-D and the end of input end the session on an empty line
+        assert_eq!(read(b"\x04", &mut reader), Ok(None));
+        assert_eq!(read(b"", &mut reader), Ok(None));
+        assert_eq!(read(b"\x10", &mut reader), Ok(Some("\x10\n".to_string())));
+
+        let mut io = SessionIo::from_reader(&b"ls\r"[..], true);
+        let failed =
+            display.read_edited_line_with("> ", &mut reader, &mut io, || -> io::Result<()> {
+                Err(io::Error::other("not a tty"))
+            });
+        assert!(matches!(failed, Err(CliError::TerminalError(_))));
+    }
+
+    #[test]
+    fn test_read_edited_line_keeps_interrupted_text() {
+        let display = DisplayManager::with_writer(false, false, Box::new(Vec::new()));
+        let mut reader = LineReader::new(true);
+        let mut read = |input: &'static [u8]| {
+            let mut io = SessionIo::from_reader(input, true);
+            display.read_edited_line_with("> ", &mut reader, &mut io, || Ok(()))
+        };
+
+        assert_eq!(read(b"save no\x03"), Err(CliError::InputInterrupted));
+        assert_eq!(read(b"tes.txt\r"), Ok(Some("save notes.txt\n".to_string())));
+    }
+
+    #[test]
+    fn test_restore_sequences() {
+        let mut captured = Vec::new();
+        TerminalUtils::write_restore_sequences(&mut captured).unwrap();
+        let captured = String::from_utf8(captured).unwrap();
+
+        // Reset SGR styling, then show the cursor
+        assert_eq!(captured, "\x1b[0m\x1b[?25h");
+
+        // Leaving raw mode when it was never entered is a no-op
+        assert!(!TerminalUtils::is_raw_mode());
+        assert!(TerminalUtils::disable_raw_mode().is_ok());
+    }
 }
@@ -0,0 +1,488 @@
+//! Editable input line for the CLI interface.
+//!
+//! [`LineBuffer`] holds the line being typed and the cursor position, with
+//! one method per readline-style motion. It does no terminal IO, so every
+//! motion can be tested on its own; a raw-mode reader decodes keys with
+//! [`LineKey::decode_all`], applies them, and redraws with
+//! [`LineBuffer::redraw`]. [`LineReader`] adds the Ctrl-C handling on top:
+//! one Ctrl-C abandons the line, and a second one in a row exits.
+
+use super::ansi::{self, RawKey};
+
+/// Key understood by the line buffer, decoded from raw terminal input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKey {
+    /// A character to insert at the cursor
+    Char(char),
+    /// Left arrow
+    Left,
+    /// Right arrow
+    Right,
+    /// Ctrl-A
+    Home,
+    /// Ctrl-E
+    End,
+    /// Backspace
+    Backspace,
+    /// Ctrl-W
+    DeleteWord,
+    /// Ctrl-U
+    DeleteToStart,
+    /// Ctrl-K
+    DeleteToEnd,
+    /// Enter
+    Enter,
+    /// Ctrl-C
+    Interrupt,
+    /// Any other key or sequence, which is ignored
+    Other,
+}
+
+impl LineKey {
+    /// Decode every key in a chunk of raw terminal input
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::line_buffer::LineKey;
+    ///
+    /// assert_eq!(
+    ///     LineKey::decode_all(b"a\x1b[D\x01\r"),
+    ///     [LineKey::Char('a'), LineKey::Left, LineKey::Home, LineKey::Enter]
+    /// );
+    /// ```
+    pub fn decode_all(mut bytes: &[u8]) -> Vec<LineKey> {
+        let mut keys = Vec::new();
+        while !bytes.is_empty() {
+            let (raw, used) = ansi::split_key(bytes);
+            let key = match raw {
+                RawKey::Sequence(b'C') => LineKey::Right,
+                RawKey::Sequence(b'D') => LineKey::Left,
+                RawKey::Char('\x01') => LineKey::Home,
+                RawKey::Char('\x03') => LineKey::Interrupt,
+                RawKey::Char('\x05') => LineKey::End,
+                RawKey::Char('\x0b') => LineKey::DeleteToEnd,
+                RawKey::Char('\x15') => LineKey::DeleteToStart,
+                RawKey::Char('\x17') => LineKey::DeleteWord,
+                RawKey::Char('\x7f' | '\x08') => LineKey::Backspace,
+                RawKey::Char('\r' | '\n') => LineKey::Enter,
+                RawKey::Char(c) if !c.is_control() => LineKey::Char(c),
+                _ => LineKey::Other,
+            };
+            keys.push(key);
+            bytes = &bytes[used..];
+        }
+        keys
+    }
+}
+
+/// Line being typed, with a cursor between its characters
+///
+/// The cursor is a character index from `0` (before the first character)
+/// to the line's length (after the last).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineBuffer {
+    /// Characters of the line
+    chars: Vec<char>,
+    /// Index of the character the cursor is before
+    cursor: usize,
+}
+
+impl LineBuffer {
+    /// Create an empty line
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a line holding `text`, with the cursor at its end
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::line_buffer::LineBuffer;
+    ///
+    /// let mut line = LineBuffer::from_text("load notes.txt");
+    /// line.delete_word_before();
+    /// assert_eq!(line.text(), "load ");
+    /// ```
+    pub fn from_text(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = chars.len();
+        LineBuffer { chars, cursor }
+    }
+
+    /// Get the line's text
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Get the cursor position, in characters
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Get the number of characters in the line
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Check if the line is empty
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Insert a character at the cursor and move past it
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    /// Move the cursor one character left (Left arrow)
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Move the cursor one character right (Right arrow)
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    /// Move the cursor to the start of the line (Ctrl-A)
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    /// Move the cursor to the end of the line (Ctrl-E)
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    /// Delete the character before the cursor (Backspace)
+    pub fn delete_before(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    /// Delete the word before the cursor (Ctrl-W)
+    ///
+    /// Like readline, whitespace just before the cursor goes too, so
+    /// repeated presses delete one word each.
+    pub fn delete_word_before(&mut self) {
+        let before = &self.chars[..self.cursor];
+        let word_end = before
+            .iter()
+            .rposition(|c| !c.is_whitespace())
+            .map_or(0, |index| index + 1);
+        let word_start = before[..word_end]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+            .map_or(0, |index| index + 1);
+        self.chars.drain(word_start..self.cursor);
+        self.cursor = word_start;
+    }
+
+    /// Delete everything before the cursor (Ctrl-U)
+    pub fn delete_to_start(&mut self) {
+        self.chars.drain(..self.cursor);
+        self.cursor = 0;
+    }
+
+    /// Delete everything from the cursor on (Ctrl-K)
+    pub fn delete_to_end(&mut self) {
+        self.chars.truncate(self.cursor);
+    }
+
+    /// Apply an editing key
+    ///
+    /// Returns `true` for Enter, when the line is complete; the line itself
+    /// is left unchanged by Enter, by Ctrl-C (which [`LineReader`]
+    /// handles), and by keys it does not understand.
+    pub fn apply(&mut self, key: LineKey) -> bool {
+        match key {
+            LineKey::Char(c) => self.insert(c),
+            LineKey::Left => self.move_left(),
+            LineKey::Right => self.move_right(),
+            LineKey::Home => self.move_to_start(),
+            LineKey::End => self.move_to_end(),
+            LineKey::Backspace => self.delete_before(),
+            LineKey::DeleteWord => self.delete_word_before(),
+            LineKey::DeleteToStart => self.delete_to_start(),
+            LineKey::DeleteToEnd => self.delete_to_end(),
+            LineKey::Enter => return true,
+            LineKey::Interrupt | LineKey::Other => {}
+        }
+        false
+    }
+
+    /// Get the terminal output that redraws the line after the prompt
+    ///
+    /// The output returns to the start of the row, clears it, writes the
+    /// prompt and the line, then moves back to the cursor.
+    pub fn redraw(&self, prompt: &str) -> String {
+        let mut output = format!("\r{}{prompt}{}", ansi::clear_line(), self.text());
+        let after_cursor = self.chars.len() - self.cursor;
+        if after_cursor > 0 {
+            output.push_str(&ansi::cursor_left(after_cursor));
+        }
+        output
+    }
+}
+
+/// What a key did to the line a [`LineReader`] is reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineEvent {
+    /// The line is still being typed
+    Editing,
+    /// Enter was pressed; the line is complete
+    Submitted(String),
+    /// Ctrl-C abandoned the line; the session goes on at a fresh prompt
+    Interrupted,
+    /// Ctrl-C was pressed twice in a row; the session should end
+    Exit,
+}
+
+/// Reader of one input line at a time, with Ctrl-C handling
+///
+/// A Ctrl-C abandons the line being typed and a second Ctrl-C right after
+/// it exits; any other key in between starts over. Normally the abandoned
+/// text is discarded. With `preserve_partial` it is kept instead, as the
+/// starting text of the next prompt, so a long line is not lost when
+/// Ctrl-C is pressed by mistake.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineReader {
+    /// The line being typed
+    line: LineBuffer,
+    /// Whether an abandoned line is kept for the next prompt
+    preserve_partial: bool,
+    /// Whether the last key was a Ctrl-C
+    interrupted: bool,
+}
+
+impl LineReader {
+    /// Create a reader, choosing what a Ctrl-C does to the typed text
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::line_buffer::{LineEvent, LineKey, LineReader};
+    ///
+    /// let mut reader = LineReader::new(false);
+    /// let events: Vec<LineEvent> = LineKey::decode_all(b"ls\x03\x03")
+    ///     .into_iter()
+    ///     .map(|key| reader.handle_key(key))
+    ///     .collect();
+    /// assert_eq!(events[2..], [LineEvent::Interrupted, LineEvent::Exit]);
+    /// ```
+    pub fn new(preserve_partial: bool) -> Self {
+        LineReader {
+            preserve_partial,
+            ..Self::default()
+        }
+    }
+
+    /// Choose whether an abandoned line is kept for the next prompt
+    pub fn set_preserve_partial(&mut self, preserve_partial: bool) {
+        self.preserve_partial = preserve_partial;
+    }
+
+    /// Get the line being typed, to redraw after each key
+    pub fn line(&self) -> &LineBuffer {
+        &self.line
+    }
+
+    /// Apply a key to the line being typed
+    pub fn handle_key(&mut self, key: LineKey) -> LineEvent {
+        if key == LineKey::Interrupt {
+            if std::mem::replace(&mut self.interrupted, true) {
+                return LineEvent::Exit;
+            }
+            if !self.preserve_partial {
+                self.line = LineBuffer::new();
+            }
+            return LineEvent::Interrupted;
+        }
+
+        self.interrupted = false;
+        if self.line.apply(key) {
+            LineEvent::Submitted(std::mem::take(&mut self.line).text())
+        } else {
+            LineEvent::Editing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "save --diff my notes.txt";
+
+    /// Sample line with the cursor just before `notes.txt`
+    fn sample_at_notes() -> LineBuffer {
+        let mut line = LineBuffer::from_text(SAMPLE);
+        for _ in 0.."notes.txt".len() {
+            line.move_left();
+        }
+        line
+    }
+
+    #[test]
+    fn test_arrows_move_within_the_line() {
+        let mut line = LineBuffer::from_text(SAMPLE);
+        assert_eq!(line.cursor(), SAMPLE.len());
+        line.move_right();
+        assert_eq!(line.cursor(), SAMPLE.len());
+
+        line.move_left();
+        line.move_left();
+        assert_eq!(line.cursor(), SAMPLE.len() - 2);
+        line.insert('!');
+        assert_eq!(line.text(), "save --diff my notes.t!xt");
+
+        line.move_to_start();
+        line.move_left();
+        assert_eq!(line.cursor(), 0);
+    }
+
+    #[test]
+    fn test_start_and_end_motions() {
+        let mut line = sample_at_notes();
+        line.move_to_start();
+        assert_eq!(line.cursor(), 0);
+        line.insert('>');
+        assert_eq!(line.text(), ">save --diff my notes.txt");
+
+        line.move_to_end();
+        assert_eq!(line.cursor(), line.len());
+    }
+
+    #[test]
+    fn test_delete_word_before() {
+        let mut line = sample_at_notes();
+        line.delete_word_before();
+        assert_eq!(line.text(), "save --diff notes.txt");
+        assert_eq!(line.cursor(), "save --diff ".len());
+        line.delete_word_before();
+        assert_eq!(line.text(), "save notes.txt");
+
+        // At the start of the line there is nothing to delete
+        line.move_to_start();
+        line.delete_word_before();
+        assert_eq!(line.text(), "save notes.txt");
+    }
+
+    #[test]
+    fn test_delete_to_start_and_end() {
+        let mut line = sample_at_notes();
+        line.delete_to_end();
+        assert_eq!(line.text(), "save --diff my ");
+        assert_eq!(line.cursor(), line.len());
+
+        let mut line = sample_at_notes();
+        line.delete_to_start();
+        assert_eq!(line.text(), "notes.txt");
+        assert_eq!(line.cursor(), 0);
+    }
+
+    #[test]
+    fn test_keys_apply_motions() {
+        let mut line = LineBuffer::new();
+        let keys = LineKey::decode_all("lod\x1b[Da\x05 é\x17\x7fs\r".as_bytes());
+        let done: Vec<bool> = keys.into_iter().map(|key| line.apply(key)).collect();
+        assert_eq!(line.text(), "loads");
+        assert_eq!(done.last(), Some(&true));
+        assert_eq!(done.iter().filter(|done| **done).count(), 1);
+    }
+
+    #[test]
+    fn test_decode_consumes_whole_sequences() {
+        // Delete and Ctrl-Right are ignored without leaving bytes behind
+        assert_eq!(
+            LineKey::decode_all(b"a\x1b[3~\x1b[1;5Cb\x1bOD"),
+            [
+                LineKey::Char('a'),
+                LineKey::Other,
+                LineKey::Right,
+                LineKey::Char('b'),
+                LineKey::Left,
+            ]
+        );
+    }
+
+    /// Feed raw input to a reader, returning the events that were not `Editing`
+    fn feed(reader: &mut LineReader, input: &[u8]) -> Vec<LineEvent> {
+        LineKey::decode_all(input)
+            .into_iter()
+            .map(|key| reader.handle_key(key))
+            .filter(|event| *event != LineEvent::Editing)
+            .collect()
+    }
+
+    #[test]
+    fn test_single_interrupt_clears_the_line() {
+        let mut reader = LineReader::new(false);
+        assert_eq!(
+            feed(&mut reader, b"save --diff\x03"),
+            [LineEvent::Interrupted]
+        );
+        assert!(reader.line().is_empty());
+
+        // The session goes on, and a later Ctrl-C is a first one again
+        assert_eq!(
+            feed(&mut reader, b"vers\r\x03"),
+            [
+                LineEvent::Submitted("vers".to_string()),
+                LineEvent::Interrupted
+            ]
+        );
+        assert_eq!(feed(&mut reader, b"x\x03"), [LineEvent::Interrupted]);
+    }
+
+    #[test]
+    fn test_double_interrupt_exits() {
+        let mut reader = LineReader::new(false);
+        assert_eq!(
+            feed(&mut reader, b"load\x03\x03"),
+            [LineEvent::Interrupted, LineEvent::Exit]
+        );
+
+        // Ctrl-C apart, with a key between them, does not exit
+        let mut reader = LineReader::new(false);
+        assert_eq!(
+            feed(&mut reader, b"\x03a\x03"),
+            [LineEvent::Interrupted, LineEvent::Interrupted]
+        );
+    }
+
+    #[test]
+    fn test_preserve_partial_keeps_the_line() {
+        let mut reader = LineReader::new(true);
+        assert_eq!(
+            feed(&mut reader, b"append long\x03"),
+            [LineEvent::Interrupted]
+        );
+        assert_eq!(reader.line().text(), "append long");
+        assert_eq!(reader.line().cursor(), "append long".len());
+
+        // The kept text starts the next line, which can be finished
+        assert_eq!(
+            feed(&mut reader, b" line\r"),
+            [LineEvent::Submitted("append long line".to_string())]
+        );
+        assert!(reader.line().is_empty());
+
+        // A second Ctrl-C still exits
+        assert_eq!(
+            feed(&mut reader, b"x\x03\x03"),
+            [LineEvent::Interrupted, LineEvent::Exit]
+        );
+    }
+
+    #[test]
+    fn test_redraw_places_cursor() {
+        let mut line = LineBuffer::from_text("héllo");
+        assert_eq!(line.redraw("> "), "\r\x1b[2K> héllo");
+        line.move_left();
+        line.move_left();
+        assert_eq!(line.redraw("> "), "\r\x1b[2K> héllo\x1b[2D");
+    }
+}
@@ -5,8 +5,27 @@
 //! - Input reading and prompt handling
 //! - Error display and messaging
 //! - Terminal utilities and screen management
+//! - Color themes for the prompt
+//! - The interactive command picker
+//! - The editable input line with readline-style motions
+//! - Plain-text session transcripts
+//! - CSV profiles of command timings
+//! - The ANSI escape sequences behind all terminal styling
 
+mod ansi;
 pub mod disp;
+pub mod line_buffer;
+pub mod picker;
+pub mod profile;
+pub mod theme;
+pub mod transcript;
 
 // Re-export commonly used items
-pub use disp::{DisplayManager, TerminalUtils};
+pub use disp::{
+    CapturedOutput, DisplayManager, EchoGuard, ProgressBar, RawModeGuard, TerminalUtils,
+};
+pub use line_buffer::{LineBuffer, LineEvent, LineKey, LineReader};
+pub use picker::{Picker, PickerKey, PickerOutcome, run_picker};
+pub use profile::Profile;
+pub use theme::{Theme, strip_ansi, visible_width};
+pub use transcript::Transcript;
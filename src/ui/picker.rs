@@ -0,0 +1,280 @@
+//! Interactive command picker for the CLI interface.
+//!
+//! The picker lists items below the prompt and lets the user move a
+//! highlight with the arrow keys, choosing with Enter or leaving with Esc.
+//! The selection logic lives in [`Picker`], which does no terminal IO, so
+//! it can be tested on its own. [`run_picker`] drives it from raw-mode
+//! keyboard input and draws the list in place.
+
+use super::ansi::{self, RawKey};
+use crate::ui::TerminalUtils;
+use std::io::{self, Read, Write};
+
+/// Key the picker reacts to, decoded from raw terminal input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerKey {
+    /// Up arrow
+    Up,
+    /// Down arrow
+    Down,
+    /// Enter
+    Enter,
+    /// Esc or Ctrl-C
+    Cancel,
+    /// Any other key, which is ignored
+    Other,
+}
+
+impl PickerKey {
+    /// Decode every key in a chunk of raw terminal input
+    ///
+    /// An arrow key arrives as an escape sequence; an escape byte that does
+    /// not start one is the Esc key itself.
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::picker::PickerKey;
+    ///
+    /// assert_eq!(
+    ///     PickerKey::decode_all(b"\x1b[B\x1b[A\r"),
+    ///     [PickerKey::Down, PickerKey::Up, PickerKey::Enter]
+    /// );
+    /// ```
+    pub fn decode_all(mut bytes: &[u8]) -> Vec<PickerKey> {
+        let mut keys = Vec::new();
+        while !bytes.is_empty() {
+            let (raw, used) = ansi::split_key(bytes);
+            let key = match raw {
+                RawKey::Sequence(b'A') => PickerKey::Up,
+                RawKey::Sequence(b'B') => PickerKey::Down,
+                RawKey::Escape | RawKey::Char('\x03') => PickerKey::Cancel,
+                RawKey::Char('\r' | '\n') => PickerKey::Enter,
+                _ => PickerKey::Other,
+            };
+            keys.push(key);
+            bytes = &bytes[used..];
+        }
+        keys
+    }
+}
+
+/// Result of handing the picker a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerOutcome {
+    /// Still choosing
+    Pending,
+    /// The item at this index was chosen
+    Selected(usize),
+    /// The picker was left without a choice
+    Cancelled,
+}
+
+/// Highlighted position in a list of items
+///
+/// Moving past either end wraps around to the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picker {
+    /// Number of items to choose from
+    len: usize,
+    /// Index of the highlighted item
+    index: usize,
+}
+
+impl Picker {
+    /// Create a picker over `len` items with the first highlighted
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::picker::{Picker, PickerKey, PickerOutcome};
+    ///
+    /// let mut picker = Picker::new(3);
+    /// picker.handle_key(PickerKey::Up);
+    /// assert_eq!(picker.handle_key(PickerKey::Enter), PickerOutcome::Selected(2));
+    /// ```
+    pub fn new(len: usize) -> Self {
+        Picker { len, index: 0 }
+    }
+
+    /// Get the index of the highlighted item
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the number of items
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if there is nothing to choose from
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Move the highlight up, wrapping from the first item to the last
+    pub fn move_up(&mut self) {
+        if !self.is_empty() {
+            self.index = self.index.checked_sub(1).unwrap_or(self.len - 1);
+        }
+    }
+
+    /// Move the highlight down, wrapping from the last item to the first
+    pub fn move_down(&mut self) {
+        if !self.is_empty() {
+            self.index = (self.index + 1) % self.len;
+        }
+    }
+
+    /// Apply a key and report whether the choice is made
+    ///
+    /// Enter on an empty list cancels, since there is nothing to choose.
+    pub fn handle_key(&mut self, key: PickerKey) -> PickerOutcome {
+        match key {
+            PickerKey::Up => self.move_up(),
+            PickerKey::Down => self.move_down(),
+            PickerKey::Enter if self.is_empty() => return PickerOutcome::Cancelled,
+            PickerKey::Enter => return PickerOutcome::Selected(self.index),
+            PickerKey::Cancel => return PickerOutcome::Cancelled,
+            PickerKey::Other => {}
+        }
+        PickerOutcome::Pending
+    }
+
+    /// Format the items for display, marking the highlighted one
+    pub fn render(&self, items: &[String]) -> Vec<String> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let marker = if index == self.index { ">" } else { " " };
+                format!("{marker} {item}")
+            })
+            .collect()
+    }
+}
+
+/// Let the user choose one of the items from the terminal
+///
+/// The list is drawn below the cursor and redrawn in place after every
+/// key, then erased, so the prompt can be shown again where it was. The
+/// terminal is in raw mode only while the picker is open.
+///
+/// # Returns
+/// * `Ok(Some(index))` - The index of the chosen item
+/// * `Ok(None)` - If the user cancelled or there were no items
+/// * `Err(io::Error)` - If the terminal could not be read or written
+pub fn run_picker(items: &[String]) -> io::Result<Option<usize>> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    let _raw = TerminalUtils::enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    let mut stdin = io::stdin();
+    let mut picker = Picker::new(items.len());
+    let mut buf = [0u8; 64];
+
+    let outcome = loop {
+        // Raw mode does not translate line feeds, so return the cursor too
+        for line in picker.render(items) {
+            write!(stdout, "\r{}{line}\r\n", ansi::clear_line())?;
+        }
+        write!(stdout, "{}", ansi::cursor_up(items.len()))?;
+        stdout.flush()?;
+
+        let read = stdin.read(&mut buf)?;
+        if read == 0 {
+            break PickerOutcome::Cancelled;
+        }
+        let outcome = PickerKey::decode_all(&buf[..read])
+            .into_iter()
+            .map(|key| picker.handle_key(key))
+            .find(|outcome| *outcome != PickerOutcome::Pending);
+        if let Some(outcome) = outcome {
+            break outcome;
+        }
+    };
+
+    // Erase the list, leaving the cursor where it was drawn
+    write!(stdout, "\r{}", ansi::clear_below())?;
+    stdout.flush()?;
+    Ok(match outcome {
+        PickerOutcome::Selected(index) => Some(index),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_movement() {
+        let mut picker = Picker::new(4);
+        assert_eq!(picker.index(), 0);
+        picker.move_down();
+        picker.move_down();
+        assert_eq!(picker.index(), 2);
+        picker.move_up();
+        assert_eq!(picker.index(), 1);
+        assert_eq!(picker.handle_key(PickerKey::Other), PickerOutcome::Pending);
+        assert_eq!(picker.index(), 1);
+    }
+
+    #[test]
+    fn test_wrap_around_at_both_ends() {
+        let mut picker = Picker::new(3);
+        assert_eq!(picker.handle_key(PickerKey::Up), PickerOutcome::Pending);
+        assert_eq!(picker.index(), 2);
+        assert_eq!(picker.handle_key(PickerKey::Down), PickerOutcome::Pending);
+        assert_eq!(picker.index(), 0);
+
+        // A single item wraps onto itself
+        let mut picker = Picker::new(1);
+        picker.move_up();
+        picker.move_down();
+        assert_eq!(picker.index(), 0);
+    }
+
+    #[test]
+    fn test_enter_selects_and_cancel_returns_nothing() {
+        let mut picker = Picker::new(3);
+        picker.move_down();
+        assert_eq!(
+            picker.handle_key(PickerKey::Enter),
+            PickerOutcome::Selected(1)
+        );
+        assert_eq!(
+            picker.handle_key(PickerKey::Cancel),
+            PickerOutcome::Cancelled
+        );
+
+        let mut empty = Picker::new(0);
+        empty.move_down();
+        assert_eq!(empty.index(), 0);
+        assert_eq!(empty.handle_key(PickerKey::Enter), PickerOutcome::Cancelled);
+    }
+
+    #[test]
+    fn test_decode_keys() {
+        assert_eq!(
+            PickerKey::decode_all(b"\x1bOA\x1b[C\x1b[6~\x1b\x03j\n"),
+            [
+                PickerKey::Up,
+                PickerKey::Other,
+                PickerKey::Other,
+                PickerKey::Cancel,
+                PickerKey::Cancel,
+                PickerKey::Other,
+                PickerKey::Enter,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_marks_highlight() {
+        let items = vec!["file".to_string(), "edit".to_string()];
+        let mut picker = Picker::new(items.len());
+        picker.move_down();
+        assert_eq!(picker.render(&items), ["  file", "> edit"]);
+    }
+}
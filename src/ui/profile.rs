@@ -0,0 +1,111 @@
+//! Per-command timing profiles for the CLI interface.
+//!
+//! A profile, such as the file given with `--profile`, gets one CSV row
+//! for every command the session runs: the command name as typed, its
+//! arguments, how long it took in microseconds, and whether it succeeded.
+//! Arguments are joined with the tokenizer's quoting, so a row shows how
+//! to type the line again, and every field is CSV-escaped.
+
+use crate::core::error::{CliError, CliResult};
+use crate::core::tokenizer::join_tokens;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// Header row of a profile
+pub const PROFILE_HEADER: &str = "command,args,duration_us,result";
+
+/// CSV log of command timings
+pub struct Profile {
+    /// Where profile rows are written
+    writer: Box<dyn Write>,
+}
+
+impl Profile {
+    /// Create a profile writing to the given writer, starting with the header
+    pub fn new(mut writer: Box<dyn Write>) -> Self {
+        let _ = writeln!(writer, "{PROFILE_HEADER}");
+        Self { writer }
+    }
+
+    /// Create a profile file, replacing it if it exists
+    pub fn create(path: &Path) -> CliResult<Self> {
+        let file = File::create(path).map_err(|e| {
+            CliError::execution_error(&format!(
+                "Cannot create profile file {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(Self::new(Box::new(file)))
+    }
+
+    /// Record one command's timing and outcome
+    ///
+    /// Failures to write are ignored, so a broken profile never interrupts
+    /// the session.
+    pub fn record(&mut self, command: &str, args: &[String], duration: Duration, ok: bool) {
+        let row = [
+            csv_field(command),
+            csv_field(&join_tokens(args)),
+            duration.as_micros().to_string(),
+            if ok { "ok" } else { "error" }.to_string(),
+        ];
+        let _ = writeln!(self.writer, "{}", row.join(","));
+        let _ = self.writer.flush();
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile").finish_non_exhaustive()
+    }
+}
+
+/// Escape a CSV field, quoting it when it holds a comma, quote, or line break
+///
+/// # Examples
+/// ```
+/// use sm_menu::ui::profile::csv_field;
+///
+/// assert_eq!(csv_field("save"), "save");
+/// assert_eq!(csv_field("a, b"), "\"a, b\"");
+/// assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+/// ```
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::CapturedOutput;
+
+    #[test]
+    fn test_profile_rows() {
+        let buffer = CapturedOutput::default();
+        let mut profile = Profile::new(Box::new(buffer.clone()));
+        profile.record("save", &[], Duration::from_micros(42), true);
+        let args = ["my notes.txt".to_string(), "a,b".to_string()];
+        profile.record("load", &args, Duration::from_millis(2), false);
+
+        let text = buffer.text();
+        assert_eq!(
+            text,
+            "command,args,duration_us,result\n\
+             save,,42,ok\n\
+             load,\"\"\"my notes.txt\"\" a,b\",2000,error\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_field_quotes_line_breaks() {
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_field(""), "");
+    }
+}
@@ -0,0 +1,152 @@
+//! Color theme for prompt and output styling.
+//!
+//! This module defines the escape sequences used to style the prompt and
+//! diffs, with
+//! a monochrome variant for when color is turned off. It also provides
+//! helpers for measuring styled text by its visible width, so sequences
+//! added for color never count towards layout or truncation.
+
+use super::ansi;
+
+/// Colors used to style CLI output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Color of the `sm-menu` brand token
+    pub brand: &'static str,
+    /// Color of each path segment in the prompt
+    pub path_segment: &'static str,
+    /// Style of the separators between path segments
+    pub separator: &'static str,
+    /// Color of the prompt marker for unsaved changes
+    pub modified: &'static str,
+    /// Color of lines added in a diff
+    pub diff_added: &'static str,
+    /// Color of lines removed in a diff
+    pub diff_removed: &'static str,
+    /// Color of diff hunk headers
+    pub diff_hunk: &'static str,
+    /// Sequence that resets all styling
+    pub reset: &'static str,
+}
+
+impl Theme {
+    /// Create the default color theme
+    pub const fn colored() -> Self {
+        Self {
+            // Warp-like green (24-bit color for better accuracy)
+            brand: ansi::BRAND_GREEN,
+            path_segment: ansi::CYAN,
+            separator: ansi::DIM,
+            modified: ansi::BOLD_YELLOW,
+            diff_added: ansi::GREEN,
+            diff_removed: ansi::RED,
+            diff_hunk: ansi::CYAN,
+            reset: ansi::RESET,
+        }
+    }
+
+    /// Create a theme that emits no escape sequences
+    pub const fn monochrome() -> Self {
+        Self {
+            brand: "",
+            path_segment: "",
+            separator: "",
+            modified: "",
+            diff_added: "",
+            diff_removed: "",
+            diff_hunk: "",
+            reset: "",
+        }
+    }
+
+    /// Pick the colored or monochrome theme
+    pub const fn for_color(colored: bool) -> Self {
+        if colored {
+            Self::colored()
+        } else {
+            Self::monochrome()
+        }
+    }
+
+    /// Wrap text in a style, followed by a reset
+    ///
+    /// Returns the text unchanged when the style is empty.
+    pub fn paint(&self, style: &str, text: &str) -> String {
+        if style.is_empty() {
+            text.to_string()
+        } else {
+            format!("{style}{text}{}", self.reset)
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::colored()
+    }
+}
+
+/// Remove ANSI escape sequences from text
+///
+/// # Examples
+/// ```
+/// use sm_menu::ui::theme::strip_ansi;
+///
+/// assert_eq!(strip_ansi("\x1b[36mfile\x1b[0m"), "file");
+/// ```
+pub fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end at the first byte in the range '@'..='~'
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Get the number of visible characters in styled text
+pub fn visible_width(text: &str) -> usize {
+    strip_ansi(text).chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monochrome_paint_is_plain() {
+        let theme = Theme::monochrome();
+        assert_eq!(theme.paint(theme.path_segment, "file"), "file");
+    }
+
+    #[test]
+    fn test_colored_paint_wraps_text() {
+        let theme = Theme::colored();
+        assert_eq!(
+            theme.paint(theme.path_segment, "file"),
+            "\x1b[36mfile\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_visible_width_ignores_escapes() {
+        let theme = Theme::colored();
+        let styled = format!(
+            "{} {}",
+            theme.paint(theme.brand, "sm-menu"),
+            theme.paint(theme.separator, "~")
+        );
+        assert_eq!(strip_ansi(&styled), "sm-menu ~");
+        assert_eq!(visible_width(&styled), 9);
+    }
+}
@@ -0,0 +1,143 @@
+//! Session transcript logging for the CLI interface.
+//!
+//! A transcript mirrors the session into a log, such as the file given with
+//! `--log`. The display's writers are wrapped in a tee with
+//! [`Transcript::tee`], so everything the session shows, from prompts to
+//! command listings, results, and errors, is copied to the log, and the
+//! session input copies each line read to it as well. Output is still shown
+//! on the terminal as usual. The transcript is plain text: ANSI escape
+//! codes and other control characters are removed before anything is
+//! written, and a carriage return starts the line over as it does on a
+//! terminal, so only the final form of a redrawn prompt is logged.
+
+use crate::core::error::{CliError, CliResult};
+use crate::core::security::sanitize_for_display;
+use crate::ui::theme::strip_ansi;
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Plain-text log of a session
+///
+/// Clones share the same log, so every writer of the session can feed it.
+#[derive(Clone)]
+pub struct Transcript(Rc<RefCell<Log>>);
+
+/// Destination of a transcript and the line it is building
+struct Log {
+    /// Where transcript lines are written
+    writer: Box<dyn Write>,
+    /// Text of the line not yet ended
+    pending: String,
+}
+
+impl Transcript {
+    /// Create a transcript writing to the given writer
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self(Rc::new(RefCell::new(Log {
+            writer,
+            pending: String::new(),
+        })))
+    }
+
+    /// Open a transcript file, appending to it if it exists
+    pub fn open(path: &Path) -> CliResult<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                CliError::execution_error(&format!("Cannot open log file {}: {e}", path.display()))
+            })?;
+        Ok(Self::new(Box::new(file)))
+    }
+
+    /// Wrap a writer so that everything written to it is also recorded
+    ///
+    /// # Examples
+    /// ```
+    /// use sm_menu::ui::{CapturedOutput, Transcript};
+    /// use std::io::Write;
+    ///
+    /// let log = CapturedOutput::default();
+    /// let mut tee = Transcript::new(Box::new(log.clone())).tee(Box::new(Vec::new()));
+    /// write!(tee, "\x1b[1mls\x1b[0m\n").unwrap();
+    /// assert_eq!(log.text(), "ls\n");
+    /// ```
+    pub fn tee(&self, output: Box<dyn Write>) -> Box<dyn Write> {
+        Box::new(Tee {
+            output,
+            transcript: self.clone(),
+        })
+    }
+
+    /// Record some output, logging each line once it is complete
+    ///
+    /// Failures to write are ignored, so a broken log never interrupts the
+    /// session.
+    fn record(&self, text: &str) {
+        let log = &mut *self.0.borrow_mut();
+        for c in strip_ansi(text).chars() {
+            match c {
+                '\n' => {
+                    let line = sanitize_for_display(&std::mem::take(&mut log.pending));
+                    let _ = writeln!(log.writer, "{line}");
+                }
+                '\r' => log.pending.clear(),
+                c => log.pending.push(c),
+            }
+        }
+        let _ = log.writer.flush();
+    }
+}
+
+impl fmt::Debug for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transcript").finish_non_exhaustive()
+    }
+}
+
+/// Writer that passes output on and records a copy in a transcript
+struct Tee {
+    /// Where the output goes
+    output: Box<dyn Write>,
+    /// The log the copy goes to
+    transcript: Transcript,
+}
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write_all(buf)?;
+        self.transcript.record(&String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::CapturedOutput;
+
+    #[test]
+    fn test_tee_records_plain_complete_lines() {
+        let log = CapturedOutput::default();
+        let screen = CapturedOutput::default();
+        let mut tee = Transcript::new(Box::new(log.clone())).tee(Box::new(screen.clone()));
+
+        write!(tee, "\x1b[38;2;0;215;135msm-menu\x1b[0m > ? ").unwrap();
+        assert_eq!(log.text(), "");
+        write!(tee, "file\ntwo\nlines\x07\n").unwrap();
+        // A redrawn line is logged as it was last drawn
+        write!(tee, "\r\x1b[2K> l\r\x1b[2K> ls\x1b[1D\r\x1b[2K> ls\n").unwrap();
+
+        assert_eq!(log.text(), "sm-menu > ? file\ntwo\nlines\n> ls\n");
+        assert!(screen.text().starts_with("\x1b[38;2;0;215;135msm-menu"));
+    }
+}
@@ -3,11 +3,78 @@
 //! This module contains tests that verify the error handling behavior
 //! of various commands in the CLI application.
 
+use sm_menu::app::{GOODBYE_MESSAGE, SHORTCUT_HINT_COMMANDS, WELCOME_MESSAGE};
+use sm_menu::commands::axis::AxisCommand;
+use sm_menu::commands::base::InfoCommand;
+use sm_menu::commands::edit::EditCommand;
 use sm_menu::commands::file::FileCommand;
+use sm_menu::commands::goto::GotoCommand;
 use sm_menu::commands::help::HelpCommand;
+use sm_menu::commands::history::HistoryCommand;
+use sm_menu::commands::lines::{AppendCommand, DeleteCommand, InsertCommand};
+use sm_menu::commands::load::LoadCommand;
+use sm_menu::commands::load_stdin::LoadStdinCommand;
+use sm_menu::commands::ls::LsCommand;
+use sm_menu::commands::open::{Launcher, OpenCommand};
 use sm_menu::commands::quit::QuitCommand;
+use sm_menu::commands::repeat::RepeatCommand;
+use sm_menu::commands::save::SaveCommand;
+use sm_menu::commands::show::ShowCommand;
+use sm_menu::commands::sleep::SleepCommand;
+use sm_menu::commands::tree::render_tree;
+use sm_menu::commands::undo::{RedoCommand, UndoCommand};
 use sm_menu::commands::vers::VersCommand;
-use sm_menu::{CliError, Command, CommandResult};
+use sm_menu::commands::view::ViewCommand;
+use sm_menu::commands::watch::{WatchCommand, watch_loop};
+use sm_menu::commands::which::WhichCommand;
+use sm_menu::core::axes::AxisScope;
+use sm_menu::core::logging::{Level, Logger, Record, set_logger};
+use sm_menu::core::pipeline::{Dispatch, process_input};
+use sm_menu::testing::{CapturedOutput, TestHarness};
+use sm_menu::{
+    App, CliContext, CliError, CliPreferences, CliResult, Command, CommandCategory, CommandResult,
+    CommandTreeIter, DisplayManager, EditBuffer, FileSystem, HistoryEntry, JsonValue, LineEnding,
+    Redirect, RootCommand, SecurityContext, SessionIo, SessionSnapshot, StdFileSystem, Transcript,
+    Version, validate_command_tree,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::{Duration, SystemTime};
+
+/// Temporary working root removed when dropped
+struct TempRoot(PathBuf);
+
+impl TempRoot {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("sm-menu-integration-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempRoot(dir)
+    }
+
+    /// Create a context whose file operations are confined to this root
+    fn context(&self) -> CliContext {
+        CliContext::with_security(SecurityContext::new(&self.0).unwrap())
+    }
+}
+
+impl Drop for TempRoot {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Session input that behaves like an interactive terminal
+fn terminal_input(text: &str) -> SessionIo {
+    SessionIo::from_reader(Cursor::new(text.as_bytes().to_vec()), true)
+}
 
 #[test]
 fn test_vers_command_error_handling() {
@@ -55,14 +122,158 @@ fn test_help_command_error_handling() {
     let result = cmd.execute(&["file".to_string(), "extra".to_string()]);
     assert!(result.is_err());
 
-    if let Err(CliError::TooManyArguments { expected, found }) = result {
-        assert_eq!(expected, 1);
+    if let Err(CliError::WrongArgumentCount { min, max, found }) = result {
+        assert_eq!((min, max), (0, 1));
         assert_eq!(found, 2);
     } else {
-        panic!("Expected TooManyArguments error");
+        panic!("Expected WrongArgumentCount error");
     }
 }
 
+#[test]
+fn test_builtin_command_categories() {
+    use CommandCategory::{Edit, File, General, System, View};
+    let categories = |menu: &dyn Command| -> Vec<(&'static str, CommandCategory)> {
+        menu.subcommands()
+            .iter()
+            .map(|cmd| (cmd.name(), cmd.category()))
+            .collect()
+    };
+
+    assert_eq!(
+        categories(&RootCommand),
+        [
+            ("file", File),
+            ("edit", Edit),
+            ("view", View),
+            ("help", General),
+            ("quit", System),
+            ("repeat", General),
+            ("history", General),
+            ("reload", General),
+            ("config", General),
+            ("export", General),
+            ("system", System),
+            ("info", General),
+        ]
+    );
+    assert_eq!(
+        categories(&FileCommand::new()),
+        [
+            ("ls", File),
+            ("load", File),
+            ("save", File),
+            ("save-as", File),
+            ("touch", File),
+            ("stat", File),
+            ("diff", File),
+            ("open", File),
+            ("vers", File),
+            ("info", General),
+            ("exit", System),
+        ]
+    );
+    let edit = categories(&EditCommand::new());
+    assert!(edit.contains(&("axis", Edit)) && edit.contains(&("show", Edit)));
+    assert!(edit.contains(&("exit", System)));
+    let view = categories(&ViewCommand::new());
+    assert_eq!(
+        view,
+        [
+            ("axis", View),
+            ("show", View),
+            ("goto", View),
+            ("grep", View),
+            ("info", General),
+            ("exit", System)
+        ]
+    );
+}
+
+#[test]
+fn test_help_for_any_command_in_the_tree() {
+    let harness = TestHarness::new("tree-help").unwrap();
+    let mut context = harness.context("");
+    let mut help = |name: &str| {
+        HelpCommand::new().execute_with_context(&[name.to_string()], &[], &mut context)
+    };
+
+    help("load").unwrap();
+    let output = harness.output();
+    assert!(output.starts_with("LOAD\n====\nLoad a file"), "{output}");
+    assert!(output.contains("Aliases: l\n"), "{output}");
+    assert!(
+        output.contains("Usage: load [--force] <filename | N>\n"),
+        "{output}"
+    );
+
+    harness.clear_output();
+    help("save").unwrap();
+    assert!(harness.output().contains("Usage: save [--diff] [filename]"));
+
+    harness.clear_output();
+    help("axis").unwrap();
+    assert!(harness.output().starts_with("AXIS\n"));
+
+    // Menus list their visible subcommands; aliases find the nearest menu
+    harness.clear_output();
+    help("V").unwrap();
+    let output = harness.output();
+    assert!(output.starts_with("VIEW\n"), "{output}");
+    assert!(output.contains("\nSubcommands:\n  axis"), "{output}");
+    assert!(!output.contains("  info"), "{output}");
+
+    // Commands available in every menu have help too
+    harness.clear_output();
+    help("which").unwrap();
+    assert!(harness.output().starts_with("WHICH\n"));
+
+    let message = |result: CliResult<CommandResult>| match result {
+        Err(CliError::InvalidInput(message)) => message,
+        other => panic!("expected InvalidInput, got {other:?}"),
+    };
+    assert_eq!(
+        message(help("nonsense")),
+        "No help available for command: nonsense. Use 'help' to list the commands."
+    );
+    assert_eq!(
+        message(help("lod")),
+        "No help available for command: lod. Did you mean 'load'?"
+    );
+}
+
+#[test]
+fn test_help_groups_commands_by_category() {
+    let harness = TestHarness::new("grouped-help").unwrap();
+    let mut context = harness.context("");
+    HelpCommand::new()
+        .execute_with_context(&[], &[], &mut context)
+        .unwrap();
+
+    let output = harness.output();
+    let lines: Vec<&str> = output.lines().collect();
+    let header_of = |name: &str| {
+        let index = lines
+            .iter()
+            .position(|line| line.starts_with(&format!("  {name}")))
+            .unwrap_or_else(|| panic!("{name} not listed in:\n{output}"));
+        *lines[..index]
+            .iter()
+            .rev()
+            .find(|line| line.ends_with(':'))
+            .unwrap()
+    };
+
+    assert_eq!(header_of("file"), "File Operations:");
+    assert_eq!(header_of("edit"), "Edit Operations:");
+    assert_eq!(header_of("view"), "View Operations:");
+    assert_eq!(header_of("help"), "General:");
+    assert_eq!(header_of("export"), "General:");
+    assert_eq!(header_of("quit"), "System:");
+    // Hidden commands are not listed
+    assert!(!output.contains("  info"), "{output}");
+}
+
 #[test]
 fn test_quit_command_error_handling() {
     let mut cmd = QuitCommand::new();
@@ -77,13 +288,19 @@ fn test_quit_command_error_handling() {
         panic!("Expected Quit result");
     }
 
+    // Test with a non-numeric or negative exit code (should fail)
+    for code in ["abc", "-1", "256", "99999999999999999999999"] {
+        let result = cmd.execute(&[code.to_string()]);
+        assert!(matches!(result, Err(CliError::InvalidInput(_))), "{code}");
+    }
+
     // Test with too many arguments (should fail)
-    let result = cmd.execute(&["arg1".to_string()]);
+    let result = cmd.execute(&["1".to_string(), "2".to_string()]);
     assert!(result.is_err());
 
     if let Err(CliError::TooManyArguments { expected, found }) = result {
-        assert_eq!(expected, 0);
-        assert_eq!(found, 1);
+        assert_eq!(expected, 1);
+        assert_eq!(found, 2);
     } else {
         panic!("Expected TooManyArguments error");
     }
@@ -108,3 +325,3548 @@ fn test_file_command_error_handling() {
         panic!("Expected TooManyArguments error");
     }
 }
+
+#[test]
+fn test_undo_redo_commands() {
+    let mut context = CliContext::new();
+    let mut undo = UndoCommand::new();
+    let mut redo = RedoCommand::new();
+
+    context
+        .edit_buffer(|buffer| {
+            buffer.lines_mut().push("first line".to_string());
+            Ok(())
+        })
+        .unwrap();
+
+    // Undo restores the buffer to its state before the edit
+    let result = undo.execute_with_context(&[], &[], &mut context);
+    assert!(result.is_ok());
+    assert!(context.buffer().is_empty());
+
+    // Nothing left to undo
+    let result = undo.execute_with_context(&[], &[], &mut context);
+    if let Err(CliError::Other(msg)) = result {
+        assert_eq!(msg, "nothing to undo");
+    } else {
+        panic!("Expected Other error");
+    }
+
+    // Redo re-applies the edit
+    let result = redo.execute_with_context(&[], &[], &mut context);
+    assert!(result.is_ok());
+    assert_eq!(context.buffer().lines(), ["first line"]);
+
+    // Too many arguments
+    let result = undo.execute_with_context(&["arg".to_string()], &[], &mut context);
+    assert!(matches!(
+        result,
+        Err(CliError::TooManyArguments {
+            expected: 0,
+            found: 1
+        })
+    ));
+}
+
+#[test]
+fn test_load_stdin_command() {
+    let mut context = CliContext::new();
+    context.set_io(SessionIo::from_bytes(&b"alpha\nbeta\ngamma\n"[..]));
+    let mut cmd = LoadStdinCommand::new();
+
+    let result = cmd.execute_with_context(&[], &[], &mut context);
+    assert!(result.is_ok());
+    assert_eq!(context.buffer().lines(), ["alpha", "beta", "gamma"]);
+    assert!(context.buffer().is_dirty());
+
+    // The load can be undone like any other edit
+    context.undo().unwrap();
+    assert!(context.buffer().is_empty());
+}
+
+#[test]
+fn test_load_stdin_command_refuses_terminal() {
+    let mut context = CliContext::new();
+    context.set_io(terminal_input(""));
+    let mut cmd = LoadStdinCommand::new();
+
+    let result = cmd.execute_with_context(&[], &[], &mut context);
+    assert!(matches!(result, Err(CliError::ExecutionError { .. })));
+    assert!(context.buffer().is_empty());
+}
+
+#[test]
+fn test_ls_paginates_and_load_by_number() {
+    let temp = TempRoot::new("ls-load");
+    for i in 1..=120 {
+        fs::write(
+            temp.0.join(format!("file{i:03}.txt")),
+            format!("line {i}\n"),
+        )
+        .unwrap();
+    }
+    let mut context = temp.context();
+    let mut ls = LsCommand::new();
+    let mut load = LoadCommand::new();
+
+    // Listing is stashed with all 120 entries over three pages
+    assert!(ls.execute_with_context(&[], &[], &mut context).is_ok());
+    let listing = context.last_listing().unwrap();
+    assert_eq!(listing.entries().len(), 120);
+    assert_eq!(listing.page_count(context.preferences().max_list_items), 3);
+    assert!(
+        ls.execute_with_context(&["3".to_string()], &[], &mut context)
+            .is_ok()
+    );
+    assert!(matches!(
+        ls.execute_with_context(&["4".to_string()], &[], &mut context),
+        Err(CliError::InvalidInput(_))
+    ));
+
+    // `load 3` resolves to the third listed entry
+    let result = load.execute_with_context(&["3".to_string()], &[], &mut context);
+    assert!(result.is_ok());
+    assert_eq!(context.buffer().filename(), Some("file003.txt"));
+    assert_eq!(context.buffer().lines(), ["line 3"]);
+
+    // Out-of-range entry numbers are rejected
+    assert!(matches!(
+        load.execute_with_context(&["121".to_string()], &[], &mut context),
+        Err(CliError::InvalidInput(_))
+    ));
+
+    // A stale listing is detected rather than loading something else
+    fs::remove_file(temp.0.join("file004.txt")).unwrap();
+    assert!(matches!(
+        load.execute_with_context(&["4".to_string()], &[], &mut context),
+        Err(CliError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_load_command_error_handling() {
+    let harness = TestHarness::new("load-errors").unwrap();
+    let mut context = harness.context("");
+    let mut cmd = LoadCommand::new();
+
+    assert!(matches!(
+        cmd.execute_with_context(&[], &[], &mut context),
+        Err(CliError::TooFewArguments {
+            expected: 1,
+            found: 0
+        })
+    ));
+    assert!(matches!(
+        cmd.execute_with_context(&["missing.txt".to_string()], &[], &mut context),
+        Err(CliError::FileNotFound(_))
+    ));
+    assert!(matches!(
+        cmd.execute_with_context(&["../escape.txt".to_string()], &[], &mut context),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(harness.output().is_empty());
+}
+
+#[test]
+fn test_load_refuses_binary_without_force() {
+    let harness = TestHarness::new("load-binary").unwrap();
+    fs::write(
+        harness.path("image.bin"),
+        b"\x89PNG\0\x1b[2J\x1b]0;owned\x07ok\n",
+    )
+    .unwrap();
+    let mut context = harness.context("");
+    let mut cmd = LoadCommand::new();
+
+    let result = cmd.execute_with_context(&["image.bin".to_string()], &[], &mut context);
+    assert!(
+        matches!(&result, Err(CliError::InvalidFileFormat(msg)) if msg == "binary file; use --force")
+    );
+    assert!(context.buffer().is_empty());
+
+    // Forced, the contents load but are shown without control characters
+    cmd.execute_with_context(
+        &["--force".to_string(), "image.bin".to_string()],
+        &[],
+        &mut context,
+    )
+    .unwrap();
+    assert_eq!(context.buffer().len(), 1);
+    harness.clear_output();
+    ShowCommand::new()
+        .execute_with_context(&[], &["edit".to_string(), "show".to_string()], &mut context)
+        .unwrap();
+    let output = harness.output();
+    assert!(output.contains("Buffer: 1 lines\n"));
+    assert!(output.contains("   1  \u{fffd}PNG[2J]0;ownedok\n"));
+    assert!(!output.chars().any(|c| c.is_control() && c != '\n'));
+}
+
+#[test]
+fn test_output_redirection_writes_and_appends() {
+    let harness = TestHarness::new("redirect").unwrap();
+    let mut app = harness.app("");
+    let version = format!("sm-menu > version {}\n", env!("CARGO_PKG_VERSION"));
+
+    app.handle_input("file").unwrap();
+    app.handle_input("vers > out.txt").unwrap();
+    assert_eq!(harness.read_file("out.txt"), version);
+    assert!(harness.output().is_empty());
+
+    // vers opens its own menu, so leave it before running it again
+    app.handle_input("exit").unwrap();
+    app.handle_input("vers >> out.txt").unwrap();
+    assert_eq!(harness.read_file("out.txt"), version.repeat(2));
+
+    // > replaces what was there, and output goes back to the screen after
+    app.handle_input("exit").unwrap();
+    app.handle_input("vers >out.txt").unwrap();
+    assert_eq!(harness.read_file("out.txt"), version);
+    app.handle_input("exit").unwrap();
+    app.handle_input("vers").unwrap();
+    assert_eq!(harness.output(), version);
+
+    // Listings and menu information are redirected like any other output
+    harness.clear_output();
+    app.handle_input("exit").unwrap();
+    app.handle_input("ls > listing.txt").unwrap();
+    // The target is created before the command runs, so it is listed too
+    assert_eq!(
+        harness.read_file("listing.txt"),
+        "     1. listing.txt\n     2. out.txt\n"
+    );
+    app.handle_input("info >> listing.txt").unwrap();
+    assert!(
+        harness
+            .read_file("listing.txt")
+            .ends_with("Use 'exit' (or 'e') to return to parent menu\n")
+    );
+    assert!(harness.output().is_empty());
+
+    // A quoted > is text, not a redirection
+    let files = || fs::read_dir(harness.root()).unwrap().count();
+    app.handle_input("echo \">\"").unwrap();
+    app.handle_input("echo \"a\" \">\" b").unwrap();
+    assert_eq!(harness.output(), ">\na > b\n");
+    app.handle_input("exit").unwrap();
+    app.handle_input("edit").unwrap();
+    app.handle_input("append \">quoted line\"").unwrap();
+    assert_eq!(app.context().buffer().lines(), [">quoted line"]);
+    assert_eq!(files(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_writes_refuse_dangling_symlink_out_of_root() {
+    use std::os::unix::fs::symlink;
+
+    let harness = TestHarness::new("dangling-link").unwrap();
+    let outside = TempRoot::new("dangling-link-outside");
+    symlink(outside.0.join("pwned.txt"), harness.path("evil.txt")).unwrap();
+    let mut app = harness.app("");
+    app.handle_input("edit").unwrap();
+    app.handle_input("append secret").unwrap();
+    app.handle_input("exit").unwrap();
+
+    let attempts = [
+        ("file", "save evil.txt"),
+        ("file", "touch evil.txt"),
+        ("file", "ls > evil.txt"),
+        ("", "export json evil.txt"),
+    ];
+    for (menu, line) in attempts {
+        if !menu.is_empty() {
+            app.handle_input(menu).unwrap();
+        }
+        assert!(app.handle_input(line).is_err(), "{line}");
+        assert!(!outside.0.join("pwned.txt").exists(), "{line}");
+        if !menu.is_empty() {
+            app.handle_input("exit").unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_output_redirection_target_is_validated() {
+    let harness = TestHarness::new("redirect-escape").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    assert!(matches!(
+        app.handle_input("vers > ../escape.txt"),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        app.handle_input("vers >"),
+        Err(CliError::InvalidInput(_))
+    ));
+    // The command did not run, so the session is still in the file menu
+    assert_eq!(app.context().current_path(), ["file"]);
+    assert!(harness.output().is_empty());
+}
+
+#[test]
+fn test_sleep_durations() {
+    for (arg, millis) in [("0", 0), ("2", 2000), ("0.25", 250), (" 1.5 ", 1500)] {
+        assert_eq!(
+            SleepCommand::parse_duration(arg.trim()).unwrap(),
+            Duration::from_millis(millis),
+            "{arg}"
+        );
+    }
+    for arg in ["-1", "-0.5", "soon", "", "NaN", "inf", "1e400"] {
+        assert!(
+            matches!(
+                SleepCommand::parse_duration(arg),
+                Err(CliError::InvalidInput(_))
+            ),
+            "{arg}"
+        );
+    }
+
+    // Available from any menu, including by its alias
+    let harness = TestHarness::new("sleep").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("edit").unwrap();
+    app.handle_input("sleep 0.01").unwrap();
+    app.handle_input("wait 0").unwrap();
+    assert!(matches!(
+        app.handle_input("sleep -3"),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        app.handle_input("sleep"),
+        Err(CliError::TooFewArguments { .. })
+    ));
+    assert!(harness.output().is_empty());
+}
+
+#[test]
+fn test_watch_intervals() {
+    for (arg, millis) in [("2", 2000), ("0.25", 250), ("0.001", 1)] {
+        assert_eq!(
+            WatchCommand::parse_interval(arg).unwrap(),
+            Duration::from_millis(millis),
+            "{arg}"
+        );
+    }
+    for arg in ["0", "0.0", "-1", "-0.5", "soon", "", "NaN", "inf", "1e400"] {
+        assert!(
+            matches!(
+                WatchCommand::parse_interval(arg),
+                Err(CliError::InvalidInput(_))
+            ),
+            "{arg}"
+        );
+    }
+
+    let watch = WatchCommand::new();
+    assert!(matches!(
+        watch.validate(&["2".to_string()]),
+        Err(CliError::TooFewArguments { .. })
+    ));
+    assert_eq!(
+        watch.validate(&["2".to_string(), "watch".to_string()]),
+        Err(CliError::invalid_input("watch cannot watch itself"))
+    );
+
+    // Scripts cannot watch, since nobody can press Ctrl-C
+    let harness = TestHarness::new("watch-script").unwrap();
+    let mut app = harness.app("");
+    assert!(matches!(
+        app.handle_input("watch 1 vers"),
+        Err(CliError::TerminalError(_))
+    ));
+    assert!(harness.output().is_empty());
+}
+
+#[test]
+fn test_watch_loop_stops_when_interrupted() {
+    let waits = Rc::new(RefCell::new(Vec::new()));
+    let record = Rc::clone(&waits);
+    let mut clock = move |interval| {
+        record.borrow_mut().push(interval);
+        Err(CliError::Interrupted)
+    };
+
+    let mut runs = 0;
+    let count = watch_loop(Duration::from_millis(10), &mut clock, || {
+        runs += 1;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!((count, runs), (1, 1));
+    assert_eq!(*waits.borrow(), [Duration::from_millis(10)]);
+
+    // Any other error from the clock or a run ends the watch with it
+    let mut failing = |_| Err(CliError::execution_error("clock broke"));
+    assert_eq!(
+        watch_loop(Duration::from_millis(10), &mut failing, || Ok(())),
+        Err(CliError::execution_error("clock broke"))
+    );
+    let mut never = |_| Ok(());
+    assert_eq!(
+        watch_loop(Duration::from_millis(10), &mut never, || Err(
+            CliError::EmptyInput
+        )),
+        Err(CliError::EmptyInput)
+    );
+}
+
+#[test]
+fn test_watch_reruns_command_until_interrupted() {
+    let harness = TestHarness::new("watch").unwrap();
+    let mut context = harness.context("");
+    let waits = Rc::new(Cell::new(0));
+    let count = Rc::clone(&waits);
+    let mut watch = WatchCommand::with_clock(Box::new(move |_| {
+        count.set(count.get() + 1);
+        if count.get() < 2 {
+            Ok(())
+        } else {
+            Err(CliError::Interrupted)
+        }
+    }));
+
+    let args: Vec<String> = ["0.5", "echo", "tick"].map(String::from).to_vec();
+    let result = watch
+        .execute_with_context(&args, &[], &mut context)
+        .unwrap();
+    assert_eq!(result, CommandResult::success_silent());
+    assert_eq!(waits.get(), 2);
+    assert_eq!(
+        harness.output(),
+        "Every 0.5s: echo tick\n\ntick\n".repeat(2)
+    );
+
+    // Menus, and commands whose result would change the session, are refused
+    for command in ["file", "quit"] {
+        let args = ["1".to_string(), command.to_string()];
+        assert_eq!(
+            watch.execute_with_context(&args, &[], &mut context),
+            Err(CliError::invalid_input(&format!(
+                "'{command}' cannot be watched"
+            )))
+        );
+    }
+    assert_eq!(waits.get(), 2);
+}
+
+#[test]
+fn test_echo_prints_arguments() {
+    let harness = TestHarness::new("echo").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("echo hello world").unwrap();
+    assert_eq!(harness.output(), "hello world\n");
+
+    // A quoted argument keeps its space, and the line is sanitized
+    harness.clear_output();
+    app.handle_input("echo \"a b\"").unwrap();
+    app.handle_input("echo \"\x1b[31mred\"").unwrap();
+    assert_eq!(harness.output(), "a b\n[31mred\n");
+
+    // -n leaves off the newline, and echo works in any menu
+    harness.clear_output();
+    app.handle_input("file").unwrap();
+    app.handle_input("echo -n step").unwrap();
+    app.handle_input("echo -n").unwrap();
+    app.handle_input("echo").unwrap();
+    assert_eq!(harness.output(), "step\n");
+}
+
+#[test]
+fn test_pasted_paths_load_and_save() {
+    let harness = TestHarness::new("pasted-paths").unwrap();
+    harness.write_file("my file.txt", "one\ntwo\n");
+    harness.write_file("'odd'.txt", "odd\n");
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    // A quoted path pasted inside quotes, and one with escaped spaces,
+    // both name the same file
+    for input in [r#"load "'my file.txt'""#, r"load my\ file.txt"] {
+        app.handle_input(input).unwrap();
+        assert_eq!(app.context().buffer().filename(), Some("my file.txt"));
+        assert_eq!(app.context().buffer().lines(), ["one", "two"]);
+    }
+
+    // Quotes that are part of an existing name are kept
+    app.handle_input(r#"load "'odd'.txt""#).unwrap();
+    assert_eq!(app.context().buffer().filename(), Some("'odd'.txt"));
+
+    app.handle_input(r"save copy\ of\ odd.txt").unwrap();
+    assert_eq!(harness.read_file("copy of odd.txt"), "odd\n");
+    assert!(!harness.path(r"copy\ of\ odd.txt").exists());
+}
+
+/// Command that counts how many times it has run
+#[derive(Debug)]
+struct CountCommand(Rc<Cell<usize>>);
+
+impl Command for CountCommand {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn description(&self) -> &'static str {
+        "Count executions"
+    }
+
+    fn execute(&mut self, args: &[String]) -> CliResult<CommandResult> {
+        self.0.set(self.0.get() + 1);
+        if args.first().is_some_and(|arg| arg == "fail") {
+            return Err(CliError::execution_error("asked to fail"));
+        }
+        Ok(CommandResult::success_silent())
+    }
+}
+
+/// Menu with a counting command and the repeat meta-command
+#[derive(Debug)]
+struct CountMenu(Rc<Cell<usize>>);
+
+impl Command for CountMenu {
+    fn name(&self) -> &'static str {
+        "root"
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(CountCommand(Rc::clone(&self.0))),
+            Box::new(RepeatCommand::new()),
+        ]
+    }
+}
+
+#[test]
+fn test_repeat_runs_command_three_times() {
+    let count = Rc::new(Cell::new(0));
+    let mut app = App::with_root(CliContext::new(), Box::new(CountMenu(Rc::clone(&count))));
+
+    app.handle_input("repeat 3 count").unwrap();
+    assert_eq!(count.get(), 3);
+
+    // The real root menu dispatches through the same path
+    let mut app = App::new(CliContext::new());
+    assert!(app.handle_input("repeat 3 info").is_ok());
+}
+
+#[test]
+fn test_middleware_rewrites_input() {
+    let count = Rc::new(Cell::new(0));
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut app = App::with_root(CliContext::new(), Box::new(CountMenu(Rc::clone(&count))));
+
+    // Uppercase the command token, leaving the arguments alone
+    app.add_middleware(|input, _context| {
+        Ok(Some(match input.split_once(' ') {
+            Some((command, rest)) => format!("{} {rest}", command.to_uppercase()),
+            None => input.to_uppercase(),
+        }))
+    });
+    let recorder = Rc::clone(&seen);
+    app.add_middleware(move |input, _context| {
+        recorder.borrow_mut().push(input.to_string());
+        Ok(None)
+    });
+
+    app.handle_input("repeat 2 count").unwrap();
+    assert_eq!(count.get(), 2);
+    // Each line of the batch also passes through the chain
+    assert_eq!(*seen.borrow(), ["REPEAT 2 count", "COUNT", "COUNT"]);
+}
+
+#[test]
+fn test_middleware_blocks_command() {
+    let count = Rc::new(Cell::new(0));
+    let mut app = App::with_root(CliContext::new(), Box::new(CountMenu(Rc::clone(&count))));
+    app.add_middleware(|input, _context| {
+        if input.split_whitespace().next() == Some("count") {
+            return Err(CliError::permission_denied("count is blocked"));
+        }
+        Ok(None)
+    });
+
+    assert!(matches!(
+        app.handle_input("count"),
+        Err(CliError::PermissionDenied(_))
+    ));
+    assert!(app.handle_input("repeat 3 count").is_err());
+    assert_eq!(count.get(), 0);
+}
+
+#[test]
+fn test_repeat_stops_on_error_unless_keep_going() {
+    let count = Rc::new(Cell::new(0));
+    let mut app = App::with_root(CliContext::new(), Box::new(CountMenu(Rc::clone(&count))));
+
+    assert!(app.handle_input("repeat 3 count fail").is_err());
+    assert_eq!(count.get(), 1);
+
+    count.set(0);
+    assert!(app.handle_input("repeat 3 --keep-going count fail").is_ok());
+    assert_eq!(count.get(), 3);
+}
+
+#[test]
+fn test_repeat_rejects_bad_count_and_recursion() {
+    let mut cmd = RepeatCommand::new();
+    for count in ["0", "-2", "many", "1001", "99999999999999999999999"] {
+        let result = cmd.execute(&[count.to_string(), "info".to_string()]);
+        assert!(matches!(result, Err(CliError::InvalidInput(_))), "{count}");
+    }
+
+    let result = cmd.execute(&["3".to_string()]);
+    assert!(matches!(result, Err(CliError::TooFewArguments { .. })));
+
+    let result = cmd.execute(&["2".to_string(), "repeat".to_string(), "2".to_string()]);
+    assert!(matches!(result, Err(CliError::InvalidInput(_))));
+}
+
+/// Command that renders the path it was executed at
+#[derive(Debug)]
+struct BreadcrumbCommand(Rc<RefCell<String>>);
+
+impl Command for BreadcrumbCommand {
+    fn name(&self) -> &'static str {
+        "axis"
+    }
+
+    fn description(&self) -> &'static str {
+        "Render the command path"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::success_silent())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        _args: &[String],
+        path: &[String],
+        _context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        *self.0.borrow_mut() = path.join(" > ");
+        Ok(CommandResult::success_silent())
+    }
+}
+
+/// Menu tree `root > view > axis` ending in a breadcrumb command
+#[derive(Debug)]
+struct BreadcrumbMenu(&'static str, Rc<RefCell<String>>);
+
+impl Command for BreadcrumbMenu {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        match self.0 {
+            "root" => vec![Box::new(BreadcrumbMenu("view", Rc::clone(&self.1)))],
+            _ => vec![Box::new(BreadcrumbCommand(Rc::clone(&self.1)))],
+        }
+    }
+}
+
+#[test]
+fn test_command_receives_its_path() {
+    let rendered = Rc::new(RefCell::new(String::new()));
+    let root = BreadcrumbMenu("root", Rc::clone(&rendered));
+    let mut app = App::with_root(CliContext::new(), Box::new(root));
+
+    app.handle_input("view").unwrap();
+    app.handle_input("axis").unwrap();
+    assert_eq!(*rendered.borrow(), "view > axis");
+
+    // Path-dependent built-in commands accept any path
+    let path = ["view".to_string(), "axis".to_string()];
+    let mut context = CliContext::new();
+    assert!(
+        AxisCommand::new()
+            .execute_with_context(&[], &path, &mut context)
+            .is_ok()
+    );
+    assert!(
+        ShowCommand::new()
+            .execute_with_context(&[], &path, &mut context)
+            .is_ok()
+    );
+}
+
+/// Command that counts its runs, optionally declaring itself cacheable
+#[derive(Debug)]
+struct CountingCommand(&'static str, Rc<Cell<usize>>);
+
+impl Command for CountingCommand {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn description(&self) -> &'static str {
+        "Count runs"
+    }
+
+    fn is_cacheable(&self, _args: &[String]) -> bool {
+        self.0 == "pure"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::success_silent())
+    }
+
+    fn execute_with_context(
+        &mut self,
+        args: &[String],
+        _path: &[String],
+        context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        self.1.set(self.1.get() + 1);
+        context
+            .display()
+            .display_text(&format!("{} {}", self.0, args.join(" ")));
+        Ok(CommandResult::success("counted"))
+    }
+}
+
+/// Menu offering a cacheable and a non-cacheable counting command
+#[derive(Debug)]
+struct CountingMenu(Rc<Cell<usize>>, Rc<Cell<usize>>);
+
+impl Command for CountingMenu {
+    fn name(&self) -> &'static str {
+        "root"
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(CountingCommand("pure", Rc::clone(&self.0))),
+            Box::new(CountingCommand("impure", Rc::clone(&self.1))),
+        ]
+    }
+}
+
+#[test]
+fn test_cacheable_commands_run_once_per_arguments() {
+    let harness = TestHarness::new("result-cache").unwrap();
+    let (pure, impure) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+    let root = CountingMenu(Rc::clone(&pure), Rc::clone(&impure));
+    let mut app = App::with_root(harness.context(""), Box::new(root));
+
+    for _ in 0..3 {
+        app.handle_input("pure a").unwrap();
+        app.handle_input("impure a").unwrap();
+    }
+    assert_eq!(pure.get(), 1);
+    assert_eq!(impure.get(), 3);
+
+    // A replay shows the same output and result as the run
+    assert_eq!(
+        harness.output(),
+        "pure a\nOK counted\nimpure a\nOK counted\n".repeat(3)
+    );
+
+    // Other arguments are a different run
+    app.handle_input("pure b").unwrap();
+    assert_eq!(pure.get(), 2);
+}
+
+#[test]
+fn test_vers_is_cached_but_check_is_not() {
+    let vers = VersCommand::new();
+    assert!(vers.is_cacheable(&[]));
+    assert!(vers.is_cacheable(&["--json".to_string()]));
+    assert!(!vers.is_cacheable(&["--check".to_string()]));
+    assert!(!SaveCommand::new().is_cacheable(&[]));
+}
+
+#[test]
+fn test_save_command_writes_buffer() {
+    let harness = TestHarness::new("save").unwrap();
+    let mut context = harness.context("");
+    let mut cmd = SaveCommand::new();
+
+    context
+        .edit_buffer(|buffer| {
+            buffer.set_text("one\ntwo\n");
+            Ok(())
+        })
+        .unwrap();
+
+    // Saving to a new file is not destructive
+    let args = ["out.txt".to_string()];
+    assert!(!cmd.is_destructive(&args, &context));
+    let result = cmd.execute_with_context(&args, &[], &mut context);
+    assert_eq!(result.unwrap(), CommandResult::success_silent());
+    assert_eq!(harness.output(), "OK Saved 2 lines to out.txt\n");
+    assert_eq!(harness.read_file("out.txt"), "one\ntwo\n");
+    assert!(!context.buffer().is_dirty());
+    assert_eq!(context.buffer().filename(), Some("out.txt"));
+
+    // Saving again (to the buffer's filename) would overwrite it
+    assert!(cmd.is_destructive(&[], &context));
+
+    assert!(matches!(
+        cmd.execute_with_context(&["../escape.txt".to_string()], &[], &mut context),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        cmd.execute_with_context(&["missing/out.txt".to_string()], &[], &mut context),
+        Err(CliError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_save_append_adds_to_file() {
+    let harness = TestHarness::new("save-append").unwrap();
+    harness.write_file("log.txt", "first\n");
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+    app.context_mut()
+        .edit_buffer(|buffer| {
+            buffer.set_text("second\nthird\n");
+            Ok(())
+        })
+        .unwrap();
+
+    // Appending is not destructive, so the loop does not ask even though
+    // the input is not a terminal and --yes was not given
+    let args = ["--append".to_string(), "log.txt".to_string()];
+    assert!(!SaveCommand::new().is_destructive(&args, app.context()));
+    app.handle_input("save --append log.txt").unwrap();
+    assert_eq!(harness.read_file("log.txt"), "first\nsecond\nthird\n");
+    assert_eq!(harness.output(), "OK Appended 2 lines to log.txt\n");
+    // The buffer is not what the file holds, so it stays unsaved
+    assert_eq!(app.context().buffer().filename(), None);
+
+    // A missing file is created
+    app.handle_input("save --append new.txt").unwrap();
+    assert_eq!(harness.read_file("new.txt"), "second\nthird\n");
+
+    // The target is required, and must pass the path and extension checks
+    assert!(matches!(
+        app.handle_input("save --append"),
+        Err(CliError::TooFewArguments { .. })
+    ));
+    assert!(app.handle_input("save --append ../escape.txt").is_err());
+    let mut security = SecurityContext::new(harness.root()).unwrap();
+    security.allowed_extensions = Some(vec!["txt".to_string()]);
+    app.context_mut().set_security(security);
+    assert!(matches!(
+        app.handle_input("save --append notes.md"),
+        Err(CliError::PermissionDenied(_))
+    ));
+    assert!(!harness.path("notes.md").exists());
+}
+
+#[test]
+fn test_destructive_command_is_confirmed_by_loop() {
+    let temp = TempRoot::new("confirm");
+    fs::write(temp.0.join("notes.txt"), "old\n").unwrap();
+
+    let mut app = App::new(temp.context());
+    app.handle_input("file").unwrap();
+    app.context_mut()
+        .edit_buffer(|buffer| {
+            buffer.set_text("new\n");
+            Ok(())
+        })
+        .unwrap();
+
+    // Overwriting prompts; declining leaves the file alone
+    app.context_mut().set_io(terminal_input("n\n"));
+    app.handle_input("save notes.txt").unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "old\n"
+    );
+    assert_eq!(app.context_mut().io_mut().read_line().unwrap(), None);
+
+    // Accepting runs the command
+    app.context_mut().set_io(terminal_input("y\n"));
+    app.handle_input("save notes.txt").unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "new\n"
+    );
+
+    // A non-destructive save does not prompt, so the input is left unread
+    app.context_mut().set_io(terminal_input("n\n"));
+    app.handle_input("save fresh.txt").unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("fresh.txt")).unwrap(),
+        "new\n"
+    );
+    assert_eq!(
+        app.context_mut().io_mut().read_line().unwrap(),
+        Some("n\n".to_string())
+    );
+
+    // With confirmation turned off, destructive commands run directly
+    app.context_mut().preferences_mut().confirm_destructive = false;
+    app.context_mut().set_io(terminal_input("n\n"));
+    fs::write(temp.0.join("notes.txt"), "old\n").unwrap();
+    app.handle_input("save notes.txt").unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "new\n"
+    );
+}
+
+#[test]
+fn test_line_endings_on_load_and_save() {
+    let temp = TempRoot::new("line-endings");
+    fs::write(temp.0.join("dos.txt"), "one\r\ntwo\r\n").unwrap();
+    let mut context = temp.context();
+    let mut load = LoadCommand::new();
+    let mut save = SaveCommand::new();
+
+    // Preserve (the default) writes back the style that was loaded
+    assert_eq!(context.preferences().line_ending, LineEnding::Preserve);
+    load.execute_with_context(&["dos.txt".to_string()], &[], &mut context)
+        .unwrap();
+    assert_eq!(context.buffer().line_ending(), LineEnding::CrLf);
+    save.execute_with_context(&["kept.txt".to_string()], &[], &mut context)
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("kept.txt")).unwrap(),
+        "one\r\ntwo\r\n"
+    );
+
+    // Lf normalizes on save
+    context.preferences_mut().line_ending = LineEnding::Lf;
+    save.execute_with_context(&["unix.txt".to_string()], &[], &mut context)
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("unix.txt")).unwrap(),
+        "one\ntwo\n"
+    );
+}
+
+/// Menu whose `view` and `vers` children both use the alias `v`
+#[derive(Debug)]
+struct ClashMenu;
+
+impl Command for ClashMenu {
+    fn name(&self) -> &'static str {
+        "root"
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![Box::new(VersCommand::new()), Box::new(ViewCommand::new())]
+    }
+}
+
+#[test]
+fn test_ambiguous_alias_is_reported() {
+    let mut app = App::with_root(CliContext::new(), Box::new(ClashMenu));
+
+    match app.handle_input("v") {
+        Err(CliError::AmbiguousCommand { input, candidates }) => {
+            assert_eq!(input, "v");
+            assert_eq!(candidates, ["vers", "view"]);
+        }
+        other => panic!("Expected AmbiguousCommand, got {other:?}"),
+    }
+
+    // Full names still resolve, and an exact name beats an alias
+    app.handle_input("VIEW").unwrap();
+    assert_eq!(app.context().current_path(), ["view"]);
+}
+
+/// Command that only works with an interactive terminal
+#[derive(Debug)]
+struct PagerCommand;
+
+impl Command for PagerCommand {
+    fn name(&self) -> &'static str {
+        "pager"
+    }
+
+    fn description(&self) -> &'static str {
+        "Interactive-only test command"
+    }
+
+    fn requires_tty(&self) -> bool {
+        true
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::success_silent())
+    }
+}
+
+/// Menu holding the interactive-only command
+#[derive(Debug)]
+struct PagerMenu;
+
+impl Command for PagerMenu {
+    fn name(&self) -> &'static str {
+        "root"
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![Box::new(PagerCommand)]
+    }
+}
+
+#[test]
+fn test_tty_command_rejected_in_script_mode() {
+    let mut app = App::with_root(CliContext::new(), Box::new(PagerMenu));
+
+    app.context_mut().set_io(SessionIo::from_bytes(""));
+    match app.handle_input("pager") {
+        Err(CliError::TerminalError(msg)) => {
+            assert!(msg.contains("requires an interactive terminal"));
+        }
+        other => panic!("Expected TerminalError, got {other:?}"),
+    }
+
+    app.context_mut().set_io(terminal_input(""));
+    assert!(app.handle_input("pager").is_ok());
+}
+
+#[test]
+fn test_script_confirmation_needs_yes() {
+    let temp = TempRoot::new("assume-yes");
+    fs::write(temp.0.join("notes.txt"), "old\n").unwrap();
+
+    let mut app = App::new(temp.context());
+    app.handle_input("file").unwrap();
+    app.context_mut()
+        .edit_buffer(|buffer| {
+            buffer.set_text("new\n");
+            Ok(())
+        })
+        .unwrap();
+
+    // Without --yes a script cannot answer, so the save is refused with
+    // an error and the "y" line is not consumed
+    app.context_mut().set_io(SessionIo::from_bytes("y\n"));
+    match app.handle_input("save notes.txt") {
+        Err(CliError::TerminalError(msg)) => {
+            assert!(msg.contains("input is not a terminal; pass --yes"), "{msg}");
+        }
+        other => panic!("Expected TerminalError, got {other:?}"),
+    }
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "old\n"
+    );
+    assert_eq!(
+        app.context_mut().io_mut().read_line().unwrap(),
+        Some("y\n".to_string())
+    );
+
+    // With --yes the confirmation is answered automatically
+    app.context_mut().preferences_mut().assume_yes = true;
+    app.context_mut().set_io(SessionIo::from_bytes(""));
+    app.handle_input("save notes.txt").unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "new\n"
+    );
+
+    // At a terminal too, without reading an answer
+    fs::write(temp.0.join("notes.txt"), "old\n").unwrap();
+    app.context_mut().set_io(terminal_input("n\n"));
+    app.handle_input("save notes.txt").unwrap();
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "new\n"
+    );
+    assert_eq!(
+        app.context_mut().io_mut().read_line().unwrap(),
+        Some("n\n".to_string())
+    );
+}
+
+#[test]
+fn test_line_editing_commands() {
+    let mut context = CliContext::new();
+    let mut append = AppendCommand::new();
+    let mut insert = InsertCommand::new();
+    let mut delete = DeleteCommand::new();
+    let words = |text: &str| -> Vec<String> { text.split(' ').map(str::to_string).collect() };
+
+    // Appending two lines
+    append
+        .execute_with_context(&words("hello world"), &[], &mut context)
+        .unwrap();
+    append
+        .execute_with_context(&words("second"), &[], &mut context)
+        .unwrap();
+    assert_eq!(context.buffer().lines(), ["hello world", "second"]);
+    assert!(context.buffer().is_dirty());
+
+    // Inserting at position 1
+    let result = insert.execute_with_context(&words("1 first"), &[], &mut context);
+    assert_eq!(result.unwrap(), CommandResult::success("Inserted line 1"));
+    assert_eq!(context.buffer().lines(), ["first", "hello world", "second"]);
+
+    // Deleting a line, which can be undone
+    delete
+        .execute_with_context(&words("2"), &[], &mut context)
+        .unwrap();
+    assert_eq!(context.buffer().lines(), ["first", "second"]);
+    context.undo().unwrap();
+    assert_eq!(context.buffer().lines(), ["first", "hello world", "second"]);
+
+    // Out-of-range and malformed line numbers
+    for args in ["4", "0", "x"] {
+        assert!(matches!(
+            delete.execute_with_context(&words(args), &[], &mut context),
+            Err(CliError::InvalidInput(_))
+        ));
+    }
+    assert!(matches!(
+        insert.execute_with_context(&words("5 late"), &[], &mut context),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        append.execute_with_context(&[], &[], &mut context),
+        Err(CliError::TooFewArguments { .. })
+    ));
+    assert_eq!(context.buffer().len(), 3);
+}
+
+#[test]
+fn test_which_reports_resolution() {
+    let which = |menu: &dyn Command, token: &str| {
+        let mut cmd = WhichCommand::new(menu.subcommands());
+        match cmd.execute(&[token.to_string()]).unwrap() {
+            CommandResult::Success(msg) => msg,
+            other => panic!("Expected Success, got {other:?}"),
+        }
+    };
+    let edit = EditCommand::new();
+
+    assert_eq!(
+        which(&edit, "undo"),
+        "'undo' resolves to 'undo' by name - Undo the last edit to the buffer"
+    );
+    assert_eq!(
+        which(&edit, "u"),
+        "'u' resolves to 'undo' by alias - Undo the last edit to the buffer"
+    );
+    assert_eq!(
+        which(&edit, "app"),
+        "'app' resolves to 'append' by prefix - Add a line to the end of the buffer"
+    );
+    assert_eq!(
+        which(&edit, "in"),
+        "'in' is ambiguous: could be insert, info"
+    );
+    assert_eq!(which(&edit, "zzz"), "'zzz' not found");
+
+    // `which` is available in every menu, and prefixes dispatch like names
+    let mut app = App::new(CliContext::new());
+    app.handle_input("ed").unwrap();
+    assert_eq!(app.context().current_path(), ["edit"]);
+    assert!(app.handle_input("which in").is_ok());
+    assert!(matches!(
+        app.handle_input("in"),
+        Err(CliError::AmbiguousCommand { .. })
+    ));
+}
+
+#[test]
+fn test_axis_set_valid_range() {
+    let mut app = App::new(CliContext::new());
+    app.handle_input("edit").unwrap();
+    app.handle_input("axis x set min 0 max 10").unwrap();
+
+    let axis = app
+        .context()
+        .axes("edit")
+        .unwrap()
+        .get("x")
+        .copied()
+        .unwrap();
+    assert_eq!(axis.min, Some(0.0));
+    assert_eq!(axis.max, Some(10.0));
+
+    // Setting one bound keeps the other
+    app.handle_input("axis x set max 2.5").unwrap();
+    let axis = app
+        .context()
+        .axes("edit")
+        .unwrap()
+        .get("x")
+        .copied()
+        .unwrap();
+    assert_eq!((axis.min, axis.max), (Some(0.0), Some(2.5)));
+
+    // Axes are kept per menu
+    assert!(app.context().axes("view").is_none());
+}
+
+#[test]
+fn test_axis_scope_shares_or_isolates_axes() {
+    for (scope, visible_in_view) in [(AxisScope::Shared, true), (AxisScope::Menu, false)] {
+        let mut app = App::new(CliContext::new());
+        app.context_mut().preferences_mut().axis_scope = scope;
+        app.handle_input("edit").unwrap();
+        app.handle_input("axis x set min 0 max 10").unwrap();
+        app.handle_input("exit").unwrap();
+        app.handle_input("view").unwrap();
+
+        let in_view = app.context().axes("view").and_then(|axes| axes.get("x"));
+        assert_eq!(in_view.is_some(), visible_in_view, "{scope:?}");
+
+        // Setting a bound from view changes the axis edit sees when shared
+        app.handle_input("axis x set max 5").unwrap();
+        let in_edit = app.context().axes("edit").unwrap().get("x").unwrap();
+        let expected = if visible_in_view {
+            Some(5.0)
+        } else {
+            Some(10.0)
+        };
+        assert_eq!(in_edit.max, expected, "{scope:?}");
+    }
+}
+
+#[test]
+fn test_axis_reports_argument_range() {
+    let cmd = AxisCommand::new();
+    let error = cmd
+        .validate(&["x".to_string(), "y".to_string()])
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        CliError::WrongArgumentCount {
+            min: 0,
+            max: 1,
+            found: 2
+        }
+    ));
+    assert!(
+        error
+            .to_string()
+            .contains("expected 0 to 1 arguments, found 2")
+    );
+    assert!(cmd.validate(&["x".to_string()]).is_ok());
+}
+
+#[test]
+fn test_axis_set_rejects_invalid_values() {
+    let mut app = App::new(CliContext::new());
+    app.handle_input("edit").unwrap();
+    app.handle_input("axis x set min 0 max 10").unwrap();
+    let stored = app.context().axes("edit").unwrap().get("x").copied();
+
+    for input in [
+        // min >= max, including against the stored bound
+        "axis x set min 10 max 0",
+        "axis x set min 5 max 5",
+        "axis x set min 20",
+        // non-numeric values
+        "axis x set min abc",
+        "axis x set max inf",
+        // unknown property and missing value
+        "axis x set width 3",
+        "axis x set min",
+    ] {
+        assert!(
+            matches!(app.handle_input(input), Err(CliError::InvalidInput(_))),
+            "{input}"
+        );
+        assert_eq!(
+            app.context().axes("edit").unwrap().get("x").copied(),
+            stored,
+            "{input}"
+        );
+    }
+
+    // A new axis with invalid bounds is not stored at all
+    assert!(app.handle_input("axis y set min 3 max 1").is_err());
+    assert!(app.context().axes("edit").unwrap().get("y").is_none());
+}
+
+/// Run the sm-menu binary on a script in `root`, returning its stderr
+fn run_script(root: &TempRoot, script: &str, flags: &[&str]) -> String {
+    let output = script_output(root, script, flags);
+    assert!(output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+/// Run a script through the binary and return how the process ended
+fn script_output(root: &TempRoot, script: &str, flags: &[&str]) -> std::process::Output {
+    let path = root.0.join("run.sm");
+    fs::write(&path, script).unwrap();
+
+    std::process::Command::new(env!("CARGO_BIN_EXE_sm-menu"))
+        .args([
+            "--no-config",
+            "--root",
+            root.0.to_str().unwrap(),
+            "--script",
+        ])
+        .arg(&path)
+        .args(flags)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_script_run_summary() {
+    let root = TempRoot::new("script-summary");
+    let script = "file\nls\nbogus\nexit\n\nvers extra\nquit\n";
+
+    let stderr = run_script(&root, script, &[]);
+    let summary = stderr.lines().last().unwrap();
+    assert!(
+        summary.starts_with("6 commands run: 4 succeeded, 2 failed in "),
+        "{summary}"
+    );
+
+    let stderr = run_script(&root, script, &["--json"]);
+    let summary = stderr.lines().last().unwrap();
+    assert!(
+        summary.starts_with(r#"{"commands_run":6,"succeeded":4,"failed":2,"elapsed_ms":"#),
+        "{summary}"
+    );
+
+    let stderr = run_script(&root, script, &["--quiet"]);
+    assert!(!stderr.contains("commands run"), "{stderr}");
+}
+
+#[test]
+fn test_script_echo_prints_command_lines() {
+    let root = TempRoot::new("script-echo");
+    let script = "file\nls\n";
+
+    let stderr = run_script(&root, script, &["--quiet", "--echo"]);
+    assert_eq!(
+        stderr.lines().collect::<Vec<_>>(),
+        ["sm-menu > ? file", "sm-menu ~ file > ? ls"]
+    );
+
+    let stderr = run_script(&root, script, &["--quiet"]);
+    assert!(!stderr.contains("> ? "), "{stderr}");
+}
+
+#[test]
+fn test_version_json_reports_build_info() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_sm-menu"))
+        .arg("--version-json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let info = JsonValue::parse(&stdout).unwrap();
+    assert_eq!(
+        info.get("name").and_then(JsonValue::as_str),
+        Some("sm-menu")
+    );
+    assert_eq!(
+        info.get("version").and_then(JsonValue::as_str),
+        Some(env!("CARGO_PKG_VERSION"))
+    );
+    assert!(info.get("commit").is_some() && info.get("built").is_some());
+
+    // vers --json prints the same object inside a session
+    let harness = TestHarness::new("vers-json").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+    app.handle_input("vers --json").unwrap();
+    assert_eq!(harness.output(), stdout);
+    assert_eq!(app.context().current_path(), ["file"]);
+    assert!(matches!(
+        app.handle_input("vers --json extra"),
+        Err(CliError::TooManyArguments { .. })
+    ));
+}
+
+#[test]
+fn test_workdir_from_env_and_flag() {
+    let env_root = TempRoot::new("workdir-env");
+    let flag_root = TempRoot::new("workdir-flag");
+    let run = |workdir: &Path, args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sm-menu"))
+            .current_dir(&flag_root.0)
+            .env("SM_MENU_WORKDIR", workdir)
+            .arg("--no-config")
+            .args(args)
+            .args(["--output", "result.txt", "file", "vers"])
+            .output()
+            .unwrap()
+    };
+
+    // The environment variable sets the root
+    let output = run(&env_root.0, &[]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(env_root.0.join("result.txt").exists());
+
+    // --workdir takes precedence over it
+    let output = run(&env_root.0, &["--workdir", flag_root.0.to_str().unwrap()]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(flag_root.0.join("result.txt").exists());
+
+    // A missing directory stops the session before it starts
+    let output = run(&env_root.0.join("missing"), &[]);
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Working directory from SM_MENU_WORKDIR"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_one_shot_output_lands_in_file() {
+    let root = TempRoot::new("one-shot");
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sm-menu"))
+            .current_dir(&root.0)
+            .args(["--no-config", "--root"])
+            .arg(&root.0)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["--output", "result.txt", "file", "vers"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stdout.is_empty(), "{output:?}");
+    let written = fs::read_to_string(root.0.join("result.txt")).unwrap();
+    assert_eq!(
+        written,
+        format!("sm-menu > version {}\n", env!("CARGO_PKG_VERSION"))
+    );
+
+    // Errors go to stderr and fail the process, leaving the file empty
+    let output = run(&["--output", "result.txt", "file", "load", "missing.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.txt"));
+    assert_eq!(fs::read_to_string(root.0.join("result.txt")).unwrap(), "");
+
+    // The output file is validated like a redirect target
+    let output = run(&["--output", "../escape.txt", "file", "vers"]);
+    assert!(!output.status.success());
+    assert!(!root.0.with_file_name("escape.txt").exists());
+}
+
+#[test]
+fn test_init_writes_default_config() {
+    let root = TempRoot::new("init");
+    let init = |flags: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sm-menu"))
+            .env("XDG_CONFIG_HOME", &root.0)
+            .arg("--init")
+            .args(flags)
+            .output()
+            .unwrap()
+    };
+    let path = root.0.join("sm-menu").join("config.toml");
+
+    let output = init(&[]);
+    assert!(output.status.success());
+    assert_eq!(
+        CliPreferences::from_path(&path).unwrap(),
+        CliPreferences::default()
+    );
+
+    // A second --init leaves the edited file alone unless forced
+    fs::write(&path, "max_list_items = 5\n").unwrap();
+    let output = init(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("already exists"), "{stderr}");
+    assert_eq!(CliPreferences::from_path(&path).unwrap().max_list_items, 5);
+
+    assert!(init(&["--force"]).status.success());
+    assert_eq!(
+        CliPreferences::from_path(&path).unwrap(),
+        CliPreferences::default()
+    );
+}
+
+#[test]
+fn test_recursive_file_menu_disabled_by_default() {
+    let has_nested_file = |app: &App| {
+        app.command_stack()
+            .last()
+            .unwrap()
+            .subcommands()
+            .iter()
+            .any(|cmd| cmd.name() == "file")
+    };
+
+    let mut app = App::new(CliContext::new());
+    app.handle_input("file").unwrap();
+    assert!(!has_nested_file(&app));
+    assert!(matches!(
+        app.handle_input("file"),
+        Err(CliError::InvalidCommand(_))
+    ));
+    assert_eq!(app.context().current_path(), ["file"]);
+}
+
+#[test]
+fn test_recursive_file_menu_enabled() {
+    let mut context = CliContext::new();
+    context.preferences_mut().enable_recursive_menus = true;
+    let mut app = App::new(context);
+
+    app.handle_input("file").unwrap();
+    assert!(
+        app.command_stack()
+            .last()
+            .unwrap()
+            .subcommands()
+            .iter()
+            .any(|cmd| cmd.name() == "file")
+    );
+
+    // Nesting is still bounded by the navigation depth limit
+    let mut result = Ok(());
+    for _ in 0..20 {
+        result = app.handle_input("file");
+        if result.is_err() {
+            break;
+        }
+    }
+    assert!(matches!(result, Err(CliError::ExecutionError { .. })));
+    assert_eq!(app.command_stack().len(), 10);
+}
+
+#[test]
+fn test_max_depth_caps_navigation() {
+    let mut context = CliContext::new();
+    context.preferences_mut().enable_recursive_menus = true;
+    context.preferences_mut().max_depth = Some(2);
+    let mut app = App::new(context);
+
+    app.handle_input("file").unwrap();
+    app.handle_input("file").unwrap();
+    let err = app.handle_input("file").unwrap_err();
+    assert!(matches!(err, CliError::ExecutionError { .. }));
+    assert!(
+        err.to_string().contains("the menu depth is limited to 2"),
+        "{err}"
+    );
+    assert_eq!(app.context().depth(), 2);
+    assert_eq!(app.context().current_path(), ["file", "file"]);
+
+    // Commands and going back still work at the cap
+    app.handle_input("exit").unwrap();
+    app.handle_input("file").unwrap();
+    assert_eq!(app.context().depth(), 2);
+
+    // A cap of 0 keeps the session at the root
+    let mut context = CliContext::new();
+    context.preferences_mut().max_depth = Some(0);
+    let mut app = App::new(context);
+    assert!(app.handle_input("file").is_err());
+    assert_eq!(app.command_stack().len(), 1);
+}
+
+#[test]
+fn test_validate_rejects_save_arguments_without_io() {
+    let root = TempRoot::new("validate-save");
+    let args = ["a.txt".to_string(), "b.txt".to_string()];
+
+    let save = SaveCommand::new();
+    assert!(matches!(
+        save.validate(&args),
+        Err(CliError::TooManyArguments {
+            expected: 1,
+            found: 2
+        })
+    ));
+    assert!(save.validate(&args[..1]).is_ok());
+
+    // The session loop validates before running, so nothing is written
+    let mut app = App::new(root.context());
+    app.handle_input("file").unwrap();
+    assert!(matches!(
+        app.handle_input("save a.txt b.txt"),
+        Err(CliError::TooManyArguments { .. })
+    ));
+    assert_eq!(fs::read_dir(&root.0).unwrap().count(), 0);
+}
+
+#[test]
+fn test_which_check_validates_arguments() {
+    let which = |args: &[&str]| {
+        let mut cmd = WhichCommand::new(FileCommand::new().subcommands());
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        match cmd.execute(&args).unwrap() {
+            CommandResult::Success(msg) => msg,
+            other => panic!("Expected Success, got {other:?}"),
+        }
+    };
+
+    assert!(which(&["--check", "save", "out.txt"]).ends_with("\nArguments OK"));
+    assert!(
+        which(&["--check", "load"])
+            .ends_with("\nArguments rejected: Too few arguments: expected 1, found 0")
+    );
+    assert!(!which(&["save"]).contains("Arguments"));
+}
+
+#[test]
+fn test_history_command_shows_timestamps_when_present() {
+    let mut context = CliContext::new();
+    context.extend_history([
+        HistoryEntry::parse_line("ls"),
+        HistoryEntry::parse_line("2024-06-01T10:00:00Z\tsave x"),
+    ]);
+
+    let mut cmd = HistoryCommand::new();
+    match cmd.execute_with_context(&[], &["history".to_string()], &mut context) {
+        Ok(CommandResult::Success(msg)) => {
+            assert_eq!(msg, "   1  ls\n   2  2024-06-01T10:00:00Z  save x");
+        }
+        other => panic!("Expected Success, got {other:?}"),
+    }
+
+    // Typed commands are recorded with a timestamp
+    context.add_to_history("vers".to_string());
+    assert!(context.history().back().unwrap().timestamp.is_some());
+}
+
+#[test]
+fn test_transcript_logs_plain_text_session() {
+    let harness = TestHarness::new("transcript").unwrap();
+    harness.write_file("notes.txt", "one\n");
+    let mut context = harness.context("file\nls\nexit\nedit\nappend hello world\nvers\n");
+    context.preferences_mut().colored_prompt = true;
+    let mut app = App::new(context);
+
+    let log = CapturedOutput::default();
+    app.set_transcript(Transcript::new(Box::new(log.clone())));
+    app.run().unwrap();
+
+    // The log holds what the screen showed, as plain text: the prompts
+    // with the lines typed at them, listings, results, and errors
+    let log = log.text();
+    assert!(!log.contains('\x1b'), "{log:?}");
+    assert_eq!(
+        log.lines().take(8).collect::<Vec<_>>(),
+        [
+            "sm-menu > ? file",
+            "sm-menu ~ file > ? ls",
+            "     1. notes.txt",
+            "sm-menu ~ file > ? exit",
+            "sm-menu > ? edit",
+            "sm-menu ~ edit > ? append hello world",
+            "OK Appended line 1",
+            "sm-menu ~ edit* > ? vers",
+        ]
+    );
+    assert!(log.contains("\n! Invalid command: 'vers'\n"), "{log}");
+    assert!(log.contains("\n  append - Add a line to the end of the buffer\n"));
+    assert!(harness.output().contains("     1. notes.txt"));
+}
+
+#[test]
+fn test_vers_check_compares_versions() {
+    let current: Version = "1.2.3".parse().unwrap();
+    let check = |latest: &str| VersCommand::check_against(&current, latest);
+
+    assert_eq!(
+        check("1.3.0").unwrap(),
+        "A newer version is available: 1.3.0 (running 1.2.3)"
+    );
+    assert_eq!(
+        check("v1.2.3").unwrap(),
+        "Up to date: 1.2.3 is the latest version"
+    );
+    assert_eq!(
+        check("1.2.3-rc.1").unwrap(),
+        "Running 1.2.3, which is newer than the latest known version 1.2.3-rc.1"
+    );
+    assert!(matches!(check("1.two.3"), Err(CliError::InvalidInput(_))));
+}
+
+#[test]
+fn test_vers_check_reads_latest_from_file() {
+    let root = TempRoot::new("vers-check");
+    let mut context = root.context();
+    let mut cmd = VersCommand::new();
+    let args = |file: &str| vec!["--check".to_string(), file.to_string()];
+
+    fs::write(root.0.join("latest"), "999.0.0\n").unwrap();
+    match cmd.execute_with_context(&args("latest"), &[], &mut context) {
+        Ok(CommandResult::Success(message)) => {
+            assert!(message.starts_with("A newer version is available: 999.0.0"))
+        }
+        other => panic!("Expected an update report, got {other:?}"),
+    }
+
+    fs::write(root.0.join("garbled"), "latest!\n").unwrap();
+    assert!(matches!(
+        cmd.execute_with_context(&args("garbled"), &[], &mut context),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        cmd.execute(&["--check".into(), "a".into(), "b".into()]),
+        Err(CliError::TooManyArguments {
+            expected: 2,
+            found: 3
+        })
+    ));
+}
+
+/// Menu whose commands each return one kind of `CommandResult`
+#[derive(Debug)]
+struct ResultMenu(&'static str, Rc<RefCell<Vec<String>>>);
+
+impl Command for ResultMenu {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        unreachable!("ResultMenu runs through execute_with_context")
+    }
+
+    fn execute_with_context(
+        &mut self,
+        _args: &[String],
+        path: &[String],
+        _context: &mut CliContext,
+    ) -> CliResult<CommandResult> {
+        let result = match self.0 {
+            "say" => CommandResult::success("said"),
+            "hush" => CommandResult::success_silent(),
+            "menu" | "root" => CommandResult::Continue,
+            "back" => CommandResult::GoUp,
+            "both" => CommandResult::Batch {
+                lines: vec!["say".to_string(), "say".to_string()],
+                keep_going: false,
+            },
+            _ => CommandResult::Quit,
+        };
+        self.1
+            .borrow_mut()
+            .push(format!("{} at {}", result.variant_name(), path.join(" > ")));
+        Ok(result)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        let names: &[&'static str] = match self.0 {
+            "root" => &["say", "hush", "menu", "both", "stop"],
+            "menu" => &["back"],
+            _ => &[],
+        };
+        names
+            .iter()
+            .map(|name| Box::new(ResultMenu(name, Rc::clone(&self.1))) as Box<dyn Command>)
+            .collect()
+    }
+}
+
+#[test]
+fn test_success_messages_show_with_success_icon() {
+    let harness = TestHarness::new("success-icon").unwrap();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut app = App::with_root(harness.context(""), Box::new(ResultMenu("root", seen)));
+
+    app.handle_input("say").unwrap();
+    assert_eq!(harness.output(), "OK said\n");
+
+    harness.clear_output();
+    app.handle_input("hush").unwrap();
+    assert_eq!(harness.output(), "");
+}
+
+#[test]
+fn test_loop_handles_every_command_result() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut context = CliContext::new();
+    context.set_io(SessionIo::from_bytes("say\nmenu\nback\nboth\nstop\nsay\n"));
+    let mut app = App::with_root(context, Box::new(ResultMenu("root", Rc::clone(&seen))));
+    let log = CapturedOutput::default();
+    app.set_transcript(Transcript::new(Box::new(log.clone())));
+    app.run().unwrap();
+
+    // Continue enters the menu and GoUp leaves it, so the batch's `say`
+    // lines resolve at the root again; Quit stops before the last line
+    assert_eq!(
+        *seen.borrow(),
+        [
+            "Success at say",
+            "Continue at menu",
+            "GoUp at menu > back",
+            "Batch at both",
+            "Success at say",
+            "Success at say",
+            "Quit at stop",
+        ]
+    );
+    assert!(!app.context().running);
+    assert!(app.context().current_path().is_empty());
+    let log = log.text();
+    assert_eq!(
+        log.lines().filter(|line| line.ends_with(" said")).count(),
+        3
+    );
+
+    // A new variant must be added to this session before the test passes
+    let covered: BTreeSet<String> = seen
+        .borrow()
+        .iter()
+        .map(|entry| entry.split(' ').next().unwrap().to_string())
+        .collect();
+    let expected: BTreeSet<String> = CommandResult::VARIANT_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    assert_eq!(covered, expected);
+}
+
+#[test]
+fn test_input_tokens_are_normalized_before_dispatch() {
+    let harness = TestHarness::new("normalized-args").unwrap();
+    harness.write_file("file.txt", "one\n");
+    harness.write_file("my notes.txt", "one\ntwo\n");
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    // Padding and quotes leave one clean argument
+    for input in [
+        "load   file.txt  ",
+        r#"load "file.txt""#,
+        r#"load " file.txt ""#,
+        r#"load file.txt "" '  '"#,
+    ] {
+        harness.clear_output();
+        app.handle_input(input).unwrap();
+        assert_eq!(
+            harness.output(),
+            "OK Loaded 1 lines from file.txt\n",
+            "{input}"
+        );
+    }
+
+    app.handle_input(r#"load 'my notes.txt'"#).unwrap();
+    assert_eq!(app.context().buffer().filename(), Some("my notes.txt"));
+    assert!(matches!(
+        app.handle_input(r#"load "file.txt"#),
+        Err(CliError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_shortcut_hint_lists_current_menu_letters() {
+    let harness = TestHarness::new("shortcut-hint").unwrap();
+    let mut context = harness.context("");
+    context.set_io(terminal_input("file\n"));
+    let mut app = App::new(context);
+    app.run().unwrap();
+
+    let root_hint = "Shortcuts: [f]ile [e]dit [v]iew [h]elp [q]uit";
+    let file_hint = "Shortcuts: [l]oad [s]ave [v]ers [e]xit";
+    assert_eq!(
+        harness.output(),
+        format!("{root_hint}\nsm-menu > ? {file_hint}\nsm-menu ~ file > ? ")
+    );
+}
+
+#[test]
+fn test_shortcut_hint_stops_after_threshold() {
+    let hints = |input: &str, always: bool| {
+        let harness = TestHarness::new("shortcut-hint-threshold").unwrap();
+        let mut context = harness.context("");
+        context.preferences_mut().shortcut_hints = always;
+        context.set_io(terminal_input(input));
+        App::new(context).run().unwrap();
+        harness
+            .output()
+            .lines()
+            .filter(|line| line.starts_with("Shortcuts:"))
+            .count()
+    };
+    let input = "history\n".repeat(SHORTCUT_HINT_COMMANDS + 3);
+
+    // One hint before each prompt until the threshold, counting the
+    // prompt at which input ends
+    assert_eq!(hints(&input, false), SHORTCUT_HINT_COMMANDS);
+    assert_eq!(hints(&input, true), SHORTCUT_HINT_COMMANDS + 4);
+    // Scripts never show it
+    let harness = TestHarness::new("shortcut-hint-script").unwrap();
+    App::new(harness.context("history\n")).run().unwrap();
+    assert!(!harness.output().contains("Shortcuts:"));
+}
+
+#[test]
+fn test_scripted_session_transcript() {
+    let harness = TestHarness::new("transcript").unwrap();
+    harness.write_file("notes.txt", "one\ntwo\n");
+    let mut app = harness.app("file\nload notes.txt\nexit\nquit\n");
+
+    app.welcome();
+    app.run().unwrap();
+    app.goodbye();
+
+    // The banners, every prompt, quit's goodbye, and command output all
+    // reach the writer
+    assert_eq!(
+        harness.output(),
+        format!(
+            "{WELCOME_MESSAGE}\n\
+             sm-menu > ? \
+             sm-menu ~ file > ? OK Loaded 2 lines from notes.txt\n\
+             sm-menu ~ file > ? \
+             sm-menu > ? Goodbye!\n\
+             {GOODBYE_MESSAGE}\n"
+        )
+    );
+}
+
+#[test]
+fn test_touch_creates_or_updates_files() {
+    let harness = TestHarness::new("touch").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    app.handle_input("touch new.txt").unwrap();
+    assert_eq!(harness.read_file("new.txt"), "");
+    assert_eq!(harness.output(), "OK Created empty file new.txt\n");
+
+    // An existing file keeps its contents and gets a new modified time
+    harness.write_file("old.txt", "keep me\n");
+    let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options()
+        .write(true)
+        .open(harness.path("old.txt"))
+        .unwrap()
+        .set_modified(an_hour_ago)
+        .unwrap();
+    harness.clear_output();
+    app.handle_input("touch old.txt").unwrap();
+    assert_eq!(harness.read_file("old.txt"), "keep me\n");
+    let modified = fs::metadata(harness.path("old.txt"))
+        .unwrap()
+        .modified()
+        .unwrap();
+    assert!(modified > an_hour_ago + Duration::from_secs(60));
+    assert_eq!(
+        harness.output(),
+        "OK Updated the modified time of old.txt\n"
+    );
+
+    fs::create_dir(harness.path("dir")).unwrap();
+    assert!(matches!(
+        app.handle_input("touch dir"),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        app.handle_input("touch ../escape.txt"),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(!harness.path("../escape.txt").exists());
+    assert!(matches!(
+        app.handle_input("touch"),
+        Err(CliError::TooFewArguments { .. })
+    ));
+}
+
+#[test]
+fn test_json_requests_get_json_responses() {
+    let harness = TestHarness::new("json-requests").unwrap();
+    harness.write_file("my notes.txt", "one\ntwo\n");
+    let mut app = harness.app(
+        "{\"command\":\"file\"}\n\
+         {\"command\":\"ls\"}\n\
+         {\"command\":\"load\",\"args\":[\"my notes.txt\"]}\n",
+    );
+    app.run_json_requests().unwrap();
+
+    // One compact response per request, and nothing else: the listing is
+    // in its response's output, and the filename with a space needs no
+    // quoting
+    let output = harness.output();
+    let responses: Vec<JsonValue> = output
+        .lines()
+        .map(|line| JsonValue::parse(line).unwrap())
+        .collect();
+    assert_eq!(
+        responses,
+        [
+            JsonValue::parse(r#"{"ok":true,"output":[],"path":["file"]}"#).unwrap(),
+            JsonValue::parse(r#"{"ok":true,"output":["     1. my notes.txt"],"path":["file"]}"#)
+                .unwrap(),
+            JsonValue::parse(
+                r#"{"ok":true,"output":["OK Loaded 2 lines from my notes.txt"],"path":["file"]}"#
+            )
+            .unwrap(),
+        ]
+    );
+    assert_eq!(app.context().buffer().len(), 2);
+    assert_eq!(app.stats().commands_run, 3);
+}
+
+#[test]
+fn test_json_requests_report_errors_and_continue() {
+    let harness = TestHarness::new("json-errors").unwrap();
+    let mut app = harness.app(
+        "{\"command\":\"file\"\n\
+         {\"args\":[]}\n\
+         {\"command\":\"bogus\"}\n\
+         {\"command\":\"edit\"}\n",
+    );
+    app.run_json_requests().unwrap();
+
+    let responses: Vec<JsonValue> = harness
+        .output()
+        .lines()
+        .map(|line| JsonValue::parse(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 4);
+    let ok = |response: &JsonValue| response.get("ok").cloned();
+    for response in &responses[..3] {
+        assert_eq!(ok(response), Some(JsonValue::Bool(false)));
+        assert!(response.get("error").and_then(JsonValue::as_str).is_some());
+    }
+    let error = responses[0].get("error").and_then(JsonValue::as_str);
+    assert!(error.unwrap().contains("not valid JSON"), "{error:?}");
+    // The session carries on after the bad lines
+    assert_eq!(ok(&responses[3]), Some(JsonValue::Bool(true)));
+    assert_eq!(app.context().current_path(), ["edit"]);
+}
+
+#[test]
+fn test_stat_reports_file_metadata() {
+    let harness = TestHarness::new("stat").unwrap();
+    harness.write_file("notes.txt", "hello\nworld\n");
+    fs::write(harness.path("image.bin"), b"\x89PNG\r\n\x1a\n\0\0").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    app.handle_input("stat notes.txt").unwrap();
+    let output = harness.output();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "File: notes.txt");
+    assert_eq!(lines[1], "Size: 12 bytes");
+    assert!(lines[2].starts_with("Modified: 20") && lines[2].ends_with('Z'));
+    assert!(lines[3].starts_with("Permissions: "));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(harness.path("notes.txt"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(lines[3], format!("Permissions: {:04o}", mode & 0o7777));
+    }
+    assert_eq!(lines[4], "Format: text");
+
+    harness.clear_output();
+    app.handle_input("stat image.bin").unwrap();
+    assert!(harness.output().contains("Size: 10 bytes\n"));
+    assert!(harness.output().ends_with("Format: binary\n"));
+
+    assert!(matches!(
+        app.handle_input("stat missing.txt"),
+        Err(CliError::FileNotFound(_))
+    ));
+    assert!(matches!(
+        app.handle_input("stat"),
+        Err(CliError::TooFewArguments { .. })
+    ));
+}
+
+#[test]
+fn test_diff_compares_two_files() {
+    let harness = TestHarness::new("diff").unwrap();
+    harness.write_file("old.txt", "one\ntwo\nthree\n");
+    harness.write_file("new.txt", "one\n2\nthree\nfour\n");
+    fs::write(harness.path("image.bin"), b"\x89PNG\r\n\x1a\n\0\0").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    app.handle_input("diff old.txt new.txt").unwrap();
+    assert_eq!(
+        harness.output(),
+        "--- old.txt\n+++ new.txt\n@@ -1,3 +1,4 @@\n one\n-two\n+2\n three\n+four\n"
+    );
+
+    harness.clear_output();
+    app.handle_input("diff old.txt old.txt").unwrap();
+    assert_eq!(
+        harness.output(),
+        "OK No differences between old.txt and old.txt\n"
+    );
+
+    assert!(matches!(
+        app.handle_input("diff old.txt image.bin"),
+        Err(CliError::InvalidFileFormat(_))
+    ));
+    assert!(matches!(
+        app.handle_input("diff old.txt missing.txt"),
+        Err(CliError::FileNotFound(_))
+    ));
+    assert!(matches!(
+        app.handle_input("diff old.txt"),
+        Err(CliError::TooFewArguments { .. })
+    ));
+}
+
+#[test]
+fn test_file_arguments_complete_to_filenames() {
+    let harness = TestHarness::new("complete-files").unwrap();
+    harness.write_file("notes.txt", "a\n");
+    harness.write_file("numbers.csv", "1\n");
+    harness.write_file(".hidden", "");
+    fs::create_dir(harness.path("docs")).unwrap();
+    harness.write_file("docs/guide.md", "");
+    let context = harness.context("");
+    let file_menu = FileCommand::new().subcommands();
+
+    assert_eq!(
+        context.get_completions("load ", &file_menu),
+        ["load docs/", "load notes.txt", "load numbers.csv"]
+    );
+    assert_eq!(
+        context.get_completions("l n", &file_menu),
+        ["l notes.txt", "l numbers.csv"]
+    );
+    assert_eq!(
+        context.get_completions("diff notes.txt docs/", &file_menu),
+        ["diff notes.txt docs/guide.md"]
+    );
+    assert_eq!(
+        context.get_completions("stat .", &file_menu),
+        ["stat .hidden"]
+    );
+
+    // Arguments that are not files, and paths leaving the root, get none
+    assert!(context.get_completions("vers ", &file_menu).is_empty());
+    assert!(
+        context
+            .get_completions("stat notes.txt ", &file_menu)
+            .is_empty()
+    );
+    assert!(context.get_completions("load ../", &file_menu).is_empty());
+
+    // Command names still complete
+    assert_eq!(
+        context.get_completions("sa", &file_menu),
+        ["save", "save-as"]
+    );
+}
+
+/// Menu whose only command is the hidden info command
+#[derive(Debug)]
+struct BareMenu;
+
+impl Command for BareMenu {
+    fn name(&self) -> &'static str {
+        "bare"
+    }
+
+    fn description(&self) -> &'static str {
+        "Menu without visible commands"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![Box::new(InfoCommand::new(self.name()))]
+    }
+}
+
+/// Root menu with the bare menu and the file menu
+#[derive(Debug)]
+struct BareRoot;
+
+impl Command for BareRoot {
+    fn name(&self) -> &'static str {
+        "root"
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![Box::new(BareMenu), Box::new(FileCommand::new())]
+    }
+}
+
+#[test]
+fn test_empty_input_in_empty_menu_explains_how_to_go_back() {
+    let harness = TestHarness::new("bare-menu").unwrap();
+    let mut app = App::with_root(harness.context("\nbare\n\n"), Box::new(BareRoot));
+    app.run().unwrap();
+
+    let output = harness.output();
+    let prompts: Vec<&str> = output.split("sm-menu").skip(1).collect();
+    assert_eq!(prompts.len(), 4, "{output}");
+
+    // A menu with visible commands lists them
+    assert!(prompts[0].starts_with(" > ? "), "{output}");
+    assert!(prompts[0].contains("  bare") && prompts[0].contains("  file"));
+    assert!(!prompts[0].contains("No commands available"));
+
+    // A menu whose only command is hidden explains how to leave instead
+    assert_eq!(
+        prompts[2],
+        " ~ bare > ? \nINFO No commands available here. Use 'exit' to go back.\n\n"
+    );
+}
+
+#[test]
+fn test_goto_scrolls_viewer_and_show_prints_a_range() {
+    let harness = TestHarness::new("goto").unwrap();
+    let mut app = harness.app("");
+    app.context_mut()
+        .edit_buffer(|buffer| {
+            buffer
+                .lines_mut()
+                .extend((1..=30).map(|n| format!("line {n}")));
+            buffer.lines_mut()[4] = "line \x1b[31m5".to_string();
+            Ok(())
+        })
+        .unwrap();
+    app.handle_input("view").unwrap();
+    harness.clear_output();
+
+    // A jump near the end shows the remaining lines
+    app.handle_input("goto 25").unwrap();
+    let output = harness.output();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "Lines 25-30 of 30");
+    assert_eq!(lines[1], "  25  line 25");
+    assert_eq!(lines.len(), 7);
+    assert_eq!(app.context().viewport_top(), 25);
+
+    // Earlier jumps show one screenful
+    harness.clear_output();
+    app.handle_input("goto 3").unwrap();
+    let height = GotoCommand::viewport_height();
+    assert_eq!(harness.output().lines().count(), height + 1);
+    assert!(
+        harness
+            .output()
+            .starts_with(&format!("Lines 3-{} of 30\n", height + 2))
+    );
+
+    // Lines outside the buffer are refused and leave the viewer alone
+    for line in ["goto 0", "goto 31", "goto -1", "goto x"] {
+        assert!(
+            matches!(app.handle_input(line), Err(CliError::InvalidInput(_))),
+            "{line}"
+        );
+    }
+    assert_eq!(app.context().viewport_top(), 3);
+
+    // A range prints exactly the requested lines, sanitized
+    harness.clear_output();
+    app.handle_input("show --range 4 6").unwrap();
+    assert_eq!(
+        harness.output(),
+        "   4  line 4\n   5  line [31m5\n   6  line 6\n"
+    );
+    assert_eq!(app.context().current_path(), ["view"]);
+    for line in ["show --range 6 4", "show --range 4 31", "show --range 4"] {
+        assert!(app.handle_input(line).is_err(), "{line}");
+    }
+}
+
+#[test]
+fn test_case_sensitive_commands_preference() {
+    let harness = TestHarness::new("case-sensitive").unwrap();
+    let mut app = harness.app("");
+
+    // Off by default: any case resolves
+    app.handle_input("FILE").unwrap();
+    app.handle_input("exit").unwrap();
+
+    app.context_mut().preferences_mut().case_sensitive_commands = true;
+    for line in ["FILE", "File", "F"] {
+        assert!(
+            matches!(app.handle_input(line), Err(CliError::InvalidCommand(_))),
+            "{line}"
+        );
+    }
+    app.handle_input("file").unwrap();
+    assert!(matches!(
+        app.handle_input("SAVE notes.txt"),
+        Err(CliError::InvalidCommand(_))
+    ));
+    assert!(matches!(
+        app.handle_input("Sa notes.txt"),
+        Err(CliError::InvalidCommand(_))
+    ));
+
+    // Built-in commands, aliases, and prefixes still work in lowercase
+    app.handle_input("save notes.txt").unwrap();
+    app.handle_input("st notes.txt").unwrap();
+    assert!(harness.path("notes.txt").exists());
+
+    // which reports what typing the token would run
+    harness.clear_output();
+    app.handle_input("which SAVE").unwrap();
+    assert!(
+        harness.output().contains("'SAVE' not found"),
+        "{}",
+        harness.output()
+    );
+}
+
+/// File system that times out a set number of times before deferring to the real one
+struct FlakyFileSystem {
+    failures: Rc<Cell<u32>>,
+}
+
+impl FlakyFileSystem {
+    fn fail(&self) -> std::io::Result<()> {
+        if self.failures.get() == 0 {
+            return Ok(());
+        }
+        self.failures.set(self.failures.get() - 1);
+        Err(std::io::ErrorKind::TimedOut.into())
+    }
+}
+
+impl FileSystem for FlakyFileSystem {
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        self.fail()?;
+        StdFileSystem.read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        self.fail()?;
+        StdFileSystem.write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        self.fail()?;
+        StdFileSystem.append(path, contents)
+    }
+}
+
+/// File system whose appends write everything and then time out anyway
+struct LateTimeoutFileSystem;
+
+impl FileSystem for LateTimeoutFileSystem {
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        StdFileSystem.read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        StdFileSystem.write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        StdFileSystem.append(path, contents)?;
+        Err(std::io::ErrorKind::TimedOut.into())
+    }
+}
+
+#[test]
+fn test_file_commands_retry_transient_errors() {
+    let temp = TempRoot::new("io-retry");
+    fs::write(temp.0.join("notes.txt"), "a\nb\n").unwrap();
+    let args = ["notes.txt".to_string()];
+
+    let flaky_context = |attempts: u32| {
+        let mut context = temp.context();
+        context.preferences_mut().io_retry_attempts = attempts;
+        context.preferences_mut().io_retry_backoff_ms = 0;
+        context.set_file_system(Box::new(FlakyFileSystem {
+            failures: Rc::new(Cell::new(2)),
+        }));
+        context
+    };
+
+    // Two timeouts are outlasted by three attempts
+    let mut context = flaky_context(3);
+    LoadCommand::new()
+        .execute_with_context(&args, &[], &mut context)
+        .unwrap();
+    assert_eq!(context.buffer().len(), 2);
+
+    // With two attempts the second timeout is the error reported
+    let mut context = flaky_context(2);
+    let result = LoadCommand::new().execute_with_context(&args, &[], &mut context);
+    assert!(
+        matches!(result, Err(CliError::IoError(ref e)) if e.kind() == std::io::ErrorKind::TimedOut)
+    );
+    assert!(context.buffer().is_empty());
+
+    // Retries are off by default
+    let mut context = flaky_context(CliPreferences::default().io_retry_attempts);
+    assert!(
+        LoadCommand::new()
+            .execute_with_context(&args, &[], &mut context)
+            .is_err()
+    );
+
+    // Save goes through the same policy
+    let mut context = flaky_context(3);
+    context.replace_buffer(EditBuffer::from_text("c\n"));
+    let saved = ["saved.txt".to_string()];
+    SaveCommand::new()
+        .execute_with_context(&saved, &[], &mut context)
+        .unwrap();
+    assert_eq!(fs::read_to_string(temp.0.join("saved.txt")).unwrap(), "c\n");
+
+    // An append that times out may already have written, so it is not retried
+    let mut context = flaky_context(3);
+    context.set_file_system(Box::new(LateTimeoutFileSystem));
+    context.replace_buffer(EditBuffer::from_text("d\n"));
+    let appended = ["--append".to_string(), "saved.txt".to_string()];
+    let result = SaveCommand::new().execute_with_context(&appended, &[], &mut context);
+    assert!(
+        matches!(result, Err(CliError::IoError(ref e)) if e.kind() == std::io::ErrorKind::TimedOut)
+    );
+    assert_eq!(
+        fs::read_to_string(temp.0.join("saved.txt")).unwrap(),
+        "c\nd\n"
+    );
+}
+
+#[test]
+fn test_plain_output_has_no_decoration() {
+    let root = TempRoot::new("plain");
+    let script = "edit\nappend hello\nbogus\nexit\nfile\nsave notes.txt\nquit\n";
+
+    // Without --plain the same run is colored, with icons and a goodbye
+    let decorated = script_output(&root, script, &["--quiet"]);
+    let stdout = String::from_utf8(decorated.stdout).unwrap();
+    assert!(stdout.contains('\x1b') && !stdout.is_ascii(), "{stdout:?}");
+    assert!(stdout.contains(GOODBYE_MESSAGE.trim()));
+
+    let plain = script_output(&root, script, &["--quiet", "--plain", "--yes"]);
+    let stdout = String::from_utf8(plain.stdout).unwrap();
+    let stderr = String::from_utf8(plain.stderr).unwrap();
+    for output in [&stdout, &stderr] {
+        assert!(!output.contains('\x1b'), "{output:?}");
+        assert!(output.is_ascii(), "{output:?}");
+    }
+    let all = format!("{stdout}{stderr}");
+    assert!(all.contains("sm-menu ~ edit* > "), "{all}");
+    assert!(all.contains("Invalid command"), "{all}");
+    assert!(all.contains("Saved 1 lines to notes.txt"), "{all}");
+    assert!(!all.contains(GOODBYE_MESSAGE.trim()));
+
+    // An interactive run clears the screen and says goodbye, unless plain
+    let interactive = |flags: &[&str]| {
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_sm-menu"))
+            .args(["--no-config", "--root", root.0.to_str().unwrap()])
+            .args(flags)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"quit\n").unwrap();
+        child.wait_with_output().unwrap().stdout
+    };
+    let decorated = interactive(&[]);
+    assert!(decorated.starts_with(b"\x1b[2J\x1b[H"));
+    assert!(String::from_utf8(decorated).unwrap().contains("Goodbye!"));
+
+    let plain = interactive(&["--plain"]);
+    assert!(!plain.contains(&0x1b), "{plain:?}");
+    assert!(plain.is_ascii());
+    let stdout = String::from_utf8(plain).unwrap();
+    assert!(!stdout.contains("Goodbye!"), "{stdout:?}");
+    assert!(!stdout.contains(WELCOME_MESSAGE.trim()), "{stdout:?}");
+}
+
+/// Log records captured by [`CaptureLogger`], with the thread that sent them
+static LOG_RECORDS: Mutex<Vec<(ThreadId, Level, String, String)>> = Mutex::new(Vec::new());
+
+/// Logger that keeps every record, so a test can look for its own
+struct CaptureLogger;
+
+impl Logger for CaptureLogger {
+    fn log(&self, record: &Record<'_>) {
+        LOG_RECORDS.lock().unwrap().push((
+            std::thread::current().id(),
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+}
+
+#[test]
+fn test_failing_command_emits_log_records() {
+    set_logger(Box::new(CaptureLogger)).unwrap();
+    assert!(set_logger(Box::new(CaptureLogger)).is_err());
+
+    let harness = TestHarness::new("log-records").unwrap();
+    let mut app = harness.app("");
+    app.handle_command("f", &[]).unwrap();
+    let args = ["../outside.txt".to_string()];
+    assert!(app.handle_command("load", &args).is_err());
+
+    // Other tests run on other threads and log too
+    let own = std::thread::current().id();
+    let records: Vec<_> = LOG_RECORDS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(thread, ..)| *thread == own)
+        .map(|(_, level, target, message)| (*level, target.clone(), message.clone()))
+        .collect();
+    let expected = [
+        (Level::Debug, "sm_menu::app", "Resolved 'f' to 'file'"),
+        (
+            Level::Info,
+            "sm_menu::core::navigator",
+            "Entered menu 'file'",
+        ),
+        (Level::Debug, "sm_menu::app", "Resolved 'load' to 'load'"),
+        (
+            Level::Warn,
+            "sm_menu::core::security",
+            "Rejected path '../outside.txt': Invalid input: Path traversal not allowed (.. components detected)",
+        ),
+        (
+            Level::Error,
+            "sm_menu::app",
+            "Command 'load ../outside.txt' failed: Invalid input: Path traversal not allowed (.. components detected)",
+        ),
+    ];
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(level, target, message)| (*level, target.to_string(), message.to_string()))
+        .collect();
+    assert_eq!(records, expected);
+}
+
+/// Menu paths written as slices, to compare with `SessionRun::paths`
+fn paths(expected: &[&[&str]]) -> Vec<Vec<String>> {
+    expected
+        .iter()
+        .map(|path| path.iter().map(|segment| segment.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn test_session_loads_in_file_menu_and_quits() {
+    let harness = TestHarness::new("nav-load").unwrap();
+    harness.write_file("x.txt", "one\ntwo\n");
+
+    let run = harness
+        .run_session("file\nload x.txt\nexit\nquit\nvers\n")
+        .unwrap();
+    assert_eq!(run.paths(), paths(&[&["file"], &["file"], &[], &[]]));
+    assert_eq!(run.steps[1].0, "load x.txt");
+
+    // Quit stops the loop, so the line after it is never read
+    assert_eq!(run.steps.len(), 4);
+    let context = run.app.context();
+    assert!(!context.running);
+    assert_eq!(context.buffer().lines(), ["one", "two"]);
+    assert_eq!(context.buffer().filename(), Some("x.txt"));
+}
+
+#[test]
+fn test_session_moves_between_sibling_menus() {
+    let harness = TestHarness::new("nav-siblings").unwrap();
+
+    let run = harness
+        .run_session("edit\nappend hello\nexit\nview\nshow\nexit\nexit\n")
+        .unwrap();
+    // show is itself a menu, one level below view
+    assert_eq!(
+        run.paths(),
+        paths(&[
+            &["edit"],
+            &["edit"],
+            &[],
+            &["view"],
+            &["view", "show"],
+            &["view"],
+            &[]
+        ])
+    );
+
+    // The edit survives leaving its menu
+    let context = run.app.context();
+    assert_eq!(context.buffer().lines(), ["hello"]);
+    assert!(context.is_root());
+}
+
+#[test]
+fn test_session_failures_keep_the_current_menu() {
+    let harness = TestHarness::new("nav-failures").unwrap();
+
+    let run = harness
+        .run_session("f\nbogus\nload missing.txt\n\nedit\nexit\nexit\n")
+        .unwrap();
+    // Blank lines are not commands, and edit is not in the file menu
+    assert_eq!(
+        run.paths(),
+        paths(&[&["file"], &["file"], &["file"], &["file"], &[], &[]])
+    );
+    let stats = run.app.stats();
+    assert_eq!((stats.commands_run, stats.failed), (6, 4));
+}
+
+#[test]
+fn test_profile_writes_csv_rows() {
+    let root = TempRoot::new("profile");
+    let profile = root.0.join("times.csv");
+    let script = "sleep 0.01\necho \"a, b\" 'say \"hi\"'\n";
+    let output = script_output(
+        &root,
+        script,
+        &["--quiet", "--profile", profile.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+
+    let csv = fs::read_to_string(&profile).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 3, "{csv}");
+    assert_eq!(lines[0], "command,args,duration_us,result");
+
+    // The last two fields never need quoting, so split them off the right
+    let rows: Vec<(&str, u128, &str)> = lines[1..]
+        .iter()
+        .map(|line| {
+            let mut fields = line.rsplitn(3, ',');
+            let result = fields.next().unwrap();
+            let duration = fields.next().unwrap().parse().unwrap();
+            (fields.next().unwrap(), duration, result)
+        })
+        .collect();
+    assert_eq!(rows[0].0, "sleep,0.01");
+    assert!((10_000..10_000_000).contains(&rows[0].1), "{}", rows[0].1);
+    assert_eq!(rows[1].0, "echo,\"\"\"a, b\"\" 'say \"\"hi\"\"'\"");
+    assert!(rows[1].1 < 10_000_000);
+    assert_eq!((rows[0].2, rows[1].2), ("ok", "ok"));
+}
+
+#[test]
+fn test_grep_searches_buffer() {
+    let harness = TestHarness::new("grep").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("edit").unwrap();
+    app.context_mut()
+        .edit_buffer(|buffer| {
+            buffer.set_text("Error: disk full\nok\nerror again\n\x1b[31mError\x1b[0m\n");
+            Ok(())
+        })
+        .unwrap();
+
+    // Case-sensitive by default, with 1-based line numbers and sanitized lines
+    app.handle_input("grep Error").unwrap();
+    assert_eq!(
+        harness.output(),
+        "   1  Error: disk full\n   4  [31mError[0m\nOK 2 matching lines\n"
+    );
+
+    harness.clear_output();
+    app.handle_input("search -i error").unwrap();
+    assert_eq!(
+        harness.output(),
+        "   1  Error: disk full\n   3  error again\n   4  [31mError[0m\nOK 3 matching lines\n"
+    );
+
+    // Count mode prints only the count, which is zero without a match
+    harness.clear_output();
+    app.handle_input("grep -c -i ERROR").unwrap();
+    assert_eq!(harness.output(), "OK 3 matching lines\n");
+    harness.clear_output();
+    app.handle_input("grep missing").unwrap();
+    assert_eq!(harness.output(), "OK 0 matching lines\n");
+
+    assert!(matches!(
+        app.handle_input("grep -i"),
+        Err(CliError::TooFewArguments { .. })
+    ));
+    assert!(matches!(
+        app.handle_input("grep -x disk"),
+        Err(CliError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_verbose_reports_menu_navigation() {
+    let harness = TestHarness::new("verbose-navigation").unwrap();
+    let run = |verbose: bool| {
+        let mut context = harness.context("file\nfile\nexit\nexit\n");
+        context.preferences_mut().verbose = verbose;
+        context.preferences_mut().enable_recursive_menus = true;
+        App::new(context).run().unwrap();
+        let output = harness.output();
+        harness.clear_output();
+        output
+    };
+
+    assert_eq!(
+        run(true),
+        "sm-menu > ? INFO Entered file menu\n\
+         sm-menu ~ file > ? INFO Entered file menu\n\
+         sm-menu ~ file > file > ? INFO Returned to file menu\n\
+         sm-menu ~ file > ? INFO Returned to root\n\
+         sm-menu > ? "
+    );
+    assert_eq!(
+        run(false),
+        "sm-menu > ? sm-menu ~ file > ? sm-menu ~ file > file > ? sm-menu ~ file > ? sm-menu > ? "
+    );
+}
+
+#[test]
+fn test_save_as_prompts_for_filename() {
+    let harness = TestHarness::new("save-as").unwrap();
+    harness.write_file("notes.txt", "a\nb\n");
+    let session = |input: &str| {
+        let mut context = harness.context("");
+        context.set_io(terminal_input(input));
+        App::new(context).run().unwrap();
+    };
+
+    // A filename typed at the prompt is saved to
+    session("file\nload notes.txt\nsave-as\nout.txt\n");
+    assert_eq!(harness.read_file("out.txt"), "a\nb\n");
+    assert!(harness.output().contains("Save as [notes.txt]: "));
+
+    // Empty input keeps the buffer's filename, confirming the overwrite
+    session("file\nload notes.txt\nexit\nedit\nappend c\nexit\nfile\nsave-as\n\ny\n");
+    assert_eq!(harness.read_file("notes.txt"), "a\nb\nc\n");
+}
+
+#[test]
+fn test_save_as_needs_filename_without_terminal() {
+    let harness = TestHarness::new("save-as-script").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+    assert!(matches!(
+        app.handle_input("save-as"),
+        Err(CliError::InvalidInput(_))
+    ));
+
+    // --yes or a filename argument needs no prompt
+    app.context_mut().preferences_mut().assume_yes = true;
+    app.handle_input("save-as").unwrap();
+    assert!(harness.path("untitled.txt").exists());
+    app.handle_input("save-as given.txt").unwrap();
+    assert!(harness.path("given.txt").exists());
+}
+
+#[test]
+fn test_load_success_uses_display_styling() {
+    let temp = TempRoot::new("load-display");
+    fs::write(temp.0.join("notes.txt"), "a\nb\nc\n").unwrap();
+
+    for (colored, expected) in [
+        (true, "\x1b[1;32m✓ Loaded 3 lines from notes.txt\x1b[0m\n"),
+        (false, "✓ Loaded 3 lines from notes.txt\n"),
+    ] {
+        let mut context = temp.context();
+        context.preferences_mut().colored_prompt = colored;
+        let output = CapturedOutput::default();
+        context.set_display(DisplayManager::with_writer(
+            true,
+            true,
+            Box::new(output.clone()),
+        ));
+
+        let result =
+            LoadCommand::new().execute_with_context(&["notes.txt".to_string()], &[], &mut context);
+        assert_eq!(result.unwrap(), CommandResult::success_silent());
+        assert_eq!(output.text(), expected);
+        assert_eq!(context.buffer().len(), 3);
+    }
+}
+
+#[test]
+fn test_quit_exit_code_reaches_process() {
+    let root = TempRoot::new("quit-code");
+    let code = |script: &str| script_output(&root, script, &["--quiet"]).status.code();
+
+    assert_eq!(code("quit\n"), Some(0));
+    assert_eq!(code("quit 3\n"), Some(3));
+    assert_eq!(code("file\nexit\nquit 42\n"), Some(42));
+
+    // A rejected code does not quit; the session ends at end of input
+    assert_eq!(code("quit abc\n"), Some(0));
+
+    // The error names the exit code rather than a count
+    for arg in ["abc", "-1", "256"] {
+        assert_eq!(
+            QuitCommand::new().validate(&[arg.to_string()]),
+            Err(CliError::invalid_input(
+                "Exit code must be a number from 0 to 255"
+            ))
+        );
+    }
+}
+
+#[test]
+fn test_command_tree_iter_walks_default_root() {
+    let paths: Vec<String> = CommandTreeIter::new(&RootCommand)
+        .map(|(path, _)| path.join(" "))
+        .collect();
+
+    // Depth-first, in menu order, without the hidden info commands
+    assert_eq!(
+        paths,
+        [
+            "file",
+            "file ls",
+            "file load",
+            "file load exit",
+            "file save",
+            "file save exit",
+            "file save-as",
+            "file touch",
+            "file stat",
+            "file diff",
+            "file open",
+            "file vers",
+            "file vers exit",
+            "file exit",
+            "edit",
+            "edit append",
+            "edit insert",
+            "edit delete",
+            "edit axis",
+            "edit axis exit",
+            "edit show",
+            "edit show exit",
+            "edit grep",
+            "edit load-stdin",
+            "edit undo",
+            "edit redo",
+            "edit exit",
+            "view",
+            "view axis",
+            "view axis exit",
+            "view show",
+            "view show exit",
+            "view goto",
+            "view grep",
+            "view exit",
+            "help",
+            "help exit",
+            "quit",
+            "quit exit",
+            "repeat",
+            "history",
+            "reload",
+            "config",
+            "export",
+            "system",
+            "system reset",
+            "system exit",
+        ]
+    );
+}
+
+#[test]
+fn test_command_tree_iter_stops_at_nested_menu() {
+    let paths: Vec<Vec<String>> = CommandTreeIter::new(&FileCommand::with_recursion(true))
+        .map(|(path, _)| path)
+        .collect();
+
+    // The nested file menu is listed once but not descended into
+    assert!(paths.contains(&vec!["file".to_string()]));
+    assert!(!paths.iter().any(|path| path.len() > 1 && path[0] == "file"));
+}
+
+#[test]
+fn test_session_skips_over_length_and_nul_lines() {
+    let mut io = SessionIo::from_bytes(format!("{}\nvi\0ew\nedit\n", "x".repeat(100)));
+    io.set_max_line_length(16);
+    let mut context = CliContext::new();
+    context.set_io(io);
+    let mut app = App::new(context);
+    app.run().unwrap();
+
+    // Rejected lines are neither run nor recorded
+    assert_eq!(app.context().current_path(), ["edit"]);
+    assert_eq!(app.stats().commands_run, 1);
+    assert_eq!(app.context().history().len(), 1);
+}
+
+#[test]
+fn test_menu_runs_selected_command() {
+    let output = CapturedOutput::default();
+    let mut context = CliContext::new();
+    context.preferences_mut().colored_prompt = false;
+    context.set_display(DisplayManager::with_writer(
+        false,
+        true,
+        Box::new(output.clone()),
+    ));
+    context.set_io(SessionIo::from_bytes("2\n"));
+    let mut app = App::new(context);
+
+    // The second visible root command is edit
+    app.handle_input("menu").unwrap();
+    assert_eq!(app.context().current_path(), ["edit"]);
+    let listing = output.text();
+    assert!(listing.starts_with("  1. file - "));
+    assert!(listing.contains("\n  2. edit - "));
+    assert!(!listing.contains(". info - "));
+    // The prompt goes to the same writer
+    assert!(listing.contains("\nSelect 1-"));
+}
+
+#[test]
+fn test_menu_rejects_bad_selection() {
+    for selection in ["0\n", "99\n", "abc\n", ""] {
+        let mut context = CliContext::new();
+        context.set_io(SessionIo::from_bytes(selection));
+        let mut app = App::new(context);
+
+        assert!(
+            matches!(app.handle_input("menu"), Err(CliError::InvalidInput(_))),
+            "{selection:?}"
+        );
+        assert!(app.context().current_path().is_empty());
+    }
+}
+
+#[test]
+fn test_save_diff_previews_overwrite() {
+    let temp = TempRoot::new("save-diff");
+    fs::write(temp.0.join("notes.txt"), "one\ntwo\nthree\n").unwrap();
+
+    let mut context = temp.context();
+    let output = CapturedOutput::default();
+    context.preferences_mut().colored_prompt = false;
+    context.preferences_mut().assume_yes = true;
+    context.set_display(DisplayManager::with_writer(
+        false,
+        true,
+        Box::new(output.clone()),
+    ));
+    context
+        .edit_buffer(|buffer| {
+            buffer.set_text("one\n2\nthree\n");
+            Ok(())
+        })
+        .unwrap();
+    let mut app = App::new(context);
+
+    app.handle_input("file").unwrap();
+    app.handle_input("save --diff notes.txt").unwrap();
+    assert_eq!(
+        output.text(),
+        "--- notes.txt\n+++ buffer\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n\
+         save --diff notes.txt: this cannot be undone. Continue? [y/N] y (--yes)\n\
+         ✓ Saved 3 lines to notes.txt\n"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.0.join("notes.txt")).unwrap(),
+        "one\n2\nthree\n"
+    );
+
+    // Without --diff, or with nothing to change, there is no diff
+    output.clear();
+    app.handle_input("save notes.txt").unwrap();
+    app.handle_input("save --diff").unwrap();
+    assert_eq!(
+        output.text(),
+        "save notes.txt: this cannot be undone. Continue? [y/N] y (--yes)\n\
+         ✓ Saved 3 lines to notes.txt\nNo differences from notes.txt\n\
+         save --diff: this cannot be undone. Continue? [y/N] y (--yes)\n\
+         ✓ Saved 3 lines to notes.txt\n"
+    );
+    assert!(matches!(
+        app.handle_input("save --diff a b"),
+        Err(CliError::TooManyArguments {
+            expected: 1,
+            found: 2
+        })
+    ));
+}
+
+#[test]
+fn test_invalid_command_shows_suggestions() {
+    let harness = TestHarness::new("suggestions-on").unwrap();
+    let mut app = harness.app("flie\n");
+    app.run().unwrap();
+
+    let output = harness.output();
+    assert!(
+        output.starts_with("sm-menu > ? Did you mean 'file'?\n"),
+        "{output}"
+    );
+    assert!(output.contains("  file (F) - "), "{output}");
+}
+
+#[test]
+fn test_invalid_command_without_suggestions() {
+    let harness = TestHarness::new("suggestions-off").unwrap();
+    let mut app = harness.app("flie\nbogus\n");
+    app.context_mut().preferences_mut().show_suggestions = false;
+    app.run().unwrap();
+
+    // Only the prompts and error lines are shown, and the errors go to stderr
+    assert_eq!(harness.output(), "sm-menu > ? ".repeat(3));
+    assert_eq!(app.stats().failed, 2);
+}
+
+#[test]
+fn test_reload_applies_changed_config() {
+    let harness = TestHarness::new("reload").unwrap();
+    let mut app = harness.app("");
+    app.context_mut()
+        .set_config_path(Some(harness.path("config.toml")));
+
+    // The harness turns color off, so the file keeps it off
+    harness.write_file(
+        "config.toml",
+        "colored_prompt = false\nmax_list_items = 10\n",
+    );
+    app.handle_input("reload").unwrap();
+    assert_eq!(app.context().preferences().max_list_items, 10);
+    let output = harness.output();
+    assert!(
+        output.ends_with("\n  max_list_items: 50 -> 10\n"),
+        "{output}"
+    );
+
+    harness.clear_output();
+    app.handle_input("reload").unwrap();
+    assert!(harness.output().ends_with(": no changes\n"));
+}
+
+#[test]
+fn test_reload_keeps_preferences_on_broken_config() {
+    let harness = TestHarness::new("reload-broken").unwrap();
+    harness.write_file("config.toml", "max_list_items = 10\n");
+    let mut app = harness.app("");
+    app.context_mut()
+        .set_config_path(Some(harness.path("config.toml")));
+    app.handle_input("reload").unwrap();
+
+    harness.write_file("config.toml", "max_list_items = 7\nundo_depth = lots\n");
+    assert!(matches!(
+        app.handle_input("reload"),
+        Err(CliError::InvalidFileFormat(_))
+    ));
+    assert_eq!(app.context().preferences().max_list_items, 10);
+    assert_eq!(app.context().preferences().undo_depth, 50);
+
+    // Without a configuration file there is nothing to reload
+    app.context_mut().set_config_path(None);
+    assert!(app.handle_input("reload").is_err());
+}
+
+#[test]
+fn test_config_show_and_path() {
+    let harness = TestHarness::new("config-show").unwrap();
+    let mut app = harness.app("");
+    app.context_mut().preferences_mut().max_list_items = 10;
+    app.context_mut().preferences_mut().undo_depth = 7;
+
+    // The shown preferences parse back into the ones in effect
+    app.handle_input("config show").unwrap();
+    let shown = CliPreferences::from_toml(&harness.output()).unwrap();
+    assert_eq!(&shown, app.context().preferences());
+
+    harness.clear_output();
+    app.context_mut()
+        .set_config_path(Some(harness.path("config.toml")));
+    app.handle_input("config path").unwrap();
+    assert_eq!(
+        harness.output(),
+        format!("{}\n", harness.path("config.toml").display())
+    );
+
+    // Without a configuration file there is no path, and editing needs a terminal
+    app.context_mut().set_config_path(None);
+    assert!(matches!(
+        app.handle_input("config path"),
+        Err(CliError::ExecutionError { .. })
+    ));
+    assert!(matches!(
+        app.handle_input("config edit"),
+        Err(CliError::TerminalError(_))
+    ));
+    for line in ["config", "config list", "config show extra"] {
+        assert!(app.handle_input(line).is_err(), "{line}");
+    }
+}
+
+/// Command whose name and alias break input tokenizing
+#[derive(Debug)]
+struct BadlyNamedCommand;
+
+impl Command for BadlyNamedCommand {
+    fn name(&self) -> &'static str {
+        "bad name"
+    }
+
+    fn description(&self) -> &'static str {
+        "Command with a space in its name"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["b\t"]
+    }
+
+    fn hidden(&self) -> bool {
+        true
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::success_silent())
+    }
+}
+
+/// Menu tree `root > plugins` with a badly named command in `plugins`
+#[derive(Debug)]
+struct BadlyNamedMenu(&'static str);
+
+impl Command for BadlyNamedMenu {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn description(&self) -> &'static str {
+        "Test menu"
+    }
+
+    fn execute(&mut self, _args: &[String]) -> CliResult<CommandResult> {
+        Ok(CommandResult::Continue)
+    }
+
+    fn subcommands(&self) -> Vec<Box<dyn Command>> {
+        match self.0 {
+            "root" => vec![
+                Box::new(FileCommand::new()),
+                Box::new(BadlyNamedMenu("plugins")),
+            ],
+            _ => vec![Box::new(BadlyNamedCommand)],
+        }
+    }
+}
+
+#[test]
+fn test_validate_command_tree_flags_bad_names() {
+    assert!(validate_command_tree(&RootCommand).is_ok());
+
+    let error = validate_command_tree(&BadlyNamedMenu("root")).unwrap_err();
+    assert!(matches!(error, CliError::InternalError(_)));
+    // Hidden commands are checked too, and both offenders are named
+    assert!(
+        error
+            .to_string()
+            .contains(r#"invalid command names: plugins > "bad name", plugins > "b\t""#),
+        "{error}"
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid command names")]
+fn test_app_rejects_bad_names_in_debug_builds() {
+    App::with_root(CliContext::new(), Box::new(BadlyNamedMenu("root")));
+}
+
+#[test]
+fn test_export_json_round_trips_state() {
+    let harness = TestHarness::new("export").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("edit").unwrap();
+    app.handle_input("append first line").unwrap();
+    app.handle_input("append second").unwrap();
+    app.handle_input("axis x set min 0 max 10").unwrap();
+    app.handle_input("exit").unwrap();
+    harness.clear_output();
+
+    app.handle_input("export json state.json").unwrap();
+    assert_eq!(
+        harness.output(),
+        "OK Exported session state as json to state.json\n"
+    );
+
+    let json = JsonValue::parse(&harness.read_file("state.json")).unwrap();
+    let snapshot = SessionSnapshot::from_json(&json).unwrap();
+    assert_eq!(snapshot.lines, ["first line", "second"]);
+    let axis = snapshot.axes["edit"].get("x").unwrap();
+    assert_eq!((axis.min, axis.max), (Some(0.0), Some(10.0)));
+    assert_eq!(snapshot, SessionSnapshot::capture(app.context()));
+
+    // Without a file the snapshot is shown
+    harness.clear_output();
+    app.handle_input("export toml").unwrap();
+    assert!(
+        harness
+            .output()
+            .contains("\n[axes.edit.x]\nmin = 0.0\nmax = 10.0\n")
+    );
+
+    assert!(matches!(
+        app.handle_input("export xml"),
+        Err(CliError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        app.handle_input("export"),
+        Err(CliError::WrongArgumentCount { .. })
+    ));
+}
+
+#[test]
+fn test_reset_returns_session_to_clean_state() {
+    let harness = TestHarness::new("reset").unwrap();
+    let mut context = harness.context("");
+    context.preferences_mut().assume_yes = true;
+    let mut app = App::new(context);
+    app.handle_input("edit").unwrap();
+    app.handle_input("append first line").unwrap();
+    app.handle_input("axis x set min 0 max 10").unwrap();
+    app.handle_input("exit").unwrap();
+    app.context_mut().add_to_history("edit".to_string());
+    app.handle_input("system").unwrap();
+    assert_eq!(app.context().current_path(), ["system"]);
+    harness.clear_output();
+
+    app.handle_input("reset").unwrap();
+    assert_eq!(
+        harness.output(),
+        "reset: this cannot be undone. Continue? [y/N] y (--yes)\nOK Session reset\n"
+    );
+    assert!(app.context().buffer().is_empty());
+    assert!(app.context().axes("edit").is_none());
+    assert!(app.context().current_path().is_empty());
+    assert_eq!(app.command_stack().len(), 1);
+    assert_eq!(app.context().history().len(), 1);
+
+    app.handle_input("system").unwrap();
+    app.handle_input("reset --history").unwrap();
+    assert!(app.context().history().is_empty());
+    assert!(app.context().current_path().is_empty());
+
+    app.handle_input("system").unwrap();
+    assert!(matches!(
+        app.handle_input("reset --all"),
+        Err(CliError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_reset_needs_confirmation() {
+    let harness = TestHarness::new("reset-confirm").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("edit").unwrap();
+    app.handle_input("append keep me").unwrap();
+    app.handle_input("exit").unwrap();
+    app.handle_input("system").unwrap();
+
+    // A script without --yes cannot confirm, so nothing is cleared
+    assert!(matches!(
+        app.handle_input("reset"),
+        Err(CliError::TerminalError(_))
+    ));
+    assert_eq!(app.context().buffer().lines(), ["keep me"]);
+    assert_eq!(app.context().current_path(), ["system"]);
+
+    // With confirmations off the reset runs without asking
+    app.context_mut().preferences_mut().confirm_destructive = false;
+    app.handle_input("reset").unwrap();
+    assert!(app.context().buffer().is_empty());
+    assert!(app.context().current_path().is_empty());
+}
+
+#[test]
+fn test_strict_script_stops_at_invalid_command() {
+    let root = TempRoot::new("script-strict");
+    let script = "file\nbogus\nls\nexit\n";
+
+    // Without --strict the invalid command is reported and the script goes on
+    let output = script_output(&root, script, &[]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("4 commands run: 3 succeeded, 1 failed"),
+        "{stderr}"
+    );
+
+    // With --strict it stops the script at that line with a failing status
+    let output = script_output(&root, script, &["--strict"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Stopping: --strict treats this warning as an error"),
+        "{stderr}"
+    );
+    assert!(
+        stderr
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("2 commands run: 1 succeeded, 1 failed"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_strict_leaves_interactive_sessions_running() {
+    let harness = TestHarness::new("strict-interactive").unwrap();
+    let mut context = harness.context("");
+    context.set_io(terminal_input("bogus\nfile\n"));
+    let mut app = App::new(context);
+    app.set_strict(true);
+    app.run().unwrap();
+
+    assert_eq!(app.context().current_path(), ["file"]);
+    assert_eq!(app.context().exit_code(), 0);
+    assert_eq!(app.stats().failed, 1);
+}
+
+#[test]
+fn test_aliases_expand_before_dispatch() {
+    let harness = TestHarness::new("aliases").unwrap();
+    harness.write_file("notes.txt", "one\ntwo\n");
+    let mut app = harness.app("");
+    let aliases = app.context_mut().aliases_mut();
+    aliases.define("notes", "load notes.txt").unwrap();
+    aliases.define("n", "notes").unwrap();
+    aliases.define("ls", "vers").unwrap();
+    aliases.define("ping", "pong").unwrap();
+    aliases.define("pong", "ping").unwrap();
+    app.handle_input("file").unwrap();
+    harness.clear_output();
+
+    // A two-level chain expands to the command it ends at
+    app.handle_input("n").unwrap();
+    assert_eq!(harness.output(), "OK Loaded 2 lines from notes.txt\n");
+    assert_eq!(app.context().buffer().lines(), ["one", "two"]);
+
+    // A built-in command name is never expanded
+    app.handle_input("ls").unwrap();
+    assert!(app.context().last_listing().is_some());
+
+    match app.handle_input("ping") {
+        Err(CliError::InvalidInput(message)) => {
+            assert_eq!(message, "alias cycle detected: ping -> pong -> ping")
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn test_process_input_resolves_without_side_effects() {
+    let harness = TestHarness::new("process-input").unwrap();
+    harness.write_file("notes.txt", "one\ntwo\n");
+    let mut app = harness.app("");
+    app.context_mut()
+        .aliases_mut()
+        .define("notes", "load notes.txt")
+        .unwrap();
+    app.handle_input("file").unwrap();
+    app.context_mut().add_to_history("notes".to_string());
+
+    let siblings = FileCommand::new().subcommands();
+    let dispatch = process_input("!! > out.txt", app.context(), siblings).unwrap();
+    assert_eq!(
+        dispatch,
+        Dispatch {
+            line: "load notes.txt > out.txt".to_string(),
+            token: "load".to_string(),
+            command: Box::new(LoadCommand::new()),
+            path: vec!["file".to_string(), "load".to_string()],
+            args: vec!["notes.txt".to_string()],
+            redirect: Some(Redirect {
+                target: "out.txt".to_string(),
+                append: false,
+            }),
+        }
+    );
+
+    // Nothing ran: no file, no buffer, and the session is where it was
+    assert!(!harness.path("out.txt").exists());
+    assert!(app.context().buffer().lines().is_empty());
+    assert_eq!(app.context().current_path(), ["file"]);
+    assert_eq!(app.context().history().len(), 1);
+}
+
+#[test]
+fn test_previous_command_reference_reruns_last_line() {
+    let harness = TestHarness::new("bang-bang").unwrap();
+    let run = harness.run_session("echo hello\n!! again\n").unwrap();
+
+    assert!(harness.output().contains("hello again"));
+    // The expanded line is recorded, so a later `!!` finds a real command
+    let history: Vec<_> = run
+        .app
+        .context()
+        .history()
+        .iter()
+        .map(|entry| entry.command.as_str())
+        .collect();
+    assert_eq!(history, ["echo hello", "echo hello again"]);
+}
+
+#[test]
+fn test_open_refuses_scripts_without_yes() {
+    let harness = TestHarness::new("open-script").unwrap();
+    harness.write_file("notes.txt", "one\n");
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+
+    assert!(matches!(
+        app.handle_input("open notes.txt"),
+        Err(CliError::TerminalError(_))
+    ));
+}
+
+#[test]
+fn test_open_runs_platform_launcher() {
+    let harness = TestHarness::new("open-launcher").unwrap();
+    harness.write_file("notes.txt", "one\n");
+    let mut context = harness.context("");
+    context.preferences_mut().assume_yes = true;
+
+    let launched = Rc::new(RefCell::new(Vec::new()));
+    let record = Rc::clone(&launched);
+    let mut cmd = OpenCommand::with_spawner(Box::new(move |launcher: &Launcher| {
+        record.borrow_mut().push(launcher.clone());
+        Ok(())
+    }));
+    let result = cmd
+        .execute_with_context(&["notes.txt".to_string()], &[], &mut context)
+        .unwrap();
+    assert_eq!(result, CommandResult::success("Opened notes.txt"));
+
+    let path = harness.root().canonicalize().unwrap().join("notes.txt");
+    assert_eq!(
+        launched.borrow().as_slice(),
+        [Launcher::for_current_os(&path)]
+    );
+    assert_eq!(
+        Launcher::for_os("macos", &path),
+        Launcher {
+            program: "open".into(),
+            args: vec![path.clone().into()],
+        }
+    );
+    // No shell runs on Windows, so cmd metacharacters in a name stay in
+    // the one path argument
+    let tricky = harness.root().join("a&calc^|x.txt");
+    assert_eq!(
+        Launcher::for_os("windows", &tricky),
+        Launcher {
+            program: "rundll32".into(),
+            args: vec![
+                "url.dll,FileProtocolHandler".into(),
+                tricky.clone().into_os_string()
+            ],
+        }
+    );
+    assert_eq!(Launcher::for_os("linux", &path).program, "xdg-open");
+
+    // A missing file is not launched, and a failed launch is an execution error
+    assert!(matches!(
+        cmd.execute_with_context(&["missing.txt".to_string()], &[], &mut context),
+        Err(CliError::FileNotFound(_))
+    ));
+    let mut failing = OpenCommand::with_spawner(Box::new(|_: &Launcher| {
+        Err(std::io::Error::from(std::io::ErrorKind::NotFound))
+    }));
+    assert!(matches!(
+        failing.execute_with_context(&["notes.txt".to_string()], &[], &mut context),
+        Err(CliError::ExecutionError { .. })
+    ));
+    assert_eq!(launched.borrow().len(), 1);
+}
+
+#[test]
+fn test_tree_draws_menu_hierarchy() {
+    let tree = render_tree(&RootCommand, true);
+    let lines: Vec<&str> = tree.lines().collect();
+    assert_eq!(lines[0], "root");
+    assert!(lines.contains(&"├── file"));
+    assert!(lines.contains(&"│   ├── ls"));
+    assert!(lines.contains(&"│   ├── vers"));
+    assert!(lines.contains(&"│   │   └── exit"));
+    assert!(lines.contains(&"└── system"));
+    assert!(lines.contains(&"    └── exit"));
+    // Hidden commands and descriptions are left out
+    assert!(!lines.iter().any(|line| line.ends_with(" info")));
+    assert!(!tree.contains(" - "));
+
+    let ascii = render_tree(&RootCommand, false);
+    assert!(ascii.is_ascii());
+    assert!(ascii.lines().any(|line| line == "|-- file"));
+    assert!(ascii.lines().any(|line| line == "|   |-- vers"));
+    assert!(ascii.lines().any(|line| line == "`-- system"));
+    assert_eq!(ascii.lines().count(), lines.len());
+
+    // Run from a submenu, the tree starts there
+    let harness = TestHarness::new("tree").unwrap();
+    let mut app = harness.app("");
+    app.handle_input("file").unwrap();
+    harness.clear_output();
+    app.handle_input("tree").unwrap();
+    assert_eq!(
+        harness.output(),
+        format!("{}\n", render_tree(&FileCommand::new(), false))
+    );
+}
+
+#[test]
+fn test_tree_terminates_in_recursive_file_menu() {
+    let tree = render_tree(&FileCommand::with_recursion(true), false);
+    let lines: Vec<&str> = tree.lines().collect();
+
+    // The nested file menu is drawn once, without the commands below it
+    assert_eq!(lines[0], "file");
+    assert_eq!(lines[lines.len() - 2..], ["|-- file", "`-- exit"]);
+    assert_eq!(
+        lines.len(),
+        CommandTreeIter::new(&FileCommand::with_recursion(true)).count() + 1
+    );
+}